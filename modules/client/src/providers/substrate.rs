@@ -169,67 +169,80 @@ impl<C: subxt::Config + Clone> Client for SubstrateClient<C> {
         item: RequestOrResponse,
         initial_height: u64,
     ) -> Result<BoxStream<WithMetadata<Event>>, Error> {
-        let subscription = self.client.rpc().subscribe_finalized_block_headers().await?;
-        let stream = stream::unfold(
-            (initial_height, subscription, self.clone()),
-            move |(latest_height, mut subscription, client)| {
+        /// Mirrors the filter accepted by `ismp_subscribeEvents`
+        #[derive(Serialize)]
+        struct EventFilter {
+            source: Option<StateMachine>,
+            dest: Option<StateMachine>,
+            kinds: Vec<&'static str>,
+            finalized_only: bool,
+        }
+
+        /// The subset of the events streamed by `ismp_subscribeEvents` needed here
+        #[derive(Deserialize)]
+        struct StreamedEvent {
+            meta: EventMetadata,
+            event: Event,
+        }
+
+        let (source, dest, kind) = match &item {
+            RequestOrResponse::Request(post) => (post.source, post.dest, "PostRequest"),
+            RequestOrResponse::Response(resp) =>
+                (resp.source_chain(), resp.dest_chain(), "PostResponse"),
+        };
+        let filter = EventFilter {
+            source: Some(source),
+            dest: Some(dest),
+            kinds: vec![kind],
+            finalized_only: true,
+        };
+
+        // subscribe before catching up so that no finalized block is missed in between
+        let subscription = self
+            .client
+            .rpc()
+            .subscribe::<StreamedEvent>(
+                "ismp_subscribeEvents",
+                rpc_params![filter],
+                "ismp_unsubscribeEvents",
+            )
+            .await?;
+        let finalized_hash = self.client.rpc().finalized_head().await?;
+        let finalized_height: u64 = self
+            .client
+            .rpc()
+            .header(Some(finalized_hash))
+            .await?
+            .ok_or_else(|| anyhow!("Finalized header {finalized_hash:?} not found"))?
+            .number()
+            .into();
+        let past_events = self.query_ismp_events(initial_height, finalized_height).await?;
+
+        let stream = stream::iter(past_events.into_iter().map(Ok))
+            .chain(subscription.filter_map(move |event| async move {
+                match event {
+                    // these were already returned by the query above
+                    Ok(event) if event.meta.block_number <= finalized_height => None,
+                    Ok(StreamedEvent { meta, event }) => Some(Ok(WithMetadata { meta, event })),
+                    Err(err) => Some(Err(Error::from(err))),
+                }
+            }))
+            .filter_map(move |event| {
                 let item = item.clone();
                 async move {
-                    let header = match subscription.next().await {
-                        Some(Ok(header)) => header,
-                        Some(Err(_err)) => {
-                            tracing::error!(
-                                "Error encountered while watching finalized heads: {_err:?}"
-                            );
-                            return Some((Ok(None), (latest_height, subscription, client)));
-                        },
-                        None => return None,
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(err) => return Some(Err(err)),
                     };
-
-                    let events =
-                        match client.query_ismp_events(latest_height, header.number().into()).await
-                        {
-                            Ok(e) => e,
-                            Err(_err) => {
-                                tracing::error!(
-                                    "Error encountered while querying ismp events {_err:?}"
-                                );
-                                return Some((Ok(None), (latest_height, subscription, client)));
-                            },
-                        };
-
-                    let event = events.into_iter().find_map(|event| {
-                        let value = match event.event.clone() {
-                            Event::PostRequest(post) =>
-                                Some(RequestOrResponse::Request(post.clone())),
-                            Event::PostResponse(resp) => Some(RequestOrResponse::Response(resp)),
-                            _ => None,
-                        };
-
-                        if value == Some(item.clone()) {
-                            Some(event)
-                        } else {
-                            None
-                        }
-                    });
-
-                    let value = match event {
-                        Some(event) =>
-                            Some((Ok(Some(event)), (header.number().into(), subscription, client))),
-                        None => Some((Ok(None), (header.number().into(), subscription, client))),
+                    let value = match event.event.clone() {
+                        Event::PostRequest(post) => Some(RequestOrResponse::Request(post)),
+                        Event::PostResponse(resp) => Some(RequestOrResponse::Response(resp)),
+                        _ => None,
                     };
 
-                    return value;
+                    (value == Some(item)).then_some(Ok(event))
                 }
-            },
-        )
-        .filter_map(|item| async move {
-            match item {
-                Ok(None) => None,
-                Ok(Some(event)) => Some(Ok(event)),
-                Err(err) => Some(Err(err)),
-            }
-        });
+            });
 
         Ok(Box::pin(stream))
     }
@@ -518,6 +531,7 @@ impl From<runtime::api::runtime_types::ismp::host::StateMachine> for StateMachin
                 StateMachine::Beefy(consensus_state_id),
             runtime::api::runtime_types::ismp::host::StateMachine::Polygon => StateMachine::Polygon,
             runtime::api::runtime_types::ismp::host::StateMachine::Bsc => StateMachine::Bsc,
+            runtime::api::runtime_types::ismp::host::StateMachine::Evm(id) => StateMachine::Evm(id),
         }
     }
 }
@@ -571,6 +585,7 @@ impl From<StateMachine> for runtime::api::runtime_types::ismp::host::StateMachin
 
             StateMachine::Polygon => runtime::api::runtime_types::ismp::host::StateMachine::Polygon,
             StateMachine::Bsc => runtime::api::runtime_types::ismp::host::StateMachine::Bsc,
+            StateMachine::Evm(id) => runtime::api::runtime_types::ismp::host::StateMachine::Evm(id),
         }
     }
 }
@@ -12235,6 +12235,8 @@ pub mod api {
                     Polygon,
                     #[codec(index = 6)]
                     Bsc,
+                    #[codec(index = 7)]
+                    Evm(::core::primitive::u32),
                 }
             }
             pub mod messaging {
@@ -65,7 +65,7 @@ pub struct SignedCommitment {
     pub signatures: Vec<SignatureWithAuthorityIndex>,
 }
 
-#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq)]
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// Mmr Update with proof
 pub struct MmrProof {
     /// Signed commitment
@@ -75,7 +75,7 @@ pub struct MmrProof {
     /// Proof for the latest mmr leaf
     pub mmr_proof: sp_mmr_primitives::Proof<H256>,
     /// Proof for authorities in current session
-    pub authority_proof: Vec<Vec<(usize, [u8; 32])>>,
+    pub authority_proof: Vec<Vec<(u32, [u8; 32])>>,
 }
 
 #[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
@@ -89,28 +89,28 @@ pub struct PartialMmrLeaf {
     pub beefy_next_authority_set: BeefyNextAuthoritySet<H256>,
 }
 
-#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq)]
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// Parachain header and metadata needed for merkle inclusion proof
 pub struct ParachainHeader {
     /// scale encoded parachain header
     pub header: Vec<u8>,
     /// leaf index for parachain heads proof
-    pub index: usize,
+    pub index: u32,
     /// ParaId for parachain
     pub para_id: u32,
 }
 
-#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq)]
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// Parachain proofs definition
 pub struct ParachainProof {
     /// List of parachains we have a proof for
     pub parachains: Vec<ParachainHeader>,
 
     /// Proof for parachain header inclusion in the parachain headers root
-    pub proof: Vec<Vec<(usize, [u8; 32])>>,
+    pub proof: Vec<Vec<(u32, [u8; 32])>>,
 }
 
-#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq)]
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// Parachain headers update with proof
 pub struct ConsensusMessage {
     /// Parachain headers
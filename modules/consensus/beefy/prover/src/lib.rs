@@ -209,7 +209,7 @@ impl<R: Config, P: Config> Prover<R, P> {
                 (
                     ParachainHeader {
                         header: heads[index].1.clone(),
-                        index,
+                        index: index as u32,
                         para_id: heads[index].0,
                     },
                     index,
@@ -37,7 +37,7 @@ pub struct TimeStampExtWithProof {
 /// existence of the ethereum addresses associated with the signatures.
 pub struct AuthorityProofWithSignatures {
     /// Merkle multi-proof
-    pub authority_proof: Vec<Vec<(usize, Hash)>>,
+    pub authority_proof: Vec<Vec<(u32, Hash)>>,
     /// The actual signatures alongside the authority index, used in verifying the merkle proof.
     pub signatures: Vec<SignatureWithAuthorityIndex>,
 }
@@ -168,8 +168,11 @@ impl rs_merkle::Hasher for MerkleHasher {
 }
 
 /// Generates a 2D-merkle proof for the given leaves & indices
-pub fn merkle_proof(leaves: &[Hash], indices: &[usize]) -> Vec<Vec<(usize, Hash)>> {
+pub fn merkle_proof(leaves: &[Hash], indices: &[usize]) -> Vec<Vec<(u32, Hash)>> {
     let tree = MerkleTree::<MerkleHasher>::from_leaves(leaves);
 
     tree.proof_2d(indices)
+        .into_iter()
+        .map(|layer| layer.into_iter().map(|(index, node)| (index as u32, node)).collect())
+        .collect()
 }
@@ -0,0 +1,34 @@
+// Copyright (C) 2022 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feeds arbitrary trusted states and consensus messages to the verifier, which must reject
+//! invalid input without panicking.
+
+#![no_main]
+
+use beefy_verifier_primitives::{ConsensusMessage, ConsensusState};
+use codec::Decode;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = data;
+    let (Ok(trusted_state), Ok(message)) =
+        (ConsensusState::decode(&mut input), ConsensusMessage::decode(&mut input))
+    else {
+        return;
+    };
+
+    let _ = beefy_verifier::verify_consensus(trusted_state, message);
+});
@@ -0,0 +1,77 @@
+// Copyright (C) 2022 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors returned by the BEEFY verifier
+
+use core::fmt::{Display, Formatter};
+
+/// Errors that can occur while verifying BEEFY consensus proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The signed commitment is not newer than the latest trusted height
+    StaleHeight {
+        /// Latest trusted BEEFY height
+        trusted: u32,
+        /// Height of the signed commitment
+        found: u32,
+    },
+    /// The commitment was signed by an authority set we don't know about
+    UnknownAuthoritySet(u64),
+    /// Not enough authorities signed the commitment
+    SuperMajorityThresholdNotReached,
+    /// Signatures must be sorted by strictly increasing authority indices within the set
+    InvalidAuthorityIndex(u32),
+    /// Could not recover the authority address from its signature
+    InvalidSignature(u32),
+    /// The commitment payload does not contain an mmr root hash
+    MmrRootHashNotFound,
+    /// The authority merkle multi proof does not match the trusted authority set
+    InvalidAuthorityProof,
+    /// The leaf index of the mmr proof doesn't match the latest mmr leaf
+    InvalidMmrLeafIndex,
+    /// The mmr proof does not match the signed mmr root hash
+    InvalidMmrProof,
+    /// The parachain heads proof does not match the heads root in the latest mmr leaf
+    InvalidParachainHeadsProof,
+    /// A proven parachain header could not be decoded
+    InvalidParachainHeader(u32),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::StaleHeight { trusted, found } => write!(
+                f,
+                "Commitment height {found} is not newer than the trusted height {trusted}"
+            ),
+            Error::UnknownAuthoritySet(id) => write!(f, "Unknown authority set id {id}"),
+            Error::SuperMajorityThresholdNotReached => {
+                write!(f, "Super majority threshold not reached")
+            },
+            Error::InvalidAuthorityIndex(index) => write!(f, "Invalid authority index {index}"),
+            Error::InvalidSignature(index) => {
+                write!(f, "Failed to recover signature of authority {index}")
+            },
+            Error::MmrRootHashNotFound => write!(f, "Mmr root hash not found in commitment"),
+            Error::InvalidAuthorityProof => write!(f, "Invalid authority set proof"),
+            Error::InvalidMmrLeafIndex => write!(f, "Invalid mmr leaf index"),
+            Error::InvalidMmrProof => write!(f, "Invalid mmr proof"),
+            Error::InvalidParachainHeadsProof => write!(f, "Invalid parachain heads proof"),
+            Error::InvalidParachainHeader(para_id) => {
+                write!(f, "Failed to decode header for ParaId({para_id})")
+            },
+        }
+    }
+}
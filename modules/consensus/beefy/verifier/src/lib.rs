@@ -0,0 +1,251 @@
+// Copyright (C) 2022 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verifier for BEEFY consensus proofs of the relay chain and its parachain headers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+pub mod error;
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::Error;
+use alloc::{vec, vec::Vec};
+use beefy_verifier_primitives::{
+    BeefyNextAuthoritySet, ConsensusMessage, ConsensusState, Hash, MmrProof, ParachainProof,
+    SignedCommitment,
+};
+use codec::{Decode, Encode};
+use merkle_mountain_range::{leaf_index_to_mmr_size, leaf_index_to_pos, MerkleProof};
+use sp_consensus_beefy::known_payloads::MMR_ROOT_ID;
+use sp_core::H256;
+use sp_io::hashing::keccak_256;
+use sp_runtime::{generic::Header, traits::BlakeTwo256};
+
+/// A parachain header that was proven to be finalized by the relay chain
+pub type ParachainHeader = Header<u32, BlakeTwo256>;
+
+/// Verifies a BEEFY consensus message against the trusted consensus state. Returns the new
+/// consensus state alongside the parachain headers that were finalized in the new mmr root.
+pub fn verify_consensus(
+    trusted_state: ConsensusState,
+    message: ConsensusMessage,
+) -> Result<(ConsensusState, Vec<(u32, ParachainHeader)>), Error> {
+    let (state, heads_root) = verify_mmr_update_proof(trusted_state, message.mmr)?;
+    let headers = verify_parachain_headers(heads_root, message.parachain)?;
+
+    Ok((state, headers))
+}
+
+/// Verifies a new mmr root hash signed by the relay chain authorities. The relay chain accumulates
+/// its blocks into a merkle mountain range, the latest leaf of which commits to the next authority
+/// set and the heads of all parachains. Returns the new consensus state alongside the parachain
+/// heads root.
+pub fn verify_mmr_update_proof(
+    mut trusted_state: ConsensusState,
+    proof: MmrProof,
+) -> Result<(ConsensusState, H256), Error> {
+    let block_number = proof.signed_commitment.commitment.block_number;
+    if block_number <= trusted_state.latest_beefy_height {
+        Err(Error::StaleHeight { trusted: trusted_state.latest_beefy_height, found: block_number })?
+    }
+
+    let mmr_root =
+        verify_signed_commitment(&trusted_state, &proof.signed_commitment, &proof.authority_proof)?;
+
+    // verify the latest leaf is the last leaf in the signed mmr
+    let leaf_index = proof
+        .latest_mmr_leaf
+        .parent_number_and_hash
+        .0
+        .checked_sub(trusted_state.beefy_activation_block)
+        .ok_or(Error::InvalidMmrLeafIndex)? as u64;
+    if proof.mmr_proof.leaf_indices != [leaf_index] {
+        Err(Error::InvalidMmrLeafIndex)?
+    }
+
+    let leaf_hash = H256(keccak_256(&proof.latest_mmr_leaf.encode()));
+    let mmr_proof = MerkleProof::<H256, Keccak256Merge>::new(
+        leaf_index_to_mmr_size(leaf_index),
+        proof.mmr_proof.items,
+    );
+    let valid = mmr_proof
+        .verify(mmr_root, vec![(leaf_index_to_pos(leaf_index), leaf_hash)])
+        .map_err(|_| Error::InvalidMmrProof)?;
+    if !valid {
+        Err(Error::InvalidMmrProof)?
+    }
+
+    let next_authorities = proof.latest_mmr_leaf.beefy_next_authority_set;
+    if next_authorities.id > trusted_state.next_authorities.id {
+        trusted_state.current_authorities =
+            core::mem::replace(&mut trusted_state.next_authorities, next_authorities);
+    }
+    trusted_state.latest_beefy_height = block_number;
+    trusted_state.mmr_root_hash = mmr_root;
+
+    Ok((trusted_state, proof.latest_mmr_leaf.leaf_extra))
+}
+
+/// Verifies that a supermajority of either the current or next authority set signed the
+/// commitment, returning the signed mmr root hash. This performs no height checks, so it can also
+/// be used to check commitments submitted as evidence of equivocation.
+pub fn verify_signed_commitment(
+    trusted_state: &ConsensusState,
+    signed_commitment: &SignedCommitment,
+    authority_proof: &[Vec<(u32, Hash)>],
+) -> Result<H256, Error> {
+    let commitment = &signed_commitment.commitment;
+    let authorities = authority_set(trusted_state, commitment.validator_set_id)?;
+    let signatures = &signed_commitment.signatures;
+
+    if signatures.len() < ((2 * authorities.len as usize) / 3) + 1 {
+        Err(Error::SuperMajorityThresholdNotReached)?
+    }
+
+    // an authority may only be counted once
+    let mut previous = None;
+    for signature in signatures {
+        if signature.index >= authorities.len || previous.map_or(false, |i| signature.index <= i) {
+            Err(Error::InvalidAuthorityIndex(signature.index))?
+        }
+        previous = Some(signature.index);
+    }
+
+    let mmr_root = commitment
+        .payload
+        .get_decoded::<H256>(&MMR_ROOT_ID)
+        .ok_or(Error::MmrRootHashNotFound)?;
+
+    let commitment_hash = keccak_256(&commitment.encode());
+    let authorities_leaves = signatures
+        .iter()
+        .map(|vote| {
+            let public_key =
+                sp_io::crypto::secp256k1_ecdsa_recover(&vote.signature, &commitment_hash)
+                    .map_err(|_| Error::InvalidSignature(vote.index))?;
+            // the authority set commits to the ethereum addresses of the authorities
+            let address = &keccak_256(&public_key)[12..];
+            Ok((vote.index, keccak_256(address)))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let root = calculate_merkle_multi_root(authorities_leaves, authority_proof)
+        .ok_or(Error::InvalidAuthorityProof)?;
+    if root != authorities.keyset_commitment.0 {
+        Err(Error::InvalidAuthorityProof)?
+    }
+
+    Ok(mmr_root)
+}
+
+/// Verifies the inclusion of some parachain headers in the parachain heads root of an mmr leaf,
+/// returning the decoded headers.
+pub fn verify_parachain_headers(
+    heads_root: H256,
+    proof: ParachainProof,
+) -> Result<Vec<(u32, ParachainHeader)>, Error> {
+    let leaves = proof
+        .parachains
+        .iter()
+        .map(|para| (para.index, keccak_256(&(para.para_id, &para.header).encode())))
+        .collect::<Vec<_>>();
+
+    let root = calculate_merkle_multi_root(leaves, &proof.proof)
+        .ok_or(Error::InvalidParachainHeadsProof)?;
+    if root != heads_root.0 {
+        Err(Error::InvalidParachainHeadsProof)?
+    }
+
+    proof
+        .parachains
+        .into_iter()
+        .map(|para| {
+            let header = ParachainHeader::decode(&mut &*para.header)
+                .map_err(|_| Error::InvalidParachainHeader(para.para_id))?;
+            Ok((para.para_id, header))
+        })
+        .collect()
+}
+
+/// Calculates the root of a 2D merkle multi proof, where each layer of the proof contains the
+/// sibling nodes required to hash the nodes of that layer. Leaves are given alongside their index
+/// in the tree. Returns `None` if the proof is malformed.
+pub fn calculate_merkle_multi_root(
+    leaves: Vec<(u32, Hash)>,
+    proof: &[Vec<(u32, Hash)>],
+) -> Option<Hash> {
+    let mut current_layer = leaves;
+
+    for layer in proof {
+        current_layer.extend_from_slice(layer);
+        current_layer.sort_by_key(|(index, _)| *index);
+
+        let mut next_layer = Vec::with_capacity((current_layer.len() + 1) / 2);
+        let mut nodes = current_layer.into_iter().peekable();
+        while let Some((index, node)) = nodes.next() {
+            match nodes.peek() {
+                Some((sibling, _)) if *sibling == index => return None,
+                Some((sibling, _)) if index % 2 == 0 && *sibling == index + 1 => {
+                    let (_, right) = nodes.next()?;
+                    next_layer.push((index / 2, keccak_256(&[node, right].concat())));
+                },
+                // right nodes must always have a left sibling
+                _ if index % 2 == 1 => return None,
+                // the last node in an odd layer is promoted to the next layer
+                _ => next_layer.push((index / 2, node)),
+            }
+        }
+
+        current_layer = next_layer;
+    }
+
+    match current_layer[..] {
+        [(0, root)] => Some(root),
+        _ => None,
+    }
+}
+
+/// Returns the authority set with the given id
+fn authority_set(
+    trusted_state: &ConsensusState,
+    set_id: u64,
+) -> Result<&BeefyNextAuthoritySet<H256>, Error> {
+    if set_id == trusted_state.current_authorities.id {
+        Ok(&trusted_state.current_authorities)
+    } else if set_id == trusted_state.next_authorities.id {
+        Ok(&trusted_state.next_authorities)
+    } else {
+        Err(Error::UnknownAuthoritySet(set_id))
+    }
+}
+
+/// Keccak256 hasher for the relay chain's merkle mountain range
+struct Keccak256Merge;
+
+impl merkle_mountain_range::Merge for Keccak256Merge {
+    type Item = H256;
+
+    fn merge(left: &Self::Item, right: &Self::Item) -> merkle_mountain_range::Result<Self::Item> {
+        let mut concat = left.0.to_vec();
+        concat.extend_from_slice(&right.0);
+
+        Ok(H256(keccak_256(&concat)))
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    calculate_merkle_multi_root, error::Error, verify_mmr_update_proof, verify_parachain_headers,
+    Keccak256Merge,
+};
+use beefy_verifier_primitives::{
+    BeefyNextAuthoritySet, ConsensusState, MmrLeaf, MmrProof, ParachainHeader, ParachainProof,
+    SignatureWithAuthorityIndex, SignedCommitment,
+};
+use codec::Encode;
+use merkle_mountain_range::{leaf_index_to_mmr_size, leaf_index_to_pos, util::MemStore, MMR};
+use sp_consensus_beefy::{known_payloads::MMR_ROOT_ID, mmr::MmrLeafVersion, Commitment, Payload};
+use sp_core::{ecdsa, Pair, H256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{
+    generic::Header,
+    traits::{BlakeTwo256, Header as _},
+    Digest,
+};
+
+fn hash_pair(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+    keccak_256(&[left, right].concat())
+}
+
+#[test]
+fn should_calculate_merkle_multi_root() {
+    let leaves = (0u8..5).map(|i| keccak_256(&[i])).collect::<Vec<_>>();
+    // the last leaf of an odd layer is promoted to the next layer
+    let root = hash_pair(
+        hash_pair(hash_pair(leaves[0], leaves[1]), hash_pair(leaves[2], leaves[3])),
+        leaves[4],
+    );
+
+    let proof = vec![vec![(0, leaves[0]), (3, leaves[3])], vec![], vec![(1, leaves[4])]];
+    let calculated = calculate_merkle_multi_root(vec![(1, leaves[1]), (2, leaves[2])], &proof);
+    assert_eq!(calculated, Some(root));
+
+    let proof = vec![
+        vec![],
+        vec![],
+        vec![(0, hash_pair(hash_pair(leaves[0], leaves[1]), hash_pair(leaves[2], leaves[3])))],
+    ];
+    let calculated = calculate_merkle_multi_root(vec![(4, leaves[4])], &proof);
+    assert_eq!(calculated, Some(root));
+}
+
+#[test]
+fn should_reject_malformed_merkle_multi_proofs() {
+    let leaves = (0u8..4).map(|i| keccak_256(&[i])).collect::<Vec<_>>();
+    let proof = vec![vec![(0, leaves[0])], vec![(1, hash_pair(leaves[2], leaves[3]))]];
+
+    // duplicate leaves
+    assert_eq!(calculate_merkle_multi_root(vec![(0, leaves[0])], &proof), None);
+    // right node without its left sibling
+    assert_eq!(
+        calculate_merkle_multi_root(vec![(1, leaves[1])], &[vec![], proof[1].clone()]),
+        None
+    );
+    // proof doesn't reach the root
+    assert_eq!(
+        calculate_merkle_multi_root(vec![(2, leaves[2]), (3, leaves[3])], &proof[..1]),
+        None
+    );
+}
+
+/// Ethereum address of the authority, as committed to by the authority set
+fn authority_address(pair: &ecdsa::Pair) -> [u8; 20] {
+    let signature = pair.sign_prehashed(&[0u8; 32]);
+    let signature = <[u8; 65]>::try_from(signature.as_ref()).unwrap();
+    let public = sp_io::crypto::secp256k1_ecdsa_recover(&signature, &[0u8; 32]).unwrap();
+    keccak_256(&public)[12..].try_into().unwrap()
+}
+
+/// A BEEFY update signed by three authorities, whose latest mmr leaf commits to the heads of
+/// three parachains.
+struct Fixture {
+    trusted_state: ConsensusState,
+    mmr_proof: MmrProof,
+    parachain_proof: ParachainProof,
+    heads: Vec<(u32, Vec<u8>)>,
+}
+
+fn fixture() -> Fixture {
+    let authorities = (1u8..=3).map(|i| ecdsa::Pair::from_seed(&[i; 32])).collect::<Vec<_>>();
+    let leaves = authorities
+        .iter()
+        .map(|pair| keccak_256(&authority_address(pair)))
+        .collect::<Vec<_>>();
+    let keyset_commitment = H256(hash_pair(hash_pair(leaves[0], leaves[1]), leaves[2]));
+    let authority_set = |id| BeefyNextAuthoritySet { id, len: 3, keyset_commitment };
+
+    let heads = (2000u32..2003)
+        .map(|para_id| {
+            let header = Header::<u32, BlakeTwo256>::new(
+                para_id,
+                H256::repeat_byte(1),
+                H256::repeat_byte(2),
+                H256::repeat_byte(3),
+                Digest::default(),
+            );
+            (para_id, header.encode())
+        })
+        .collect::<Vec<_>>();
+    let head_leaves = heads
+        .iter()
+        .map(|(para_id, header)| keccak_256(&(para_id, header).encode()))
+        .collect::<Vec<_>>();
+    let heads_root = hash_pair(hash_pair(head_leaves[0], head_leaves[1]), head_leaves[2]);
+    // prove the first and last parachains
+    let parachain_proof = ParachainProof {
+        parachains: vec![
+            ParachainHeader { header: heads[0].1.clone(), index: 0, para_id: heads[0].0 },
+            ParachainHeader { header: heads[2].1.clone(), index: 2, para_id: heads[2].0 },
+        ],
+        proof: vec![vec![(1, head_leaves[1])], vec![]],
+    };
+
+    let latest_mmr_leaf = MmrLeaf {
+        version: MmrLeafVersion::new(0, 0),
+        parent_number_and_hash: (4, H256::repeat_byte(4)),
+        beefy_next_authority_set: authority_set(2),
+        leaf_extra: H256(heads_root),
+    };
+    let store = MemStore::default();
+    let mut mmr = MMR::<H256, Keccak256Merge, _>::new(0, &store);
+    for i in 0u8..4 {
+        mmr.push(H256(keccak_256(&[i]))).unwrap();
+    }
+    let position = mmr.push(H256(keccak_256(&latest_mmr_leaf.encode()))).unwrap();
+    let mmr_root = mmr.get_root().unwrap();
+    let items = mmr.gen_proof(vec![position]).unwrap().proof_items().to_vec();
+    assert_eq!(position, leaf_index_to_pos(4));
+
+    let commitment = Commitment {
+        payload: Payload::from_single_entry(MMR_ROOT_ID, mmr_root.encode()),
+        block_number: 5,
+        validator_set_id: 0,
+    };
+    let commitment_hash = keccak_256(&commitment.encode());
+    let signatures = authorities
+        .iter()
+        .enumerate()
+        .map(|(index, pair)| SignatureWithAuthorityIndex {
+            signature: <[u8; 65]>::try_from(pair.sign_prehashed(&commitment_hash).as_ref())
+                .unwrap(),
+            index: index as u32,
+        })
+        .collect();
+
+    Fixture {
+        trusted_state: ConsensusState {
+            latest_beefy_height: 1,
+            beefy_activation_block: 0,
+            mmr_root_hash: H256::zero(),
+            current_authorities: authority_set(0),
+            next_authorities: authority_set(1),
+        },
+        mmr_proof: MmrProof {
+            signed_commitment: SignedCommitment { commitment, signatures },
+            latest_mmr_leaf,
+            mmr_proof: sp_mmr_primitives::Proof { leaf_indices: vec![4], leaf_count: 5, items },
+            // every authority signed, so the proof only needs to reach the root
+            authority_proof: vec![vec![], vec![]],
+        },
+        parachain_proof,
+        heads,
+    }
+}
+
+#[test]
+fn should_verify_mmr_update_proof() {
+    let Fixture { trusted_state, mmr_proof, .. } = fixture();
+    assert_eq!(leaf_index_to_mmr_size(4), 8);
+
+    let (state, heads_root) =
+        verify_mmr_update_proof(trusted_state.clone(), mmr_proof.clone()).unwrap();
+    assert_eq!(heads_root, mmr_proof.latest_mmr_leaf.leaf_extra);
+    assert_eq!(state.latest_beefy_height, 5);
+    assert_eq!(
+        Some(state.mmr_root_hash),
+        mmr_proof.signed_commitment.commitment.payload.get_decoded::<H256>(&MMR_ROOT_ID)
+    );
+    // the authority sets are rotated once the leaf commits to a newer set
+    assert_eq!(state.current_authorities, trusted_state.next_authorities);
+    assert_eq!(state.next_authorities, mmr_proof.latest_mmr_leaf.beefy_next_authority_set);
+
+    // the same commitment can't be used twice
+    assert_eq!(
+        verify_mmr_update_proof(state, mmr_proof.clone()),
+        Err(Error::StaleHeight { trusted: 5, found: 5 })
+    );
+
+    let mut proof = mmr_proof.clone();
+    proof.signed_commitment.signatures.pop();
+    assert_eq!(
+        verify_mmr_update_proof(trusted_state.clone(), proof),
+        Err(Error::SuperMajorityThresholdNotReached)
+    );
+
+    let mut proof = mmr_proof.clone();
+    proof.signed_commitment.signatures.swap(0, 1);
+    assert_eq!(
+        verify_mmr_update_proof(trusted_state.clone(), proof),
+        Err(Error::InvalidAuthorityIndex(0))
+    );
+
+    // signatures over a different commitment recover to unknown authorities
+    let mut proof = mmr_proof.clone();
+    proof.signed_commitment.commitment.block_number = 6;
+    assert_eq!(
+        verify_mmr_update_proof(trusted_state.clone(), proof),
+        Err(Error::InvalidAuthorityProof)
+    );
+
+    let mut proof = mmr_proof.clone();
+    proof.latest_mmr_leaf.leaf_extra = H256::zero();
+    assert_eq!(verify_mmr_update_proof(trusted_state.clone(), proof), Err(Error::InvalidMmrProof));
+
+    let mut proof = mmr_proof;
+    proof.mmr_proof.leaf_indices = vec![3];
+    assert_eq!(verify_mmr_update_proof(trusted_state, proof), Err(Error::InvalidMmrLeafIndex));
+}
+
+#[test]
+fn should_verify_parachain_headers() {
+    let Fixture { mmr_proof, parachain_proof, heads, .. } = fixture();
+    let heads_root = mmr_proof.latest_mmr_leaf.leaf_extra;
+
+    let headers = verify_parachain_headers(heads_root, parachain_proof.clone()).unwrap();
+    assert_eq!(
+        headers
+            .iter()
+            .map(|(para_id, header)| (*para_id, header.encode()))
+            .collect::<Vec<_>>(),
+        vec![heads[0].clone(), heads[2].clone()]
+    );
+    assert_eq!(headers[1].1.number, 2002);
+
+    let mut proof = parachain_proof.clone();
+    proof.parachains[1].header = heads[1].1.clone();
+    assert_eq!(verify_parachain_headers(heads_root, proof), Err(Error::InvalidParachainHeadsProof));
+
+    let mut proof = parachain_proof;
+    proof.parachains[0].para_id = 3000;
+    assert_eq!(verify_parachain_headers(heads_root, proof), Err(Error::InvalidParachainHeadsProof));
+}
@@ -5,8 +5,9 @@ use ethers::{
     types::BlockId,
 };
 use geth_primitives::CodecHeader;
-use polygon_pos_verifier::primitives::{parse_validators, SPAN_LENGTH};
-use primitive_types::H160;
+use ismp::util::Keccak256;
+use polygon_pos_verifier::primitives::{checkpoint_leaf, parse_validators, SPAN_LENGTH};
+use primitive_types::{H160, H256};
 use std::{fmt::Debug, sync::Arc};
 
 #[cfg(test)]
@@ -58,6 +59,55 @@ impl PolygonPosProver {
             .ok_or_else(|| anyhow!("Header not found for {finalized_block:?}"))?;
         Ok((finalized_header, validators))
     }
+
+    /// Generates a merkle proof for the header at `number` in the heimdall checkpoint that
+    /// covers the headers from `start` to `end` inclusive.
+    pub async fn checkpoint_merkle_proof(
+        &self,
+        start: u64,
+        end: u64,
+        number: u64,
+    ) -> Result<Vec<H256>, anyhow::Error> {
+        if number < start || number > end {
+            Err(anyhow!("Header {number} is not in checkpoint [{start}, {end}]"))?
+        }
+
+        let mut leaves = vec![];
+        for block in start..=end {
+            let header = self
+                .fetch_header(block)
+                .await?
+                .ok_or_else(|| anyhow!("Header not found for {block:?}"))?;
+            leaves.push(checkpoint_leaf::<KeccakHasher>(&header));
+        }
+
+        // heimdall pads the leaves with empty hashes to the next power of two
+        leaves.resize(leaves.len().next_power_of_two(), H256::zero());
+
+        let mut index = (number - start) as usize;
+        let mut proof = vec![];
+        while leaves.len() > 1 {
+            proof.push(leaves[index ^ 1]);
+            leaves = leaves
+                .chunks(2)
+                .map(|pair| KeccakHasher::keccak256(&[pair[0].0, pair[1].0].concat()))
+                .collect();
+            index /= 2;
+        }
+
+        Ok(proof)
+    }
+}
+
+pub struct KeccakHasher;
+
+impl Keccak256 for KeccakHasher {
+    fn keccak256(bytes: &[u8]) -> H256
+    where
+        Self: Sized,
+    {
+        ethers::utils::keccak256(bytes).into()
+    }
 }
 
 pub fn is_span_start(block_number: u64) -> bool {
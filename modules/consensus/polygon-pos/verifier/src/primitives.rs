@@ -2,7 +2,7 @@ use alloc::vec::Vec;
 use anyhow::anyhow;
 use ethabi::ethereum_types::{H160, H256};
 
-use geth_primitives::Header;
+use geth_primitives::{CodecHeader, Header};
 use ismp::util::Keccak256;
 
 const EXTRA_VANITY_LENGTH: usize = 32;
@@ -56,3 +56,41 @@ pub fn parse_validators(extra_data: &[u8]) -> Result<Option<Vec<H160>>, anyhow::
     validators.sort_by(|a, b| a.0.cmp(&b.0));
     Ok(Some(validators))
 }
+
+/// Computes the leaf of a header in the merkle tree whose root is checkpointed by heimdall to the
+/// `RootChain` contract on the L1, `keccak256(abi.encodePacked(number, timestamp,
+/// transactionsRoot, receiptsRoot))`
+pub fn checkpoint_leaf<H: Keccak256>(header: &CodecHeader) -> H256 {
+    let mut buf = [0u8; 128];
+    header.number.to_big_endian(&mut buf[..32]);
+    buf[56..64].copy_from_slice(&header.timestamp.to_be_bytes());
+    buf[64..96].copy_from_slice(&header.transactions_root[..]);
+    buf[96..].copy_from_slice(&header.receipts_root[..]);
+    H::keccak256(&buf)
+}
+
+/// Verifies that `leaf` is at position `index` in the merkle tree with the given root.
+/// This mirrors `Merkle.checkMembership` used by the `RootChain` contract.
+pub fn check_membership<H: Keccak256>(leaf: H256, index: u64, root: H256, proof: &[H256]) -> bool {
+    // the index must fit in a tree of this height
+    if proof.len() < 64 && index >> proof.len() != 0 {
+        return false
+    }
+
+    let mut index = index;
+    let mut computed = leaf;
+    for node in proof {
+        let mut buf = [0u8; 64];
+        if index % 2 == 0 {
+            buf[..32].copy_from_slice(&computed[..]);
+            buf[32..].copy_from_slice(&node[..]);
+        } else {
+            buf[..32].copy_from_slice(&node[..]);
+            buf[32..].copy_from_slice(&computed[..]);
+        }
+        computed = H::keccak256(&buf);
+        index /= 2;
+    }
+
+    computed == root
+}
@@ -0,0 +1,117 @@
+//! Types for verifying the beacon chain's Casper FFG finality gadget using the full validator set.
+use crate::{
+    consensus_types::{BeaconBlockHeader, Checkpoint, IndexedAttestation, Validator},
+    constants::{Epoch, Gwei, Root, ValidatorIndex, FAR_FUTURE_EPOCH},
+    electra::MAX_VALIDATORS_PER_SLOT,
+    types::ExecutionPayloadProof,
+};
+use alloc::vec::Vec;
+use ssz_rs::Node;
+
+/// A commitment to the beacon chain's validator registry at a given epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct ValidatorSetCommitment {
+    /// The state root of the beacon state the registry was taken from.
+    pub state_root: Root,
+    /// `hash_tree_root(state.validators)`
+    pub validators_root: Root,
+    /// Sum of the effective balances of all validators that count towards the total balance, see
+    /// [`counts_towards_total_balance`].
+    pub total_balance: Gwei,
+    /// The epoch of the beacon state the registry was taken from.
+    pub epoch: Epoch,
+}
+
+/// Returns true if the validator's effective balance is part of the trusted total balance.
+///
+/// Unlike the active balance, this only depends on the validator record and not on the epoch, so
+/// the total can be maintained from the records that changed between two registries. Validators
+/// awaiting activation are counted, which only raises the supermajority threshold, while
+/// validators which initiated an exit are excluded along with their votes.
+pub fn counts_towards_total_balance(validator: &Validator) -> bool {
+    !validator.slashed && validator.exit_epoch == FAR_FUTURE_EPOCH
+}
+
+/// Minimum state required by the light client to validate Casper FFG votes.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct CasperFfgState {
+    /// The latest checkpoint justified by a supermajority link.
+    pub current_justified_checkpoint: Checkpoint,
+    /// The latest finalized checkpoint.
+    pub finalized_checkpoint: Checkpoint,
+    /// The beacon block header of the latest finalized checkpoint.
+    pub finalized_header: BeaconBlockHeader,
+    /// The validator registry used to weigh attestations.
+    pub validator_set: ValidatorSetCommitment,
+}
+
+/// A validator record along with its index in the validator registry.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct ValidatorRecord {
+    /// Index of the validator in `state.validators`
+    pub index: ValidatorIndex,
+    /// The validator record
+    pub validator: Validator,
+}
+
+/// The records of all validators that participated in a set of attestations.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct ValidatorsProof {
+    /// Attesting validators, sorted by their index.
+    pub validators: Vec<ValidatorRecord>,
+    /// ssz merkle multi proof of the validators in the trusted `validators_root`.
+    pub multi_proof: Vec<Node>,
+}
+
+/// A validator record which differs between the trusted and the new validator registry.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct ValidatorChange {
+    /// Index of the validator in `state.validators`
+    pub index: ValidatorIndex,
+    /// The record in the trusted registry, `None` for validators appended to the registry since.
+    pub previous: Option<Validator>,
+    /// The record in the new registry
+    pub validator: Validator,
+}
+
+/// Rotates the trusted validator registry to the one in the state of a newly finalized header.
+///
+/// Only the records that changed are provided. They are proven with a single multi proof against
+/// both registries, which is only possible if every other record is identical.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct ValidatorSetUpdate {
+    /// Changed and appended validators, sorted by their index.
+    pub changes: Vec<ValidatorChange>,
+    /// Length of the trusted registry
+    pub previous_count: u64,
+    /// Length of the new registry
+    pub validator_count: u64,
+    /// ssz merkle multi proof of the changed records in the data tree of both registries, i.e
+    /// without the length mix-in.
+    pub multi_proof: Vec<Node>,
+    /// ssz merkle proof of `state.validators` in the finalized state
+    pub validators_branch: Vec<Node>,
+}
+
+/// The newly finalized header, accompanied by its execution payload.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct FinalizedHeaderUpdate {
+    /// The beacon block header of the finalized checkpoint
+    pub header: BeaconBlockHeader,
+    /// Execution payload of the finalized header
+    pub execution_payload: ExecutionPayloadProof,
+    /// Optionally rotate the trusted validator set to the one in the finalized state.
+    pub validator_set_update: Option<ValidatorSetUpdate>,
+}
+
+/// Data required to advance the Casper FFG light client.
+#[derive(Debug, Clone, PartialEq, Eq, Default, codec::Encode, codec::Decode)]
+pub struct CasperFfgUpdate {
+    /// Aggregated attestations which all vote for the same `source -> target` link. Since Electra,
+    /// a single aggregate may span all the committees of a slot.
+    pub attestations: Vec<IndexedAttestation<MAX_VALIDATORS_PER_SLOT>>,
+    /// Records of the attesting validators
+    pub validators_proof: ValidatorsProof,
+    /// Must be present when the link finalizes its source checkpoint.
+    pub finalized_header: Option<FinalizedHeaderUpdate>,
+}
@@ -58,18 +58,38 @@ pub const MAX_EXTRA_DATA_BYTES: usize = 32;
 pub const DEPOSIT_PROOF_LENGTH: usize = 33;
 
 pub const DOMAIN_SYNC_COMMITTEE: DomainType = DomainType::SyncCommittee;
+pub const DOMAIN_BEACON_ATTESTER: DomainType = DomainType::BeaconAttester;
 pub const FINALIZED_ROOT_INDEX: u64 = 52;
 pub const EXECUTION_PAYLOAD_INDEX: u64 = 56;
 pub const NEXT_SYNC_COMMITTEE_INDEX: u64 = 55;
 pub const BLOCK_ROOTS_INDEX: u64 = 37;
 pub const HISTORICAL_ROOTS_INDEX: u64 = 39;
 pub const HISTORICAL_BATCH_BLOCK_ROOTS_INDEX: u64 = 2;
+pub const VALIDATORS_INDEX: u64 = 43;
+
+// Generalized indices in the Electra `BeaconState`, which grew past 32 fields.
+pub const FINALIZED_ROOT_INDEX_ELECTRA: u64 = 169;
+pub const EXECUTION_PAYLOAD_INDEX_ELECTRA: u64 = 88;
+pub const NEXT_SYNC_COMMITTEE_INDEX_ELECTRA: u64 = 87;
+pub const VALIDATORS_INDEX_ELECTRA: u64 = 75;
+pub const BLOCK_ROOTS_INDEX_ELECTRA: u64 = 69;
 
 pub const FINALIZED_ROOT_INDEX_LOG2: u64 = 5;
 pub const EXECUTION_PAYLOAD_INDEX_LOG2: u64 = 5;
 pub const NEXT_SYNC_COMMITTEE_INDEX_LOG2: u64 = 5;
 pub const BLOCK_ROOTS_INDEX_LOG2: u64 = 5;
 pub const HISTORICAL_ROOTS_INDEX_LOG2: u64 = 5;
+pub const VALIDATORS_INDEX_LOG2: u64 = 5;
+
+pub const FINALIZED_ROOT_INDEX_LOG2_ELECTRA: u64 = 7;
+pub const EXECUTION_PAYLOAD_INDEX_LOG2_ELECTRA: u64 = 6;
+pub const NEXT_SYNC_COMMITTEE_INDEX_LOG2_ELECTRA: u64 = 6;
+pub const VALIDATORS_INDEX_LOG2_ELECTRA: u64 = 6;
+/// Depth of the merkle tree of the `validators` list in the `BeaconState`, excluding the length
+/// mix-in.
+pub const VALIDATOR_REGISTRY_LIMIT_LOG2: u64 = 40;
+/// `exit_epoch` of validators which have not initiated an exit.
+pub const FAR_FUTURE_EPOCH: Epoch = u64::MAX;
 pub const ETH1_DATA_VOTES_BOUND: usize = (EPOCHS_PER_ETH1_VOTING_PERIOD * 32) as usize;
 
 pub trait Config {
@@ -84,6 +104,8 @@ pub trait Config {
     const CAPELLA_FORK_VERSION: Version;
     const DENEB_FORK_EPOCH: Epoch;
     const DENEB_FORK_VERSION: Version;
+    const ELECTRA_FORK_EPOCH: Epoch;
+    const ELECTRA_FORK_VERSION: Version;
     const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: Epoch;
     const EXECUTION_PAYLOAD_STATE_ROOT_INDEX: u64;
     const EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX: u64;
@@ -112,6 +134,37 @@ pub mod sepolia {
         const CAPELLA_FORK_VERSION: Version = hex_literal::hex!("90000072");
         const DENEB_FORK_EPOCH: Epoch = 132608;
         const DENEB_FORK_VERSION: Version = hex!("90000073");
+        const ELECTRA_FORK_EPOCH: Epoch = 222464;
+        const ELECTRA_FORK_VERSION: Version = hex!("90000074");
+        const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: Epoch = 256;
+        const EXECUTION_PAYLOAD_STATE_ROOT_INDEX: u64 = 34;
+        const EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX: u64 = 38;
+        const EXECUTION_PAYLOAD_TIMESTAMP_INDEX: u64 = 41;
+    }
+}
+
+pub mod holesky {
+    use super::*;
+    use hex_literal::hex;
+
+    #[derive(Default)]
+    pub struct Holesky;
+
+    impl Config for Holesky {
+        const SLOTS_PER_EPOCH: Slot = 32;
+        const GENESIS_VALIDATORS_ROOT: [u8; 32] =
+            hex_literal::hex!("9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1");
+        const BELLATRIX_FORK_VERSION: Version = hex!("03017000");
+        const ALTAIR_FORK_VERSION: Version = hex!("02017000");
+        const GENESIS_FORK_VERSION: Version = hex!("01017000");
+        const ALTAIR_FORK_EPOCH: Epoch = 0;
+        const BELLATRIX_FORK_EPOCH: Epoch = 0;
+        const CAPELLA_FORK_EPOCH: Epoch = 256;
+        const CAPELLA_FORK_VERSION: Version = hex!("04017000");
+        const DENEB_FORK_EPOCH: Epoch = 29696;
+        const DENEB_FORK_VERSION: Version = hex!("05017000");
+        const ELECTRA_FORK_EPOCH: Epoch = 115968;
+        const ELECTRA_FORK_VERSION: Version = hex!("06017000");
         const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: Epoch = 256;
         const EXECUTION_PAYLOAD_STATE_ROOT_INDEX: u64 = 34;
         const EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX: u64 = 38;
@@ -138,6 +191,8 @@ pub mod mainnet {
         const CAPELLA_FORK_VERSION: Version = hex_literal::hex!("03000000");
         const DENEB_FORK_EPOCH: Epoch = 269568;
         const DENEB_FORK_VERSION: Version = hex_literal::hex!("04000000");
+        const ELECTRA_FORK_EPOCH: Epoch = 364032;
+        const ELECTRA_FORK_VERSION: Version = hex_literal::hex!("05000000");
         const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: Epoch = 256;
         const EXECUTION_PAYLOAD_STATE_ROOT_INDEX: u64 = 34;
         const EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX: u64 = 38;
@@ -165,6 +220,8 @@ pub mod devnet {
         const CAPELLA_FORK_VERSION: Version = hex!("52525503");
         const DENEB_FORK_EPOCH: Epoch = 0;
         const DENEB_FORK_VERSION: Version = hex!("52525504");
+        const ELECTRA_FORK_EPOCH: Epoch = FAR_FUTURE_EPOCH;
+        const ELECTRA_FORK_VERSION: Version = hex!("52525505");
         const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: Epoch = 4;
         const EXECUTION_PAYLOAD_STATE_ROOT_INDEX: u64 = 34;
         const EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX: u64 = 38;
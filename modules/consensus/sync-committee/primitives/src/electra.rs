@@ -0,0 +1,288 @@
+//! Containers introduced or modified by the Electra hard fork.
+use crate::{
+    consensus_types::{
+        AttestationData, AttesterSlashing, BeaconBlockHeader, Checkpoint, Deposit, Eth1Data,
+        ExecutionPayload, ExecutionPayloadHeader, Fork, HistoricalSummary, ProposerSlashing,
+        SignedBlsToExecutionChange, SignedVoluntaryExit, SyncAggregate, SyncCommittee, Validator,
+    },
+    constants::{
+        BlsPublicKey, BlsSignature, Bytes32, Epoch, ExecutionAddress, Gwei, ParticipationFlags,
+        Root, Slot, ValidatorIndex, WithdrawalIndex, JUSTIFICATION_BITS_LENGTH,
+        MAX_VALIDATORS_PER_COMMITTEE,
+    },
+    deneb::KzgCommitment,
+};
+use alloc::{vec, vec::Vec};
+use ssz_rs::{prelude::*, Deserialize, List, Vector};
+
+pub const MAX_COMMITTEES_PER_SLOT: usize = 64;
+pub const MAX_VALIDATORS_PER_SLOT: usize = MAX_VALIDATORS_PER_COMMITTEE * MAX_COMMITTEES_PER_SLOT;
+pub const MAX_ATTESTER_SLASHINGS_ELECTRA: usize = 1;
+pub const MAX_ATTESTATIONS_ELECTRA: usize = 8;
+pub const MAX_DEPOSIT_REQUESTS_PER_PAYLOAD: usize = 8192;
+pub const MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD: usize = 16;
+pub const MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD: usize = 2;
+pub const PENDING_DEPOSITS_LIMIT: usize = 2usize.saturating_pow(27);
+pub const PENDING_PARTIAL_WITHDRAWALS_LIMIT: usize = 2usize.saturating_pow(27);
+pub const PENDING_CONSOLIDATIONS_LIMIT: usize = 2usize.saturating_pow(18);
+
+/// An aggregate attestation which may span all the committees of a slot. `aggregation_bits` is
+/// the concatenation of the participation bits of the committees selected by `committee_bits`.
+#[derive(Default, Debug, SimpleSerialize, codec::Encode, codec::Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attestation<const MAX_VALIDATORS_PER_SLOT: usize, const MAX_COMMITTEES_PER_SLOT: usize> {
+    pub aggregation_bits: Bitlist<MAX_VALIDATORS_PER_SLOT>,
+    pub data: AttestationData,
+    pub signature: BlsSignature,
+    pub committee_bits: Bitvector<MAX_COMMITTEES_PER_SLOT>,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepositRequest {
+    #[cfg_attr(feature = "std", serde(rename = "pubkey"))]
+    pub public_key: BlsPublicKey,
+    pub withdrawal_credentials: Bytes32,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub amount: Gwei,
+    pub signature: BlsSignature,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub index: u64,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct WithdrawalRequest {
+    pub source_address: ExecutionAddress,
+    #[cfg_attr(feature = "std", serde(rename = "validator_pubkey"))]
+    pub validator_public_key: BlsPublicKey,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub amount: Gwei,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsolidationRequest {
+    pub source_address: ExecutionAddress,
+    #[cfg_attr(feature = "std", serde(rename = "source_pubkey"))]
+    pub source_public_key: BlsPublicKey,
+    #[cfg_attr(feature = "std", serde(rename = "target_pubkey"))]
+    pub target_public_key: BlsPublicKey,
+}
+
+/// Requests from the execution layer which are processed by the consensus layer.
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionRequests<
+    const MAX_DEPOSIT_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD: usize,
+> {
+    pub deposits: List<DepositRequest, MAX_DEPOSIT_REQUESTS_PER_PAYLOAD>,
+    pub withdrawals: List<WithdrawalRequest, MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD>,
+    pub consolidations: List<ConsolidationRequest, MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD>,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingDeposit {
+    #[cfg_attr(feature = "std", serde(rename = "pubkey"))]
+    pub public_key: BlsPublicKey,
+    pub withdrawal_credentials: Bytes32,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub amount: Gwei,
+    pub signature: BlsSignature,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub slot: Slot,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingPartialWithdrawal {
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub validator_index: ValidatorIndex,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub amount: Gwei,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub withdrawable_epoch: Epoch,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, codec::Encode, codec::Decode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingConsolidation {
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub source_index: ValidatorIndex,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub target_index: ValidatorIndex,
+}
+
+#[derive(Default, Debug, Clone, SimpleSerialize, PartialEq, Eq, codec::Encode, codec::Decode)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeaconBlockBody<
+    const MAX_PROPOSER_SLASHINGS: usize,
+    const MAX_VALIDATORS_PER_SLOT: usize,
+    const MAX_COMMITTEES_PER_SLOT: usize,
+    const MAX_ATTESTER_SLASHINGS: usize,
+    const MAX_ATTESTATIONS: usize,
+    const MAX_DEPOSITS: usize,
+    const MAX_VOLUNTARY_EXITS: usize,
+    const SYNC_COMMITTEE_SIZE: usize,
+    const BYTES_PER_LOGS_BLOOM: usize,
+    const MAX_EXTRA_DATA_BYTES: usize,
+    const MAX_BYTES_PER_TRANSACTION: usize,
+    const MAX_TRANSACTIONS_PER_PAYLOAD: usize,
+    const MAX_WITHDRAWALS_PER_PAYLOAD: usize,
+    const MAX_BLS_TO_EXECUTION_CHANGES: usize,
+    const MAX_BLOB_COMMITMENTS_PER_BLOCK: usize,
+    const MAX_DEPOSIT_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD: usize,
+> {
+    pub randao_reveal: BlsSignature,
+    pub eth1_data: Eth1Data,
+    pub graffiti: Bytes32,
+    pub proposer_slashings: List<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    pub attester_slashings: List<AttesterSlashing<MAX_VALIDATORS_PER_SLOT>, MAX_ATTESTER_SLASHINGS>,
+    pub attestations:
+        List<Attestation<MAX_VALIDATORS_PER_SLOT, MAX_COMMITTEES_PER_SLOT>, MAX_ATTESTATIONS>,
+    pub deposits: List<Deposit, MAX_DEPOSITS>,
+    pub voluntary_exits: List<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+    pub sync_aggregate: SyncAggregate<SYNC_COMMITTEE_SIZE>,
+    pub execution_payload: ExecutionPayload<
+        BYTES_PER_LOGS_BLOOM,
+        MAX_EXTRA_DATA_BYTES,
+        MAX_BYTES_PER_TRANSACTION,
+        MAX_TRANSACTIONS_PER_PAYLOAD,
+        MAX_WITHDRAWALS_PER_PAYLOAD,
+    >,
+    pub bls_to_execution_changes: List<SignedBlsToExecutionChange, MAX_BLS_TO_EXECUTION_CHANGES>,
+    pub blob_kzg_commitments: List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+    pub execution_requests: ExecutionRequests<
+        MAX_DEPOSIT_REQUESTS_PER_PAYLOAD,
+        MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD,
+        MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD,
+    >,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, SimpleSerialize, codec::Encode, codec::Decode)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeaconBlock<
+    const MAX_PROPOSER_SLASHINGS: usize,
+    const MAX_VALIDATORS_PER_SLOT: usize,
+    const MAX_COMMITTEES_PER_SLOT: usize,
+    const MAX_ATTESTER_SLASHINGS: usize,
+    const MAX_ATTESTATIONS: usize,
+    const MAX_DEPOSITS: usize,
+    const MAX_VOLUNTARY_EXITS: usize,
+    const SYNC_COMMITTEE_SIZE: usize,
+    const BYTES_PER_LOGS_BLOOM: usize,
+    const MAX_EXTRA_DATA_BYTES: usize,
+    const MAX_BYTES_PER_TRANSACTION: usize,
+    const MAX_TRANSACTIONS_PER_PAYLOAD: usize,
+    const MAX_WITHDRAWALS_PER_PAYLOAD: usize,
+    const MAX_BLS_TO_EXECUTION_CHANGES: usize,
+    const MAX_BLOB_COMMITMENTS_PER_BLOCK: usize,
+    const MAX_DEPOSIT_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD: usize,
+    const MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD: usize,
+> {
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub slot: Slot,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub proposer_index: ValidatorIndex,
+    pub parent_root: Root,
+    pub state_root: Root,
+    pub body: BeaconBlockBody<
+        MAX_PROPOSER_SLASHINGS,
+        MAX_VALIDATORS_PER_SLOT,
+        MAX_COMMITTEES_PER_SLOT,
+        MAX_ATTESTER_SLASHINGS,
+        MAX_ATTESTATIONS,
+        MAX_DEPOSITS,
+        MAX_VOLUNTARY_EXITS,
+        SYNC_COMMITTEE_SIZE,
+        BYTES_PER_LOGS_BLOOM,
+        MAX_EXTRA_DATA_BYTES,
+        MAX_BYTES_PER_TRANSACTION,
+        MAX_TRANSACTIONS_PER_PAYLOAD,
+        MAX_WITHDRAWALS_PER_PAYLOAD,
+        MAX_BLS_TO_EXECUTION_CHANGES,
+        MAX_BLOB_COMMITMENTS_PER_BLOCK,
+        MAX_DEPOSIT_REQUESTS_PER_PAYLOAD,
+        MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD,
+        MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD,
+    >,
+}
+
+#[derive(Default, Debug, SimpleSerialize, Clone, PartialEq, Eq, codec::Encode, codec::Decode)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeaconState<
+    const SLOTS_PER_HISTORICAL_ROOT: usize,
+    const HISTORICAL_ROOTS_LIMIT: usize,
+    const ETH1_DATA_VOTES_BOUND: usize,
+    const VALIDATOR_REGISTRY_LIMIT: usize,
+    const EPOCHS_PER_HISTORICAL_VECTOR: usize,
+    const EPOCHS_PER_SLASHINGS_VECTOR: usize,
+    const SYNC_COMMITTEE_SIZE: usize,
+    const BYTES_PER_LOGS_BLOOM: usize,
+    const MAX_EXTRA_DATA_BYTES: usize,
+    const PENDING_DEPOSITS_LIMIT: usize,
+    const PENDING_PARTIAL_WITHDRAWALS_LIMIT: usize,
+    const PENDING_CONSOLIDATIONS_LIMIT: usize,
+> {
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub genesis_time: u64,
+    pub genesis_validators_root: Root,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub slot: Slot,
+    pub fork: Fork,
+    pub latest_block_header: BeaconBlockHeader,
+    pub block_roots: Vector<Root, SLOTS_PER_HISTORICAL_ROOT>,
+    pub state_roots: Vector<Root, SLOTS_PER_HISTORICAL_ROOT>,
+    pub historical_roots: List<Root, HISTORICAL_ROOTS_LIMIT>,
+    pub eth1_data: Eth1Data,
+    pub eth1_data_votes: List<Eth1Data, ETH1_DATA_VOTES_BOUND>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub eth1_deposit_index: u64,
+    pub validators: List<Validator, VALIDATOR_REGISTRY_LIMIT>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::seq_of_str"))]
+    pub balances: List<Gwei, VALIDATOR_REGISTRY_LIMIT>,
+    pub randao_mixes: Vector<Bytes32, EPOCHS_PER_HISTORICAL_VECTOR>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::seq_of_str"))]
+    pub slashings: Vector<Gwei, EPOCHS_PER_SLASHINGS_VECTOR>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::seq_of_str"))]
+    pub previous_epoch_participation: List<ParticipationFlags, VALIDATOR_REGISTRY_LIMIT>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::seq_of_str"))]
+    pub current_epoch_participation: List<ParticipationFlags, VALIDATOR_REGISTRY_LIMIT>,
+    pub justification_bits: Bitvector<JUSTIFICATION_BITS_LENGTH>,
+    pub previous_justified_checkpoint: Checkpoint,
+    pub current_justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::seq_of_str"))]
+    pub inactivity_scores: List<u64, VALIDATOR_REGISTRY_LIMIT>,
+    pub current_sync_committee: SyncCommittee<SYNC_COMMITTEE_SIZE>,
+    pub next_sync_committee: SyncCommittee<SYNC_COMMITTEE_SIZE>,
+    pub latest_execution_payload_header:
+        ExecutionPayloadHeader<BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub next_withdrawal_index: WithdrawalIndex,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub next_withdrawal_validator_index: ValidatorIndex,
+    pub historical_summaries: List<HistoricalSummary, HISTORICAL_ROOTS_LIMIT>,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub deposit_requests_start_index: u64,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub deposit_balance_to_consume: Gwei,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub exit_balance_to_consume: Gwei,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub earliest_exit_epoch: Epoch,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub consolidation_balance_to_consume: Gwei,
+    #[cfg_attr(feature = "std", serde(with = "crate::serde::as_string"))]
+    pub earliest_consolidation_epoch: Epoch,
+    pub pending_deposits: List<PendingDeposit, PENDING_DEPOSITS_LIMIT>,
+    pub pending_partial_withdrawals:
+        List<PendingPartialWithdrawal, PENDING_PARTIAL_WITHDRAWALS_LIMIT>,
+    pub pending_consolidations: List<PendingConsolidation, PENDING_CONSOLIDATIONS_LIMIT>,
+}
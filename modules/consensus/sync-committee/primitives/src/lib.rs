@@ -5,10 +5,12 @@
 #[warn(unused_variables)]
 extern crate alloc;
 
+pub mod casper_ffg;
 pub mod consensus_types;
 pub mod constants;
 pub mod deneb;
 pub mod domains;
+pub mod electra;
 pub mod error;
 #[cfg(feature = "std")]
 pub mod serde;
@@ -1,6 +1,14 @@
 use crate::{
     consensus_types::ForkData,
-    constants::{Config, Domain, Root, Version},
+    constants::{
+        Config, Domain, Epoch, Root, Version, EXECUTION_PAYLOAD_INDEX,
+        EXECUTION_PAYLOAD_INDEX_ELECTRA, EXECUTION_PAYLOAD_INDEX_LOG2,
+        EXECUTION_PAYLOAD_INDEX_LOG2_ELECTRA, FINALIZED_ROOT_INDEX, FINALIZED_ROOT_INDEX_ELECTRA,
+        FINALIZED_ROOT_INDEX_LOG2, FINALIZED_ROOT_INDEX_LOG2_ELECTRA, NEXT_SYNC_COMMITTEE_INDEX,
+        NEXT_SYNC_COMMITTEE_INDEX_ELECTRA, NEXT_SYNC_COMMITTEE_INDEX_LOG2,
+        NEXT_SYNC_COMMITTEE_INDEX_LOG2_ELECTRA, VALIDATORS_INDEX, VALIDATORS_INDEX_ELECTRA,
+        VALIDATORS_INDEX_LOG2, VALIDATORS_INDEX_LOG2_ELECTRA,
+    },
     domains::DomainType,
 };
 use alloc::{vec, vec::Vec};
@@ -24,7 +32,9 @@ pub fn compute_epoch_at_slot<C: Config>(slot: u64) -> u64 {
 
 /// Return the fork version at the given ``epoch``.
 pub fn compute_fork_version<C: Config>(epoch: u64) -> [u8; 4] {
-    if epoch >= C::DENEB_FORK_EPOCH {
+    if epoch >= C::ELECTRA_FORK_EPOCH {
+        C::ELECTRA_FORK_VERSION
+    } else if epoch >= C::DENEB_FORK_EPOCH {
         C::DENEB_FORK_VERSION
     } else if epoch >= C::CAPELLA_FORK_EPOCH {
         C::CAPELLA_FORK_VERSION
@@ -37,6 +47,48 @@ pub fn compute_fork_version<C: Config>(epoch: u64) -> [u8; 4] {
     }
 }
 
+/// Returns true if the beacon state at the given ``epoch`` uses the Electra layout.
+pub fn is_electra<C: Config>(epoch: Epoch) -> bool {
+    epoch >= C::ELECTRA_FORK_EPOCH
+}
+
+/// Return the generalized index and depth of `state.finalized_checkpoint.root` at ``epoch``.
+pub fn finalized_root_gindex<C: Config>(epoch: Epoch) -> (u64, u64) {
+    if is_electra::<C>(epoch) {
+        (FINALIZED_ROOT_INDEX_ELECTRA, FINALIZED_ROOT_INDEX_LOG2_ELECTRA)
+    } else {
+        (FINALIZED_ROOT_INDEX, FINALIZED_ROOT_INDEX_LOG2)
+    }
+}
+
+/// Return the generalized index and depth of `state.next_sync_committee` at ``epoch``.
+pub fn next_sync_committee_gindex<C: Config>(epoch: Epoch) -> (u64, u64) {
+    if is_electra::<C>(epoch) {
+        (NEXT_SYNC_COMMITTEE_INDEX_ELECTRA, NEXT_SYNC_COMMITTEE_INDEX_LOG2_ELECTRA)
+    } else {
+        (NEXT_SYNC_COMMITTEE_INDEX, NEXT_SYNC_COMMITTEE_INDEX_LOG2)
+    }
+}
+
+/// Return the generalized index and depth of `state.latest_execution_payload_header` at
+/// ``epoch``.
+pub fn execution_payload_gindex<C: Config>(epoch: Epoch) -> (u64, u64) {
+    if is_electra::<C>(epoch) {
+        (EXECUTION_PAYLOAD_INDEX_ELECTRA, EXECUTION_PAYLOAD_INDEX_LOG2_ELECTRA)
+    } else {
+        (EXECUTION_PAYLOAD_INDEX, EXECUTION_PAYLOAD_INDEX_LOG2)
+    }
+}
+
+/// Return the generalized index and depth of `state.validators` at ``epoch``.
+pub fn validators_gindex<C: Config>(epoch: Epoch) -> (u64, u64) {
+    if is_electra::<C>(epoch) {
+        (VALIDATORS_INDEX_ELECTRA, VALIDATORS_INDEX_LOG2_ELECTRA)
+    } else {
+        (VALIDATORS_INDEX, VALIDATORS_INDEX_LOG2)
+    }
+}
+
 pub fn compute_domain(
     domain_type: DomainType,
     fork_version: Option<Version>,
@@ -3,6 +3,7 @@ mod middleware;
 #[warn(unused_variables)]
 mod responses;
 mod routes;
+pub mod versioned;
 
 #[cfg(test)]
 mod test;
@@ -10,10 +11,11 @@ mod test;
 use crate::{
     middleware::SwitchProviderMiddleware,
     responses::{
-        finality_checkpoint_response::FinalityCheckpoint,
+        committee_response::Committee, finality_checkpoint_response::FinalityCheckpoint,
         sync_committee_response::NodeSyncCommittee,
     },
     routes::*,
+    versioned::{VersionedBeaconBlock, VersionedBeaconState},
 };
 use anyhow::anyhow;
 use bls_on_arkworks::{point_to_pubkey, types::G1ProjectivePoint};
@@ -22,21 +24,35 @@ use primitive_types::H256;
 use reqwest::{Client, Url};
 use reqwest_chain::ChainMiddleware;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use ssz_rs::{Merkleized, Node};
-use std::marker::PhantomData;
+use ssz_rs::{List, Merkleized, Node};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+};
 use sync_committee_primitives::{
-    consensus_types::{BeaconBlock, BeaconBlockHeader, BeaconState, Checkpoint, Validator},
+    casper_ffg::{
+        counts_towards_total_balance, CasperFfgState, CasperFfgUpdate, FinalizedHeaderUpdate,
+        ValidatorChange, ValidatorRecord, ValidatorSetUpdate, ValidatorsProof,
+    },
+    consensus_types::{
+        BeaconBlock, BeaconBlockHeader, BeaconState, Checkpoint, IndexedAttestation, Validator,
+    },
     constants::{
-        BlsPublicKey, Config, Root, BLOCK_ROOTS_INDEX, BYTES_PER_LOGS_BLOOM,
-        EPOCHS_PER_HISTORICAL_VECTOR, EPOCHS_PER_SLASHINGS_VECTOR, ETH1_DATA_VOTES_BOUND,
-        EXECUTION_PAYLOAD_INDEX, FINALIZED_ROOT_INDEX, HISTORICAL_ROOTS_LIMIT, MAX_ATTESTATIONS,
-        MAX_ATTESTER_SLASHINGS, MAX_BLS_TO_EXECUTION_CHANGES, MAX_BYTES_PER_TRANSACTION,
-        MAX_DEPOSITS, MAX_EXTRA_DATA_BYTES, MAX_PROPOSER_SLASHINGS, MAX_TRANSACTIONS_PER_PAYLOAD,
-        MAX_VALIDATORS_PER_COMMITTEE, MAX_VOLUNTARY_EXITS, MAX_WITHDRAWALS_PER_PAYLOAD,
-        NEXT_SYNC_COMMITTEE_INDEX, SLOTS_PER_HISTORICAL_ROOT, SYNC_COMMITTEE_SIZE,
-        VALIDATOR_REGISTRY_LIMIT,
+        BlsPublicKey, Config, Root, BYTES_PER_LOGS_BLOOM, EPOCHS_PER_HISTORICAL_VECTOR,
+        EPOCHS_PER_SLASHINGS_VECTOR, ETH1_DATA_VOTES_BOUND, HISTORICAL_ROOTS_LIMIT,
+        MAX_ATTESTATIONS, MAX_ATTESTER_SLASHINGS, MAX_BLS_TO_EXECUTION_CHANGES,
+        MAX_BYTES_PER_TRANSACTION, MAX_DEPOSITS, MAX_EXTRA_DATA_BYTES, MAX_PROPOSER_SLASHINGS,
+        MAX_TRANSACTIONS_PER_PAYLOAD, MAX_VALIDATORS_PER_COMMITTEE, MAX_VOLUNTARY_EXITS,
+        MAX_WITHDRAWALS_PER_PAYLOAD, SLOTS_PER_HISTORICAL_ROOT, SYNC_COMMITTEE_SIZE,
+        VALIDATOR_REGISTRY_LIMIT, VALIDATOR_REGISTRY_LIMIT_LOG2,
     },
     deneb::MAX_BLOB_COMMITMENTS_PER_BLOCK,
+    electra::{
+        self, MAX_ATTESTATIONS_ELECTRA, MAX_ATTESTER_SLASHINGS_ELECTRA, MAX_COMMITTEES_PER_SLOT,
+        MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD, MAX_DEPOSIT_REQUESTS_PER_PAYLOAD,
+        MAX_VALIDATORS_PER_SLOT, MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD, PENDING_CONSOLIDATIONS_LIMIT,
+        PENDING_DEPOSITS_LIMIT, PENDING_PARTIAL_WITHDRAWALS_LIMIT,
+    },
     types::{
         AncestryProof, BlockRootsProof, ExecutionPayloadProof, FinalityProof, SyncCommitteeUpdate,
         VerifierState, VerifierStateUpdate,
@@ -63,6 +79,59 @@ pub type BeaconStateType = BeaconState<
     MAX_EXTRA_DATA_BYTES,
 >;
 
+pub type ElectraBeaconStateType = electra::BeaconState<
+    SLOTS_PER_HISTORICAL_ROOT,
+    HISTORICAL_ROOTS_LIMIT,
+    ETH1_DATA_VOTES_BOUND,
+    VALIDATOR_REGISTRY_LIMIT,
+    EPOCHS_PER_HISTORICAL_VECTOR,
+    EPOCHS_PER_SLASHINGS_VECTOR,
+    SYNC_COMMITTEE_SIZE,
+    BYTES_PER_LOGS_BLOOM,
+    MAX_EXTRA_DATA_BYTES,
+    PENDING_DEPOSITS_LIMIT,
+    PENDING_PARTIAL_WITHDRAWALS_LIMIT,
+    PENDING_CONSOLIDATIONS_LIMIT,
+>;
+
+pub type BeaconBlockType = BeaconBlock<
+    MAX_PROPOSER_SLASHINGS,
+    MAX_VALIDATORS_PER_COMMITTEE,
+    MAX_ATTESTER_SLASHINGS,
+    MAX_ATTESTATIONS,
+    MAX_DEPOSITS,
+    MAX_VOLUNTARY_EXITS,
+    SYNC_COMMITTEE_SIZE,
+    BYTES_PER_LOGS_BLOOM,
+    MAX_EXTRA_DATA_BYTES,
+    MAX_BYTES_PER_TRANSACTION,
+    MAX_TRANSACTIONS_PER_PAYLOAD,
+    MAX_WITHDRAWALS_PER_PAYLOAD,
+    MAX_BLS_TO_EXECUTION_CHANGES,
+    MAX_BLOB_COMMITMENTS_PER_BLOCK,
+>;
+
+pub type ElectraBeaconBlockType = electra::BeaconBlock<
+    MAX_PROPOSER_SLASHINGS,
+    MAX_VALIDATORS_PER_SLOT,
+    MAX_COMMITTEES_PER_SLOT,
+    MAX_ATTESTER_SLASHINGS_ELECTRA,
+    MAX_ATTESTATIONS_ELECTRA,
+    MAX_DEPOSITS,
+    MAX_VOLUNTARY_EXITS,
+    SYNC_COMMITTEE_SIZE,
+    BYTES_PER_LOGS_BLOOM,
+    MAX_EXTRA_DATA_BYTES,
+    MAX_BYTES_PER_TRANSACTION,
+    MAX_TRANSACTIONS_PER_PAYLOAD,
+    MAX_WITHDRAWALS_PER_PAYLOAD,
+    MAX_BLS_TO_EXECUTION_CHANGES,
+    MAX_BLOB_COMMITMENTS_PER_BLOCK,
+    MAX_DEPOSIT_REQUESTS_PER_PAYLOAD,
+    MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD,
+    MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD,
+>;
+
 pub struct SyncCommitteeProver<C: Config> {
     pub primary_url: String,
     pub providers: Vec<String>,
@@ -136,28 +205,7 @@ impl<C: Config> SyncCommitteeProver<C> {
     }
 
     #[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
-    pub async fn fetch_block(
-        &self,
-        block_id: &str,
-    ) -> Result<
-        BeaconBlock<
-            MAX_PROPOSER_SLASHINGS,
-            MAX_VALIDATORS_PER_COMMITTEE,
-            MAX_ATTESTER_SLASHINGS,
-            MAX_ATTESTATIONS,
-            MAX_DEPOSITS,
-            MAX_VOLUNTARY_EXITS,
-            SYNC_COMMITTEE_SIZE,
-            BYTES_PER_LOGS_BLOOM,
-            MAX_EXTRA_DATA_BYTES,
-            MAX_BYTES_PER_TRANSACTION,
-            MAX_TRANSACTIONS_PER_PAYLOAD,
-            MAX_WITHDRAWALS_PER_PAYLOAD,
-            MAX_BLS_TO_EXECUTION_CHANGES,
-            MAX_BLOB_COMMITMENTS_PER_BLOCK,
-        >,
-        anyhow::Error,
-    > {
+    pub async fn fetch_block(&self, block_id: &str) -> Result<VersionedBeaconBlock, anyhow::Error> {
         trace!(target: "sync-committee-prover", "Fetching block {block_id}");
         let path = block_route(block_id);
         let full_url = self.generate_route(&path)?;
@@ -172,7 +220,12 @@ impl<C: Config> SyncCommitteeProver<C> {
             .await
             .map_err(|e| anyhow!("Failed to fetch block with id {block_id} due to error {e:?}"))?;
 
-        let beacon_block = response_data.data.message;
+        let beacon_block = match response_data {
+            responses::beacon_block_response::Response::Deneb(data) =>
+                VersionedBeaconBlock::Deneb(data.message),
+            responses::beacon_block_response::Response::Electra(data) =>
+                VersionedBeaconBlock::Electra(data.message),
+        };
 
         Ok(beacon_block)
     }
@@ -210,11 +263,26 @@ impl<C: Config> SyncCommitteeProver<C> {
         Ok(validator)
     }
 
+    pub async fn fetch_committees(
+        &self,
+        state_id: &str,
+        epoch: u64,
+    ) -> Result<Vec<Committee>, anyhow::Error> {
+        let path = committees_route(state_id, epoch);
+        let full_url = self.generate_route(&path)?;
+
+        let response = self.client.get(full_url).send().await?;
+
+        let response_data = response.json::<responses::committee_response::Response>().await?;
+
+        Ok(response_data.data)
+    }
+
     #[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
     pub async fn fetch_beacon_state(
         &self,
         state_id: &str,
-    ) -> Result<BeaconStateType, anyhow::Error> {
+    ) -> Result<VersionedBeaconState, anyhow::Error> {
         trace!(target: "sync-committee-prover", "Fetching beacon state {state_id}");
         let path = beacon_state_route(state_id);
         let full_url = self.generate_route(&path)?;
@@ -230,7 +298,12 @@ impl<C: Config> SyncCommitteeProver<C> {
                 anyhow!("Failed to fetch beacon state with id {state_id} due to error {e:?}")
             })?;
 
-        let beacon_state = response_data.data;
+        let beacon_state = match response_data {
+            responses::beacon_state_response::Response::Deneb(state) =>
+                VersionedBeaconState::Deneb(state),
+            responses::beacon_state_response::Response::Electra(state) =>
+                VersionedBeaconState::Electra(state),
+        };
 
         Ok(beacon_state)
     }
@@ -270,10 +343,10 @@ impl<C: Config> SyncCommitteeProver<C> {
         let state_period = client_state.state_period;
         loop {
             // Some checks on the epoch finalized by the signature block
-            let parent_root = block.parent_root;
+            let parent_root = block.parent_root();
             let parent_block_id = get_block_id(parent_root);
             let parent_block = self.fetch_block(&parent_block_id).await?;
-            let parent_state_id = get_block_id(parent_block.state_root);
+            let parent_state_id = get_block_id(parent_block.state_root());
             let parent_block_finality_checkpoint =
                 self.fetch_finalized_checkpoint(Some(&parent_state_id)).await?.finalized;
             if parent_block_finality_checkpoint.epoch <= client_state.latest_finalized_epoch {
@@ -281,9 +354,9 @@ impl<C: Config> SyncCommitteeProver<C> {
                 return Ok(None);
             }
 
-            let num_signatures = block.body.sync_aggregate.sync_committee_bits.count_ones();
+            let num_signatures = block.sync_aggregate().sync_committee_bits.count_ones();
 
-            let signature_period = compute_sync_committee_period_at_slot::<C>(block.slot);
+            let signature_period = compute_sync_committee_period_at_slot::<C>(block.slot());
 
             if num_signatures >= min_signatures &&
                 (state_period..=state_period + 1).contains(&signature_period) &&
@@ -294,35 +367,35 @@ impl<C: Config> SyncCommitteeProver<C> {
             block = parent_block;
         }
 
-        let attested_block_id = get_block_id(block.parent_root);
+        let attested_block_id = get_block_id(block.parent_root());
         let attested_header = self.fetch_header(&attested_block_id).await?;
         let mut attested_state =
             self.fetch_beacon_state(&get_block_id(attested_header.state_root)).await?;
-        if attested_state.finalized_checkpoint.root == Node::default() {
+        if attested_state.finalized_checkpoint().root == Node::default() {
             return Ok(None);
         }
-        let finalized_block_id = get_block_id(attested_state.finalized_checkpoint.root);
+        let finalized_block_id = get_block_id(attested_state.finalized_checkpoint().root.clone());
         let finalized_header = self.fetch_header(&finalized_block_id).await?;
         let mut finalized_state =
             self.fetch_beacon_state(&get_block_id(finalized_header.state_root)).await?;
         let finality_proof = FinalityProof {
-            epoch: attested_state.finalized_checkpoint.epoch,
+            epoch: attested_state.finalized_checkpoint().epoch,
             finality_branch: prove_finalized_header::<C>(&mut attested_state)?,
         };
 
         let execution_payload_proof = prove_execution_payload::<C>(&mut finalized_state)?;
 
-        let signature_period = compute_sync_committee_period_at_slot::<C>(block.slot);
+        let signature_period = compute_sync_committee_period_at_slot::<C>(block.slot());
         let client_state_next_sync_committee_root =
             client_state.next_sync_committee.hash_tree_root()?;
         let attested_state_current_sync_committee_root =
-            attested_state.current_sync_committee.hash_tree_root()?;
+            attested_state.current_sync_committee().clone().hash_tree_root()?;
         let sync_committee_update =
             // We must make sure we switch the sync comittee only when the finalized header has changed sync committees
             if should_have_sync_committee_update(state_period, signature_period) && client_state_next_sync_committee_root == attested_state_current_sync_committee_root {
                 let sync_committee_proof = prove_sync_committee_update::<C>(&mut attested_state)?;
                 Some(SyncCommitteeUpdate {
-                    next_sync_committee: attested_state.next_sync_committee,
+                    next_sync_committee: attested_state.next_sync_committee().clone(),
                     next_sync_committee_branch: sync_committee_proof,
                 })
             } else {
@@ -336,13 +409,218 @@ impl<C: Config> SyncCommitteeProver<C> {
             finalized_header,
             execution_payload: execution_payload_proof,
             finality_proof,
-            sync_aggregate: block.body.sync_aggregate,
-            signature_slot: block.slot,
+            sync_aggregate: block.sync_aggregate().clone(),
+            signature_slot: block.slot(),
         };
 
         Ok(Some(light_client_update))
     }
 
+    /// Returns the checkpoint root for the given epoch, this is the root of the block at the start
+    /// slot of the epoch or the latest block before it if the slot was empty.
+    pub async fn fetch_checkpoint_root(&self, epoch: u64) -> Result<Root, anyhow::Error> {
+        let mut slot = epoch * C::SLOTS_PER_EPOCH;
+        let mut count = 0;
+        let mut header = loop {
+            // Prevent an infinite loop
+            if count == 100 {
+                return Err(anyhow!("Could not find a block for the checkpoint at epoch {epoch}"));
+            }
+
+            if let Ok(header) = self.fetch_header(&slot.to_string()).await {
+                break header;
+            } else if slot == 0 {
+                return Err(anyhow!("Could not find a block for the checkpoint at epoch {epoch}"));
+            } else {
+                slot -= 1;
+                count += 1;
+            }
+        };
+
+        Ok(header.hash_tree_root()?)
+    }
+
+    /// Fetches all aggregated attestations included on chain that vote for the `source -> target`
+    /// link, converted to [`IndexedAttestation`]s. Electra aggregates span all the committees
+    /// selected by their `committee_bits`.
+    pub async fn fetch_link_attestations(
+        &self,
+        source: &Checkpoint,
+        target: &Checkpoint,
+    ) -> Result<Vec<IndexedAttestation<MAX_VALIDATORS_PER_SLOT>>, anyhow::Error> {
+        let start_slot = target.epoch * C::SLOTS_PER_EPOCH;
+        let committees = self
+            .fetch_committees(&start_slot.to_string(), target.epoch)
+            .await?
+            .into_iter()
+            .map(|committee| {
+                let validators = committee
+                    .validators
+                    .iter()
+                    .map(|index| index.parse::<u64>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(((committee.slot.parse::<u64>()?, committee.index.parse::<u64>()?), validators))
+            })
+            .collect::<Result<BTreeMap<_, _>, anyhow::Error>>()?;
+
+        let mut indexed_attestations = vec![];
+        // Attestations for an epoch can be included in blocks up to the end of the next epoch
+        for slot in start_slot..start_slot + (2 * C::SLOTS_PER_EPOCH) {
+            let Ok(block) = self.fetch_block(&slot.to_string()).await else { continue };
+            // (data, signature, attesting validators, aggregation bits)
+            let attestations = match block {
+                VersionedBeaconBlock::Deneb(block) => block
+                    .body
+                    .attestations
+                    .into_iter()
+                    .map(|attestation| {
+                        let committee = committees
+                            .get(&(attestation.data.slot, attestation.data.index))
+                            .cloned()
+                            .ok_or_else(|| anyhow!("Committee for attestation not found"))?;
+                        let bits = attestation.aggregation_bits.iter().map(|bit| *bit).collect();
+                        Ok((attestation.data, attestation.signature, committee, bits))
+                    })
+                    .collect::<Result<Vec<(_, _, Vec<u64>, Vec<bool>)>, anyhow::Error>>()?,
+                VersionedBeaconBlock::Electra(block) => block
+                    .body
+                    .attestations
+                    .into_iter()
+                    .map(|attestation| {
+                        // The aggregation bits are the concatenation of the participation bits of
+                        // every committee selected by `committee_bits`, in ascending order.
+                        let mut validators = vec![];
+                        for (index, bit) in attestation.committee_bits.iter().enumerate() {
+                            if !*bit {
+                                continue;
+                            }
+                            let committee = committees
+                                .get(&(attestation.data.slot, index as u64))
+                                .ok_or_else(|| anyhow!("Committee for attestation not found"))?;
+                            validators.extend_from_slice(committee);
+                        }
+                        let bits = attestation.aggregation_bits.iter().map(|bit| *bit).collect();
+                        Ok((attestation.data, attestation.signature, validators, bits))
+                    })
+                    .collect::<Result<Vec<(_, _, Vec<u64>, Vec<bool>)>, anyhow::Error>>()?,
+            };
+
+            for (data, signature, validators, bits) in attestations {
+                if &data.source != source || &data.target != target {
+                    continue;
+                }
+
+                let attesting_indices = validators
+                    .iter()
+                    .zip(bits.iter())
+                    .filter_map(|(index, bit)| if *bit { Some(*index) } else { None })
+                    .collect::<Vec<_>>();
+
+                indexed_attestations.push(IndexedAttestation {
+                    attesting_indices: List::try_from(attesting_indices)
+                        .map_err(|e| anyhow!("{:?}", e))?,
+                    data,
+                    signature,
+                });
+            }
+        }
+
+        Ok(indexed_attestations)
+    }
+
+    /// Fetches the latest supermajority link that can be verified by the casper ffg client,
+    /// starting from its trusted justified checkpoint. Links which finalize the trusted justified
+    /// checkpoint are preferred.
+    pub async fn fetch_casper_ffg_update(
+        &self,
+        client_state: CasperFfgState,
+    ) -> Result<Option<CasperFfgUpdate>, anyhow::Error> {
+        let source = client_state.current_justified_checkpoint.clone();
+        let checkpoints = self.fetch_finalized_checkpoint(Some("head")).await?;
+        if checkpoints.current_justified.epoch <= source.epoch {
+            trace!(target: "sync-committee-prover", "No new epoch justified yet {}", checkpoints.current_justified.epoch);
+            return Ok(None);
+        }
+
+        let get_block_id = |root: Root| {
+            let mut block_id = hex::encode(root.0.to_vec());
+            block_id.insert_str(0, "0x");
+            block_id
+        };
+        // The validator records are proven against the trusted validator registry
+        let mut validator_set_state = self
+            .fetch_beacon_state(&get_block_id(client_state.validator_set.state_root.clone()))
+            .await?;
+
+        for target_epoch in (source.epoch + 1)..=checkpoints.current_justified.epoch {
+            let target = Checkpoint {
+                epoch: target_epoch,
+                root: self.fetch_checkpoint_root(target_epoch).await?,
+            };
+            let attestations = self.fetch_link_attestations(&source, &target).await?;
+
+            let attesting_indices = attestations
+                .iter()
+                .flat_map(|attestation| attestation.attesting_indices.iter().cloned())
+                .collect::<BTreeSet<_>>();
+            let attesting_balance = attesting_indices
+                .iter()
+                .filter_map(|index| validator_set_state.validators().get(*index as usize))
+                .filter(|validator| {
+                    counts_towards_total_balance(validator) &&
+                        validator.activation_epoch <= target_epoch
+                })
+                .fold(0u64, |acc, validator| acc + validator.effective_balance);
+            if attesting_balance * 3 < client_state.validator_set.total_balance * 2 {
+                trace!(target: "sync-committee-prover", "Link to epoch {target_epoch} does not have a supermajority");
+                continue;
+            }
+
+            let validators = attesting_indices
+                .iter()
+                .map(|index| {
+                    let validator = validator_set_state
+                        .validators()
+                        .get(*index as usize)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("Validator {index} not found in registry"))?;
+                    Ok(ValidatorRecord { index: *index, validator })
+                })
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            let multi_proof = prove_validators::<C>(
+                &mut validator_set_state,
+                attesting_indices.into_iter().collect::<Vec<_>>().as_slice(),
+            )?;
+
+            let finalized_header = if target_epoch == source.epoch + 1 {
+                let header = self.fetch_header(&get_block_id(source.root.clone())).await?;
+                let mut finalized_state =
+                    self.fetch_beacon_state(&get_block_id(header.state_root.clone())).await?;
+                let execution_payload = prove_execution_payload::<C>(&mut finalized_state)?;
+                // Rotate the validator set once per sync committee period
+                let validator_set_update = if source.epoch - client_state.validator_set.epoch >=
+                    C::EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+                {
+                    prove_validator_set_update::<C>(&validator_set_state, &mut finalized_state)?
+                } else {
+                    None
+                };
+
+                Some(FinalizedHeaderUpdate { header, execution_payload, validator_set_update })
+            } else {
+                None
+            };
+
+            return Ok(Some(CasperFfgUpdate {
+                attestations,
+                validators_proof: ValidatorsProof { validators, multi_proof },
+                finalized_header,
+            }));
+        }
+
+        Ok(None)
+    }
+
     #[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
     pub async fn latest_update_for_period(
         &self,
@@ -376,29 +654,29 @@ impl<C: Config> SyncCommitteeProver<C> {
             block_id
         };
         loop {
-            let num_signatures = block.body.sync_aggregate.sync_committee_bits.count_ones();
+            let num_signatures = block.sync_aggregate().sync_committee_bits.count_ones();
             if num_signatures >= min_signatures {
                 break;
             }
 
-            let parent_root = block.parent_root;
+            let parent_root = block.parent_root();
             let parent_block_id = get_block_id(parent_root);
             let parent_block = self.fetch_block(&parent_block_id).await?;
 
             block = parent_block;
         }
 
-        let attested_block_id = get_block_id(block.parent_root);
+        let attested_block_id = get_block_id(block.parent_root());
 
         let attested_header = self.fetch_header(&attested_block_id).await?;
         let mut attested_state =
             self.fetch_beacon_state(&get_block_id(attested_header.state_root)).await?;
-        let finalized_block_id = get_block_id(attested_state.finalized_checkpoint.root);
+        let finalized_block_id = get_block_id(attested_state.finalized_checkpoint().root.clone());
         let finalized_header = self.fetch_header(&finalized_block_id).await?;
         let mut finalized_state =
             self.fetch_beacon_state(&get_block_id(finalized_header.state_root)).await?;
         let finality_proof = FinalityProof {
-            epoch: attested_state.finalized_checkpoint.epoch,
+            epoch: attested_state.finalized_checkpoint().epoch,
             finality_branch: prove_finalized_header::<C>(&mut attested_state)?,
         };
 
@@ -407,7 +685,7 @@ impl<C: Config> SyncCommitteeProver<C> {
         let sync_committee_update = {
             let sync_committee_proof = prove_sync_committee_update::<C>(&mut attested_state)?;
             Some(SyncCommitteeUpdate {
-                next_sync_committee: attested_state.next_sync_committee,
+                next_sync_committee: attested_state.next_sync_committee().clone(),
                 next_sync_committee_branch: sync_committee_proof,
             })
         };
@@ -419,8 +697,8 @@ impl<C: Config> SyncCommitteeProver<C> {
             finalized_header,
             execution_payload: execution_payload_proof,
             finality_proof,
-            sync_aggregate: block.body.sync_aggregate,
-            signature_slot: block.slot,
+            sync_aggregate: block.sync_aggregate().clone(),
+            signature_slot: block.slot(),
         };
 
         Ok(light_client_update)
@@ -429,7 +707,7 @@ impl<C: Config> SyncCommitteeProver<C> {
 
 #[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
 pub fn prove_execution_payload<C: Config>(
-    beacon_state: &mut BeaconStateType,
+    beacon_state: &mut VersionedBeaconState,
 ) -> anyhow::Result<ExecutionPayloadProof> {
     trace!(target: "sync-committee-prover", "Proving execution payload");
     let indices = [
@@ -437,51 +715,115 @@ pub fn prove_execution_payload<C: Config>(
         C::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize,
         C::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize,
     ];
+    let execution_payload_header = beacon_state.latest_execution_payload_header_mut();
     // generate multi proofs
-    let multi_proof = ssz_rs::generate_proof(
-        &mut beacon_state.latest_execution_payload_header,
-        indices.as_slice(),
-    )?;
+    let multi_proof = ssz_rs::generate_proof(execution_payload_header, indices.as_slice())?;
+    let state_root = H256::from_slice(execution_payload_header.state_root.as_slice());
+    let block_number = execution_payload_header.block_number;
+    let timestamp = execution_payload_header.timestamp;
 
+    let execution_payload_index = beacon_state.execution_payload_index();
     Ok(ExecutionPayloadProof {
-        state_root: H256::from_slice(
-            beacon_state.latest_execution_payload_header.state_root.as_slice(),
-        ),
-        block_number: beacon_state.latest_execution_payload_header.block_number,
-        timestamp: beacon_state.latest_execution_payload_header.timestamp,
+        state_root,
+        block_number,
+        timestamp,
         multi_proof,
-        execution_payload_branch: ssz_rs::generate_proof(
-            beacon_state,
-            &[EXECUTION_PAYLOAD_INDEX as usize],
-        )?,
+        execution_payload_branch: beacon_state
+            .generate_proof(&[execution_payload_index as usize])?,
     })
 }
 
 #[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
 pub fn prove_sync_committee_update<C: Config>(
-    state: &mut BeaconStateType,
+    state: &mut VersionedBeaconState,
 ) -> anyhow::Result<Vec<Node>> {
     trace!(target: "sync-committee-prover", "Proving sync committee update");
-    let proof = ssz_rs::generate_proof(state, &[NEXT_SYNC_COMMITTEE_INDEX as usize])?;
+    let index = state.next_sync_committee_index();
+    let proof = state.generate_proof(&[index as usize])?;
     Ok(proof)
 }
 
 #[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
-pub fn prove_finalized_header<C: Config>(state: &mut BeaconStateType) -> anyhow::Result<Vec<Node>> {
+pub fn prove_finalized_header<C: Config>(
+    state: &mut VersionedBeaconState,
+) -> anyhow::Result<Vec<Node>> {
     trace!(target: "sync-committee-prover", "Proving finalized head");
-    let indices = [FINALIZED_ROOT_INDEX as usize];
-    let proof = ssz_rs::generate_proof(state, indices.as_slice())?;
+    let indices = [state.finalized_root_index() as usize];
+    let proof = state.generate_proof(indices.as_slice())?;
 
     Ok(proof)
 }
 
+#[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
+pub fn prove_validator_set<C: Config>(
+    state: &mut VersionedBeaconState,
+) -> anyhow::Result<Vec<Node>> {
+    trace!(target: "sync-committee-prover", "Proving validator set");
+    let index = state.validators_index();
+    let proof = state.generate_proof(&[index as usize])?;
+    Ok(proof)
+}
+
+/// Proves the validator records that changed between the registries of `trusted_state` and
+/// `state`. Returns `None` if the registries are identical.
+pub fn prove_validator_set_update<C: Config>(
+    trusted_state: &VersionedBeaconState,
+    state: &mut VersionedBeaconState,
+) -> anyhow::Result<Option<ValidatorSetUpdate>> {
+    let previous_validators = trusted_state.validators();
+    let changes = state
+        .validators()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, validator)| {
+            let previous = previous_validators.get(index).cloned();
+            (previous.as_ref() != Some(validator)).then(|| ValidatorChange {
+                index: index as u64,
+                previous,
+                validator: validator.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    let indices = changes.iter().map(|change| change.index).collect::<Vec<_>>();
+    let mut multi_proof = prove_validators::<C>(state, &indices)?;
+    // The last helper is the length mix-in, the update is proven against the data tree only.
+    multi_proof.pop();
+
+    Ok(Some(ValidatorSetUpdate {
+        changes,
+        previous_count: previous_validators.len() as u64,
+        validator_count: state.validators().len() as u64,
+        multi_proof,
+        validators_branch: prove_validator_set::<C>(state)?,
+    }))
+}
+
+/// Generates a multi proof for the validators at the given indices in `state.validators`
+#[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
+pub fn prove_validators<C: Config>(
+    state: &mut VersionedBeaconState,
+    indices: &[u64],
+) -> anyhow::Result<Vec<Node>> {
+    trace!(target: "sync-committee-prover", "Proving validators");
+    let indices = indices
+        .iter()
+        .map(|index| ((2u64 << VALIDATOR_REGISTRY_LIMIT_LOG2) + index) as usize)
+        .collect::<Vec<_>>();
+    let proof = ssz_rs::generate_proof(state.validators_mut(), indices.as_slice())?;
+    Ok(proof)
+}
+
 pub fn prove_block_roots_proof<C: Config>(
-    state: &mut BeaconStateType,
+    state: &mut VersionedBeaconState,
     mut header: BeaconBlockHeader,
 ) -> anyhow::Result<AncestryProof> {
     // Check if block root should still be part of the block roots vector on the beacon state
     let epoch_for_header = compute_epoch_at_slot::<C>(header.slot) as usize;
-    let epoch_for_state = compute_epoch_at_slot::<C>(state.slot) as usize;
+    let epoch_for_state = compute_epoch_at_slot::<C>(state.slot()) as usize;
 
     if epoch_for_state.saturating_sub(epoch_for_header) >=
         SLOTS_PER_HISTORICAL_ROOT / C::SLOTS_PER_EPOCH as usize
@@ -491,19 +833,20 @@ pub fn prove_block_roots_proof<C: Config>(
     } else {
         // Get index of block root in the block roots
         let block_root = header.hash_tree_root().expect("hash tree root should be valid");
-        let block_index = state
-            .block_roots
+        let block_roots = state.block_roots_mut();
+        let block_index = block_roots
             .as_ref()
             .into_iter()
             .position(|root| root == &block_root)
             .expect("Block root should exist in block_roots");
 
-        let proof = ssz_rs::generate_proof(&mut state.block_roots, &[block_index])?;
+        let proof = ssz_rs::generate_proof(block_roots, &[block_index])?;
 
         let block_roots_proof =
             BlockRootsProof { block_header_index: block_index as u64, block_header_branch: proof };
 
-        let block_roots_branch = ssz_rs::generate_proof(state, &[BLOCK_ROOTS_INDEX as usize])?;
+        let block_roots_index = state.block_roots_index();
+        let block_roots_branch = state.generate_proof(&[block_roots_index as usize])?;
         Ok(AncestryProof::BlockRoots { block_roots_proof, block_roots_branch })
     }
 }
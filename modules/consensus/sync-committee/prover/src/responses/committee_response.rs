@@ -0,0 +1,12 @@
+#[derive(Default, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Response {
+    pub(crate) data: Vec<Committee>,
+    execution_optimistic: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Committee {
+    pub index: String,
+    pub slot: String,
+    pub validators: Vec<String>,
+}
@@ -1,6 +1,7 @@
 pub mod beacon_block_header_response;
 pub mod beacon_block_response;
 pub mod beacon_state_response;
+pub mod committee_response;
 pub mod finality_checkpoint_response;
 pub mod sync_committee_response;
 pub mod validator_response;
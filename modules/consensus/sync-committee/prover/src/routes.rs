@@ -19,3 +19,6 @@ pub fn beacon_state_route(state_id: &str) -> String {
 pub fn finality_checkpoints(state_id: &str) -> String {
     format!("/eth/v1/beacon/states/{state_id}/finality_checkpoints")
 }
+pub fn committees_route(state_id: &str, epoch: u64) -> String {
+    format!("/eth/v1/beacon/states/{state_id}/committees?epoch={epoch}")
+}
@@ -3,12 +3,14 @@ use reqwest_eventsource::EventSource;
 
 use ssz_rs::{calculate_multi_merkle_root, is_valid_merkle_branch, GeneralizedIndex, Merkleized};
 use sync_committee_primitives::{
-    constants::{
-        devnet::Devnet, Root, EXECUTION_PAYLOAD_INDEX_LOG2, NEXT_SYNC_COMMITTEE_INDEX_LOG2,
-    },
+    casper_ffg::{counts_towards_total_balance, ValidatorSetCommitment},
+    constants::{devnet::Devnet, Root},
     types::VerifierState,
+    util::{execution_payload_gindex, finalized_root_gindex, next_sync_committee_gindex},
+};
+use sync_committee_verifier::{
+    casper_ffg::verify_casper_ffg_update, verify_sync_committee_attestation,
 };
-use sync_committee_verifier::verify_sync_committee_attestation;
 use tokio_stream::StreamExt;
 
 #[allow(non_snake_case)]
@@ -54,7 +56,7 @@ async fn state_root_and_block_header_root_matches() {
     let sync_committee_prover = setup_prover();
     let mut beacon_state = sync_committee_prover.fetch_beacon_state("head").await.unwrap();
 
-    let block_header = sync_committee_prover.fetch_header(&beacon_state.slot.to_string()).await;
+    let block_header = sync_committee_prover.fetch_header(&beacon_state.slot().to_string()).await;
     assert!(block_header.is_ok());
 
     let block_header = block_header.unwrap();
@@ -79,11 +81,14 @@ async fn test_finalized_header() {
     let sync_committee_prover = setup_prover();
     let mut state = sync_committee_prover.fetch_beacon_state("head").await.unwrap();
 
-    let proof = ssz_rs::generate_proof(&mut state, &vec![FINALIZED_ROOT_INDEX as usize]).unwrap();
+    let (finalized_root_index, _) =
+        finalized_root_gindex::<Devnet>(compute_epoch_at_slot::<Devnet>(state.slot()));
+    let proof = prove_finalized_header::<Devnet>(&mut state).unwrap();
 
     let leaves = vec![Node::from_bytes(
         state
-            .finalized_checkpoint
+            .finalized_checkpoint()
+            .clone()
             .hash_tree_root()
             .unwrap()
             .as_ref()
@@ -93,7 +98,7 @@ async fn test_finalized_header() {
     let root = calculate_multi_merkle_root(
         &leaves,
         &proof,
-        &[GeneralizedIndex(FINALIZED_ROOT_INDEX as usize)],
+        &[GeneralizedIndex(finalized_root_index as usize)],
     );
     assert_eq!(root, state.hash_tree_root().unwrap());
 }
@@ -105,7 +110,9 @@ async fn test_execution_payload_proof() {
     let sync_committee_prover = setup_prover();
 
     let mut finalized_state = sync_committee_prover.fetch_beacon_state("head").await.unwrap();
-    let block_id = finalized_state.slot.to_string();
+    let block_id = finalized_state.slot().to_string();
+    let (execution_payload_index, execution_payload_depth) =
+        execution_payload_gindex::<Devnet>(compute_epoch_at_slot::<Devnet>(finalized_state.slot()));
     let execution_payload_proof = prove_execution_payload::<Devnet>(&mut finalized_state).unwrap();
 
     let finalized_header = sync_committee_prover.fetch_header(&block_id).await.unwrap();
@@ -128,7 +135,7 @@ async fn test_execution_payload_proof() {
     );
 
     let execution_payload_hash_tree_root = finalized_state
-        .latest_execution_payload_header
+        .latest_execution_payload_header_mut()
         .clone()
         .hash_tree_root()
         .unwrap();
@@ -140,8 +147,8 @@ async fn test_execution_payload_proof() {
     let is_merkle_branch_valid = is_valid_merkle_branch(
         &execution_payload_root,
         execution_payload_branch,
-        EXECUTION_PAYLOAD_INDEX_LOG2 as usize,
-        EXECUTION_PAYLOAD_INDEX as usize,
+        execution_payload_depth as usize,
+        execution_payload_index as usize,
         &finalized_header.state_root,
     );
 
@@ -155,17 +162,20 @@ async fn test_sync_committee_update_proof() {
     let sync_committee_prover = setup_prover();
 
     let mut finalized_state = sync_committee_prover.fetch_beacon_state("head").await.unwrap();
-    let block_id = finalized_state.slot.to_string();
+    let block_id = finalized_state.slot().to_string();
+    let (next_sync_committee_index, next_sync_committee_depth) = next_sync_committee_gindex::<Devnet>(
+        compute_epoch_at_slot::<Devnet>(finalized_state.slot()),
+    );
     let finalized_header = sync_committee_prover.fetch_header(&block_id).await.unwrap();
 
     let sync_committee_proof = prove_sync_committee_update::<Devnet>(&mut finalized_state).unwrap();
 
-    let mut sync_committee = finalized_state.next_sync_committee;
+    let mut sync_committee = finalized_state.next_sync_committee().clone();
 
     let calculated_finalized_root = calculate_multi_merkle_root(
         &[sync_committee.hash_tree_root().unwrap()],
         &sync_committee_proof,
-        &[GeneralizedIndex(NEXT_SYNC_COMMITTEE_INDEX as usize)],
+        &[GeneralizedIndex(next_sync_committee_index as usize)],
     );
 
     assert_eq!(calculated_finalized_root.as_bytes(), finalized_header.state_root.as_bytes());
@@ -173,8 +183,8 @@ async fn test_sync_committee_update_proof() {
     let is_merkle_branch_valid = is_valid_merkle_branch(
         &sync_committee.hash_tree_root().unwrap(),
         sync_committee_proof.iter(),
-        NEXT_SYNC_COMMITTEE_INDEX_LOG2 as usize,
-        NEXT_SYNC_COMMITTEE_INDEX as usize,
+        next_sync_committee_depth as usize,
+        next_sync_committee_index as usize,
         &finalized_header.state_root,
     );
 
@@ -205,8 +215,8 @@ async fn test_prover() {
     let mut client_state = VerifierState {
         finalized_header: block_header.clone(),
         latest_finalized_epoch: compute_epoch_at_slot::<Devnet>(block_header.slot),
-        current_sync_committee: state.current_sync_committee,
-        next_sync_committee: state.next_sync_committee,
+        current_sync_committee: state.current_sync_committee().clone(),
+        next_sync_committee: state.next_sync_committee().clone(),
         state_period: compute_sync_committee_period_at_slot::<Devnet>(block_header.slot),
     };
 
@@ -263,6 +273,57 @@ async fn test_prover() {
     }
 }
 
+#[allow(non_snake_case)]
+#[tokio::test]
+#[ignore]
+async fn test_casper_ffg_prover() {
+    let sync_committee_prover = setup_prover();
+    let checkpoints = sync_committee_prover.fetch_finalized_checkpoint(Some("head")).await.unwrap();
+    let finalized_header = sync_committee_prover
+        .fetch_header(&format!("0x{}", hex::encode(checkpoints.finalized.root.0)))
+        .await
+        .unwrap();
+    let mut state = sync_committee_prover
+        .fetch_beacon_state(&format!("0x{}", hex::encode(finalized_header.state_root.0)))
+        .await
+        .unwrap();
+    let epoch = checkpoints.finalized.epoch;
+    let total_balance = state
+        .validators()
+        .iter()
+        .filter(|v| counts_towards_total_balance(v))
+        .fold(0u64, |acc, v| acc + v.effective_balance);
+
+    let client_state = CasperFfgState {
+        current_justified_checkpoint: checkpoints.finalized.clone(),
+        finalized_checkpoint: checkpoints.finalized,
+        finalized_header: finalized_header.clone(),
+        validator_set: ValidatorSetCommitment {
+            state_root: finalized_header.state_root,
+            validators_root: state.validators_mut().hash_tree_root().unwrap(),
+            total_balance,
+            epoch,
+        },
+    };
+
+    let update = loop {
+        if let Some(update) = sync_committee_prover
+            .fetch_casper_ffg_update(client_state.clone())
+            .await
+            .unwrap()
+        {
+            break update;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(12)).await;
+    };
+
+    let new_state = verify_casper_ffg_update::<Devnet>(client_state.clone(), update).unwrap();
+    assert!(
+        new_state.current_justified_checkpoint.epoch >
+            client_state.current_justified_checkpoint.epoch
+    );
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_switch_provider_middleware() {
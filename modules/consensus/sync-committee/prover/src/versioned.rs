@@ -0,0 +1,176 @@
+//! Beacon blocks and states whose layout depends on the fork they belong to.
+use crate::{BeaconBlockType, BeaconStateType, ElectraBeaconBlockType, ElectraBeaconStateType};
+use ssz_rs::{List, MerkleizationError, Merkleized, Node, Vector};
+use sync_committee_primitives::{
+    consensus_types::{
+        Checkpoint, ExecutionPayloadHeader, SyncAggregate, SyncCommittee, Validator,
+    },
+    constants::{
+        Root, Slot, BLOCK_ROOTS_INDEX, BLOCK_ROOTS_INDEX_ELECTRA, BYTES_PER_LOGS_BLOOM,
+        EXECUTION_PAYLOAD_INDEX, EXECUTION_PAYLOAD_INDEX_ELECTRA, FINALIZED_ROOT_INDEX,
+        FINALIZED_ROOT_INDEX_ELECTRA, MAX_EXTRA_DATA_BYTES, NEXT_SYNC_COMMITTEE_INDEX,
+        NEXT_SYNC_COMMITTEE_INDEX_ELECTRA, SLOTS_PER_HISTORICAL_ROOT, SYNC_COMMITTEE_SIZE,
+        VALIDATORS_INDEX, VALIDATORS_INDEX_ELECTRA, VALIDATOR_REGISTRY_LIMIT,
+    },
+};
+
+/// A beacon block from any of the supported forks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionedBeaconBlock {
+    Deneb(BeaconBlockType),
+    Electra(ElectraBeaconBlockType),
+}
+
+impl VersionedBeaconBlock {
+    pub fn slot(&self) -> Slot {
+        match self {
+            VersionedBeaconBlock::Deneb(block) => block.slot,
+            VersionedBeaconBlock::Electra(block) => block.slot,
+        }
+    }
+
+    pub fn parent_root(&self) -> Root {
+        match self {
+            VersionedBeaconBlock::Deneb(block) => block.parent_root.clone(),
+            VersionedBeaconBlock::Electra(block) => block.parent_root.clone(),
+        }
+    }
+
+    pub fn state_root(&self) -> Root {
+        match self {
+            VersionedBeaconBlock::Deneb(block) => block.state_root.clone(),
+            VersionedBeaconBlock::Electra(block) => block.state_root.clone(),
+        }
+    }
+
+    pub fn sync_aggregate(&self) -> &SyncAggregate<SYNC_COMMITTEE_SIZE> {
+        match self {
+            VersionedBeaconBlock::Deneb(block) => &block.body.sync_aggregate,
+            VersionedBeaconBlock::Electra(block) => &block.body.sync_aggregate,
+        }
+    }
+}
+
+/// A beacon state from any of the supported forks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionedBeaconState {
+    Deneb(BeaconStateType),
+    Electra(ElectraBeaconStateType),
+}
+
+impl VersionedBeaconState {
+    pub fn slot(&self) -> Slot {
+        match self {
+            VersionedBeaconState::Deneb(state) => state.slot,
+            VersionedBeaconState::Electra(state) => state.slot,
+        }
+    }
+
+    pub fn finalized_checkpoint(&self) -> &Checkpoint {
+        match self {
+            VersionedBeaconState::Deneb(state) => &state.finalized_checkpoint,
+            VersionedBeaconState::Electra(state) => &state.finalized_checkpoint,
+        }
+    }
+
+    pub fn current_sync_committee(&self) -> &SyncCommittee<SYNC_COMMITTEE_SIZE> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &state.current_sync_committee,
+            VersionedBeaconState::Electra(state) => &state.current_sync_committee,
+        }
+    }
+
+    pub fn next_sync_committee(&self) -> &SyncCommittee<SYNC_COMMITTEE_SIZE> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &state.next_sync_committee,
+            VersionedBeaconState::Electra(state) => &state.next_sync_committee,
+        }
+    }
+
+    pub fn validators(&self) -> &List<Validator, VALIDATOR_REGISTRY_LIMIT> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &state.validators,
+            VersionedBeaconState::Electra(state) => &state.validators,
+        }
+    }
+
+    pub fn validators_mut(&mut self) -> &mut List<Validator, VALIDATOR_REGISTRY_LIMIT> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &mut state.validators,
+            VersionedBeaconState::Electra(state) => &mut state.validators,
+        }
+    }
+
+    pub fn block_roots_mut(&mut self) -> &mut Vector<Root, SLOTS_PER_HISTORICAL_ROOT> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &mut state.block_roots,
+            VersionedBeaconState::Electra(state) => &mut state.block_roots,
+        }
+    }
+
+    pub fn latest_execution_payload_header_mut(
+        &mut self,
+    ) -> &mut ExecutionPayloadHeader<BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES> {
+        match self {
+            VersionedBeaconState::Deneb(state) => &mut state.latest_execution_payload_header,
+            VersionedBeaconState::Electra(state) => &mut state.latest_execution_payload_header,
+        }
+    }
+
+    pub fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        match self {
+            VersionedBeaconState::Deneb(state) => state.hash_tree_root(),
+            VersionedBeaconState::Electra(state) => state.hash_tree_root(),
+        }
+    }
+
+    /// Generalized index of `state.finalized_checkpoint.root`
+    pub fn finalized_root_index(&self) -> u64 {
+        match self {
+            VersionedBeaconState::Deneb(_) => FINALIZED_ROOT_INDEX,
+            VersionedBeaconState::Electra(_) => FINALIZED_ROOT_INDEX_ELECTRA,
+        }
+    }
+
+    /// Generalized index of `state.next_sync_committee`
+    pub fn next_sync_committee_index(&self) -> u64 {
+        match self {
+            VersionedBeaconState::Deneb(_) => NEXT_SYNC_COMMITTEE_INDEX,
+            VersionedBeaconState::Electra(_) => NEXT_SYNC_COMMITTEE_INDEX_ELECTRA,
+        }
+    }
+
+    /// Generalized index of `state.latest_execution_payload_header`
+    pub fn execution_payload_index(&self) -> u64 {
+        match self {
+            VersionedBeaconState::Deneb(_) => EXECUTION_PAYLOAD_INDEX,
+            VersionedBeaconState::Electra(_) => EXECUTION_PAYLOAD_INDEX_ELECTRA,
+        }
+    }
+
+    /// Generalized index of `state.validators`
+    pub fn validators_index(&self) -> u64 {
+        match self {
+            VersionedBeaconState::Deneb(_) => VALIDATORS_INDEX,
+            VersionedBeaconState::Electra(_) => VALIDATORS_INDEX_ELECTRA,
+        }
+    }
+
+    /// Generalized index of `state.block_roots`
+    pub fn block_roots_index(&self) -> u64 {
+        match self {
+            VersionedBeaconState::Deneb(_) => BLOCK_ROOTS_INDEX,
+            VersionedBeaconState::Electra(_) => BLOCK_ROOTS_INDEX_ELECTRA,
+        }
+    }
+
+    /// Generates a merkle proof for the fields at the given generalized indices of the state.
+    pub fn generate_proof(&mut self, indices: &[usize]) -> anyhow::Result<Vec<Node>> {
+        let proof = match self {
+            VersionedBeaconState::Deneb(state) => ssz_rs::generate_proof(state, indices)?,
+            VersionedBeaconState::Electra(state) => ssz_rs::generate_proof(state, indices)?,
+        };
+
+        Ok(proof)
+    }
+}
@@ -0,0 +1,282 @@
+//! Verification of Casper FFG supermajority links using the full validator set.
+
+use crate::{
+    crypto::{aggregate_public_keys, verify_signature},
+    error::Error,
+    verify_execution_payload,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use ssz_rs::{
+    calculate_multi_merkle_root, prelude::is_valid_merkle_branch, GeneralizedIndex, Merkleized,
+    Node,
+};
+use sync_committee_primitives::{
+    casper_ffg::{
+        counts_towards_total_balance, CasperFfgState, CasperFfgUpdate, ValidatorSetCommitment,
+        ValidatorSetUpdate,
+    },
+    consensus_types::{BeaconBlockHeader, Validator},
+    constants::{Config, Epoch, Gwei, Root, DOMAIN_BEACON_ATTESTER, VALIDATOR_REGISTRY_LIMIT_LOG2},
+    util::{
+        compute_domain, compute_epoch_at_slot, compute_fork_version, compute_signing_root,
+        validators_gindex,
+    },
+};
+
+/// Returns true if the validator is active and can be counted towards the FFG vote at `epoch`
+fn is_eligible_validator(validator: &Validator, epoch: Epoch) -> bool {
+    counts_towards_total_balance(validator) && validator.activation_epoch <= epoch
+}
+
+/// Verifies a supermajority link `source -> target` signed by the validators in the trusted
+/// validator set. The source of the link must be the trusted justified checkpoint, the target
+/// becomes justified and if both checkpoints are in adjacent epochs, the source is finalized.
+pub fn verify_casper_ffg_update<C: Config>(
+    trusted_state: CasperFfgState,
+    update: CasperFfgUpdate,
+) -> Result<CasperFfgState, Error> {
+    let data = update
+        .attestations
+        .first()
+        .map(|attestation| attestation.data.clone())
+        .ok_or_else(|| Error::InvalidUpdate("No attestations provided".into()))?;
+    let (source, target) = (data.source, data.target);
+
+    if update
+        .attestations
+        .iter()
+        .any(|attestation| attestation.data.source != source || attestation.data.target != target)
+    {
+        Err(Error::InvalidUpdate("Attestations must vote for the same link".into()))?
+    }
+
+    if source != trusted_state.current_justified_checkpoint {
+        Err(Error::InvalidUpdate("Link source is not the trusted justified checkpoint".into()))?
+    }
+
+    if target.epoch <= source.epoch {
+        Err(Error::InvalidUpdate("Link target must be newer than its source".into()))?
+    }
+
+    // Verify the attesting validators are members of the trusted validator set
+    let validators_proof = update.validators_proof;
+    let leaves =
+        validators_proof
+            .validators
+            .iter()
+            .map(|record| {
+                record.validator.clone().hash_tree_root().map_err(|_| {
+                    Error::MerkleizationError("Failed to hash validator record".into())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+    // `List` roots mix in the length, so the data subtree is the left child of the root.
+    let indices = validators_proof
+        .validators
+        .iter()
+        .map(|record| {
+            GeneralizedIndex(((2u64 << VALIDATOR_REGISTRY_LIMIT_LOG2) + record.index) as usize)
+        })
+        .collect::<Vec<_>>();
+    let validators_root =
+        calculate_multi_merkle_root(&leaves, &validators_proof.multi_proof, &indices);
+    if validators_root != trusted_state.validator_set.validators_root {
+        Err(Error::InvalidMerkleBranch("Validators proof".into()))?
+    }
+
+    let validators = validators_proof
+        .validators
+        .into_iter()
+        .map(|record| (record.index, record.validator))
+        .collect::<BTreeMap<_, _>>();
+
+    let fork_version = compute_fork_version::<C>(target.epoch);
+    let domain = compute_domain(
+        DOMAIN_BEACON_ATTESTER,
+        Some(fork_version),
+        Some(Root::from_bytes(C::GENESIS_VALIDATORS_ROOT.try_into().expect("Infallible"))),
+        C::GENESIS_FORK_VERSION,
+    )
+    .map_err(|_| Error::InvalidUpdate("Failed to compute domain".into()))?;
+
+    // Verify each aggregate signature, a validator's balance is only counted once
+    let mut attesters = BTreeMap::<u64, Gwei>::new();
+    for mut attestation in update.attestations {
+        let public_keys = attestation
+            .attesting_indices
+            .iter()
+            .map(|index| {
+                let validator = validators.get(index).ok_or_else(|| {
+                    Error::InvalidUpdate("Missing record for attesting validator".into())
+                })?;
+                if is_eligible_validator(validator, target.epoch) {
+                    attesters.insert(*index, validator.effective_balance);
+                }
+                Ok(validator.public_key.clone())
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let aggregate =
+            aggregate_public_keys(&public_keys).map_err(|_| Error::SignatureVerification)?;
+        let signing_root = compute_signing_root(&mut attestation.data, domain)
+            .map_err(|_| Error::InvalidRoot("Failed to compute signing root".into()))?;
+        verify_signature(aggregate, signing_root.as_bytes().to_vec(), &attestation.signature)
+            .map_err(|_| Error::SignatureVerification)?;
+    }
+
+    let attesting_balance =
+        attesters.values().fold(0u64, |acc, balance| acc.saturating_add(*balance));
+    if attesting_balance.saturating_mul(3) <
+        trusted_state.validator_set.total_balance.saturating_mul(2)
+    {
+        Err(Error::InsufficientAttestingBalance)?
+    }
+
+    // The target is now justified, the source is finalized by a link between adjacent epochs.
+    let finalizes_source = target.epoch == source.epoch + 1;
+    let Some(finalized_update) = update.finalized_header else {
+        if finalizes_source {
+            Err(Error::InvalidUpdate("Expected finalized header to be present".into()))?
+        }
+
+        return Ok(CasperFfgState { current_justified_checkpoint: target, ..trusted_state })
+    };
+
+    if !finalizes_source {
+        Err(Error::InvalidUpdate("Link does not finalize its source".into()))?
+    }
+
+    let mut finalized_header = finalized_update.header;
+    let finalized_root = finalized_header
+        .hash_tree_root()
+        .map_err(|_| Error::MerkleizationError("Error hashing finalized header".into()))?;
+    if finalized_root != source.root {
+        Err(Error::InvalidUpdate("Finalized header does not match the source checkpoint".into()))?
+    }
+
+    verify_execution_payload::<C>(finalized_update.execution_payload, &finalized_header)?;
+
+    let validator_set = match finalized_update.validator_set_update {
+        Some(validator_set_update) => verify_validator_set_update::<C>(
+            &trusted_state.validator_set,
+            validator_set_update,
+            &finalized_header,
+            source.epoch,
+        )?,
+        None => trusted_state.validator_set,
+    };
+
+    Ok(CasperFfgState {
+        current_justified_checkpoint: target,
+        finalized_checkpoint: source,
+        finalized_header,
+        validator_set,
+    })
+}
+
+/// Verifies the records that changed between the trusted validator registry and the one in the
+/// state of the finalized header, and updates the total balance accordingly.
+pub fn verify_validator_set_update<C: Config>(
+    trusted_set: &ValidatorSetCommitment,
+    update: ValidatorSetUpdate,
+    header: &BeaconBlockHeader,
+    epoch: Epoch,
+) -> Result<ValidatorSetCommitment, Error> {
+    if update.changes.is_empty() {
+        Err(Error::InvalidUpdate("Validator set update has no changes".into()))?
+    }
+
+    if update.validator_count < update.previous_count {
+        Err(Error::InvalidUpdate("Validator registry cannot shrink".into()))?
+    }
+
+    let mut total_balance = trusted_set.total_balance;
+    let mut previous_leaves = Vec::with_capacity(update.changes.len());
+    let mut leaves = Vec::with_capacity(update.changes.len());
+    let mut indices = Vec::with_capacity(update.changes.len());
+    let mut last_index = None;
+    for change in update.changes {
+        if last_index.map_or(false, |last| change.index <= last) {
+            Err(Error::InvalidUpdate("Validator changes must be sorted by index".into()))?
+        }
+        last_index = Some(change.index);
+
+        if change.index >= update.validator_count {
+            Err(Error::InvalidUpdate("Validator index exceeds the registry length".into()))?
+        }
+
+        // Slots past the end of the trusted registry are zero leaves in its data tree.
+        let previous_leaf = match change.previous {
+            Some(mut previous) if change.index < update.previous_count => {
+                if counts_towards_total_balance(&previous) {
+                    total_balance = total_balance
+                        .checked_sub(previous.effective_balance)
+                        .ok_or_else(|| Error::InvalidUpdate("Total balance underflow".into()))?;
+                }
+                previous.hash_tree_root().map_err(|_| {
+                    Error::MerkleizationError("Failed to hash validator record".into())
+                })?
+            },
+            None if change.index >= update.previous_count => Node::default(),
+            _ => Err(Error::InvalidUpdate("Previous validator record is inconsistent".into()))?,
+        };
+
+        let mut validator = change.validator;
+        if counts_towards_total_balance(&validator) {
+            total_balance = total_balance.saturating_add(validator.effective_balance);
+        }
+
+        previous_leaves.push(previous_leaf);
+        leaves.push(
+            validator
+                .hash_tree_root()
+                .map_err(|_| Error::MerkleizationError("Failed to hash validator record".into()))?,
+        );
+        indices.push(GeneralizedIndex(
+            ((1u64 << VALIDATOR_REGISTRY_LIMIT_LOG2) + change.index) as usize,
+        ));
+    }
+
+    // The same siblings prove the changed records in both data trees, so all other records match.
+    let previous_root = mix_in_length(
+        calculate_multi_merkle_root(&previous_leaves, &update.multi_proof, &indices),
+        update.previous_count,
+    )?;
+    if previous_root != trusted_set.validators_root {
+        Err(Error::InvalidMerkleBranch("Trusted validator registry".into()))?
+    }
+
+    let validators_root = mix_in_length(
+        calculate_multi_merkle_root(&leaves, &update.multi_proof, &indices),
+        update.validator_count,
+    )?;
+
+    let (validators_index, validators_depth) =
+        validators_gindex::<C>(compute_epoch_at_slot::<C>(header.slot));
+    let is_merkle_branch_valid = is_valid_merkle_branch(
+        &validators_root,
+        update.validators_branch.iter(),
+        validators_depth as usize,
+        validators_index as usize,
+        &header.state_root,
+    );
+
+    if !is_merkle_branch_valid {
+        Err(Error::InvalidMerkleBranch("Validators branch".into()))?;
+    }
+
+    Ok(ValidatorSetCommitment {
+        state_root: header.state_root.clone(),
+        validators_root,
+        total_balance,
+        epoch,
+    })
+}
+
+/// Computes the root of an ssz list from the root of its data tree and its length.
+fn mix_in_length(data_root: Node, mut length: u64) -> Result<Node, Error> {
+    let length_root = length
+        .hash_tree_root()
+        .map_err(|_| Error::MerkleizationError("Failed to hash list length".into()))?;
+    Ok(calculate_multi_merkle_root(&[data_root], &[length_root], &[GeneralizedIndex(2)]))
+}
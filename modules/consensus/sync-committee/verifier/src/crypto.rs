@@ -27,6 +27,15 @@ fn subtract_points_from_aggregate(
     Ok(subset_aggregate)
 }
 
+/// Aggregates the given public keys into a single bls12-381 public key
+pub fn aggregate_public_keys(keys: &[BlsPublicKey]) -> anyhow::Result<G1ProjectivePoint> {
+    let points = keys
+        .iter()
+        .map(|key| pubkey_to_projective(key))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(points.into_iter().fold(G1ProjectivePoint::default(), |acc, point| acc + point))
+}
+
 pub fn pairing(u: G2AffinePoint, v: G1AffinePoint) -> BLS12381Pairing {
     Bls12_381::pairing(v, u)
 }
@@ -41,7 +50,17 @@ pub fn verify_aggregate_signature(
     signature: &Signature,
 ) -> anyhow::Result<()> {
     let subset_aggregate = subtract_points_from_aggregate(aggregate, non_participants)?;
-    let aggregate_key_point: G1AffinePoint = subset_aggregate.into();
+    verify_signature(subset_aggregate, msg, signature)
+}
+
+/// Verifies a bls12-381 signature over `msg` using an already aggregated public key.
+/// Expects signature subgroup to be valid
+pub fn verify_signature(
+    aggregate: G1ProjectivePoint,
+    msg: Vec<u8>,
+    signature: &Signature,
+) -> anyhow::Result<()> {
+    let aggregate_key_point: G1AffinePoint = aggregate.into();
     let signature = bls::signature_to_point(signature).map_err(|e| anyhow!("{:?}", e))?;
 
     if !bls::signature_subgroup_check(signature) {
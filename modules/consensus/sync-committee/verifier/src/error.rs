@@ -10,6 +10,7 @@ pub enum Error {
     InvalidRoot(String),
     MerkleizationError(String),
     SignatureVerification,
+    InsufficientAttestingBalance,
 }
 
 impl Display for Error {
@@ -24,6 +25,9 @@ impl Display for Error {
             Error::InvalidRoot(err) => write!(f, "Invalid root {err:?}"),
             Error::MerkleizationError(err) => write!(f, "Merkleization error {err:?}"),
             Error::SignatureVerification => write!(f, "Signature verification failed"),
+            Error::InsufficientAttestingBalance => {
+                write!(f, "Attesting balance does not meet the supermajority threshold")
+            },
         }
     }
 }
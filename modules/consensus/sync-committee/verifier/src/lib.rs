@@ -3,8 +3,11 @@
 #[warn(unused_variables)]
 extern crate alloc;
 
+pub mod casper_ffg;
 pub mod crypto;
 pub mod error;
+#[cfg(test)]
+mod tests;
 
 use crate::{crypto::verify_aggregate_signature, error::Error};
 use alloc::vec::Vec;
@@ -13,16 +16,13 @@ use ssz_rs::{
     Node,
 };
 use sync_committee_primitives::{
-    consensus_types::Checkpoint,
-    constants::{
-        Config, Root, DOMAIN_SYNC_COMMITTEE, EXECUTION_PAYLOAD_INDEX, EXECUTION_PAYLOAD_INDEX_LOG2,
-        FINALIZED_ROOT_INDEX, FINALIZED_ROOT_INDEX_LOG2, NEXT_SYNC_COMMITTEE_INDEX,
-        NEXT_SYNC_COMMITTEE_INDEX_LOG2,
-    },
-    types::{VerifierState, VerifierStateUpdate},
+    consensus_types::{BeaconBlockHeader, Checkpoint, SyncCommittee},
+    constants::{Config, Root, DOMAIN_SYNC_COMMITTEE, SYNC_COMMITTEE_SIZE},
+    types::{ExecutionPayloadProof, VerifierState, VerifierStateUpdate},
     util::{
         compute_domain, compute_epoch_at_slot, compute_fork_version, compute_signing_root,
-        compute_sync_committee_period_at_slot, should_have_sync_committee_update,
+        compute_sync_committee_period_at_slot, execution_payload_gindex, finalized_root_gindex,
+        next_sync_committee_gindex, should_have_sync_committee_update,
     },
 };
 
@@ -31,16 +31,95 @@ pub fn verify_sync_committee_attestation<C: Config>(
     trusted_state: VerifierState,
     mut update: VerifierStateUpdate,
 ) -> Result<VerifierState, Error> {
-    if update.finality_proof.finality_branch.len() != FINALIZED_ROOT_INDEX_LOG2 as usize &&
+    let state_period = trusted_state.state_period;
+    let update_signature_period = compute_sync_committee_period_at_slot::<C>(update.signature_slot);
+    if !(state_period..=state_period + 1).contains(&update_signature_period) {
+        Err(Error::InvalidUpdate("State period does not contain signature period".into()))?
+    }
+
+    if update.attested_header.slot <= trusted_state.finalized_header.slot ||
+        update.finality_proof.epoch <= trusted_state.latest_finalized_epoch
+    {
+        Err(Error::InvalidUpdate("Update is expired".into()))?
+    }
+
+    // Verify sync committee aggregate signature
+    let sync_committee = if update_signature_period == state_period {
+        &trusted_state.current_sync_committee
+    } else {
+        &trusted_state.next_sync_committee
+    };
+
+    verify_update_proofs::<C>(sync_committee, &mut update)?;
+
+    let verifier_state = if should_have_sync_committee_update(state_period, update_signature_period)
+    {
+        if let Some(sync_committee_update) = update.sync_committee_update {
+            VerifierState {
+                finalized_header: update.finalized_header,
+                latest_finalized_epoch: update.finality_proof.epoch,
+                current_sync_committee: trusted_state.next_sync_committee,
+                next_sync_committee: sync_committee_update.next_sync_committee,
+                state_period: state_period + 1,
+            }
+        } else {
+            Err(Error::InvalidUpdate("Expected sync committee update to be present".into()))?
+        }
+    } else {
+        VerifierState {
+            finalized_header: update.finalized_header,
+            latest_finalized_epoch: update.finality_proof.epoch,
+            ..trusted_state
+        }
+    };
+
+    Ok(verifier_state)
+}
+
+/// Verifies the sync committee signature and merkle branches of an update without checking that it
+/// is newer than the trusted state. This is used for fraud proofs, whose conflicting updates may
+/// be older than the trusted state once one of them has been applied.
+pub fn verify_sync_committee_signature<C: Config>(
+    trusted_state: &VerifierState,
+    mut update: VerifierStateUpdate,
+) -> Result<(), Error> {
+    let state_period = trusted_state.state_period;
+    let update_signature_period = compute_sync_committee_period_at_slot::<C>(update.signature_slot);
+    let sync_committee = if update_signature_period == state_period {
+        &trusted_state.current_sync_committee
+    } else if update_signature_period == state_period + 1 {
+        &trusted_state.next_sync_committee
+    } else {
+        Err(Error::InvalidUpdate(
+            "Sync committee for the signature period is not in the trusted state".into(),
+        ))?
+    };
+
+    verify_update_proofs::<C>(sync_committee, &mut update)
+}
+
+/// Verifies the participation, aggregate signature and merkle branches of an update signed by the
+/// given sync committee.
+fn verify_update_proofs<C: Config>(
+    sync_committee: &SyncCommittee<SYNC_COMMITTEE_SIZE>,
+    update: &mut VerifierStateUpdate,
+) -> Result<(), Error> {
+    // The generalized indices of fields in the attested state depend on the fork it belongs to.
+    let attested_epoch = compute_epoch_at_slot::<C>(update.attested_header.slot);
+    let (finalized_root_index, finalized_root_depth) = finalized_root_gindex::<C>(attested_epoch);
+    let (next_sync_committee_index, next_sync_committee_depth) =
+        next_sync_committee_gindex::<C>(attested_epoch);
+
+    if update.finality_proof.finality_branch.len() != finalized_root_depth as usize &&
         update.sync_committee_update.is_some() &&
         update.sync_committee_update.as_ref().unwrap().next_sync_committee_branch.len() !=
-            NEXT_SYNC_COMMITTEE_INDEX_LOG2 as usize
+            next_sync_committee_depth as usize
     {
         Err(Error::InvalidUpdate("Finality branch is incorrect".into()))?
     }
 
     // Verify sync committee has super majority participants
-    let sync_committee_bits = update.sync_aggregate.sync_committee_bits;
+    let sync_committee_bits = &update.sync_aggregate.sync_committee_bits;
     let sync_aggregate_participants: u64 =
         sync_committee_bits.iter().as_bitslice().count_ones() as u64;
 
@@ -57,30 +136,9 @@ pub fn verify_sync_committee_attestation<C: Config>(
         ))?
     }
 
-    let state_period = trusted_state.state_period;
-    let update_signature_period = compute_sync_committee_period_at_slot::<C>(update.signature_slot);
-    if !(state_period..=state_period + 1).contains(&update_signature_period) {
-        Err(Error::InvalidUpdate("State period does not contain signature period".into()))?
-    }
-
-    if update.attested_header.slot <= trusted_state.finalized_header.slot ||
-        update.finality_proof.epoch <= trusted_state.latest_finalized_epoch
-    {
-        Err(Error::InvalidUpdate("Update is expired".into()))?
-    }
-
-    // Verify sync committee aggregate signature
-    let sync_committee = if update_signature_period == state_period {
-        trusted_state.current_sync_committee.clone()
-    } else {
-        trusted_state.next_sync_committee.clone()
-    };
-
-    let sync_committee_pubkeys = sync_committee.public_keys;
-
     let non_participant_pubkeys = sync_committee_bits
         .iter()
-        .zip(sync_committee_pubkeys.iter())
+        .zip(sync_committee.public_keys.iter())
         .filter_map(|(bit, key)| if !(*bit) { Some(key.clone()) } else { None })
         .collect::<Vec<_>>();
 
@@ -121,8 +179,8 @@ pub fn verify_sync_committee_attestation<C: Config>(
             .hash_tree_root()
             .map_err(|_| Error::MerkleizationError("Failed to hash finality checkpoint".into()))?,
         update.finality_proof.finality_branch.iter(),
-        FINALIZED_ROOT_INDEX_LOG2 as usize,
-        FINALIZED_ROOT_INDEX as usize,
+        finalized_root_depth as usize,
+        finalized_root_index as usize,
         &update.attested_header.state_root,
     );
 
@@ -131,7 +189,35 @@ pub fn verify_sync_committee_attestation<C: Config>(
     }
 
     // verify the associated execution header of the finalized beacon header.
-    let mut execution_payload = update.execution_payload;
+    verify_execution_payload::<C>(update.execution_payload.clone(), &update.finalized_header)?;
+
+    if let Some(mut sync_committee_update) = update.sync_committee_update.clone() {
+        let sync_root = sync_committee_update
+            .next_sync_committee
+            .hash_tree_root()
+            .map_err(|_| Error::MerkleizationError("Failed to hash next sync committee".into()))?;
+
+        let is_merkle_branch_valid = is_valid_merkle_branch(
+            &sync_root,
+            sync_committee_update.next_sync_committee_branch.iter(),
+            next_sync_committee_depth as usize,
+            next_sync_committee_index as usize,
+            &update.attested_header.state_root,
+        );
+
+        if !is_merkle_branch_valid {
+            Err(Error::InvalidMerkleBranch("Next sync committee branch".into()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies the execution payload of a finalized beacon block header.
+pub fn verify_execution_payload<C: Config>(
+    mut execution_payload: ExecutionPayloadProof,
+    header: &BeaconBlockHeader,
+) -> Result<(), Error> {
     let execution_payload_root = calculate_multi_merkle_root(
         &[
             Node::from_bytes(execution_payload.state_root.as_ref().try_into().expect("Infallible")),
@@ -151,57 +237,19 @@ pub fn verify_sync_committee_attestation<C: Config>(
         ],
     );
 
+    let (execution_payload_index, execution_payload_depth) =
+        execution_payload_gindex::<C>(compute_epoch_at_slot::<C>(header.slot));
     let is_merkle_branch_valid = is_valid_merkle_branch(
         &execution_payload_root,
         execution_payload.execution_payload_branch.iter(),
-        EXECUTION_PAYLOAD_INDEX_LOG2 as usize,
-        EXECUTION_PAYLOAD_INDEX as usize,
-        &update.finalized_header.state_root,
+        execution_payload_depth as usize,
+        execution_payload_index as usize,
+        &header.state_root,
     );
 
     if !is_merkle_branch_valid {
         Err(Error::InvalidMerkleBranch("Execution payload branch".into()))?;
     }
 
-    if let Some(mut sync_committee_update) = update.sync_committee_update.clone() {
-        let sync_root = sync_committee_update
-            .next_sync_committee
-            .hash_tree_root()
-            .map_err(|_| Error::MerkleizationError("Failed to hash next sync committee".into()))?;
-
-        let is_merkle_branch_valid = is_valid_merkle_branch(
-            &sync_root,
-            sync_committee_update.next_sync_committee_branch.iter(),
-            NEXT_SYNC_COMMITTEE_INDEX_LOG2 as usize,
-            NEXT_SYNC_COMMITTEE_INDEX as usize,
-            &update.attested_header.state_root,
-        );
-
-        if !is_merkle_branch_valid {
-            Err(Error::InvalidMerkleBranch("Next sync committee branch".into()))?;
-        }
-    }
-
-    let verifier_state = if should_have_sync_committee_update(state_period, update_signature_period)
-    {
-        if let Some(sync_committee_update) = update.sync_committee_update {
-            VerifierState {
-                finalized_header: update.finalized_header,
-                latest_finalized_epoch: update.finality_proof.epoch,
-                current_sync_committee: trusted_state.next_sync_committee,
-                next_sync_committee: sync_committee_update.next_sync_committee,
-                state_period: state_period + 1,
-            }
-        } else {
-            Err(Error::InvalidUpdate("Expected sync committee update to be present".into()))?
-        }
-    } else {
-        VerifierState {
-            finalized_header: update.finalized_header,
-            latest_finalized_epoch: update.finality_proof.epoch,
-            ..trusted_state
-        }
-    };
-
-    Ok(verifier_state)
+    Ok(())
 }
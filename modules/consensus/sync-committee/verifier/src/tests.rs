@@ -0,0 +1,428 @@
+#![cfg(test)]
+
+use crate::{
+    casper_ffg::{verify_casper_ffg_update, verify_validator_set_update},
+    error::Error,
+    verify_sync_committee_attestation, verify_sync_committee_signature,
+};
+use alloc::collections::{BTreeMap, BTreeSet};
+use bls::DST_ETHEREUM;
+use sha2::{Digest, Sha256};
+use ssz_rs::{Bitvector, List, Merkleized, Node, Vector};
+use sync_committee_primitives::{
+    casper_ffg::{
+        CasperFfgState, CasperFfgUpdate, FinalizedHeaderUpdate, ValidatorChange, ValidatorRecord,
+        ValidatorSetCommitment, ValidatorSetUpdate, ValidatorsProof,
+    },
+    consensus_types::{
+        AttestationData, BeaconBlockHeader, Checkpoint, ExecutionPayloadHeader, IndexedAttestation,
+        SyncAggregate, SyncCommittee, Validator,
+    },
+    constants::{
+        mainnet::Mainnet, BlsPublicKey, BlsSignature, Config, Root, BYTES_PER_LOGS_BLOOM,
+        DOMAIN_BEACON_ATTESTER, DOMAIN_SYNC_COMMITTEE, FAR_FUTURE_EPOCH, MAX_EXTRA_DATA_BYTES,
+        SYNC_COMMITTEE_SIZE, VALIDATOR_REGISTRY_LIMIT, VALIDATOR_REGISTRY_LIMIT_LOG2,
+    },
+    electra::MAX_VALIDATORS_PER_SLOT,
+    types::{ExecutionPayloadProof, FinalityProof, VerifierState, VerifierStateUpdate},
+    util::{
+        compute_domain, compute_epoch_at_slot, compute_fork_version, compute_signing_root,
+        execution_payload_gindex, finalized_root_gindex, validators_gindex,
+    },
+};
+
+/// Computes the nodes of a merkle tree containing the given leaves, keyed by their generalized
+/// index. Any other node is a zero hash.
+fn merkle_tree(leaves: &[(u64, Node)]) -> BTreeMap<u64, Node> {
+    let mut tree = leaves.iter().cloned().collect::<BTreeMap<_, _>>();
+    // Deeper nodes have larger generalized indices, so children are always hashed first.
+    let mut pending = tree.keys().cloned().collect::<BTreeSet<_>>();
+    while let Some(index) = pending.pop_last() {
+        let parent = index / 2;
+        if parent == 0 || tree.contains_key(&parent) {
+            continue;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(tree.get(&(parent * 2)).cloned().unwrap_or_default().as_ref());
+        hasher.update(tree.get(&(parent * 2 + 1)).cloned().unwrap_or_default().as_ref());
+        tree.insert(parent, Node::from_bytes(hasher.finalize().into()));
+        pending.insert(parent);
+    }
+    tree
+}
+
+/// Returns the merkle branch of the node at the generalized `index`.
+fn merkle_branch(tree: &BTreeMap<u64, Node>, mut index: u64) -> Vec<Node> {
+    let mut branch = vec![];
+    while index > 1 {
+        branch.push(tree.get(&(index ^ 1)).cloned().unwrap_or_default());
+        index /= 2;
+    }
+    branch
+}
+
+/// Builds the execution payload proof of a header at `slot`, returns the root of the execution
+/// payload along with the proof, whose `execution_payload_branch` is left empty.
+fn execution_payload(slot: u64, seed: u8) -> (Node, ExecutionPayloadProof) {
+    let mut execution_payload_header =
+        ExecutionPayloadHeader::<BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES>::default();
+    execution_payload_header.state_root = [seed; 32].as_slice().try_into().unwrap();
+    execution_payload_header.block_number = slot;
+    execution_payload_header.timestamp = slot * 12;
+    let multi_proof = ssz_rs::generate_proof(
+        &mut execution_payload_header,
+        &[
+            Mainnet::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize,
+            Mainnet::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize,
+            Mainnet::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize,
+        ],
+    )
+    .unwrap();
+    let execution_payload = ExecutionPayloadProof {
+        state_root: [seed; 32].into(),
+        block_number: execution_payload_header.block_number,
+        multi_proof,
+        execution_payload_branch: vec![],
+        timestamp: execution_payload_header.timestamp,
+    };
+
+    (execution_payload_header.hash_tree_root().unwrap(), execution_payload)
+}
+
+/// Builds a light client update signed by the key `secret_key` which finalizes a header at
+/// `finalized_slot`, whose contents are derived from `seed`.
+fn signed_update(
+    secret_key: bls::types::SecretKey,
+    finalized_slot: u64,
+    seed: u8,
+) -> VerifierStateUpdate {
+    let finalized_epoch = compute_epoch_at_slot::<Mainnet>(finalized_slot);
+
+    let (execution_payload_index, _) = execution_payload_gindex::<Mainnet>(finalized_epoch);
+    let (execution_payload_root, mut execution_payload) = execution_payload(finalized_slot, seed);
+    let state_tree = merkle_tree(&[(execution_payload_index, execution_payload_root)]);
+    execution_payload.execution_payload_branch =
+        merkle_branch(&state_tree, execution_payload_index);
+
+    let mut finalized_header = BeaconBlockHeader {
+        slot: finalized_slot,
+        proposer_index: seed as u64,
+        parent_root: Node::default(),
+        state_root: state_tree[&1].clone(),
+        body_root: Node::from_bytes([seed; 32]),
+    };
+
+    let attested_slot = finalized_slot + 2 * Mainnet::SLOTS_PER_EPOCH;
+    let (finalized_root_index, _) =
+        finalized_root_gindex::<Mainnet>(compute_epoch_at_slot::<Mainnet>(attested_slot));
+    let mut checkpoint =
+        Checkpoint { epoch: finalized_epoch, root: finalized_header.hash_tree_root().unwrap() };
+    let attested_state_tree =
+        merkle_tree(&[(finalized_root_index, checkpoint.hash_tree_root().unwrap())]);
+    let mut attested_header = BeaconBlockHeader {
+        slot: attested_slot,
+        proposer_index: seed as u64,
+        parent_root: Node::default(),
+        state_root: attested_state_tree[&1].clone(),
+        body_root: Node::default(),
+    };
+
+    let signature_slot = attested_slot + 1;
+    let domain = compute_domain(
+        DOMAIN_SYNC_COMMITTEE,
+        Some(compute_fork_version::<Mainnet>(compute_epoch_at_slot::<Mainnet>(signature_slot))),
+        Some(Root::from_bytes(Mainnet::GENESIS_VALIDATORS_ROOT)),
+        Mainnet::GENESIS_FORK_VERSION,
+    )
+    .unwrap();
+    let signing_root = compute_signing_root(&mut attested_header, domain).unwrap();
+    let signature =
+        bls::sign(secret_key, &signing_root.as_bytes().to_vec(), &DST_ETHEREUM.as_bytes().to_vec())
+            .unwrap();
+
+    let mut sync_committee_bits = Bitvector::<SYNC_COMMITTEE_SIZE>::default();
+    for i in 0..SYNC_COMMITTEE_SIZE {
+        sync_committee_bits.set(i, true);
+    }
+
+    VerifierStateUpdate {
+        attested_header,
+        sync_committee_update: None,
+        finalized_header,
+        execution_payload,
+        finality_proof: FinalityProof {
+            epoch: finalized_epoch,
+            finality_branch: merkle_branch(&attested_state_tree, finalized_root_index),
+        },
+        sync_aggregate: SyncAggregate {
+            sync_committee_bits,
+            sync_committee_signature: BlsSignature::try_from(signature.as_slice()).unwrap(),
+        },
+        signature_slot,
+    }
+}
+
+#[test]
+fn should_verify_conflicting_updates_after_one_has_been_applied() {
+    let secret_key = bls::keygen(&vec![7u8; 32], &vec![]);
+    let public_key = BlsPublicKey::try_from(bls::sk_to_pk(secret_key).as_slice()).unwrap();
+    // Every member signs, so the aggregate key alone determines the signing key.
+    let sync_committee = SyncCommittee::<SYNC_COMMITTEE_SIZE> {
+        public_keys: Vector::try_from(vec![public_key.clone(); SYNC_COMMITTEE_SIZE]).unwrap(),
+        aggregate_public_key: public_key,
+    };
+
+    let trusted_slot = 5 * Mainnet::SLOTS_PER_EPOCH;
+    let trusted_state = VerifierState {
+        finalized_header: BeaconBlockHeader { slot: trusted_slot, ..Default::default() },
+        latest_finalized_epoch: compute_epoch_at_slot::<Mainnet>(trusted_slot),
+        current_sync_committee: sync_committee.clone(),
+        next_sync_committee: sync_committee,
+        state_period: 0,
+    };
+
+    let finalized_slot = 10 * Mainnet::SLOTS_PER_EPOCH;
+    let update_1 = signed_update(secret_key, finalized_slot, 1);
+    let update_2 = signed_update(secret_key, finalized_slot, 2);
+
+    // The first update is applied to the trusted state
+    let trusted_state =
+        verify_sync_committee_attestation::<Mainnet>(trusted_state, update_1.clone()).unwrap();
+    assert_eq!(trusted_state.finalized_header, update_1.finalized_header);
+
+    // The conflicting update is now expired for regular consensus updates
+    assert!(matches!(
+        verify_sync_committee_attestation::<Mainnet>(trusted_state.clone(), update_2.clone()),
+        Err(Error::InvalidUpdate(_))
+    ));
+
+    // but both updates can still be verified as a fraud proof
+    verify_sync_committee_signature::<Mainnet>(&trusted_state, update_1).unwrap();
+    verify_sync_committee_signature::<Mainnet>(&trusted_state, update_2.clone()).unwrap();
+
+    let mut forged = update_2;
+    forged.finalized_header.body_root = Node::from_bytes([3u8; 32]);
+    assert!(verify_sync_committee_signature::<Mainnet>(&trusted_state, forged).is_err());
+}
+
+/// Builds an active validator with the public key of `secret_key`
+fn validator(secret_key: bls::types::SecretKey) -> Validator {
+    Validator {
+        public_key: BlsPublicKey::try_from(bls::sk_to_pk(secret_key).as_slice()).unwrap(),
+        effective_balance: 32_000_000_000,
+        exit_epoch: FAR_FUTURE_EPOCH,
+        withdrawable_epoch: FAR_FUTURE_EPOCH,
+        ..Default::default()
+    }
+}
+
+/// Proves the validators at the given indices in the data tree of the registry.
+fn prove_validator_changes(
+    registry: &mut List<Validator, VALIDATOR_REGISTRY_LIMIT>,
+    indices: &[u64],
+) -> Vec<Node> {
+    let indices = indices
+        .iter()
+        .map(|index| ((2u64 << VALIDATOR_REGISTRY_LIMIT_LOG2) + index) as usize)
+        .collect::<Vec<_>>();
+    let mut multi_proof = ssz_rs::generate_proof(registry, &indices).unwrap();
+    // Drop the length mix-in
+    multi_proof.pop();
+    multi_proof
+}
+
+/// A registry of four validators, the new registry changes the balance of the second validator and
+/// appends a fifth one.
+fn validator_registries(
+    secret_keys: &[bls::types::SecretKey],
+) -> (
+    List<Validator, VALIDATOR_REGISTRY_LIMIT>,
+    List<Validator, VALIDATOR_REGISTRY_LIMIT>,
+    ValidatorSetUpdate,
+) {
+    let validators = secret_keys.iter().map(|key| validator(*key)).collect::<Vec<_>>();
+    let registry = List::try_from(validators[..4].to_vec()).unwrap();
+
+    let mut new_validators = validators.clone();
+    new_validators[1].effective_balance = 31_000_000_000;
+    let mut new_registry = List::try_from(new_validators.clone()).unwrap();
+
+    let update = ValidatorSetUpdate {
+        changes: vec![
+            ValidatorChange {
+                index: 1,
+                previous: Some(validators[1].clone()),
+                validator: new_validators[1].clone(),
+            },
+            ValidatorChange { index: 4, previous: None, validator: new_validators[4].clone() },
+        ],
+        previous_count: 4,
+        validator_count: 5,
+        multi_proof: prove_validator_changes(&mut new_registry, &[1, 4]),
+        validators_branch: vec![],
+    };
+
+    (registry, new_registry, update)
+}
+
+/// Signs a vote for `source -> target` by the validator at `index`
+fn attestation(
+    secret_key: bls::types::SecretKey,
+    index: u64,
+    source: &Checkpoint,
+    target: &Checkpoint,
+) -> IndexedAttestation<MAX_VALIDATORS_PER_SLOT> {
+    let mut data = AttestationData {
+        slot: target.epoch * Mainnet::SLOTS_PER_EPOCH,
+        index: 0,
+        beacon_block_root: target.root.clone(),
+        source: source.clone(),
+        target: target.clone(),
+    };
+    let domain = compute_domain(
+        DOMAIN_BEACON_ATTESTER,
+        Some(compute_fork_version::<Mainnet>(target.epoch)),
+        Some(Root::from_bytes(Mainnet::GENESIS_VALIDATORS_ROOT)),
+        Mainnet::GENESIS_FORK_VERSION,
+    )
+    .unwrap();
+    let signing_root = compute_signing_root(&mut data, domain).unwrap();
+    let signature =
+        bls::sign(secret_key, &signing_root.as_bytes().to_vec(), &DST_ETHEREUM.as_bytes().to_vec())
+            .unwrap();
+
+    IndexedAttestation {
+        attesting_indices: List::try_from(vec![index]).unwrap(),
+        data,
+        signature: BlsSignature::try_from(signature.as_slice()).unwrap(),
+    }
+}
+
+#[test]
+fn should_verify_validator_set_update() {
+    let secret_keys =
+        (1..=5u8).map(|seed| bls::keygen(&vec![seed; 32], &vec![])).collect::<Vec<_>>();
+    let (mut registry, mut new_registry, mut update) = validator_registries(&secret_keys);
+    let trusted_set = ValidatorSetCommitment {
+        state_root: Node::default(),
+        validators_root: registry.hash_tree_root().unwrap(),
+        total_balance: 4 * 32_000_000_000,
+        epoch: 0,
+    };
+
+    let new_validators_root = new_registry.hash_tree_root().unwrap();
+    let (validators_index, _) = validators_gindex::<Mainnet>(0);
+    let state_tree = merkle_tree(&[(validators_index, new_validators_root.clone())]);
+    update.validators_branch = merkle_branch(&state_tree, validators_index);
+    let header = BeaconBlockHeader { state_root: state_tree[&1].clone(), ..Default::default() };
+
+    let validator_set =
+        verify_validator_set_update::<Mainnet>(&trusted_set, update.clone(), &header, 10).unwrap();
+    assert_eq!(validator_set.validators_root, new_validators_root);
+    assert_eq!(validator_set.total_balance, 4 * 32_000_000_000 + 31_000_000_000);
+    assert_eq!(validator_set.epoch, 10);
+
+    // The previous record must match the trusted registry
+    let mut forged = update.clone();
+    forged.changes[0].previous.as_mut().unwrap().effective_balance = 1_000_000_000;
+    assert!(verify_validator_set_update::<Mainnet>(&trusted_set, forged, &header, 10).is_err());
+
+    // The registry length is part of the trusted root
+    let mut forged = update.clone();
+    forged.previous_count = 5;
+    forged.changes[1].previous = Some(forged.changes[1].validator.clone());
+    assert!(verify_validator_set_update::<Mainnet>(&trusted_set, forged, &header, 10).is_err());
+
+    // The changed records must be the ones committed to in the finalized state
+    let mut forged = update;
+    forged.changes[1].validator.effective_balance = 2_048_000_000_000;
+    assert!(verify_validator_set_update::<Mainnet>(&trusted_set, forged, &header, 10).is_err());
+}
+
+#[test]
+fn should_verify_casper_ffg_update() {
+    let secret_keys =
+        (1..=5u8).map(|seed| bls::keygen(&vec![seed; 32], &vec![])).collect::<Vec<_>>();
+    let (mut registry, mut new_registry, mut validator_set_update) =
+        validator_registries(&secret_keys);
+    let trusted_validators_root = registry.hash_tree_root().unwrap();
+
+    // The source checkpoint's state commits to its execution payload and the new registry
+    let source_slot = 9 * Mainnet::SLOTS_PER_EPOCH;
+    let (execution_payload_index, _) = execution_payload_gindex::<Mainnet>(9);
+    let (validators_index, _) = validators_gindex::<Mainnet>(9);
+    let (execution_payload_root, mut execution_payload) = execution_payload(source_slot, 1);
+    let state_tree = merkle_tree(&[
+        (execution_payload_index, execution_payload_root),
+        (validators_index, new_registry.hash_tree_root().unwrap()),
+    ]);
+    execution_payload.execution_payload_branch =
+        merkle_branch(&state_tree, execution_payload_index);
+    validator_set_update.validators_branch = merkle_branch(&state_tree, validators_index);
+    let mut header = BeaconBlockHeader {
+        slot: source_slot,
+        state_root: state_tree[&1].clone(),
+        ..Default::default()
+    };
+
+    let source = Checkpoint { epoch: 9, root: header.hash_tree_root().unwrap() };
+    let target = Checkpoint { epoch: 10, root: Node::from_bytes([10u8; 32]) };
+    let trusted_state = CasperFfgState {
+        current_justified_checkpoint: source.clone(),
+        validator_set: ValidatorSetCommitment {
+            state_root: Node::default(),
+            validators_root: trusted_validators_root,
+            total_balance: 4 * 32_000_000_000,
+            epoch: 0,
+        },
+        ..Default::default()
+    };
+
+    let update_for = |indices: &[u64]| CasperFfgUpdate {
+        attestations: indices
+            .iter()
+            .map(|index| attestation(secret_keys[*index as usize], *index, &source, &target))
+            .collect(),
+        validators_proof: ValidatorsProof {
+            validators: indices
+                .iter()
+                .map(|index| ValidatorRecord {
+                    index: *index,
+                    validator: registry[*index as usize].clone(),
+                })
+                .collect(),
+            multi_proof: ssz_rs::generate_proof(
+                &mut registry.clone(),
+                &indices
+                    .iter()
+                    .map(|index| ((2u64 << VALIDATOR_REGISTRY_LIMIT_LOG2) + index) as usize)
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        },
+        finalized_header: Some(FinalizedHeaderUpdate {
+            header: header.clone(),
+            execution_payload: execution_payload.clone(),
+            validator_set_update: Some(validator_set_update.clone()),
+        }),
+    };
+
+    // Two thirds of the trusted total balance must attest to the link
+    assert!(matches!(
+        verify_casper_ffg_update::<Mainnet>(trusted_state.clone(), update_for(&[0, 1])),
+        Err(Error::InsufficientAttestingBalance)
+    ));
+
+    // The finalized header must be the source checkpoint
+    let mut forged = update_for(&[0, 1, 2]);
+    forged.finalized_header.as_mut().unwrap().header.proposer_index = 1;
+    assert!(verify_casper_ffg_update::<Mainnet>(trusted_state.clone(), forged).is_err());
+
+    let new_state =
+        verify_casper_ffg_update::<Mainnet>(trusted_state, update_for(&[0, 1, 2])).unwrap();
+    assert_eq!(new_state.current_justified_checkpoint, target);
+    assert_eq!(new_state.finalized_checkpoint, source);
+    assert_eq!(new_state.finalized_header, header);
+    assert_eq!(new_state.validator_set.validators_root, new_registry.hash_tree_root().unwrap());
+    assert_eq!(new_state.validator_set.total_balance, 4 * 32_000_000_000 + 31_000_000_000);
+}
@@ -0,0 +1,173 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of confirmed assertions in the BoLD rollup protocol.
+
+use crate::{verify_global_state, GlobalState, MachineStatus};
+use alloc::format;
+use alloy_rlp::Decodable;
+use ethabi::ethereum_types::{H160, H256};
+use evm_common::{derive_map_key, get_contract_storage_root, get_value_from_proof, prelude::*};
+use geth_primitives::CodecHeader;
+use ismp::{
+    consensus::{
+        ConsensusStateId, IntermediateState, StateCommitment, StateMachineHeight, StateMachineId,
+    },
+    error::Error,
+    host::{IsmpHost, StateMachine},
+};
+
+/// Storage layout slot for the `_assertions` map in the BoLD RollupCore contract, as laid out by
+/// the nitro-contracts v3.0.0 release which introduced BoLD. It can be confirmed with
+/// `forge inspect src/rollup/RollupCore.sol:RollupCore storage-layout`, and must be revisited
+/// whenever the rollup contracts are upgraded.
+pub const ASSERTIONS_SLOT: u64 = 117;
+
+/// Status of an assertion node in the BoLD Rollup Contract
+#[derive(codec::Encode, codec::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionStatus {
+    NoAssertion = 0,
+    Pending = 1,
+    Confirmed = 2,
+}
+
+impl TryFrom<u8> for AssertionStatus {
+    type Error = &'static str;
+
+    fn try_from(status: u8) -> Result<Self, Self::Error> {
+        match status {
+            0 => Ok(AssertionStatus::NoAssertion),
+            1 => Ok(AssertionStatus::Pending),
+            2 => Ok(AssertionStatus::Confirmed),
+            _ => Err("Invalid assertion status received"),
+        }
+    }
+}
+
+/// The state of the chain after executing an assertion
+#[derive(codec::Encode, codec::Decode, Debug)]
+pub struct AssertionState {
+    pub global_state: GlobalState,
+    pub machine_status: MachineStatus,
+    pub end_history_root: H256,
+}
+
+impl AssertionState {
+    /// https://github.com/OffchainLabs/bold/blob/main/contracts/src/rollup/RollupLib.sol
+    pub fn hash<H: IsmpHost>(&self) -> H256 {
+        // abi encode
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&self.global_state.block_hash[..]);
+        buf.extend_from_slice(&self.global_state.send_root[..]);
+        buf.extend_from_slice(&H256::from_low_u64_be(self.global_state.inbox_position)[..]);
+        buf.extend_from_slice(&H256::from_low_u64_be(self.global_state.position_in_message)[..]);
+        buf.extend_from_slice(&H256::from_low_u64_be(self.machine_status as u64)[..]);
+        buf.extend_from_slice(&self.end_history_root[..]);
+        H::keccak256(&buf)
+    }
+}
+
+#[derive(codec::Encode, codec::Decode, Debug)]
+pub struct ArbitrumBoldProof {
+    /// Arbitrum header that corresponds to the global state of the assertion
+    pub arbitrum_header: CodecHeader,
+    /// The hash of the parent of this assertion
+    pub previous_assertion_hash: H256,
+    /// State after executing this assertion as recorded in the AssertionCreated event
+    pub after_state: AssertionState,
+    /// Sequencer inbox accumulator as recorded in the AssertionCreated event
+    pub inbox_acc: H256,
+    /// Proof for the first slot of the AssertionNode struct inside the _assertions mapping in the
+    /// RollupCore
+    pub storage_proof: Vec<Vec<u8>>,
+    /// RollupCore contract proof in the ethereum world trie
+    pub contract_proof: Vec<Vec<u8>>,
+}
+
+/// https://github.com/OffchainLabs/bold/blob/main/contracts/src/rollup/RollupLib.sol
+pub fn compute_assertion_hash<H: IsmpHost>(
+    previous_assertion_hash: H256,
+    after_state: &AssertionState,
+    inbox_acc: H256,
+) -> H256 {
+    // abi encode packed
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&previous_assertion_hash[..]);
+    buf.extend_from_slice(&after_state.hash::<H>()[..]);
+    buf.extend_from_slice(&inbox_acc[..]);
+    H::keccak256(&buf)
+}
+
+/// Extracts the status from the first storage slot of an AssertionNode. The slot packs
+/// `firstChildBlock`, `secondChildBlock`, `createdAtBlock`, `isFirstChild` and `status` starting
+/// from the lowest order bytes.
+pub fn assertion_status(slot: [u8; 32]) -> Result<AssertionStatus, Error> {
+    AssertionStatus::try_from(slot[6]).map_err(|e| Error::ImplementationSpecific(e.to_string()))
+}
+
+pub fn verify_arbitrum_bold<H: IsmpHost + Send + Sync>(
+    payload: ArbitrumBoldProof,
+    root: H256,
+    rollup_core_address: H160,
+    state_machine: StateMachine,
+    consensus_state_id: ConsensusStateId,
+) -> Result<IntermediateState, Error> {
+    let storage_root =
+        get_contract_storage_root::<H>(payload.contract_proof, &rollup_core_address.0, root)?;
+
+    verify_global_state::<H>(&payload.arbitrum_header, &payload.after_state.global_state)?;
+
+    let block_number = payload.arbitrum_header.number.low_u64();
+    let timestamp = payload.arbitrum_header.timestamp;
+    let state_root = payload.arbitrum_header.state_root.0.into();
+
+    let assertion_hash = compute_assertion_hash::<H>(
+        payload.previous_assertion_hash,
+        &payload.after_state,
+        payload.inbox_acc,
+    );
+
+    let assertion_key = derive_map_key::<H>(assertion_hash.0.to_vec(), ASSERTIONS_SLOT);
+    let proof_value = match get_value_from_proof::<H>(
+        assertion_key.0.to_vec(),
+        storage_root,
+        payload.storage_proof,
+    )? {
+        Some(value) => value.clone(),
+        _ => Err(Error::MembershipProofVerificationFailed(
+            "Assertion not found in proof".to_string(),
+        ))?,
+    };
+
+    let slot = <alloy_primitives::U256 as Decodable>::decode(&mut &*proof_value)
+        .map_err(|_| {
+            Error::ImplementationSpecific(format!("Error decoding assertion {:?}", &proof_value))
+        })?
+        .to_be_bytes::<32>();
+
+    if assertion_status(slot)? != AssertionStatus::Confirmed {
+        Err(Error::ImplementationSpecific(format!(
+            "Assertion {assertion_hash:?} has not been confirmed"
+        )))?
+    }
+
+    Ok(IntermediateState {
+        height: StateMachineHeight {
+            id: StateMachineId { state_id: state_machine, consensus_state_id },
+            height: block_number,
+        },
+        commitment: StateCommitment { timestamp, overlay_root: None, state_root },
+    })
+}
@@ -17,6 +17,7 @@
 #![allow(unused_variables)]
 extern crate alloc;
 
+pub mod bold;
 #[cfg(test)]
 mod tests;
 
@@ -30,7 +31,7 @@ use ismp::{
         ConsensusStateId, IntermediateState, StateCommitment, StateMachineHeight, StateMachineId,
     },
     error::Error,
-    host::{Ethereum, IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine},
 };
 
 /// Storage layout slot for the nodes map in the Rollup Contract
@@ -58,7 +59,7 @@ impl GlobalState {
     }
 }
 
-#[derive(codec::Encode, codec::Decode, Debug)]
+#[derive(codec::Encode, codec::Decode, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MachineStatus {
     Running = 0,
     Finished = 1,
@@ -120,33 +121,43 @@ fn get_state_hash<H: IsmpHost>(
     H::keccak256(&buf)
 }
 
+/// Verifies that the arbitrum header is the one committed to by the global state
+pub(crate) fn verify_global_state<H: IsmpHost>(
+    arbitrum_header: &CodecHeader,
+    global_state: &GlobalState,
+) -> Result<(), Error> {
+    if &global_state.send_root[..] != &arbitrum_header.extra_data {
+        Err(Error::ImplementationSpecific(
+            "Arbitrum header extra data does not match send root in global state".to_string(),
+        ))?
+    }
+
+    let header: Header = arbitrum_header.into();
+    if global_state.block_hash != header.hash::<H>() {
+        Err(Error::ImplementationSpecific(
+            "Arbitrum header hash does not match block hash in global state".to_string(),
+        ))?
+    }
+
+    Ok(())
+}
+
 pub fn verify_arbitrum_payload<H: IsmpHost + Send + Sync>(
     payload: ArbitrumPayloadProof,
     root: H256,
     rollup_core_address: H160,
+    state_machine: StateMachine,
     consensus_state_id: ConsensusStateId,
 ) -> Result<IntermediateState, Error> {
     let storage_root =
         get_contract_storage_root::<H>(payload.contract_proof, &rollup_core_address.0, root)?;
 
-    let header: Header = payload.arbitrum_header.as_ref().into();
-    if &payload.global_state.send_root[..] != &payload.arbitrum_header.extra_data {
-        Err(Error::ImplementationSpecific(
-            "Arbitrum header extra data does not match send root in global state".to_string(),
-        ))?
-    }
+    verify_global_state::<H>(&payload.arbitrum_header, &payload.global_state)?;
 
     let block_number = payload.arbitrum_header.number.low_u64();
     let timestamp = payload.arbitrum_header.timestamp;
     let state_root = payload.arbitrum_header.state_root.0.into();
 
-    let header_hash = header.hash::<H>();
-    if payload.global_state.block_hash != header_hash {
-        Err(Error::ImplementationSpecific(
-            "Arbitrum header hash does not match block hash in global state".to_string(),
-        ))?
-    }
-
     let state_hash =
         get_state_hash::<H>(payload.global_state, payload.machine_status, payload.inbox_max_count);
 
@@ -176,10 +187,7 @@ pub fn verify_arbitrum_payload<H: IsmpHost + Send + Sync>(
 
     Ok(IntermediateState {
         height: StateMachineHeight {
-            id: StateMachineId {
-                state_id: StateMachine::Ethereum(Ethereum::Arbitrum),
-                consensus_state_id,
-            },
+            id: StateMachineId { state_id: state_machine, consensus_state_id },
             height: block_number,
         },
         commitment: StateCommitment { timestamp, overlay_root: None, state_root },
@@ -52,3 +52,67 @@ async fn fetch_arbitrum_node_state_hash() {
     let state_hash = hex::encode(buf);
     println!("State Hash {}", state_hash);
 }
+
+#[test]
+fn decodes_bold_assertion_status() {
+    use crate::bold::{assertion_status, AssertionStatus};
+
+    // firstChildBlock = 1, secondChildBlock = 2, createdAtBlock = 3, isFirstChild = true
+    let mut slot = [0u8; 32];
+    slot[31] = 1;
+    slot[23] = 2;
+    slot[15] = 3;
+    slot[7] = 1;
+    slot[6] = 2;
+    assert_eq!(assertion_status(slot).unwrap(), AssertionStatus::Confirmed);
+
+    slot[6] = 1;
+    assert_eq!(assertion_status(slot).unwrap(), AssertionStatus::Pending);
+
+    slot[6] = 3;
+    assert!(assertion_status(slot).is_err());
+}
+
+#[test]
+fn computes_bold_assertion_hash_and_storage_key() {
+    use crate::{
+        bold::{compute_assertion_hash, AssertionState, ASSERTIONS_SLOT},
+        GlobalState, MachineStatus,
+    };
+    use evm_common::derive_unhashed_map_key;
+
+    let after_state = AssertionState {
+        global_state: GlobalState {
+            block_hash: [0x11; 32].into(),
+            send_root: [0x22; 32].into(),
+            inbox_position: 5,
+            position_in_message: 7,
+        },
+        machine_status: MachineStatus::Finished,
+        end_history_root: [0x33; 32].into(),
+    };
+
+    // keccak256(abi.encode(afterState))
+    assert_eq!(
+        after_state.hash::<Host>().0,
+        hex!("a6af86aa590f74cae8d37f247897be63b614560e83ceb886b7c00b38af7abbae")
+    );
+
+    // keccak256(abi.encodePacked(prevAssertionHash, afterStateHash, inboxAcc))
+    let assertion_hash =
+        compute_assertion_hash::<Host>([0x44; 32].into(), &after_state, [0x55; 32].into());
+    assert_eq!(
+        assertion_hash.0,
+        hex!("415fe76fc5b095550a1d6100e59909960447b07619c19e8749f2d47f8d3cb85d")
+    );
+
+    // slot of the assertion node in the contract storage and its key in the storage trie
+    assert_eq!(
+        derive_unhashed_map_key::<Host>(assertion_hash.0.to_vec(), ASSERTIONS_SLOT).0,
+        hex!("d010ff41028cfdc63a40b8edbc7bee68b6405e297682591f3e6373f67db39016")
+    );
+    assert_eq!(
+        derive_map_key::<Host>(assertion_hash.0.to_vec(), ASSERTIONS_SLOT).0,
+        hex!("841ae2d093204e7849b38421a94eb7c42e33032df49a963e03b7203c0ed3fb81")
+    );
+}
@@ -0,0 +1,293 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ISMP BEEFY Consensus Client
+//!
+//! This allows substrate chains track the relay chain and its parachains using BEEFY
+//! finality proofs.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, collections::BTreeMap, format, vec, vec::Vec};
+use beefy_verifier_primitives::{ConsensusMessage, MmrProof};
+use codec::{Decode, Encode};
+use core::{marker::PhantomData, time::Duration};
+use ismp::{
+    consensus::{
+        ConsensusClient, ConsensusClientId, ConsensusStateId, StateCommitment, StateMachineClient,
+        VerifiedCommitments,
+    },
+    error::Error,
+    host::{IsmpHost, StateMachine},
+    messaging::{Proof, StateCommitmentHeight},
+    router::RequestResponse,
+};
+use pallet_ismp::primitives::{IsmpConsensusLog, ISMP_ID};
+use primitive_types::H256;
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_runtime::{traits::Header as _, DigestItem};
+use substrate_state_machine::SubstrateStateMachine;
+
+/// ConsensusClientId for [`BeefyConsensusClient`]
+pub const BEEFY_CONSENSUS_ID: ConsensusClientId = *b"BEEF";
+
+/// Slot duration in milliseconds
+const SLOT_DURATION: u64 = 12_000;
+
+/// The relay chain tracked by the BEEFY client
+#[derive(Debug, Clone, Copy, Encode, Decode, PartialEq, Eq)]
+pub enum RelayChain {
+    /// The polkadot relay chain
+    Polkadot,
+    /// The kusama relay chain
+    Kusama,
+}
+
+impl RelayChain {
+    /// Returns the state machine id of a parachain on this relay chain
+    pub fn state_machine(&self, para_id: u32) -> StateMachine {
+        match self {
+            RelayChain::Polkadot => StateMachine::Polkadot(para_id),
+            RelayChain::Kusama => StateMachine::Kusama(para_id),
+        }
+    }
+}
+
+/// The consensus state of the BEEFY consensus client
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub struct ConsensusState {
+    /// The trusted BEEFY light client state
+    pub light_client_state: beefy_verifier_primitives::ConsensusState,
+    /// The relay chain the parachains are on
+    pub relay_chain: RelayChain,
+    /// Parachains whose headers are tracked by this client
+    pub para_ids: Vec<u32>,
+}
+
+/// The BEEFY consensus client implementation for ISMP.
+pub struct BeefyConsensusClient<T>(PhantomData<T>);
+
+impl<T> Default for BeefyConsensusClient<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> ConsensusClient for BeefyConsensusClient<T>
+where
+    T: pallet_ismp::Config,
+{
+    fn verify_consensus(
+        &self,
+        _host: &dyn IsmpHost,
+        _consensus_state_id: ConsensusStateId,
+        trusted_consensus_state: Vec<u8>,
+        proof: Vec<u8>,
+    ) -> Result<(Vec<u8>, VerifiedCommitments), Error> {
+        let consensus_state =
+            ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|e| {
+                Error::ImplementationSpecific(format!("Cannot decode consensus state: {e:?}"))
+            })?;
+        let message = ConsensusMessage::decode(&mut &proof[..]).map_err(|e| {
+            Error::ImplementationSpecific(format!("Cannot decode beefy consensus proof: {e:?}"))
+        })?;
+
+        let (light_client_state, headers) =
+            beefy_verifier::verify_consensus(consensus_state.light_client_state, message)
+                .map_err(|e| Error::ImplementationSpecific(format!("{e}")))?;
+
+        let mut intermediates = BTreeMap::new();
+        for (para_id, header) in headers {
+            if !consensus_state.para_ids.contains(&para_id) {
+                Err(Error::ImplementationSpecific(format!("Unknown parachain with Id({para_id})")))?
+            }
+
+            if *header.number() == 0 {
+                Err(Error::ImplementationSpecific("Genesis block should not be included".into()))?
+            }
+
+            let (mut timestamp, mut overlay_root) = (0, H256::default());
+            for digest in header.digest().logs.iter() {
+                match digest {
+                    DigestItem::PreRuntime(consensus_engine_id, value)
+                        if *consensus_engine_id == AURA_ENGINE_ID =>
+                    {
+                        let slot = Slot::decode(&mut &value[..]).map_err(|e| {
+                            Error::ImplementationSpecific(format!("Cannot slot: {e:?}"))
+                        })?;
+                        timestamp = Duration::from_millis(*slot * SLOT_DURATION).as_secs();
+                    },
+                    DigestItem::Consensus(consensus_engine_id, value)
+                        if *consensus_engine_id == ISMP_ID =>
+                    {
+                        let log = IsmpConsensusLog::decode(&mut &value[..]).map_err(|_| {
+                            Error::ImplementationSpecific(
+                                "Header contains an invalid ismp consensus log".into(),
+                            )
+                        })?;
+                        overlay_root = log.child_trie_root;
+                    },
+                    // don't really care about the rest
+                    _ => {},
+                };
+            }
+
+            if timestamp == 0 {
+                Err(Error::ImplementationSpecific("Timestamp not found".into()))?
+            }
+
+            let commitment = StateCommitmentHeight {
+                commitment: StateCommitment {
+                    timestamp,
+                    overlay_root: Some(overlay_root),
+                    state_root: header.state_root,
+                },
+                height: (*header.number()).into(),
+            };
+            intermediates
+                .insert(consensus_state.relay_chain.state_machine(para_id), vec![commitment]);
+        }
+
+        let consensus_state = ConsensusState { light_client_state, ..consensus_state };
+
+        Ok((consensus_state.encode(), intermediates))
+    }
+
+    fn verify_fraud_proof(
+        &self,
+        _host: &dyn IsmpHost,
+        trusted_consensus_state: Vec<u8>,
+        proof_1: Vec<u8>,
+        proof_2: Vec<u8>,
+    ) -> Result<(), Error> {
+        let consensus_state =
+            ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|e| {
+                Error::ImplementationSpecific(format!("Cannot decode consensus state: {e:?}"))
+            })?;
+        let proof_1 = MmrProof::decode(&mut &proof_1[..]).map_err(|e| {
+            Error::ImplementationSpecific(format!("Cannot decode first fraud proof: {e:?}"))
+        })?;
+        let proof_2 = MmrProof::decode(&mut &proof_2[..]).map_err(|e| {
+            Error::ImplementationSpecific(format!("Cannot decode second fraud proof: {e:?}"))
+        })?;
+
+        if proof_1.signed_commitment.commitment.block_number !=
+            proof_2.signed_commitment.commitment.block_number
+        {
+            Err(Error::ImplementationSpecific(
+                "Fraud proofs must be for the same block number".into(),
+            ))?
+        }
+
+        let mmr_root_1 = beefy_verifier::verify_signed_commitment(
+            &consensus_state.light_client_state,
+            &proof_1.signed_commitment,
+            &proof_1.authority_proof,
+        )
+        .map_err(|e| Error::ImplementationSpecific(format!("Invalid first fraud proof: {e}")))?;
+        let mmr_root_2 = beefy_verifier::verify_signed_commitment(
+            &consensus_state.light_client_state,
+            &proof_2.signed_commitment,
+            &proof_2.authority_proof,
+        )
+        .map_err(|e| Error::ImplementationSpecific(format!("Invalid second fraud proof: {e}")))?;
+
+        if mmr_root_1 == mmr_root_2 {
+            Err(Error::ImplementationSpecific("Mmr roots are identical, no equivocation".into()))?
+        }
+
+        Ok(())
+    }
+
+    fn consensus_client_id(&self) -> ConsensusClientId {
+        BEEFY_CONSENSUS_ID
+    }
+
+    fn state_machine(&self, id: StateMachine) -> Result<Box<dyn StateMachineClient>, Error> {
+        match id {
+            StateMachine::Polkadot(_) | StateMachine::Kusama(_) =>
+                Ok(Box::new(BeefyStateMachine::<T>::default())),
+            _ => Err(Error::ImplementationSpecific(
+                "State Machine is not supported by this consensus client".into(),
+            )),
+        }
+    }
+}
+
+/// Verifies state proofs of the parachains tracked by a [`BeefyConsensusClient`]. Proofs are
+/// only accepted for parachains on the client's relay chain that are listed in its `para_ids`.
+pub struct BeefyStateMachine<T>(PhantomData<T>);
+
+impl<T> Default for BeefyStateMachine<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> BeefyStateMachine<T> {
+    /// Ensures the proof is for a parachain tracked by its consensus state
+    fn ensure_tracked(host: &dyn IsmpHost, proof: &Proof) -> Result<(), Error> {
+        let consensus_state = host.consensus_state(proof.height.id.consensus_state_id)?;
+        let consensus_state = ConsensusState::decode(&mut &consensus_state[..]).map_err(|e| {
+            Error::ImplementationSpecific(format!("Cannot decode consensus state: {e:?}"))
+        })?;
+
+        let state_id = proof.height.id.state_id;
+        let tracked = consensus_state
+            .para_ids
+            .iter()
+            .any(|para_id| consensus_state.relay_chain.state_machine(*para_id) == state_id);
+        if !tracked {
+            Err(Error::ImplementationSpecific(format!(
+                "State machine {state_id:?} is not tracked by this consensus client"
+            )))?
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> StateMachineClient for BeefyStateMachine<T>
+where
+    T: pallet_ismp::Config,
+{
+    fn verify_membership(
+        &self,
+        host: &dyn IsmpHost,
+        item: RequestResponse,
+        root: StateCommitment,
+        proof: &Proof,
+    ) -> Result<(), Error> {
+        Self::ensure_tracked(host, proof)?;
+        SubstrateStateMachine::<T>::default().verify_membership(host, item, root, proof)
+    }
+
+    fn state_trie_key(&self, request: RequestResponse) -> Vec<Vec<u8>> {
+        SubstrateStateMachine::<T>::default().state_trie_key(request)
+    }
+
+    fn verify_state_proof(
+        &self,
+        host: &dyn IsmpHost,
+        keys: Vec<Vec<u8>>,
+        root: StateCommitment,
+        proof: &Proof,
+    ) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, Error> {
+        Self::ensure_tracked(host, proof)?;
+        SubstrateStateMachine::<T>::default().verify_state_proof(host, keys, root, proof)
+    }
+}
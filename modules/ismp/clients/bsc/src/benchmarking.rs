@@ -0,0 +1,174 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for verifying BSC client updates of varying sizes.
+//!
+//! The BSC client has no pallet of its own, so runtimes run these benchmarks on [`Pallet`] after
+//! implementing [`Config`] for the runtime.
+
+use super::*;
+use alloy_primitives::FixedBytes;
+use bls::{types::SecretKey, DST_ETHEREUM};
+use bsc_verifier::primitives::{VoteAttestationData, VoteData};
+use frame_benchmarking::v2::*;
+use geth_primitives::CodecHeader;
+use ismp::util::Keccak256;
+use pallet_ismp::host::Host;
+
+/// The pallet the BSC client benchmarks are run on
+pub struct Pallet<T: Config>(frame_system::Pallet<T>);
+
+/// Configuration for the BSC client benchmarks
+pub trait Config: pallet_ismp::Config {}
+
+/// Size of the validator set that signs the benchmarked updates
+const VALIDATORS: u8 = 21;
+
+/// Height of the header finalized by the benchmarked updates, which is not an epoch header
+const SOURCE_HEIGHT: u64 = 1001;
+
+fn header(number: u64, parent_hash: H256, extra_data: Vec<u8>) -> CodecHeader {
+    CodecHeader {
+        parent_hash,
+        uncle_hash: Default::default(),
+        coinbase: Default::default(),
+        state_root: Default::default(),
+        transactions_root: Default::default(),
+        receipts_root: Default::default(),
+        logs_bloom: Default::default(),
+        difficulty: 2u64.into(),
+        number: number.into(),
+        gas_limit: 30_000_000,
+        gas_used: 0,
+        timestamp: number * 3,
+        extra_data,
+        mix_hash: Default::default(),
+        nonce: Default::default(),
+        base_fee_per_gas: None,
+        withdrawals_hash: None,
+        blob_gas_used: None,
+        excess_blob_gas_used: None,
+        parent_beacon_root: None,
+    }
+}
+
+fn secret_keys() -> Vec<SecretKey> {
+    (1..=VALIDATORS).map(|i| bls::keygen(&vec![i; 32], &vec![])).collect()
+}
+
+/// Returns the encoded consensus state of a client whose current validators hold `secret_keys`
+fn consensus_state(secret_keys: &[SecretKey]) -> Vec<u8> {
+    let current_validators = secret_keys
+        .iter()
+        .map(|key| {
+            BlsPublicKey::try_from(bls::sk_to_pk(*key).as_slice())
+                .expect("Public keys are 48 bytes")
+        })
+        .collect();
+    ConsensusState {
+        current_validators,
+        next_validators: None,
+        finalized_height: SOURCE_HEIGHT - 1,
+        finalized_hash: Default::default(),
+        current_epoch: compute_epoch(SOURCE_HEIGHT - 1),
+        ismp_contract_address: Default::default(),
+    }
+    .encode()
+}
+
+/// Builds an update attested by all the validators of `secret_keys`, which finalizes a header at
+/// [`SOURCE_HEIGHT`] whose parent is derived from `seed` and whose extra data is `padding` bytes.
+fn signed_update<T: Config>(secret_keys: &[SecretKey], seed: u8, padding: u32) -> BscClientUpdate {
+    let source_header = header(SOURCE_HEIGHT, H256::repeat_byte(seed), vec![0u8; padding as usize]);
+    let source_hash = Header::from(&source_header).hash::<Host<T>>();
+    let target_header = header(SOURCE_HEIGHT + 1, source_hash, vec![]);
+    let target_hash = Header::from(&target_header).hash::<Host<T>>();
+
+    let vote_data = VoteData {
+        source_number: SOURCE_HEIGHT,
+        source_hash: source_hash.0.into(),
+        target_number: SOURCE_HEIGHT + 1,
+        target_hash: target_hash.0.into(),
+    };
+    let msg = Host::<T>::keccak256(alloy_rlp::encode(vote_data.clone()).as_slice());
+    let signatures = secret_keys
+        .iter()
+        .map(|key| {
+            bls::sign(*key, &msg.0.to_vec(), &DST_ETHEREUM.as_bytes().to_vec())
+                .expect("Signing is infallible")
+        })
+        .collect::<Vec<_>>();
+    let agg_signature = bls::aggregate(&signatures).expect("Signatures are valid");
+    let attestation = VoteAttestationData {
+        vote_address_set: (1 << secret_keys.len()) - 1,
+        agg_signature: FixedBytes::from_slice(&agg_signature),
+        data: vote_data,
+        extra: Default::default(),
+    };
+
+    // extra vanity, followed by the vote attestation and the extra seal
+    let mut extra_data = vec![0u8; 32];
+    extra_data.extend_from_slice(&alloy_rlp::encode(attestation));
+    extra_data.extend_from_slice(&[0u8; 65]);
+    let attested_header = header(SOURCE_HEIGHT + 2, target_hash, extra_data);
+
+    BscClientUpdate {
+        source_header,
+        target_header,
+        attested_header,
+        epoch_header_ancestry: Default::default(),
+    }
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn verify_consensus(n: Linear<0, 100_000>) {
+        let secret_keys = secret_keys();
+        let consensus_state = consensus_state(&secret_keys);
+        let proof = signed_update::<T>(&secret_keys, 1, n).encode();
+        let client = BscClient::<Host<T>>::default();
+        let host = Host::<T>::default();
+
+        let result;
+        #[block]
+        {
+            result = client.verify_consensus(&host, BSC_CONSENSUS_ID, consensus_state, proof);
+        }
+
+        assert!(result.is_ok());
+    }
+
+    #[benchmark]
+    fn verify_fraud_proof(n: Linear<0, 100_000>) {
+        let secret_keys = secret_keys();
+        let consensus_state = consensus_state(&secret_keys);
+        // both updates are attested at the same height, but finalize different headers
+        let proof_1 = signed_update::<T>(&secret_keys, 1, n / 2).encode();
+        let proof_2 = signed_update::<T>(&secret_keys, 2, n / 2).encode();
+        let client = BscClient::<Host<T>>::default();
+        let host = Host::<T>::default();
+
+        let result;
+        #[block]
+        {
+            result = client.verify_fraud_proof(&host, consensus_state, proof_1, proof_2);
+        }
+
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights for the BSC consensus client
+//!
+//! These are hand-written placeholder estimates, they have not been produced by the benchmarking
+//! CLI. They must be replaced with generated weights on the reference hardware before being relied
+//! on in production, and regenerated whenever the benchmarks or the client change, using:
+//!
+//! ```sh
+//! ./target/release/hyperbridge benchmark pallet \
+//!     --chain=gargantua-2000 \
+//!     --pallet=ismp_bsc \
+//!     --extrinsic='*' \
+//!     --steps=50 \
+//!     --repeat=20 \
+//!     --output=modules/ismp/clients/bsc/src/weights.rs
+//! ```
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use core::marker::PhantomData;
+use frame_support::{traits::Get, weights::Weight};
+use pallet_ismp::weight_info::{BenchmarkedConsensusClient, ConsensusClientWeightInfo};
+
+/// The [`ConsensusClientWeight`](pallet_ismp::weight_info::ConsensusClientWeight) of the
+/// BSC consensus client, to be returned from the runtime's
+/// [`WeightProvider`](pallet_ismp::weight_info::WeightProvider)
+pub type BscClientWeight<T> = BenchmarkedConsensusClient<SubstrateWeight<T>>;
+
+/// Placeholder weights for the BSC consensus client, until they are generated on the
+/// reference hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> ConsensusClientWeightInfo for SubstrateWeight<T> {
+    /// The range of component `n` is `[0, 100000]`.
+    fn verify_consensus(n: u32) -> Weight {
+        Weight::from_parts(86_000_000, 0)
+            .saturating_add(Weight::from_parts(2_900, 0).saturating_mul(n.into()))
+    }
+    /// The range of component `n` is `[0, 100000]`.
+    fn verify_fraud_proof(n: u32) -> Weight {
+        Weight::from_parts(171_000_000, 0)
+            .saturating_add(Weight::from_parts(2_900, 0).saturating_mul(n.into()))
+    }
+}
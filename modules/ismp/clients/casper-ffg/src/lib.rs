@@ -14,3 +14,198 @@
 // limitations under the License.
 
 //! ISMP Consensus Client for the Beacon Chain's Casper-FFG Consensus Protocol
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod prelude {
+    pub use alloc::{boxed::Box, vec, vec::Vec};
+}
+
+pub mod types;
+
+use crate::{prelude::*, types::ConsensusState};
+use alloc::{collections::BTreeMap, format, string::ToString};
+use codec::{Decode, Encode};
+use ethabi::ethereum_types::H160;
+use evm_common::{
+    construct_intermediate_state, req_res_receipt_keys, verify_membership, verify_state_proof,
+};
+use ismp::{
+    consensus::{
+        ConsensusClient, ConsensusClientId, ConsensusStateId, StateCommitment, StateMachineClient,
+        VerifiedCommitments,
+    },
+    error::Error,
+    host::{Ethereum, IsmpHost, StateMachine},
+    messaging::{Proof, StateCommitmentHeight},
+    router::RequestResponse,
+};
+use sync_committee_primitives::{casper_ffg::CasperFfgUpdate, constants::Config};
+use sync_committee_verifier::casper_ffg::verify_casper_ffg_update;
+
+pub const CASPER_FFG_CONSENSUS_ID: ConsensusClientId = *b"CFFG";
+
+/// A consensus client which tracks the beacon chain by verifying Casper FFG votes from the full
+/// validator set, rather than relying on the sync committee.
+#[derive(Default, Clone)]
+pub struct CasperFfgConsensusClient<H: IsmpHost, C: Config>(core::marker::PhantomData<(H, C)>);
+
+impl<
+        H: IsmpHost + Send + Sync + Default + 'static,
+        C: Config + Send + Sync + Default + 'static,
+    > ConsensusClient for CasperFfgConsensusClient<H, C>
+{
+    fn verify_consensus(
+        &self,
+        _host: &dyn IsmpHost,
+        consensus_state_id: ConsensusStateId,
+        trusted_consensus_state: Vec<u8>,
+        consensus_proof: Vec<u8>,
+    ) -> Result<(Vec<u8>, VerifiedCommitments), Error> {
+        let update = CasperFfgUpdate::decode(&mut &consensus_proof[..]).map_err(|_| {
+            Error::ImplementationSpecific("Cannot decode casper ffg update".to_string())
+        })?;
+
+        let consensus_state =
+            ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|_| {
+                Error::ImplementationSpecific("Cannot decode trusted consensus state".to_string())
+            })?;
+
+        let execution_payload = update
+            .finalized_header
+            .as_ref()
+            .map(|finalized| finalized.execution_payload.clone());
+
+        let new_light_client_state =
+            verify_casper_ffg_update::<C>(consensus_state.light_client_state, update)
+                .map_err(|e| Error::ImplementationSpecific(format!("{:?}", e)))?;
+
+        let mut state_machine_map: BTreeMap<StateMachine, Vec<StateCommitmentHeight>> =
+            BTreeMap::new();
+
+        // Only finalized execution payloads yield new state commitments
+        if let Some(execution_payload) = execution_payload {
+            let intermediate_state = construct_intermediate_state(
+                StateMachine::Ethereum(Ethereum::ExecutionLayer),
+                consensus_state_id.clone(),
+                execution_payload.block_number,
+                execution_payload.timestamp,
+                &execution_payload.state_root[..],
+            )?;
+
+            state_machine_map.insert(
+                StateMachine::Ethereum(Ethereum::ExecutionLayer),
+                vec![StateCommitmentHeight {
+                    commitment: intermediate_state.commitment,
+                    height: intermediate_state.height.height,
+                }],
+            );
+        }
+
+        let new_consensus_state = ConsensusState {
+            frozen_height: None,
+            light_client_state: new_light_client_state,
+            ismp_contract_addresses: consensus_state.ismp_contract_addresses,
+        };
+
+        Ok((new_consensus_state.encode(), state_machine_map))
+    }
+
+    /// Two supermajority links from the trusted justified checkpoint to distinct checkpoints in
+    /// the same target epoch constitute a double vote by at least a third of the validator set.
+    fn verify_fraud_proof(
+        &self,
+        _host: &dyn IsmpHost,
+        trusted_consensus_state: Vec<u8>,
+        proof_1: Vec<u8>,
+        proof_2: Vec<u8>,
+    ) -> Result<(), Error> {
+        let consensus_state =
+            ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|_| {
+                Error::ImplementationSpecific("Cannot decode trusted consensus state".to_string())
+            })?;
+
+        let verify = |proof: Vec<u8>| {
+            let update = CasperFfgUpdate::decode(&mut &proof[..]).map_err(|_| {
+                Error::ImplementationSpecific("Cannot decode casper ffg update".to_string())
+            })?;
+            verify_casper_ffg_update::<C>(consensus_state.light_client_state.clone(), update)
+                .map_err(|e| Error::ImplementationSpecific(format!("{:?}", e)))
+        };
+
+        let state_1 = verify(proof_1)?;
+        let state_2 = verify(proof_2)?;
+
+        let (target_1, target_2) =
+            (state_1.current_justified_checkpoint, state_2.current_justified_checkpoint);
+        if target_1.epoch != target_2.epoch || target_1.root == target_2.root {
+            Err(Error::ImplementationSpecific(
+                "Fraud proof does not contain conflicting checkpoints".to_string(),
+            ))?
+        }
+
+        Ok(())
+    }
+
+    fn consensus_client_id(&self) -> ConsensusClientId {
+        CASPER_FFG_CONSENSUS_ID
+    }
+
+    fn state_machine(&self, id: StateMachine) -> Result<Box<dyn StateMachineClient>, Error> {
+        match id {
+            StateMachine::Ethereum(Ethereum::ExecutionLayer) =>
+                Ok(Box::new(<EvmStateMachine<H>>::default())),
+            _ => Err(Error::ImplementationSpecific("State machine not supported".to_string())),
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct EvmStateMachine<H: IsmpHost>(core::marker::PhantomData<H>);
+
+impl<H: IsmpHost> EvmStateMachine<H> {
+    fn ismp_contract_address(host: &dyn IsmpHost, proof: &Proof) -> Result<H160, Error> {
+        let consensus_state = host.consensus_state(proof.height.id.consensus_state_id)?;
+        let consensus_state = ConsensusState::decode(&mut &consensus_state[..]).map_err(|_| {
+            Error::ImplementationSpecific("Cannot decode consensus state".to_string())
+        })?;
+
+        consensus_state
+            .ismp_contract_addresses
+            .get(&proof.height.id.state_id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::ImplementationSpecific("Ismp contract address not found".to_string())
+            })
+    }
+}
+
+impl<H: IsmpHost + Send + Sync> StateMachineClient for EvmStateMachine<H> {
+    fn verify_membership(
+        &self,
+        host: &dyn IsmpHost,
+        item: RequestResponse,
+        root: StateCommitment,
+        proof: &Proof,
+    ) -> Result<(), Error> {
+        let contract_address = Self::ismp_contract_address(host, proof)?;
+        verify_membership::<H>(item, root, proof, contract_address)
+    }
+
+    fn state_trie_key(&self, items: RequestResponse) -> Vec<Vec<u8>> {
+        req_res_receipt_keys::<H>(items)
+    }
+
+    fn verify_state_proof(
+        &self,
+        host: &dyn IsmpHost,
+        keys: Vec<Vec<u8>>,
+        root: StateCommitment,
+        proof: &Proof,
+    ) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, Error> {
+        let ismp_address = Self::ismp_contract_address(host, proof)?;
+        verify_state_proof::<H>(keys, root, proof, ismp_address)
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use alloc::collections::BTreeMap;
+use codec::{Decode, Encode};
+use ethabi::ethereum_types::H160;
+use ismp::host::StateMachine;
+use sync_committee_primitives::casper_ffg::CasperFfgState;
+
+#[derive(Debug, Encode, Decode, Clone)]
+pub struct ConsensusState {
+    pub frozen_height: Option<u64>,
+    pub light_client_state: CasperFfgState,
+    pub ismp_contract_addresses: BTreeMap<StateMachine, H160>,
+}
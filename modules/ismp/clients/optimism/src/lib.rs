@@ -17,6 +17,9 @@
 
 extern crate alloc;
 
+#[cfg(test)]
+mod tests;
+
 use alloc::format;
 use alloy_rlp::Decodable;
 use ethabi::ethereum_types::{H160, H256, U128, U256};
@@ -30,7 +33,7 @@ use ismp::{
         ConsensusStateId, IntermediateState, StateCommitment, StateMachineHeight, StateMachineId,
     },
     error::Error,
-    host::{Ethereum, IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine},
     util::Keccak256,
 };
 
@@ -40,6 +43,11 @@ use ismp::{
 pub const DISPUTE_GAMES_SLOT: u64 = 103;
 /// Slot for the l2Outputs array in the L2Oracle contract
 pub const L2_OUTPUTS_SLOT: u64 = 3;
+/// Slot for the disputeGameBlacklist map in the OptimismPortal contract
+pub const DISPUTE_GAME_BLACKLIST_SLOT: u64 = 58;
+/// Slot for the respectedGameType and respectedGameTypeUpdatedAt fields in the OptimismPortal
+/// contract
+pub const RESPECTED_GAME_TYPE_SLOT: u64 = 59;
 
 #[derive(codec::Encode, codec::Decode, Debug)]
 pub struct OptimismPayloadProof {
@@ -69,6 +77,7 @@ pub fn verify_optimism_payload<H: IsmpHost + Send + Sync>(
     payload: OptimismPayloadProof,
     root: H256,
     l2_oracle_address: H160,
+    state_machine: StateMachine,
     consensus_state_id: ConsensusStateId,
 ) -> Result<IntermediateState, Error> {
     let storage_root =
@@ -145,10 +154,7 @@ pub fn verify_optimism_payload<H: IsmpHost + Send + Sync>(
 
     Ok(IntermediateState {
         height: StateMachineHeight {
-            id: StateMachineId {
-                state_id: StateMachine::Ethereum(Ethereum::Optimism),
-                consensus_state_id,
-            },
+            id: StateMachineId { state_id: state_machine, consensus_state_id },
             height: payload.block_number,
         },
         commitment: StateCommitment {
@@ -179,6 +185,12 @@ pub struct OptimismDisputeGameProof {
     pub game_type: u32,
     /// L1 Timestamp at game creation
     pub timestamp: u64,
+    /// Membership Proof for the OptimismPortal contract account in the ethereum world trie
+    pub optimism_portal_proof: Vec<Vec<u8>>,
+    /// Membership proof for the respectedGameType slot in the OptimismPortal
+    pub respected_game_type_proof: Vec<Vec<u8>>,
+    /// Proof for the dispute game proxy in the disputeGameBlacklist map of the OptimismPortal
+    pub blacklist_proof: Vec<Vec<u8>>,
 }
 
 // https://github.com/ethereum-optimism/optimism/blob/f707883038d527cbf1e9f8ea513fe33255deadbc/packages/contracts-bedrock/src/dispute/DisputeGameFactory.sol#L127
@@ -210,20 +222,109 @@ pub fn calculate_output_root<H: Keccak256>(
 // https://github.com/ethereum-optimism/optimism/blob/f707883038d527cbf1e9f8ea513fe33255deadbc/packages/contracts-bedrock/src/libraries/DisputeTypes.sol#L94
 /// Game types
 pub const CANNON: u32 = 0;
-pub const _PERMISSIONED: u32 = 1;
+pub const PERMISSIONED_CANNON: u32 = 1;
+
+/// Verifies that the dispute game can be used to finalize withdrawals in the OptimismPortal: the
+/// game type must be the portal's respected game type, the game must have been created after the
+/// respected game type was last updated and it must not be blacklisted.
+pub fn verify_respected_dispute_game<H: IsmpHost + Send + Sync>(
+    payload: &OptimismDisputeGameProof,
+    root: H256,
+    optimism_portal_address: H160,
+) -> Result<(), Error> {
+    let storage_root = get_contract_storage_root::<H>(
+        payload.optimism_portal_proof.clone(),
+        &optimism_portal_address.0,
+        root,
+    )?;
+
+    let mut slot = [0u8; 32];
+    U256::from(RESPECTED_GAME_TYPE_SLOT).to_big_endian(&mut slot);
+    let respected_game_type_key = H::keccak256(&slot);
+    let proof_value = match get_value_from_proof::<H>(
+        respected_game_type_key.0.to_vec(),
+        storage_root,
+        payload.respected_game_type_proof.clone(),
+    )? {
+        Some(value) => value.clone(),
+        _ => Err(Error::MembershipProofVerificationFailed(
+            "Respected game type not found in proof".to_string(),
+        ))?,
+    };
+
+    let proof_value = <alloy_primitives::U256 as Decodable>::decode(&mut &*proof_value)
+        .map_err(|_| {
+            Error::ImplementationSpecific(format!(
+                "Error decoding respected game type from {:?}",
+                &proof_value
+            ))
+        })?
+        .to_be_bytes::<32>();
+
+    // respectedGameType occupies the lowest 4 bytes, followed by respectedGameTypeUpdatedAt
+    let respected_game_type = u32::from_be_bytes(proof_value[28..].try_into().expect("Infallible"));
+    let updated_at = u64::from_be_bytes(proof_value[20..28].try_into().expect("Infallible"));
+
+    if payload.game_type != respected_game_type {
+        Err(Error::MembershipProofVerificationFailed(
+            "Game type must be the respected game type".to_string(),
+        ))?
+    }
+
+    if payload.timestamp < updated_at {
+        Err(Error::MembershipProofVerificationFailed(
+            "Dispute game was created before the respected game type was updated".to_string(),
+        ))?
+    }
+
+    let mut proxy = [0u8; 32];
+    proxy[12..].copy_from_slice(&payload.proxy.0);
+    let blacklist_key = derive_map_key::<H>(proxy.to_vec(), DISPUTE_GAME_BLACKLIST_SLOT);
+    // zero values are not stored, so a game that isn't blacklisted has no value in the trie
+    if let Some(value) = get_value_from_proof::<H>(
+        blacklist_key.0.to_vec(),
+        storage_root,
+        payload.blacklist_proof.clone(),
+    )? {
+        let blacklisted =
+            <alloy_primitives::U256 as Decodable>::decode(&mut &*value).map_err(|_| {
+                Error::ImplementationSpecific(format!(
+                    "Error decoding blacklist entry from {:?}",
+                    &value
+                ))
+            })?;
+        if blacklisted != alloy_primitives::U256::ZERO {
+            Err(Error::MembershipProofVerificationFailed(
+                "Dispute game has been blacklisted".to_string(),
+            ))?
+        }
+    }
+
+    Ok(())
+}
 
 pub fn verify_optimism_dispute_game_proof<H: IsmpHost + Send + Sync>(
     payload: OptimismDisputeGameProof,
     root: H256,
     dispute_factory_address: H160,
+    optimism_portal_address: Option<H160>,
+    respected_game_types: &[u32],
+    state_machine: StateMachine,
     consensus_state_id: ConsensusStateId,
 ) -> Result<IntermediateState, Error> {
-    // Is the game type the respected game type?
-    if payload.game_type != CANNON {
-        Err(Error::MembershipProofVerificationFailed(
-            "Game type must be the respected game type".to_string(),
-        ))?;
+    // Is the game type accepted for this chain?
+    if !respected_game_types.contains(&payload.game_type) {
+        Err(Error::MembershipProofVerificationFailed(format!(
+            "Game type {} is not accepted for this chain",
+            payload.game_type
+        )))?;
     }
+
+    // Chains configured before the OptimismPortal was tracked only accept cannon games
+    if let Some(optimism_portal_address) = optimism_portal_address {
+        verify_respected_dispute_game::<H>(&payload, root, optimism_portal_address)?;
+    }
+
     let storage_root = get_contract_storage_root::<H>(
         payload.dispute_factory_proof,
         &dispute_factory_address.0,
@@ -281,10 +382,7 @@ pub fn verify_optimism_dispute_game_proof<H: IsmpHost + Send + Sync>(
 
     Ok(IntermediateState {
         height: StateMachineHeight {
-            id: StateMachineId {
-                state_id: StateMachine::Ethereum(Ethereum::Optimism),
-                consensus_state_id,
-            },
+            id: StateMachineId { state_id: state_machine, consensus_state_id },
             height: payload.header.number.low_u64(),
         },
         commitment: StateCommitment {
@@ -0,0 +1,194 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![cfg(test)]
+
+use crate::{
+    verify_optimism_dispute_game_proof, verify_respected_dispute_game, OptimismDisputeGameProof,
+    CANNON, DISPUTE_GAME_BLACKLIST_SLOT, PERMISSIONED_CANNON, RESPECTED_GAME_TYPE_SLOT,
+};
+use alloy_primitives::B256;
+use ethabi::ethereum_types::{Bloom, H160, H256, H64, U256};
+use ethereum_trie::{keccak::KeccakHasher, EIP1186Layout, MemoryDB};
+use evm_common::{derive_map_key, types::Account};
+use geth_primitives::CodecHeader;
+use ismp::{host::StateMachine, util::Keccak256};
+use ismp_testsuite::mocks::Host;
+use trie_db::{Recorder, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut};
+
+type Layout = EIP1186Layout<KeccakHasher>;
+
+const OPTIMISM_PORTAL: H160 = H160([1u8; 20]);
+const PROXY: H160 = H160([2u8; 20]);
+
+/// Builds a trie with the given entries and returns its root along with a proof for each key
+fn build_trie(entries: &[(Vec<u8>, Vec<u8>)], keys: &[Vec<u8>]) -> (H256, Vec<Vec<Vec<u8>>>) {
+    let mut db = MemoryDB::<KeccakHasher>::default();
+    let mut root = Default::default();
+    {
+        let mut trie = TrieDBMutBuilder::<Layout>::new(&mut db, &mut root).build();
+        for (key, value) in entries {
+            trie.insert(key, value).unwrap();
+        }
+    }
+
+    let proofs = keys
+        .iter()
+        .map(|key| {
+            let mut recorder = Recorder::<Layout>::default();
+            {
+                let trie =
+                    TrieDBBuilder::<Layout>::new(&db, &root).with_recorder(&mut recorder).build();
+                trie.get(key).unwrap();
+            }
+            recorder.drain().into_iter().map(|record| record.data).collect()
+        })
+        .collect();
+
+    (H256(root.0), proofs)
+}
+
+fn slot_key(slot: u64) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    U256::from(slot).to_big_endian(&mut bytes);
+    Host::keccak256(&bytes).0.to_vec()
+}
+
+fn blacklist_key(proxy: H160) -> Vec<u8> {
+    let mut key = [0u8; 32];
+    key[12..].copy_from_slice(&proxy.0);
+    derive_map_key::<Host>(key.to_vec(), DISPUTE_GAME_BLACKLIST_SLOT).0.to_vec()
+}
+
+/// Produces a world state root and a dispute game payload with proofs of the OptimismPortal
+/// storage, where the portal has the given respected game type and the game was created at
+/// `created_at`.
+fn portal_fixture(
+    respected_game_type: u32,
+    updated_at: u64,
+    blacklisted: bool,
+    game_type: u32,
+    created_at: u64,
+) -> (H256, OptimismDisputeGameProof) {
+    // respectedGameType occupies the lowest 4 bytes of the slot, followed by
+    // respectedGameTypeUpdatedAt
+    let mut word = [0u8; 32];
+    word[28..].copy_from_slice(&respected_game_type.to_be_bytes());
+    word[20..28].copy_from_slice(&updated_at.to_be_bytes());
+    let mut storage = vec![(
+        slot_key(RESPECTED_GAME_TYPE_SLOT),
+        alloy_rlp::encode(alloy_primitives::U256::from_be_bytes(word)),
+    )];
+    // an unrelated game is always blacklisted, so the blacklist map is never empty
+    storage
+        .push((blacklist_key(H160([3u8; 20])), alloy_rlp::encode(alloy_primitives::U256::from(1))));
+    if blacklisted {
+        storage.push((blacklist_key(PROXY), alloy_rlp::encode(alloy_primitives::U256::from(1))));
+    }
+    let (storage_root, storage_proofs) =
+        build_trie(&storage, &[slot_key(RESPECTED_GAME_TYPE_SLOT), blacklist_key(PROXY)]);
+
+    let account = Account {
+        nonce: 1,
+        balance: alloy_primitives::U256::ZERO,
+        storage_root: B256::from(storage_root.0),
+        code_hash: B256::ZERO,
+    };
+    let account_key = Host::keccak256(&OPTIMISM_PORTAL.0).0.to_vec();
+    let (root, account_proofs) =
+        build_trie(&[(account_key.clone(), alloy_rlp::encode(&account))], &[account_key]);
+
+    let payload = OptimismDisputeGameProof {
+        header: CodecHeader {
+            parent_hash: H256::zero(),
+            uncle_hash: H256::zero(),
+            coinbase: H160::zero(),
+            state_root: H256::zero(),
+            transactions_root: H256::zero(),
+            receipts_root: H256::zero(),
+            logs_bloom: Bloom::zero(),
+            difficulty: U256::zero(),
+            number: U256::zero(),
+            gas_limit: 0,
+            gas_used: 0,
+            timestamp: 0,
+            extra_data: vec![],
+            mix_hash: H256::zero(),
+            nonce: H64::zero(),
+            base_fee_per_gas: None,
+            withdrawals_hash: None,
+            blob_gas_used: None,
+            excess_blob_gas_used: None,
+            parent_beacon_root: None,
+        },
+        withdrawal_storage_root: H256::zero(),
+        version: H256::zero(),
+        dispute_factory_proof: vec![],
+        dispute_game_proof: vec![],
+        proxy: PROXY,
+        extra_data: vec![],
+        game_type,
+        timestamp: created_at,
+        optimism_portal_proof: account_proofs[0].clone(),
+        respected_game_type_proof: storage_proofs[0].clone(),
+        blacklist_proof: storage_proofs[1].clone(),
+    };
+
+    (root, payload)
+}
+
+#[test]
+fn accepts_games_of_the_respected_game_type() {
+    let (root, payload) = portal_fixture(CANNON, 100, false, CANNON, 101);
+    verify_respected_dispute_game::<Host>(&payload, root, OPTIMISM_PORTAL).unwrap();
+}
+
+#[test]
+fn rejects_games_that_are_not_the_respected_game_type() {
+    let (root, payload) = portal_fixture(PERMISSIONED_CANNON, 100, false, CANNON, 101);
+    assert!(verify_respected_dispute_game::<Host>(&payload, root, OPTIMISM_PORTAL).is_err());
+}
+
+#[test]
+fn rejects_games_created_before_the_respected_game_type_was_updated() {
+    let (root, payload) = portal_fixture(CANNON, 100, false, CANNON, 99);
+    assert!(verify_respected_dispute_game::<Host>(&payload, root, OPTIMISM_PORTAL).is_err());
+}
+
+#[test]
+fn rejects_blacklisted_games() {
+    let (root, payload) = portal_fixture(CANNON, 100, true, CANNON, 101);
+    assert!(verify_respected_dispute_game::<Host>(&payload, root, OPTIMISM_PORTAL).is_err());
+}
+
+#[test]
+fn rejects_proofs_against_a_different_portal() {
+    let (root, payload) = portal_fixture(CANNON, 100, false, CANNON, 101);
+    assert!(verify_respected_dispute_game::<Host>(&payload, root, H160([4u8; 20])).is_err());
+}
+
+#[test]
+fn rejects_game_types_not_accepted_for_the_chain() {
+    let (root, payload) = portal_fixture(PERMISSIONED_CANNON, 100, false, PERMISSIONED_CANNON, 101);
+    let result = verify_optimism_dispute_game_proof::<Host>(
+        payload,
+        root,
+        H160::zero(),
+        Some(OPTIMISM_PORTAL),
+        &[CANNON],
+        StateMachine::Ethereum(ismp::host::Ethereum::Optimism),
+        *b"ETH0",
+    );
+    assert!(result.is_err());
+}
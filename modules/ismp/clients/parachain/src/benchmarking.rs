@@ -0,0 +1,109 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for verifying parachain consensus proofs of varying sizes.
+//!
+//! These must be run on a parachain runtime, since the parachain client derives the state machine
+//! ids of the verified heads from the host state machine.
+
+use super::*;
+use codec::Encode;
+use frame_benchmarking::v2::*;
+use ismp::consensus::ConsensusClient;
+use pallet_ismp::primitives::{IsmpConsensusLog, ISMP_ID};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_runtime::{
+    generic::{Digest, Header},
+    traits::BlakeTwo256,
+    DigestItem,
+};
+use sp_trie::{LayoutV1, MemoryDB, TrieDBMutBuilder, TrieMut};
+
+const PARA_ID: u32 = 2000;
+const RELAY_HEIGHT: u32 = 100;
+
+/// Inserts a relay chain state root which commits to a head of [`PARA_ID`] whose digest is padded
+/// with `n` bytes, and returns the encoded [`ParachainConsensusProof`] for it.
+fn parachain_head_proof<T: Config>(n: u32) -> Vec<u8> {
+    let header = Header::<u32, BlakeTwo256> {
+        parent_hash: Default::default(),
+        number: 100,
+        state_root: Default::default(),
+        extrinsics_root: Default::default(),
+        digest: Digest {
+            logs: vec![
+                DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(100_000u64).encode()),
+                DigestItem::Consensus(
+                    ISMP_ID,
+                    IsmpConsensusLog {
+                        mmr_root: Default::default(),
+                        child_trie_root: Default::default(),
+                    }
+                    .encode(),
+                ),
+                DigestItem::Other(vec![0u8; n as usize]),
+            ],
+        },
+    };
+
+    let mut db = MemoryDB::<BlakeTwo256>::default();
+    let mut root = Default::default();
+    {
+        let mut trie = TrieDBMutBuilder::<LayoutV1<BlakeTwo256>>::new(&mut db, &mut root).build();
+        trie.insert(&parachain_header_storage_key(PARA_ID).0, &header.encode())
+            .expect("Inserting into an in-memory trie is infallible");
+    }
+    RelayChainState::<T>::insert(RELAY_HEIGHT, root);
+
+    // the trie only contains the parachain head, so all of its nodes make up the proof
+    let storage_proof = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+    ParachainConsensusProof { para_ids: vec![PARA_ID], relay_height: RELAY_HEIGHT, storage_proof }
+        .encode()
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn verify_consensus(n: Linear<0, 100_000>) {
+        Parachains::<T>::insert(PARA_ID, ());
+        let proof = parachain_head_proof::<T>(n);
+        let client = ParachainConsensusClient::<T, Pallet<T>>::default();
+        let host = Host::<T>::default();
+
+        let result;
+        #[block]
+        {
+            result = client.verify_consensus(&host, PARACHAIN_CONSENSUS_ID, vec![], proof);
+        }
+
+        assert!(result.is_ok());
+    }
+
+    #[benchmark]
+    fn verify_fraud_proof(n: Linear<0, 100_000>) {
+        Parachains::<T>::insert(PARA_ID, ());
+        let proof = parachain_head_proof::<T>(n / 2);
+        let client = ParachainConsensusClient::<T, Pallet<T>>::default();
+        let host = Host::<T>::default();
+
+        #[block]
+        {
+            // fraud proofs are rejected without being decoded
+            let _ = client.verify_fraud_proof(&host, vec![], proof.clone(), proof);
+        }
+    }
+}
@@ -198,6 +198,11 @@ where
         Ok((state, intermediates))
     }
 
+    /// Parachain heads are finalized by the relay chain, so this client has no equivocation to
+    /// prove. The [`RelayChainOracle`] tracks a single relay chain state root per height, two
+    /// storage proofs of a parachain head at the same relay chain height are therefore checked
+    /// against the same root and can never yield conflicting heads. Every fraud proof is rejected,
+    /// so that the consensus state can't be frozen by submitting arbitrary proofs.
     fn verify_fraud_proof(
         &self,
         _host: &dyn IsmpHost,
@@ -205,8 +210,11 @@ where
         _proof_1: Vec<u8>,
         _proof_2: Vec<u8>,
     ) -> Result<(), Error> {
-        // There are no fraud proofs for the parachain client
-        Ok(())
+        Err(Error::ImplementationSpecific(
+            "Fraud proofs are not supported by the parachain consensus client, parachain heads \
+             are finalized by the relay chain"
+                .into(),
+        ))
     }
 
     fn state_machine(&self, id: StateMachine) -> Result<Box<dyn StateMachineClient>, Error> {
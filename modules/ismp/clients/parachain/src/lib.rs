@@ -16,13 +16,17 @@
 //! ISMP Parachain Consensus Client
 //!
 //! This allows parachains communicate over ISMP leveraging the relay chain as a consensus oracle.
+//! Since parachain heads are finalized by the relay chain, the client does not accept fraud proofs.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
 extern crate alloc;
 extern crate core;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 pub mod consensus;
+pub mod weights;
 pub use consensus::*;
 
 use alloc::{vec, vec::Vec};
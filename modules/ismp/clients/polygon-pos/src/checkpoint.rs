@@ -0,0 +1,145 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finality of polygon headers through heimdall checkpoints submitted to the `RootChain` contract
+//! on the L1.
+
+use alloc::{format, string::ToString, vec::Vec};
+use alloy_rlp::Decodable;
+use codec::{Decode, Encode};
+use evm_common::{derive_map_key_with_offset, get_contract_storage_root, get_values_from_proof};
+use geth_primitives::CodecHeader;
+use ismp::{
+    consensus::{StateMachineHeight, StateMachineId},
+    error::Error,
+    host::IsmpHost,
+};
+use polygon_pos_verifier::primitives::{check_membership, checkpoint_leaf};
+use sp_core::{H160, H256, U256};
+
+/// Storage slot of the `headerBlocks` mapping in the `RootChain` contract
+pub const HEADER_BLOCKS_SLOT: u64 = 5;
+
+/// Offset of the `root` field in the `HeaderBlock` struct
+pub const HEADER_BLOCK_ROOT_OFFSET: u64 = 0;
+/// Offset of the `start` field in the `HeaderBlock` struct
+pub const HEADER_BLOCK_START_OFFSET: u64 = 1;
+/// Offset of the `end` field in the `HeaderBlock` struct
+pub const HEADER_BLOCK_END_OFFSET: u64 = 2;
+
+/// Configuration for finalizing polygon headers with heimdall checkpoints
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, scale_info::TypeInfo)]
+pub struct CheckpointConfig {
+    /// Address of the `RootChain` contract on the L1
+    pub root_chain: H160,
+    /// The L1 state machine whose verified state commitments checkpoints are proven against
+    pub l1_state_machine: StateMachineId,
+}
+
+/// Proof that a polygon header is included in a checkpoint on the L1
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct CheckpointProof {
+    /// Height of the L1 state commitment the proof is verified against
+    pub l1_height: u64,
+    /// Id of the checkpoint in the `headerBlocks` mapping
+    pub header_block_id: u64,
+    /// Account proof of the `RootChain` contract
+    pub contract_proof: Vec<Vec<u8>>,
+    /// Storage proof of the root, start and end fields of the checkpoint
+    pub storage_proof: Vec<Vec<u8>>,
+    /// Hash of the polygon header being finalized, it must be a header already tracked by the
+    /// client
+    pub header_hash: H256,
+    /// Merkle proof of the header in the checkpoint root
+    pub merkle_proof: Vec<H256>,
+}
+
+/// Verifies that `header` is included in the checkpoint described by the proof.
+pub fn verify_checkpoint<H: IsmpHost + Send + Sync>(
+    host: &dyn IsmpHost,
+    config: &CheckpointConfig,
+    proof: CheckpointProof,
+    header: &CodecHeader,
+) -> Result<(), Error> {
+    let height = StateMachineHeight { id: config.l1_state_machine, height: proof.l1_height };
+    host.is_consensus_client_frozen(height.id.consensus_state_id)?;
+    host.is_state_machine_frozen(height.id)?;
+
+    let update_time = host.state_machine_update_time(height)?;
+    let challenge_period = host.challenge_period(height.id.consensus_state_id).ok_or(
+        Error::ChallengePeriodNotConfigured { consensus_state_id: height.id.consensus_state_id },
+    )?;
+    if challenge_period.as_secs() != 0 &&
+        host.timestamp().saturating_sub(update_time) <= challenge_period
+    {
+        Err(Error::ChallengePeriodNotElapsed {
+            consensus_state_id: height.id.consensus_state_id,
+            current_time: host.timestamp(),
+            update_time,
+        })?
+    }
+
+    let commitment = host.state_machine_commitment(height)?;
+    let storage_root = get_contract_storage_root::<H>(
+        proof.contract_proof,
+        &config.root_chain.0,
+        commitment.state_root,
+    )?;
+
+    let header_block_id = H256::from_low_u64_be(proof.header_block_id).0.to_vec();
+    let keys = [HEADER_BLOCK_ROOT_OFFSET, HEADER_BLOCK_START_OFFSET, HEADER_BLOCK_END_OFFSET]
+        .into_iter()
+        .map(|offset| {
+            derive_map_key_with_offset::<H>(header_block_id.clone(), HEADER_BLOCKS_SLOT, offset)
+                .0
+                .to_vec()
+        })
+        .collect();
+    let values = get_values_from_proof::<H>(keys, storage_root, proof.storage_proof)?
+        .into_iter()
+        .map(|value| {
+            let value = value.ok_or_else(|| {
+                Error::MembershipProofVerificationFailed(format!(
+                    "Checkpoint {} not found in proof",
+                    proof.header_block_id
+                ))
+            })?;
+            let value = <alloy_primitives::U256 as Decodable>::decode(&mut &*value)
+                .map_err(|_| {
+                    Error::ImplementationSpecific(format!("Error decoding checkpoint {value:?}"))
+                })?
+                .to_be_bytes::<32>();
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let root = H256(values[0]);
+    let start = U256::from_big_endian(&values[1]);
+    let end = U256::from_big_endian(&values[2]);
+
+    if header.number < start || header.number > end {
+        Err(Error::ImplementationSpecific(format!(
+            "Header {} is not in checkpoint {}",
+            header.number, proof.header_block_id
+        )))?
+    }
+
+    let index = (header.number - start).low_u64();
+    if !check_membership::<H>(checkpoint_leaf::<H>(header), index, root, &proof.merkle_proof) {
+        Err(Error::ImplementationSpecific("Invalid checkpoint merkle proof".to_string()))?
+    }
+
+    Ok(())
+}
@@ -3,14 +3,15 @@
 #[warn(unused_variables)]
 extern crate alloc;
 
+pub mod checkpoint;
 pub mod pallet;
-#[cfg(test)]
-mod test;
 
 use core::marker::PhantomData;
 
 use alloc::{boxed::Box, collections::BTreeMap, string::ToString, vec, vec::Vec};
+use checkpoint::{verify_checkpoint, CheckpointConfig, CheckpointProof};
 use codec::{Decode, Encode};
+use evm_common::{req_res_receipt_keys, verify_membership, verify_state_proof};
 use geth_primitives::CodecHeader;
 use ismp::{
     consensus::{
@@ -21,7 +22,6 @@ use ismp::{
     messaging::{Proof, StateCommitmentHeight},
     router::RequestResponse,
 };
-use ismp_sync_committee::{utils::req_res_receipt_keys, verify_membership, verify_state_proof};
 use pallet::{Config, Headers};
 use polygon_pos_verifier::{
     primitives::{SPAN_LENGTH, SPRINT_LENGTH},
@@ -62,6 +62,40 @@ pub struct ConsensusState {
     pub finalized_validators: Vec<H160>,
     pub forks: Vec<Chain>,
     pub ismp_contract_address: H160,
+    /// When set, headers are only finalized by heimdall checkpoints on the L1 rather than by
+    /// cumulative difficulty
+    pub checkpoint_config: Option<CheckpointConfig>,
+}
+
+impl ConsensusState {
+    /// Finalizes the header at `index` in the fork at `fork_index`. Every other fork is dropped
+    /// along with the headers of the finalized fork up to the new finalized header. Returns the
+    /// hashes of the headers that are no longer needed.
+    fn finalize(&mut self, fork_index: usize, index: usize, finalized_span: u64) -> Vec<H256> {
+        let mut chain = self.forks.swap_remove(fork_index);
+        let mut pruned = chain.hashes.drain(..=index).map(|(_, hash)| hash).collect::<Vec<H256>>();
+        let finalized_hash = pruned.pop().expect("Finalized header is in the fork; qed");
+        pruned.push(self.finalized_hash);
+
+        // Forks may share headers with the finalized fork, don't prune those
+        for fork in self.forks.drain(..) {
+            pruned.extend(fork.hashes.into_iter().map(|(_, hash)| hash).filter(|hash| {
+                *hash != finalized_hash && !chain.hashes.iter().any(|(_, h)| h == hash)
+            }));
+        }
+
+        if let Some(validators) = chain.validators.get(&finalized_span) {
+            self.finalized_validators = validators.clone();
+        }
+        chain.validators.retain(|span, _| *span > finalized_span);
+
+        self.finalized_hash = finalized_hash;
+        // The finalized fork is kept even when it has no headers left, since it holds the
+        // validator sets of upcoming spans
+        self.forks = vec![chain];
+
+        pruned
+    }
 }
 
 #[derive(Encode, Decode, Debug)]
@@ -70,6 +104,8 @@ pub struct PolygonClientUpdate {
     pub consensus_update: BoundedVec<CodecHeader, ConstU32<1000>>,
     /// Parent hash of the first header in the list
     pub chain_head: H256,
+    /// Proof that one of the tracked headers has been checkpointed on the L1
+    pub checkpoint_proof: Option<CheckpointProof>,
 }
 
 pub struct PolygonClient<T: Config, H: IsmpHost>(PhantomData<(T, H)>);
@@ -91,12 +127,12 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
 {
     fn verify_consensus(
         &self,
-        _host: &dyn IsmpHost,
+        host: &dyn IsmpHost,
         _consensus_state_id: ismp::consensus::ConsensusStateId,
         trusted_consensus_state: Vec<u8>,
         proof: Vec<u8>,
     ) -> Result<(Vec<u8>, ismp::consensus::VerifiedCommitments), ismp::error::Error> {
-        let PolygonClientUpdate { consensus_update, chain_head } =
+        let PolygonClientUpdate { consensus_update, chain_head, checkpoint_proof } =
             PolygonClientUpdate::decode(&mut &proof[..]).map_err(|_| {
                 Error::ImplementationSpecific("Cannot decode polygon client update".to_string())
             })?;
@@ -106,16 +142,24 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
                 Error::ImplementationSpecific("Cannot decode trusted consensus state".to_string())
             })?;
 
-        if consensus_update.is_empty() {
+        if consensus_update.is_empty() && checkpoint_proof.is_none() {
             Err(Error::ImplementationSpecific("Consensus update is empty".to_string()))?
         }
 
-        if consensus_update[0].parent_hash == consensus_state.finalized_hash {
-            let mut chain = Chain {
-                validators: Default::default(),
-                hashes: vec![],
-                difficulty: Default::default(),
-            };
+        if consensus_update.is_empty() {
+            // Nothing to import, only the checkpoint needs to be verified
+        } else if consensus_update[0].parent_hash == consensus_state.finalized_hash {
+            // Continue from the fork left behind by the last finalization if there is one
+            let mut chain = consensus_state
+                .forks
+                .iter()
+                .position(|chain| chain.hashes.is_empty())
+                .map(|index| consensus_state.forks.remove(index))
+                .unwrap_or_else(|| Chain {
+                    validators: Default::default(),
+                    hashes: vec![],
+                    difficulty: Default::default(),
+                });
 
             let mut parent_hash = consensus_update[0].parent_hash;
             for header in consensus_update {
@@ -141,7 +185,7 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
             let chain = if let Some(chain) = consensus_state
                 .forks
                 .iter_mut()
-                .find(|chain| chain.hashes[chain.hashes.len() - 1].1 == chain_head)
+                .find(|chain| chain.hashes.last().map(|(_, hash)| *hash) == Some(chain_head))
             {
                 chain
             } else {
@@ -168,76 +212,43 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
             }
         }
 
-        // Try to finalize the longest chain
-        let mut longest_chains = consensus_state
-            .forks
-            .iter()
-            .filter(|chain| {
-                log::info!(target: "pallet-ismp", "Chain : {:?} --> {:?}; Difficulty -> {:#?}; length: {:?}", chain.hashes[0].1, chain.hashes[chain.hashes.len() - 1].1, chain.difficulty, chain.hashes.len());
-                chain.hashes.len() >= (consensus_state.finalized_validators.len() * SPRINT_LENGTH as usize)
-            })
-            .collect::<Vec<&Chain>>();
-
-        let longest_chain = {
-            if longest_chains.is_empty() {
-                None
-            } else {
-                // Sort by highest cumulative difficulty
-                longest_chains.sort_by(|a, b| a.difficulty.cmp(&b.difficulty));
-                if longest_chains.len() > 1 &&
-                    longest_chains[longest_chains.len() - 1].difficulty ==
-                        longest_chains[longest_chains.len() - 2].difficulty
-                {
-                    None
-                } else {
-                    longest_chains.pop().cloned()
-                }
-            }
-        };
-
-        // we want to ensure that before we finalize a chain, most blocks have been signed by unique
-        // validators
-        let longest_chain = if let Some(chain) = longest_chain {
-            // The composition of validators in consecutive chunks must be unique
-            let mut validator_distribution = vec![];
-            for (_, hashes) in chain.hashes.chunks(SPRINT_LENGTH as usize).enumerate() {
-                let mut validator_dist = BTreeMap::<H160, u64>::new();
-                hashes.iter().for_each(|(signer, _)| {
-                    let entry = validator_dist.entry(*signer).or_insert(0);
-                    *entry += 1;
-                });
-
-                let vals = validator_dist.into_iter().map(|a| a.0).collect::<Vec<_>>();
-                validator_distribution.push(vals);
-            }
-
-            log::info!(target: "pallet-ismp", "Validator distribution : {:?}", validator_distribution);
-
-            // Ensure that the composition of validators in each chunk is different
-            let mut prev = &validator_distribution[0];
-            if validator_distribution[1..].iter().all(|next| {
-                let check = next != prev;
-                prev = next;
-                check
-            }) {
-                Some(chain)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
         let mut state_machine_map: BTreeMap<StateMachine, Vec<StateCommitmentHeight>> =
             BTreeMap::new();
-        if let Some(mut longest_chain) = longest_chain {
-            // we want 16 mins of probabilistic finality
-            let finality_index = longest_chain.hashes.len().saturating_sub(480);
-            let finalized_hash = longest_chain.hashes[finality_index].1;
 
-            let header = Headers::<T>::get(finalized_hash).ok_or_else(|| {
-                Error::ImplementationSpecific("Expected header to be found in storage".to_string())
-            })?;
+        let finalized = match (consensus_state.checkpoint_config.clone(), checkpoint_proof) {
+            (Some(config), Some(proof)) => {
+                let (fork_index, index) = consensus_state
+                    .forks
+                    .iter()
+                    .enumerate()
+                    .find_map(|(fork_index, chain)| {
+                        chain
+                            .hashes
+                            .iter()
+                            .position(|(_, hash)| *hash == proof.header_hash)
+                            .map(|index| (fork_index, index))
+                    })
+                    .ok_or_else(|| {
+                        Error::ImplementationSpecific(
+                            "Checkpointed header is not tracked by the client".to_string(),
+                        )
+                    })?;
+                let header = Headers::<T>::get(proof.header_hash).ok_or_else(|| {
+                    Error::ImplementationSpecific(
+                        "Expected header to be found in storage".to_string(),
+                    )
+                })?;
+                verify_checkpoint::<H>(host, &config, proof, &header)?;
+                Some((fork_index, index, header))
+            },
+            (None, Some(_)) => Err(Error::ImplementationSpecific(
+                "Checkpoint finality is not enabled".to_string(),
+            ))?,
+            (Some(_), None) => None,
+            (None, None) => finalize_longest_chain::<T>(&consensus_state)?,
+        };
+
+        if let Some((fork_index, index, header)) = finalized {
             let state_commitment = StateCommitmentHeight {
                 commitment: StateCommitment {
                     timestamp: header.timestamp,
@@ -248,16 +259,10 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
             };
 
             state_machine_map.insert(StateMachine::Polygon, vec![state_commitment]);
-            consensus_state.finalized_hash = finalized_hash;
             let finalized_span = get_span(header.number.low_u64());
-            if let Some(validators) = longest_chain.validators.get(&finalized_span) {
-                consensus_state.finalized_validators = validators.clone();
+            for hash in consensus_state.finalize(fork_index, index, finalized_span) {
+                Headers::<T>::remove(hash);
             }
-
-            longest_chain.hashes = longest_chain.hashes[(finality_index + 1)..].to_vec();
-            longest_chain.validators.remove(&finalized_span);
-            // Drop all other chain forks
-            consensus_state.forks = vec![longest_chain];
         }
 
         Ok((consensus_state.encode(), state_machine_map))
@@ -321,7 +326,7 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
         id: ismp::host::StateMachine,
     ) -> Result<Box<dyn StateMachineClient>, ismp::error::Error> {
         match id {
-            StateMachine::Bsc => Ok(Box::new(<EvmStateMachine<H>>::default())),
+            StateMachine::Polygon => Ok(Box::new(<EvmStateMachine<H>>::default())),
             state_machine =>
                 return Err(Error::ImplementationSpecific(alloc::format!(
                     "Unsupported state machine: {state_machine:?}"
@@ -330,6 +335,86 @@ impl<T: Config, H: IsmpHost + Send + Sync + Default + 'static> ConsensusClient
     }
 }
 
+/// Picks the fork with the highest cumulative difficulty, provided it is long enough and its
+/// blocks have been signed by a rotating set of validators, and returns the header in it that
+/// should be finalized.
+fn finalize_longest_chain<T: Config>(
+    consensus_state: &ConsensusState,
+) -> Result<Option<(usize, usize, CodecHeader)>, Error> {
+    let mut longest_chains = consensus_state
+        .forks
+        .iter()
+        .enumerate()
+        .filter(|(_, chain)| {
+            chain.hashes.len() >=
+                (consensus_state.finalized_validators.len() * SPRINT_LENGTH as usize)
+        })
+        .inspect(|(_, chain)| {
+            log::info!(target: "pallet-ismp", "Chain : {:?} --> {:?}; Difficulty -> {:#?}; length: {:?}", chain.hashes[0].1, chain.hashes[chain.hashes.len() - 1].1, chain.difficulty, chain.hashes.len());
+        })
+        .collect::<Vec<(usize, &Chain)>>();
+
+    let longest_chain = {
+        if longest_chains.is_empty() {
+            None
+        } else {
+            // Sort by highest cumulative difficulty
+            longest_chains.sort_by(|(_, a), (_, b)| a.difficulty.cmp(&b.difficulty));
+            if longest_chains.len() > 1 &&
+                longest_chains[longest_chains.len() - 1].1.difficulty ==
+                    longest_chains[longest_chains.len() - 2].1.difficulty
+            {
+                None
+            } else {
+                longest_chains.pop()
+            }
+        }
+    };
+
+    // we want to ensure that before we finalize a chain, most blocks have been signed by unique
+    // validators
+    let longest_chain = if let Some((fork_index, chain)) = longest_chain {
+        // The composition of validators in consecutive chunks must be unique
+        let mut validator_distribution = vec![];
+        for hashes in chain.hashes.chunks(SPRINT_LENGTH as usize) {
+            let mut validator_dist = BTreeMap::<H160, u64>::new();
+            hashes.iter().for_each(|(signer, _)| {
+                let entry = validator_dist.entry(*signer).or_insert(0);
+                *entry += 1;
+            });
+
+            let vals = validator_dist.into_iter().map(|a| a.0).collect::<Vec<_>>();
+            validator_distribution.push(vals);
+        }
+
+        log::info!(target: "pallet-ismp", "Validator distribution : {:?}", validator_distribution);
+
+        // Ensure that the composition of validators in each chunk is different
+        let mut prev = &validator_distribution[0];
+        if validator_distribution[1..].iter().all(|next| {
+            let check = next != prev;
+            prev = next;
+            check
+        }) {
+            Some((fork_index, chain))
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let Some((fork_index, chain)) = longest_chain else { return Ok(None) };
+
+    // we want 16 mins of probabilistic finality
+    let finality_index = chain.hashes.len().saturating_sub(480);
+    let header = Headers::<T>::get(chain.hashes[finality_index].1).ok_or_else(|| {
+        Error::ImplementationSpecific("Expected header to be found in storage".to_string())
+    })?;
+
+    Ok(Some((fork_index, finality_index, header)))
+}
+
 fn get_span(number: u64) -> u64 {
     number / SPAN_LENGTH
 }
@@ -14,11 +14,17 @@
 // limitations under the License.
 
 pub use pallet::*;
+use pallet_ismp::host::Host;
 
 #[frame_support::pallet]
 pub mod pallet {
+    use super::*;
+    use crate::{checkpoint::CheckpointConfig, ConsensusState};
+    use codec::Encode;
     use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
     use geth_primitives::CodecHeader;
+    use ismp::{consensus::ConsensusStateId, host::IsmpHost};
     use sp_core::H256;
 
     #[pallet::pallet]
@@ -29,8 +35,48 @@ pub mod pallet {
     #[pallet::config]
     pub trait Config: frame_system::Config + pallet_ismp::Config {}
 
-    /// Polygon block headers
+    /// Polygon block headers, headers below the finalized header are pruned as the client
+    /// finalizes new headers.
     #[pallet::storage]
     #[pallet::getter(fn headers)]
     pub type Headers<T: Config> = StorageMap<_, Identity, H256, CodecHeader, OptionQuery>;
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Error fetching consensus state
+        ErrorFetchingConsensusState,
+        /// Error decoding consensus state
+        ErrorDecodingConsensusState,
+        /// Error storing consensus state
+        ErrorStoringConsensusState,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Enable or disable finality through heimdall checkpoints for a polygon consensus state
+        #[pallet::call_index(0)]
+        #[pallet::weight(<T as frame_system::Config>::DbWeight::get().reads_writes(1, 1))]
+        pub fn set_checkpoint_config(
+            origin: OriginFor<T>,
+            consensus_state_id: ConsensusStateId,
+            checkpoint_config: Option<CheckpointConfig>,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+
+            let ismp_host = Host::<T>::default();
+            let encoded_consensus_state = ismp_host
+                .consensus_state(consensus_state_id)
+                .map_err(|_| Error::<T>::ErrorFetchingConsensusState)?;
+            let mut consensus_state: ConsensusState =
+                codec::Decode::decode(&mut &encoded_consensus_state[..])
+                    .map_err(|_| Error::<T>::ErrorDecodingConsensusState)?;
+
+            consensus_state.checkpoint_config = checkpoint_config;
+
+            ismp_host
+                .store_consensus_state(consensus_state_id, consensus_state.encode())
+                .map_err(|_| Error::<T>::ErrorStoringConsensusState)?;
+            Ok(())
+        }
+    }
 }
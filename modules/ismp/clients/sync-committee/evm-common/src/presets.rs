@@ -21,3 +21,10 @@ pub const RESPONSE_COMMITMENTS_SLOT: u64 = 1;
 pub const REQUEST_RECEIPTS_SLOT: u64 = 2;
 /// Slot index for response receipts map
 pub const RESPONSE_RECEIPTS_SLOT: u64 = 3;
+
+/// Chain id of the ethereum mainnet execution layer
+pub const MAINNET_CHAIN_ID: u64 = 1;
+/// Chain id of the sepolia testnet execution layer
+pub const SEPOLIA_CHAIN_ID: u64 = 11155111;
+/// Chain id of the holesky testnet execution layer
+pub const HOLESKY_CHAIN_ID: u64 = 17000;
@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use alloc::{collections::BTreeMap, format, string::ToString};
-use arbitrum_verifier::verify_arbitrum_payload;
+use arbitrum_verifier::{bold::verify_arbitrum_bold, verify_arbitrum_payload};
 use codec::{Decode, Encode};
 use evm_common::{
     construct_intermediate_state, req_res_receipt_keys, verify_membership, verify_state_proof,
@@ -31,8 +31,10 @@ use ismp::{
     messaging::{Proof, StateCommitmentHeight},
     router::RequestResponse,
 };
-use op_verifier::{verify_optimism_dispute_game_proof, verify_optimism_payload};
-use sync_committee_primitives::constants::Config;
+use op_verifier::{verify_optimism_dispute_game_proof, verify_optimism_payload, CANNON};
+use sync_committee_primitives::{
+    constants::Config, types::VerifierStateUpdate, util::compute_sync_committee_period_at_slot,
+};
 
 use crate::prelude::*;
 
@@ -58,9 +60,11 @@ impl<
             mut dispute_game_payload,
             consensus_update,
             mut arbitrum_payload,
+            arbitrum_bold_payload,
         } = BeaconClientUpdate::decode(&mut &consensus_proof[..]).map_err(|_| {
             Error::ImplementationSpecific("Cannot decode beacon client update".to_string())
         })?;
+        let mut arbitrum_bold_payload = arbitrum_bold_payload.unwrap_or_default();
 
         let consensus_state =
             ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|_| {
@@ -107,6 +111,7 @@ impl<
                             arbitrum_payload,
                             state_root,
                             rollup_core_address,
+                            state_machine,
                             consensus_state_id.clone(),
                         )?;
 
@@ -126,6 +131,7 @@ impl<
                             payload,
                             state_root,
                             l2_oracle,
+                            state_machine,
                             consensus_state_id.clone(),
                         )?;
 
@@ -145,6 +151,51 @@ impl<
                             payload,
                             state_root,
                             dispute_game_factory,
+                            None,
+                            &[CANNON],
+                            state_machine,
+                            consensus_state_id.clone(),
+                        )?;
+
+                        let state_commitment_height = StateCommitmentHeight {
+                            commitment: state.commitment,
+                            height: state.height.height,
+                        };
+
+                        let mut state_commitment_vec: Vec<StateCommitmentHeight> = Vec::new();
+                        state_commitment_vec.push(state_commitment_height);
+                        state_machine_map.insert(state_machine, state_commitment_vec);
+                    }
+                },
+                L2Consensus::OpFaultProofGames(params) => {
+                    if let Some(payload) = dispute_game_payload.remove(&state_machine) {
+                        let state = verify_optimism_dispute_game_proof::<H>(
+                            payload,
+                            state_root,
+                            params.dispute_game_factory,
+                            Some(params.optimism_portal),
+                            &params.respected_game_types,
+                            state_machine,
+                            consensus_state_id.clone(),
+                        )?;
+
+                        let state_commitment_height = StateCommitmentHeight {
+                            commitment: state.commitment,
+                            height: state.height.height,
+                        };
+
+                        let mut state_commitment_vec: Vec<StateCommitmentHeight> = Vec::new();
+                        state_commitment_vec.push(state_commitment_height);
+                        state_machine_map.insert(state_machine, state_commitment_vec);
+                    }
+                },
+                L2Consensus::ArbitrumBold(rollup_core_address) => {
+                    if let Some(payload) = arbitrum_bold_payload.remove(&state_machine) {
+                        let state = verify_arbitrum_bold::<H>(
+                            payload,
+                            state_root,
+                            rollup_core_address,
+                            state_machine,
                             consensus_state_id.clone(),
                         )?;
 
@@ -175,14 +226,60 @@ impl<
         Ok((new_consensus_state.encode(), state_machine_map))
     }
 
+    /// A fraud proof consists of two light client updates which are both signed by the same sync
+    /// committee known to the trusted state, but finalize different headers for the same slot.
     fn verify_fraud_proof(
         &self,
         _host: &dyn IsmpHost,
-        _trusted_consensus_state: Vec<u8>,
-        _proof_1: Vec<u8>,
-        _proof_2: Vec<u8>,
+        trusted_consensus_state: Vec<u8>,
+        proof_1: Vec<u8>,
+        proof_2: Vec<u8>,
     ) -> Result<(), Error> {
-        Err(Error::ImplementationSpecific("fraud proof verification unimplemented".to_string()))
+        let consensus_state =
+            ConsensusState::decode(&mut &trusted_consensus_state[..]).map_err(|_| {
+                Error::ImplementationSpecific("Cannot decode trusted consensus state".to_string())
+            })?;
+
+        let update_1 = VerifierStateUpdate::decode(&mut &proof_1[..]).map_err(|_| {
+            Error::ImplementationSpecific("Cannot decode first light client update".to_string())
+        })?;
+        let update_2 = VerifierStateUpdate::decode(&mut &proof_2[..]).map_err(|_| {
+            Error::ImplementationSpecific("Cannot decode second light client update".to_string())
+        })?;
+
+        if update_1.finalized_header.slot != update_2.finalized_header.slot {
+            Err(Error::ImplementationSpecific(
+                "Fraud proof updates must finalize headers for the same slot".to_string(),
+            ))?
+        }
+
+        if update_1.finalized_header == update_2.finalized_header {
+            Err(Error::ImplementationSpecific(
+                "Fraud proof updates finalize the same header".to_string(),
+            ))?
+        }
+
+        let signature_period_1 =
+            compute_sync_committee_period_at_slot::<C>(update_1.signature_slot);
+        let signature_period_2 =
+            compute_sync_committee_period_at_slot::<C>(update_2.signature_slot);
+        if signature_period_1 != signature_period_2 {
+            Err(Error::ImplementationSpecific(
+                "Fraud proof updates must be signed by the same sync committee".to_string(),
+            ))?
+        }
+
+        // One of the conflicting updates may already have been applied, so the updates are not
+        // required to be newer than the trusted state.
+        for update in [update_1, update_2] {
+            sync_committee_verifier::verify_sync_committee_signature::<C>(
+                &consensus_state.light_client_state,
+                update,
+            )
+            .map_err(|e| Error::ImplementationSpecific(format!("{:?}", e)))?;
+        }
+
+        Ok(())
     }
 
     fn consensus_client_id(&self) -> ConsensusClientId {
@@ -191,7 +288,8 @@ impl<
 
     fn state_machine(&self, id: StateMachine) -> Result<Box<dyn StateMachineClient>, Error> {
         match id {
-            StateMachine::Ethereum(_) => Ok(Box::new(<EvmStateMachine<H>>::default())),
+            StateMachine::Ethereum(_) | StateMachine::Evm(_) =>
+                Ok(Box::new(<EvmStateMachine<H>>::default())),
             _ => Err(Error::ImplementationSpecific("State machine not supported".to_string())),
         }
     }
@@ -0,0 +1,290 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for verifying sync committee updates of varying sizes.
+//!
+//! The updates are built for the mainnet fork schedule and signed by a sync committee whose
+//! members all share a single key, the cost of verifying them does not depend on the network.
+
+use crate::{
+    pallet::{Config, Pallet},
+    prelude::*,
+    types::{BeaconClientUpdate, ConsensusState},
+    SyncCommitteeConsensusClient, BEACON_CONSENSUS_ID,
+};
+use alloc::collections::{BTreeMap, BTreeSet};
+use bls::{types::SecretKey, DST_ETHEREUM};
+use codec::Encode;
+use frame_benchmarking::v2::*;
+use ismp::{consensus::ConsensusClient, host::StateMachine};
+use op_verifier::OptimismPayloadProof;
+use pallet_ismp::host::Host;
+use ssz_rs::{Bitvector, Merkleized, Node, Vector};
+use sync_committee_primitives::{
+    consensus_types::{
+        BeaconBlockHeader, Checkpoint, ExecutionPayloadHeader, SyncAggregate, SyncCommittee,
+    },
+    constants::{
+        mainnet::Mainnet, BlsPublicKey, BlsSignature, Config as EthereumConfig, Root,
+        BYTES_PER_LOGS_BLOOM, DOMAIN_SYNC_COMMITTEE, MAX_EXTRA_DATA_BYTES, SYNC_COMMITTEE_SIZE,
+    },
+    types::{
+        ExecutionPayloadProof, FinalityProof, SyncCommitteeUpdate, VerifierState,
+        VerifierStateUpdate,
+    },
+    util::{
+        compute_domain, compute_epoch_at_slot, compute_fork_version, compute_signing_root,
+        execution_payload_gindex, finalized_root_gindex, next_sync_committee_gindex,
+    },
+};
+
+const TRUSTED_SLOT: u64 = 5 * Mainnet::SLOTS_PER_EPOCH;
+const FINALIZED_SLOT: u64 = 10 * Mainnet::SLOTS_PER_EPOCH;
+
+/// Computes the nodes of a merkle tree containing the given leaves, keyed by their generalized
+/// index. Any other node is a zero hash.
+fn merkle_tree(leaves: &[(u64, Node)]) -> BTreeMap<u64, Node> {
+    let mut tree = leaves.iter().cloned().collect::<BTreeMap<_, _>>();
+    // Deeper nodes have larger generalized indices, so children are always hashed first.
+    let mut pending = tree.keys().cloned().collect::<BTreeSet<_>>();
+    while let Some(index) = pending.pop_last() {
+        let parent = index / 2;
+        if parent == 0 || tree.contains_key(&parent) {
+            continue;
+        }
+        let mut children = tree.get(&(parent * 2)).cloned().unwrap_or_default().as_ref().to_vec();
+        children
+            .extend_from_slice(tree.get(&(parent * 2 + 1)).cloned().unwrap_or_default().as_ref());
+        tree.insert(parent, Node::from_bytes(sp_io::hashing::sha2_256(&children)));
+        pending.insert(parent);
+    }
+    tree
+}
+
+/// Returns the merkle branch of the node at the generalized `index`.
+fn merkle_branch(tree: &BTreeMap<u64, Node>, mut index: u64) -> Vec<Node> {
+    let mut branch = vec![];
+    while index > 1 {
+        branch.push(tree.get(&(index ^ 1)).cloned().unwrap_or_default());
+        index /= 2;
+    }
+    branch
+}
+
+/// Returns a sync committee whose members all share the key of `secret_key`.
+fn sync_committee(secret_key: SecretKey) -> SyncCommittee<SYNC_COMMITTEE_SIZE> {
+    let public_key = BlsPublicKey::try_from(bls::sk_to_pk(secret_key).as_slice())
+        .expect("Public keys are 48 bytes");
+    SyncCommittee {
+        public_keys: Vector::try_from(vec![public_key.clone(); SYNC_COMMITTEE_SIZE])
+            .expect("Committee has the correct size"),
+        // Every member signs, so the aggregate key alone determines the signing key.
+        aggregate_public_key: public_key,
+    }
+}
+
+/// Returns the encoded consensus state of a light client that trusts the sync committee of
+/// `secret_key`.
+fn consensus_state(secret_key: SecretKey) -> Vec<u8> {
+    ConsensusState {
+        frozen_height: None,
+        light_client_state: VerifierState {
+            finalized_header: BeaconBlockHeader { slot: TRUSTED_SLOT, ..Default::default() },
+            latest_finalized_epoch: compute_epoch_at_slot::<Mainnet>(TRUSTED_SLOT),
+            current_sync_committee: sync_committee(secret_key),
+            next_sync_committee: sync_committee(secret_key),
+            state_period: 0,
+        },
+        ismp_contract_addresses: Default::default(),
+        l2_consensus: Default::default(),
+    }
+    .encode()
+}
+
+/// Builds a light client update signed by `secret_key` which finalizes a header at
+/// [`FINALIZED_SLOT`], whose contents are derived from `seed`. The update also carries the next
+/// sync committee, which is the most expensive update to verify.
+fn signed_update(secret_key: SecretKey, seed: u8) -> VerifierStateUpdate {
+    let finalized_epoch = compute_epoch_at_slot::<Mainnet>(FINALIZED_SLOT);
+
+    let mut execution_payload_header =
+        ExecutionPayloadHeader::<BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES>::default();
+    execution_payload_header.state_root =
+        [seed; 32].as_slice().try_into().expect("State root is 32 bytes");
+    execution_payload_header.block_number = FINALIZED_SLOT;
+    execution_payload_header.timestamp = FINALIZED_SLOT * 12;
+    let multi_proof = ssz_rs::generate_proof(
+        &mut execution_payload_header,
+        &[
+            Mainnet::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize,
+            Mainnet::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize,
+            Mainnet::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize,
+        ],
+    )
+    .expect("Execution payload header can be merkleized");
+    let (execution_payload_index, _) = execution_payload_gindex::<Mainnet>(finalized_epoch);
+    let state_tree = merkle_tree(&[(
+        execution_payload_index,
+        execution_payload_header
+            .hash_tree_root()
+            .expect("Execution payload header can be merkleized"),
+    )]);
+
+    let mut finalized_header = BeaconBlockHeader {
+        slot: FINALIZED_SLOT,
+        proposer_index: seed as u64,
+        parent_root: Node::default(),
+        state_root: state_tree[&1].clone(),
+        body_root: Node::from_bytes([seed; 32]),
+    };
+
+    let attested_slot = FINALIZED_SLOT + 2 * Mainnet::SLOTS_PER_EPOCH;
+    let attested_epoch = compute_epoch_at_slot::<Mainnet>(attested_slot);
+    let (finalized_root_index, _) = finalized_root_gindex::<Mainnet>(attested_epoch);
+    let (next_sync_committee_index, _) = next_sync_committee_gindex::<Mainnet>(attested_epoch);
+    let mut next_sync_committee = sync_committee(secret_key);
+    let mut checkpoint = Checkpoint {
+        epoch: finalized_epoch,
+        root: finalized_header.hash_tree_root().expect("Header can be merkleized"),
+    };
+    let attested_state_tree = merkle_tree(&[
+        (finalized_root_index, checkpoint.hash_tree_root().expect("Checkpoint can be merkleized")),
+        (
+            next_sync_committee_index,
+            next_sync_committee.hash_tree_root().expect("Sync committee can be merkleized"),
+        ),
+    ]);
+    let mut attested_header = BeaconBlockHeader {
+        slot: attested_slot,
+        proposer_index: seed as u64,
+        parent_root: Node::default(),
+        state_root: attested_state_tree[&1].clone(),
+        body_root: Node::default(),
+    };
+
+    let signature_slot = attested_slot + 1;
+    let domain = compute_domain(
+        DOMAIN_SYNC_COMMITTEE,
+        Some(compute_fork_version::<Mainnet>(compute_epoch_at_slot::<Mainnet>(signature_slot))),
+        Some(Root::from_bytes(Mainnet::GENESIS_VALIDATORS_ROOT)),
+        Mainnet::GENESIS_FORK_VERSION,
+    )
+    .expect("Domain can be computed");
+    let signing_root =
+        compute_signing_root(&mut attested_header, domain).expect("Header can be merkleized");
+    let signature =
+        bls::sign(secret_key, &signing_root.as_bytes().to_vec(), &DST_ETHEREUM.as_bytes().to_vec())
+            .expect("Signing is infallible");
+
+    let mut sync_committee_bits = Bitvector::<SYNC_COMMITTEE_SIZE>::default();
+    for i in 0..SYNC_COMMITTEE_SIZE {
+        sync_committee_bits.set(i, true);
+    }
+
+    VerifierStateUpdate {
+        attested_header,
+        sync_committee_update: Some(SyncCommitteeUpdate {
+            next_sync_committee_branch: merkle_branch(
+                &attested_state_tree,
+                next_sync_committee_index,
+            ),
+            next_sync_committee,
+        }),
+        finalized_header,
+        execution_payload: ExecutionPayloadProof {
+            state_root: [seed; 32].into(),
+            block_number: FINALIZED_SLOT,
+            multi_proof,
+            execution_payload_branch: merkle_branch(&state_tree, execution_payload_index),
+            timestamp: FINALIZED_SLOT * 12,
+        },
+        finality_proof: FinalityProof {
+            epoch: finalized_epoch,
+            finality_branch: merkle_branch(&attested_state_tree, finalized_root_index),
+        },
+        sync_aggregate: SyncAggregate {
+            sync_committee_bits,
+            sync_committee_signature: BlsSignature::try_from(signature.as_slice())
+                .expect("Signatures are 96 bytes"),
+        },
+        signature_slot,
+    }
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn verify_consensus(n: Linear<0, 100_000>) {
+        let secret_key = bls::keygen(&vec![7u8; 32], &vec![]);
+        // The update is padded with an l2 oracle payload of `n` bytes for a state machine the
+        // client doesn't track, which is decoded but never verified.
+        let padding = OptimismPayloadProof {
+            state_root: Default::default(),
+            withdrawal_storage_root: Default::default(),
+            l2_block_hash: Default::default(),
+            version: Default::default(),
+            l2_oracle_proof: vec![vec![0u8; n as usize]],
+            output_root_proof: vec![],
+            multi_proof: vec![],
+            output_root_index: 0,
+            block_number: 0,
+            timestamp: 0,
+        };
+        let proof = BeaconClientUpdate {
+            consensus_update: signed_update(secret_key, 1),
+            l2_oracle_payload: BTreeMap::from([(StateMachine::Evm(10), padding)]),
+            dispute_game_payload: Default::default(),
+            arbitrum_payload: Default::default(),
+            arbitrum_bold_payload: None,
+        }
+        .encode();
+        let consensus_state = consensus_state(secret_key);
+        let client = SyncCommitteeConsensusClient::<Host<T>, Mainnet>::default();
+        let host = Host::<T>::default();
+
+        let result;
+        #[block]
+        {
+            result = client.verify_consensus(&host, BEACON_CONSENSUS_ID, consensus_state, proof);
+        }
+
+        assert!(result.is_ok());
+    }
+
+    #[benchmark]
+    fn verify_fraud_proof(n: Linear<0, 100_000>) {
+        let secret_key = bls::keygen(&vec![7u8; 32], &vec![]);
+        // Trailing bytes are never decoded, both updates already carry the next sync committee.
+        let proof = |seed| {
+            let mut proof = signed_update(secret_key, seed).encode();
+            proof.resize(proof.len() + n as usize / 2, 0);
+            proof
+        };
+        let (proof_1, proof_2) = (proof(1), proof(2));
+        let consensus_state = consensus_state(secret_key);
+        let client = SyncCommitteeConsensusClient::<Host<T>, Mainnet>::default();
+        let host = Host::<T>::default();
+
+        let result;
+        #[block]
+        {
+            result = client.verify_fraud_proof(&host, consensus_state, proof_1, proof_2);
+        }
+
+        assert!(result.is_ok());
+    }
+}
@@ -25,8 +25,11 @@ pub mod prelude {
 }
 
 pub mod beacon_client;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 pub mod pallet;
 pub mod types;
+pub mod weights;
 
 pub use beacon_client::*;
 
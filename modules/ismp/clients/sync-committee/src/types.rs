@@ -12,9 +12,9 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use alloc::collections::BTreeMap;
-use arbitrum_verifier::ArbitrumPayloadProof;
-use codec::{Decode, Encode};
+use alloc::{collections::BTreeMap, vec::Vec};
+use arbitrum_verifier::{bold::ArbitrumBoldProof, ArbitrumPayloadProof};
+use codec::{Decode, Encode, Input};
 use ethabi::ethereum_types::H160;
 use ismp::host::StateMachine;
 use op_verifier::{OptimismDisputeGameProof, OptimismPayloadProof};
@@ -28,12 +28,36 @@ pub struct ConsensusState {
     pub l2_consensus: BTreeMap<StateMachine, L2Consensus>,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Encode)]
 pub struct BeaconClientUpdate {
     pub consensus_update: VerifierStateUpdate,
     pub l2_oracle_payload: BTreeMap<StateMachine, OptimismPayloadProof>,
     pub dispute_game_payload: BTreeMap<StateMachine, OptimismDisputeGameProof>,
     pub arbitrum_payload: BTreeMap<StateMachine, ArbitrumPayloadProof>,
+    /// Must remain the last field. Updates encoded before Arbitrum BoLD was supported end before
+    /// it and are decoded with no BoLD payload.
+    pub arbitrum_bold_payload: Option<BTreeMap<StateMachine, ArbitrumBoldProof>>,
+}
+
+impl Decode for BeaconClientUpdate {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let consensus_update = Decode::decode(input)?;
+        let l2_oracle_payload = Decode::decode(input)?;
+        let dispute_game_payload = Decode::decode(input)?;
+        let arbitrum_payload = Decode::decode(input)?;
+        let arbitrum_bold_payload = match input.remaining_len()? {
+            Some(0) => None,
+            _ => Decode::decode(input)?,
+        };
+
+        Ok(BeaconClientUpdate {
+            consensus_update,
+            l2_oracle_payload,
+            dispute_game_payload,
+            arbitrum_payload,
+            arbitrum_bold_payload,
+        })
+    }
 }
 
 /// Description of the various consensus mechanics supported for ethereum L2s
@@ -43,6 +67,22 @@ pub enum L2Consensus {
     ArbitrumOrbit(H160),
     /// Op Stack L2 Oracle Address
     OpL2Oracle(H160),
-    /// Op Stack Dispute game factory address
+    /// Op Stack Dispute game factory address, only cannon games are accepted. Superseded by
+    /// [`L2Consensus::OpFaultProofGames`], kept so that existing consensus states still decode.
     OpFaultProofs(H160),
+    /// Arbitrum BoLD chains Rollup Core Address
+    ArbitrumBold(H160),
+    /// Op Stack fault proof contracts and accepted dispute game types
+    OpFaultProofGames(OpFaultProofParams),
+}
+
+/// The Op Stack fault proof contracts and dispute game types a state machine is verified with
+#[derive(Encode, Decode, Debug, Clone, scale_info::TypeInfo, Eq, PartialEq)]
+pub struct OpFaultProofParams {
+    /// Dispute game factory address
+    pub dispute_game_factory: H160,
+    /// OptimismPortal address, which holds the respected game type and the game blacklist
+    pub optimism_portal: H160,
+    /// Dispute game types accepted for this state machine, e.g. permissionless cannon games only
+    pub respected_game_types: Vec<u32>,
 }
@@ -210,4 +210,9 @@ pub enum Error {
         /// The request metadata
         meta: Meta,
     },
+    /// Traffic for the request or response has been paused by the host
+    Paused {
+        /// The request or response metadata
+        meta: Meta,
+    },
 }
@@ -19,7 +19,7 @@ use crate::{
     error::Error,
     events::{Event, RequestResponseHandled},
     handlers::{validate_state_machine, MessageResult},
-    host::{IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine, Traffic},
     messaging::RequestMessage,
     router::{Request, RequestResponse},
     util::hash_request,
@@ -87,6 +87,9 @@ where
         .map(|request| {
             let wrapped_req = Request::Post(request.clone());
             let lambda = || {
+                if host.is_paused(Traffic::Incoming, request.source, &request.to) {
+                    Err(Error::Paused { meta: wrapped_req.clone().into() })?
+                }
                 let cb = router.module_for_id(request.to.clone())?;
                 let res = cb.on_accept(request.clone()).map(|_| {
                     let commitment = hash_request::<H>(&wrapped_req);
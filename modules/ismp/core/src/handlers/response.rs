@@ -19,7 +19,7 @@ use crate::{
     error::Error,
     events::{Event, RequestResponseHandled},
     handlers::{validate_state_machine, MessageResult},
-    host::{IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine, Traffic},
     messaging::ResponseMessage,
     router::{GetResponse, Request, RequestResponse, Response},
     util::{hash_request, hash_response},
@@ -87,6 +87,13 @@ where
                 .clone()
                 .into_iter()
                 .map(|response| {
+                    if host.is_paused(
+                        Traffic::Incoming,
+                        response.source_chain(),
+                        &response.destination_module(),
+                    ) {
+                        return Ok(Err(Error::Paused { meta: (&response).into() }));
+                    }
                     let cb = router.module_for_id(response.destination_module())?;
                     let res = cb.on_response(response.clone()).map(|_| {
                         let commitment = hash_response::<H>(&response);
@@ -144,6 +151,9 @@ where
                 .into_iter()
                 .map(|request| {
                     let wrapped_req = Request::Get(request.clone());
+                    if host.is_paused(Traffic::Incoming, request.dest, &request.from) {
+                        return Ok(Err(Error::Paused { meta: (&wrapped_req).into() }));
+                    }
                     let keys = request.keys.clone();
                     let values = state_machine.verify_state_proof(host, keys, state, &proof)?;
 
@@ -19,7 +19,7 @@ use crate::{
     error::Error,
     events::{Event, TimeoutHandled},
     handlers::{validate_state_machine, MessageResult},
-    host::{IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine, Traffic},
     messaging::TimeoutMessage,
     router::Response,
     util::{hash_post_response, hash_request},
@@ -83,6 +83,13 @@ where
             requests
                 .into_iter()
                 .map(|request| {
+                    if host.is_paused(
+                        Traffic::Timeout,
+                        request.dest_chain(),
+                        &request.source_module(),
+                    ) {
+                        return Ok(Err(Error::Paused { meta: (&request).into() }));
+                    }
                     let cb = router.module_for_id(request.source_module())?;
                     let res = cb.on_timeout(request.clone().into()).map(|_| {
                         let commitment = hash_request::<H>(&request);
@@ -142,6 +149,13 @@ where
             responses
                 .into_iter()
                 .map(|response| {
+                    if host.is_paused(
+                        Traffic::Timeout,
+                        response.dest_chain(),
+                        &response.source_module(),
+                    ) {
+                        return Ok(Err(Error::Paused { meta: (&response).into() }));
+                    }
                     let cb = router.module_for_id(response.source_module())?;
                     let res = cb.on_timeout(response.clone().into()).map(|_| {
                         let commitment = hash_post_response::<H>(&response);
@@ -180,6 +194,13 @@ where
             requests
                 .into_iter()
                 .map(|request| {
+                    if host.is_paused(
+                        Traffic::Timeout,
+                        request.dest_chain(),
+                        &request.source_module(),
+                    ) {
+                        return Ok(Err(Error::Paused { meta: (&request).into() }));
+                    }
                     let cb = router.module_for_id(request.source_module())?;
                     let res = cb.on_timeout(request.clone().into()).map(|_| {
                         let commitment = hash_request::<H>(&request);
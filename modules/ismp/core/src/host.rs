@@ -237,6 +237,24 @@ pub trait IsmpHost: Keccak256 {
             .map(|proxy| proxy == self.host_state_machine())
             .unwrap_or(false)
     }
+
+    /// Should return true if the given kind of traffic between the module on the host and the
+    /// counterparty state machine has been paused. Paused requests, responses and timeouts are
+    /// rejected with [`Error::Paused`] and can be retried once they are unpaused.
+    fn is_paused(&self, _traffic: Traffic, _state_machine: StateMachine, _module: &[u8]) -> bool {
+        false
+    }
+}
+
+/// The kinds of ISMP traffic that can be paused by the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Traffic {
+    /// Requests and responses received from a counterparty state machine
+    Incoming,
+    /// Requests and responses dispatched by modules on the host
+    Outgoing,
+    /// Timeouts of requests and responses dispatched by modules on the host
+    Timeout,
 }
 
 /// Currently supported ethereum state machines.
@@ -304,6 +322,10 @@ pub enum StateMachine {
     /// Bsc Pos
     #[codec(index = 6)]
     Bsc,
+    /// Evm state machines, such as OP-stack and Arbitrum Orbit chains, identified by their chain
+    /// id
+    #[codec(index = 7)]
+    Evm(u32),
 }
 
 impl Display for StateMachine {
@@ -321,6 +343,7 @@ impl Display for StateMachine {
             StateMachine::Beefy(id) => format!("BEEFY-{}", u32::from_be_bytes(*id)),
             StateMachine::Polygon => "POLY".to_string(),
             StateMachine::Bsc => "BSC".to_string(),
+            StateMachine::Evm(id) => format!("EVM-{id}"),
         };
         write!(f, "{}", str)
     }
@@ -369,6 +392,14 @@ impl FromStr for StateMachine {
                     .ok_or_else(|| format!("invalid state machine: {name}"))?;
                 StateMachine::Beefy(id)
             },
+            name if name.starts_with("EVM-") => {
+                let id = name
+                    .split('-')
+                    .last()
+                    .and_then(|id| u32::from_str(id).ok())
+                    .ok_or_else(|| format!("invalid state machine: {name}"))?;
+                StateMachine::Evm(id)
+            },
             name => Err(format!("Unknown state machine: {name}"))?,
         };
 
@@ -390,6 +421,7 @@ mod tests {
         let arb = StateMachine::Ethereum(Ethereum::Arbitrum);
         let op = StateMachine::Ethereum(Ethereum::Optimism);
         let base = StateMachine::Ethereum(Ethereum::Base);
+        let evm = StateMachine::Evm(8453);
 
         let grandpa_string = grandpa.to_string();
         let beefy_string = beefy.to_string();
@@ -397,6 +429,7 @@ mod tests {
         let arb_str = arb.to_string();
         let op_str = op.to_string();
         let base_str = base.to_string();
+        let evm_str = evm.to_string();
 
         dbg!(&grandpa_string);
         dbg!(&beefy_string);
@@ -407,5 +440,7 @@ mod tests {
         assert_eq!(arb, StateMachine::from_str(&arb_str).unwrap());
         assert_eq!(op, StateMachine::from_str(&op_str).unwrap());
         assert_eq!(base, StateMachine::from_str(&base_str).unwrap());
+        assert_eq!(evm, StateMachine::from_str(&evm_str).unwrap());
+        assert_eq!(evm_str, "EVM-8453");
     }
 }
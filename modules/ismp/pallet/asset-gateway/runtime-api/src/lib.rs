@@ -0,0 +1,37 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API for the asset gateway.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::Codec;
+use sp_core::H256;
+
+sp_api::decl_runtime_apis! {
+    /// Asset Gateway Runtime Apis
+    pub trait AssetGatewayApi<AccountId>
+    where
+        AccountId: Codec,
+    {
+        /// Return the assets held in custody for an account, as pairs of token gateway asset id
+        /// and amount
+        fn pending_claims(account: AccountId) -> Vec<(H256, u128)>;
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Custody of incoming transfers.
+//!
+//! When [`CustodyMode`](crate::CustodyMode) is enabled, incoming transfers are credited to the
+//! beneficiary as a pending claim rather than being sent over xcm as part of the ismp request.
+//! A failed xcm delivery then no longer fails the request, and the claim can be delivered by
+//! anyone through [`Call::claim_assets`](crate::Call), as many times as needed.
+
+use crate::{Config, CustodyMode, Error, Event, Pallet, PendingClaims};
+use alloc::vec;
+use frame_support::{ensure, traits::fungibles};
+use ismp::host::StateMachine;
+use sp_core::H256;
+use sp_runtime::DispatchResult;
+use staging_xcm::v3::{MultiLocation, WeightLimit};
+
+impl<T: Config> Pallet<T>
+where
+    T::AccountId: From<[u8; 32]> + Into<[u8; 32]>,
+    <T::Assets as fungibles::Inspect<T::AccountId>>::Balance: From<u128>,
+    u128: From<<T::Assets as fungibles::Inspect<T::AccountId>>::Balance>,
+{
+    /// Deliver an incoming transfer to the beneficiary on the reserve chain, or credit it as a
+    /// pending claim if custody mode is enabled
+    pub(crate) fn deliver_inbound(
+        asset_id: H256,
+        location: MultiLocation,
+        beneficiary: [u8; 32],
+        amount: u128,
+        source: StateMachine,
+    ) -> DispatchResult {
+        if CustodyMode::<T>::get() {
+            PendingClaims::<T>::mutate(T::AccountId::from(beneficiary), asset_id, |claim| {
+                *claim = claim.saturating_add(amount)
+            });
+            Self::deposit_event(Event::<T>::AssetClaimable {
+                beneficiary: beneficiary.into(),
+                amount: amount.into(),
+                asset_id,
+                source,
+            });
+            return Ok(())
+        }
+
+        Self::send_to_reserve(location, beneficiary, amount)?;
+        Self::deposit_event(Event::<T>::AssetReceived {
+            beneficiary: beneficiary.into(),
+            amount: amount.into(),
+            asset_id,
+            source,
+        });
+
+        Ok(())
+    }
+
+    /// Send the pending claim of `beneficiary` to its reserve chain. If a fee asset is provided,
+    /// the beneficiary's pending claim of it is sent along and used to pay for execution.
+    pub(crate) fn deliver_claim(
+        beneficiary: T::AccountId,
+        asset_id: H256,
+        fee_asset_id: Option<H256>,
+        weight_limit: WeightLimit,
+    ) -> DispatchResult {
+        let mut claims = vec![asset_id];
+        if let Some(fee_asset_id) = fee_asset_id.filter(|id| *id != asset_id) {
+            claims.push(fee_asset_id);
+        }
+
+        let mut assets = vec![];
+        for id in claims.iter() {
+            let amount = PendingClaims::<T>::take(&beneficiary, id);
+            ensure!(amount > 0, Error::<T>::NoPendingClaim);
+            let registration = Self::asset_registration(*id).ok_or(Error::<T>::UnknownAsset)?;
+            assets.push((registration.location, amount));
+        }
+
+        let (fee_asset, _) = *assets.last().expect("At least one asset is claimed; qed");
+        let reserve = Self::reserve_location(&fee_asset);
+        ensure!(
+            assets.iter().all(|(location, _)| Self::reserve_location(location) == reserve),
+            Error::<T>::IncompatibleFeeAsset
+        );
+
+        Self::reserve_transfer(
+            assets.clone(),
+            fee_asset,
+            beneficiary.clone().into(),
+            weight_limit,
+        )?;
+
+        for (asset_id, (_, amount)) in claims.into_iter().zip(assets) {
+            Self::deposit_event(Event::<T>::AssetClaimed {
+                beneficiary: beneficiary.clone(),
+                amount: amount.into(),
+                asset_id,
+            });
+        }
+
+        Ok(())
+    }
+}
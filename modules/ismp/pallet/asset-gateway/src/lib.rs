@@ -15,11 +15,13 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod custody;
+pub mod rate_limit;
 pub mod xcm_utilities;
 
 extern crate alloc;
 
-use alloc::{boxed::Box, string::ToString};
+use alloc::{boxed::Box, string::ToString, vec::Vec};
 use core::marker::PhantomData;
 
 use alloc::vec;
@@ -40,11 +42,15 @@ use ismp::{
     util::hash_request,
 };
 pub use pallet::*;
-use pallet_ismp::{dispatcher::Dispatcher, host::Host};
+use pallet_ismp::{dispatcher::Dispatcher, host::Host, primitives::ModuleId};
+use rate_limit::{QueuedTransferKind, TransferDirection};
 use sp_core::{H160, H256, U256};
-use sp_runtime::{traits::AccountIdConversion, Percent};
+use sp_runtime::{traits::AccountIdConversion, DispatchResult, Percent};
 use staging_xcm::{
-    v3::{AssetId, Fungibility, Junction, MultiAsset, MultiAssets, MultiLocation, WeightLimit},
+    v3::{
+        AssetId, Fungibility, Junction, Junctions, MultiAsset, MultiAssets, MultiLocation,
+        WeightLimit,
+    },
     VersionedMultiAssets, VersionedMultiLocation,
 };
 use xcm_utilities::MultiAccount;
@@ -52,9 +58,10 @@ use xcm_utilities::MultiAccount;
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use alloc::vec;
+    use crate::rate_limit::{QueuedTransfer, RateLimit, RouteUsage};
+    use alloc::{vec, vec::Vec};
     use frame_support::{pallet_prelude::*, traits::fungibles, PalletId};
-    use frame_system::pallet_prelude::OriginFor;
+    use frame_system::pallet_prelude::{ensure_signed, BlockNumberFor, OriginFor};
     use sp_runtime::Percent;
 
     #[pallet::pallet]
@@ -88,10 +95,103 @@ pub mod pallet {
     #[pallet::getter(fn params)]
     pub type Params<T> = StorageValue<_, TokenGatewayParams, OptionQuery>;
 
+    /// Assets that can be bridged through the gateway, keyed by their 32 byte token gateway
+    /// asset id
+    #[pallet::storage]
+    pub type SupportedAssets<T> =
+        StorageMap<_, Blake2_128Concat, H256, AssetRegistration, OptionQuery>;
+
+    /// Reverse lookup of the token gateway asset id for a registered asset location
+    #[pallet::storage]
+    pub type AssetIds<T> = StorageMap<_, Blake2_128Concat, MultiLocation, H256, OptionQuery>;
+
+    /// Routes to asset gateways on substrate based state machines
+    #[pallet::storage]
+    pub type SubstrateRoutes<T> =
+        StorageMap<_, Blake2_128Concat, StateMachine, SubstrateRoute, OptionQuery>;
+
+    /// Inflow and outflow limits for each asset on each route
+    #[pallet::storage]
+    pub type RateLimits<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        H256,
+        Blake2_128Concat,
+        StateMachine,
+        RateLimit<BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Amounts that have recently flowed through each rate limited route
+    #[pallet::storage]
+    pub type FlowUsage<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        H256,
+        Blake2_128Concat,
+        StateMachine,
+        RouteUsage<BlockNumberFor<T>>,
+        ValueQuery,
+    >;
+
+    /// Number of blocks that transfers exceeding a rate limit are held back for
+    #[pallet::storage]
+    pub type TransferDelay<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Transfers held back for exceeding a rate limit
+    #[pallet::storage]
+    pub type QueuedTransfers<T: Config> =
+        StorageMap<_, Identity, u64, QueuedTransfer<T::AccountId, BlockNumberFor<T>>, OptionQuery>;
+
+    /// Id of the next queued transfer
+    #[pallet::storage]
+    pub type NextQueuedTransferId<T> = StorageValue<_, u64, ValueQuery>;
+
+    /// When enabled, incoming transfers are credited to the beneficiary as a pending claim
+    /// instead of being sent to the reserve chain immediately
+    #[pallet::storage]
+    pub type CustodyMode<T> = StorageValue<_, bool, ValueQuery>;
+
+    /// Amounts held in custody for each beneficiary, keyed by token gateway asset id
+    #[pallet::storage]
+    pub type PendingClaims<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        H256,
+        u128,
+        ValueQuery,
+    >;
+
     #[pallet::error]
     pub enum Error<T> {
         /// Error encountered while dispatching post request
         DispatchPostError,
+        /// The asset id is not registered
+        UnknownAsset,
+        /// The asset location is already registered under a different asset id
+        LocationAlreadyRegistered,
+        /// Only assets whose reserve is the relay chain or a sibling parachain are supported
+        UnsupportedAssetLocation,
+        /// Routes can only be configured for substrate based state machines
+        UnsupportedStateMachine,
+        /// The counterpart module id is not a valid ismp module id
+        InvalidModuleId,
+        /// No route has been configured for the destination state machine
+        UnknownRoute,
+        /// The transfer amount exceeds the limit configured for the route
+        TransferLimitExceeded,
+        /// No queued transfer exists with the given id
+        UnknownQueuedTransfer,
+        /// The queued transfer can't be released yet
+        TransferNotReleasable,
+        /// The beneficiary has no pending claim for the asset
+        NoPendingClaim,
+        /// The fee asset does not share a reserve chain with the claimed asset
+        IncompatibleFeeAsset,
+        /// Only the beneficiary can choose the fee asset and weight limit of a claim
+        NotBeneficiary,
     }
 
     /// Events emiited by the relayer pallet
@@ -102,10 +202,12 @@ pub mod pallet {
         AssetTeleported {
             /// Source account on the relaychain
             from: T::AccountId,
-            /// beneficiary account on destination
-            to: H160,
+            /// beneficiary account on destination, evm addresses occupy the first 20 bytes
+            to: H256,
             /// Amount transferred
             amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
+            /// Token gateway asset id
+            asset_id: H256,
             /// Destination chain
             dest: StateMachine,
         },
@@ -117,6 +219,8 @@ pub mod pallet {
             beneficiary: T::AccountId,
             /// Amount transferred
             amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
+            /// Token gateway asset id
+            asset_id: H256,
             /// Destination chain
             source: StateMachine,
         },
@@ -128,9 +232,105 @@ pub mod pallet {
             beneficiary: T::AccountId,
             /// Amount transferred
             amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
+            /// Token gateway asset id
+            asset_id: H256,
             /// Destination chain
             source: StateMachine,
         },
+
+        /// An asset has been added to the registry
+        AssetRegistered {
+            /// Token gateway asset id
+            asset_id: H256,
+            /// Location of the asset relative to this chain
+            location: MultiLocation,
+        },
+
+        /// An asset has been removed from the registry
+        AssetDeregistered {
+            /// Token gateway asset id
+            asset_id: H256,
+        },
+
+        /// The route to a substrate based state machine has been updated
+        SubstrateRouteUpdated {
+            /// The counterpart state machine
+            state_machine: StateMachine,
+            /// The new route, `None` if it was removed
+            route: Option<SubstrateRoute>,
+        },
+
+        /// The rate limit for an asset on a route has been updated
+        RateLimitUpdated {
+            /// Token gateway asset id
+            asset_id: H256,
+            /// The counterpart state machine
+            state_machine: StateMachine,
+            /// The new limit, `None` if it was removed
+            limit: Option<RateLimit<BlockNumberFor<T>>>,
+        },
+
+        /// The delay for transfers exceeding a rate limit has been updated
+        TransferDelayUpdated {
+            /// Number of blocks transfers are held back for
+            delay: BlockNumberFor<T>,
+        },
+
+        /// A transfer exceeded the rate limit of its route and has been queued
+        RateLimitExceeded {
+            /// Id of the queued transfer
+            id: u64,
+            /// Token gateway asset id
+            asset_id: H256,
+            /// The counterpart state machine
+            state_machine: StateMachine,
+            /// Direction of the transfer
+            direction: TransferDirection,
+            /// Amount transferred
+            amount: u128,
+            /// The block from which the transfer can be released
+            release_at: BlockNumberFor<T>,
+        },
+
+        /// A queued transfer has been released
+        QueuedTransferReleased {
+            /// Id of the queued transfer
+            id: u64,
+        },
+
+        /// A queued transfer has been rejected
+        QueuedTransferRejected {
+            /// Id of the queued transfer
+            id: u64,
+        },
+
+        /// Custody mode has been enabled or disabled
+        CustodyModeUpdated {
+            /// Whether incoming transfers are held in custody
+            enabled: bool,
+        },
+
+        /// An incoming transfer has been credited to the beneficiary as a pending claim
+        AssetClaimable {
+            /// beneficiary account on the reserve chain
+            beneficiary: T::AccountId,
+            /// Amount credited
+            amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
+            /// Token gateway asset id
+            asset_id: H256,
+            /// Source chain
+            source: StateMachine,
+        },
+
+        /// A pending claim has been delivered to the beneficiary on the reserve chain
+        AssetClaimed {
+            /// beneficiary account on the reserve chain
+            beneficiary: T::AccountId,
+            /// Amount delivered
+            amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
+            /// Token gateway asset id
+            asset_id: H256,
+        },
     }
 
     #[derive(Clone, Encode, Decode, scale_info::TypeInfo, Eq, PartialEq, RuntimeDebug)]
@@ -153,6 +353,24 @@ pub mod pallet {
         }
     }
 
+    /// Describes an asset that can be bridged through the gateway
+    #[derive(Clone, Encode, Decode, scale_info::TypeInfo, Eq, PartialEq, RuntimeDebug)]
+    pub struct AssetRegistration {
+        /// Location of the asset relative to this chain
+        pub location: MultiLocation,
+        /// Percentage to be taken as protocol fees on transfers of this asset
+        pub protocol_fee_percentage: Percent,
+    }
+
+    /// Describes the asset gateway deployed on a substrate based state machine
+    #[derive(Clone, Encode, Decode, scale_info::TypeInfo, Eq, PartialEq, RuntimeDebug)]
+    pub struct SubstrateRoute {
+        /// Ismp module id of the asset gateway on the counterpart state machine
+        pub module_id: Vec<u8>,
+        /// The maximum amount that can be sent or received in a single transfer on this route
+        pub max_transfer_amount: u128,
+    }
+
     #[derive(Clone, Encode, Decode, scale_info::TypeInfo, Eq, PartialEq, RuntimeDebug)]
     pub struct TokenGatewayParamsUpdate {
         pub protocol_fee_percentage: Option<Percent>,
@@ -163,7 +381,9 @@ pub mod pallet {
     #[pallet::call]
     impl<T: Config> Pallet<T>
     where
-        T::AccountId: From<[u8; 32]>,
+        T::AccountId: From<[u8; 32]> + Into<[u8; 32]>,
+        <T::Assets as fungibles::Inspect<T::AccountId>>::Balance: From<u128>,
+        u128: From<<T::Assets as fungibles::Inspect<T::AccountId>>::Balance>,
     {
         #[pallet::weight(T::DbWeight::get().writes(1))]
         #[pallet::call_index(0)]
@@ -189,6 +409,191 @@ pub mod pallet {
             Params::<T>::put(current_params);
             Ok(())
         }
+
+        /// Register an asset with the gateway, replacing any existing registration for the same
+        /// asset id
+        #[pallet::weight(T::DbWeight::get().reads_writes(2, 3))]
+        #[pallet::call_index(1)]
+        pub fn register_asset(
+            origin: OriginFor<T>,
+            asset_id: H256,
+            registration: AssetRegistration,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(registration.location.parents == 1, Error::<T>::UnsupportedAssetLocation);
+            ensure!(
+                AssetIds::<T>::get(&registration.location)
+                    .map_or(true, |existing| existing == asset_id),
+                Error::<T>::LocationAlreadyRegistered
+            );
+
+            if let Some(previous) = SupportedAssets::<T>::get(asset_id) {
+                AssetIds::<T>::remove(&previous.location);
+            }
+
+            let location = registration.location.clone();
+            AssetIds::<T>::insert(&location, asset_id);
+            SupportedAssets::<T>::insert(asset_id, registration);
+
+            Self::deposit_event(Event::<T>::AssetRegistered { asset_id, location });
+            Ok(())
+        }
+
+        /// Remove an asset from the registry.
+        ///
+        /// Requests for this asset that are still in flight will fail to be delivered or refunded
+        /// until it is registered again.
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+        #[pallet::call_index(2)]
+        pub fn deregister_asset(origin: OriginFor<T>, asset_id: H256) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            let registration =
+                SupportedAssets::<T>::take(asset_id).ok_or(Error::<T>::UnknownAsset)?;
+            AssetIds::<T>::remove(&registration.location);
+
+            Self::deposit_event(Event::<T>::AssetDeregistered { asset_id });
+            Ok(())
+        }
+
+        /// Set or remove the route to the asset gateway on a substrate based state machine
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        #[pallet::call_index(3)]
+        pub fn set_substrate_route(
+            origin: OriginFor<T>,
+            state_machine: StateMachine,
+            route: Option<SubstrateRoute>,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(is_substrate_chain(&state_machine), Error::<T>::UnsupportedStateMachine);
+
+            match route.clone() {
+                Some(route) => {
+                    ensure!(
+                        ModuleId::from_bytes(&route.module_id).is_ok(),
+                        Error::<T>::InvalidModuleId
+                    );
+                    SubstrateRoutes::<T>::insert(state_machine, route);
+                },
+                None => SubstrateRoutes::<T>::remove(state_machine),
+            }
+
+            Self::deposit_event(Event::<T>::SubstrateRouteUpdated { state_machine, route });
+            Ok(())
+        }
+
+        /// Set or remove the inflow and outflow limits for an asset on a route
+        #[pallet::weight(T::DbWeight::get().writes(2))]
+        #[pallet::call_index(4)]
+        pub fn set_rate_limit(
+            origin: OriginFor<T>,
+            asset_id: H256,
+            state_machine: StateMachine,
+            limit: Option<RateLimit<BlockNumberFor<T>>>,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+
+            match limit.clone() {
+                Some(limit) => RateLimits::<T>::insert(asset_id, state_machine, limit),
+                None => {
+                    RateLimits::<T>::remove(asset_id, state_machine);
+                    FlowUsage::<T>::remove(asset_id, state_machine);
+                },
+            }
+
+            Self::deposit_event(Event::<T>::RateLimitUpdated { asset_id, state_machine, limit });
+            Ok(())
+        }
+
+        /// Set the number of blocks that transfers exceeding a rate limit are held back for
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        #[pallet::call_index(5)]
+        pub fn set_transfer_delay(
+            origin: OriginFor<T>,
+            delay: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            TransferDelay::<T>::put(delay);
+
+            Self::deposit_event(Event::<T>::TransferDelayUpdated { delay });
+            Ok(())
+        }
+
+        /// Release a queued transfer whose delay has elapsed, this can be called by anyone
+        #[pallet::weight(T::DbWeight::get().reads_writes(4, 4))]
+        #[pallet::call_index(6)]
+        pub fn release_queued_transfer(origin: OriginFor<T>, id: u64) -> DispatchResult {
+            ensure_signed(origin)?;
+            let transfer =
+                QueuedTransfers::<T>::take(id).ok_or(Error::<T>::UnknownQueuedTransfer)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= transfer.release_at,
+                Error::<T>::TransferNotReleasable
+            );
+
+            Self::execute_queued_transfer(transfer)?;
+            Self::deposit_event(Event::<T>::QueuedTransferReleased { id });
+            Ok(())
+        }
+
+        /// Release a queued transfer immediately, or reject it. Rejected outgoing transfers are
+        /// refunded to the sender on the asset's reserve chain, while rejected incoming transfers
+        /// remain in custody.
+        #[pallet::weight(T::DbWeight::get().reads_writes(4, 4))]
+        #[pallet::call_index(7)]
+        pub fn resolve_queued_transfer(
+            origin: OriginFor<T>,
+            id: u64,
+            release: bool,
+        ) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            let transfer =
+                QueuedTransfers::<T>::take(id).ok_or(Error::<T>::UnknownQueuedTransfer)?;
+
+            if release {
+                Self::execute_queued_transfer(transfer)?;
+                Self::deposit_event(Event::<T>::QueuedTransferReleased { id });
+            } else {
+                Self::reject_queued_transfer(transfer)?;
+                Self::deposit_event(Event::<T>::QueuedTransferRejected { id });
+            }
+
+            Ok(())
+        }
+
+        /// Enable or disable custody mode for incoming transfers
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        #[pallet::call_index(8)]
+        pub fn set_custody_mode(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+            <T as pallet_ismp::Config>::AdminOrigin::ensure_origin(origin)?;
+            CustodyMode::<T>::put(enabled);
+
+            Self::deposit_event(Event::<T>::CustodyModeUpdated { enabled });
+            Ok(())
+        }
+
+        /// Deliver a pending claim to the beneficiary on the asset's reserve chain. This can be
+        /// called by anyone and retried if delivery fails. The xcm fees can optionally be paid
+        /// with another pending claim of the beneficiary that shares the same reserve chain.
+        ///
+        /// Only the beneficiary can choose the fee asset and weight limit, anyone else must
+        /// deliver the claim with no fee asset and an unlimited weight.
+        #[pallet::weight(T::DbWeight::get().reads_writes(4, 4))]
+        #[pallet::call_index(9)]
+        pub fn claim_assets(
+            origin: OriginFor<T>,
+            beneficiary: T::AccountId,
+            asset_id: H256,
+            fee_asset_id: Option<H256>,
+            weight_limit: WeightLimit,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                who == beneficiary ||
+                    (fee_asset_id.is_none() && weight_limit == WeightLimit::Unlimited),
+                Error::<T>::NotBeneficiary
+            );
+            Self::deliver_claim(beneficiary, asset_id, fee_asset_id, weight_limit)
+        }
     }
 }
 
@@ -201,6 +606,11 @@ where
         T::PalletId::get().into_account_truncating()
     }
 
+    /// The ismp module id of this pallet, requests to substrate chains are dispatched from it
+    pub fn module_id() -> ModuleId {
+        ModuleId::Pallet(T::PalletId::get())
+    }
+
     pub fn protocol_account_id() -> T::AccountId {
         T::ProtocolAccount::get().into_account_truncating()
     }
@@ -219,17 +629,128 @@ where
         Params::<T>::get().unwrap_or(<T as Config>::Params::get()).dot_asset_id
     }
 
-    /// Dispatch ismp request to token gateway on destination chain
+    /// Resolve a token gateway asset id through the registry. The relay chain asset is always
+    /// available under `dot_asset_id` unless it has been explicitly registered.
+    pub fn asset_registration(asset_id: H256) -> Option<AssetRegistration> {
+        SupportedAssets::<T>::get(asset_id).or_else(|| {
+            (asset_id == Self::dot_asset_id()).then(|| AssetRegistration {
+                location: MultiLocation::parent(),
+                protocol_fee_percentage: Self::protocol_fee_percentage(),
+            })
+        })
+    }
+
+    /// Resolve the token gateway asset id for an asset location through the registry
+    pub fn token_gateway_asset_id(location: &MultiLocation) -> Option<H256> {
+        AssetIds::<T>::get(location)
+            .or_else(|| (*location == MultiLocation::parent()).then(Self::dot_asset_id))
+    }
+
+    /// Returns the pending claims of an account as pairs of token gateway asset id and amount
+    pub fn pending_claims(account: T::AccountId) -> Vec<(H256, u128)> {
+        PendingClaims::<T>::iter_prefix(account).collect()
+    }
+
+    /// The reserve chain of an asset, this is where assets are sent back to over xcm
+    pub fn reserve_location(location: &MultiLocation) -> MultiLocation {
+        match location.first_interior() {
+            Some(Junction::Parachain(id)) =>
+                MultiLocation::new(location.parents, Junction::Parachain(*id)),
+            _ => MultiLocation::new(location.parents, Junctions::Here),
+        }
+    }
+
+    /// Send `amount` of the asset at `location` from the pallet custody account to the
+    /// beneficiary on the asset's reserve chain
+    pub fn send_to_reserve(
+        location: MultiLocation,
+        beneficiary: [u8; 32],
+        amount: u128,
+    ) -> DispatchResult {
+        Self::reserve_transfer(
+            vec![(location, amount)],
+            location,
+            beneficiary,
+            WeightLimit::Unlimited,
+        )
+    }
+
+    /// Send `assets` from the pallet custody account to the beneficiary on their reserve chain.
+    /// All assets must share the reserve chain of `fee_asset`, which is used to pay for execution.
+    pub fn reserve_transfer(
+        assets: Vec<(MultiLocation, u128)>,
+        fee_asset: MultiLocation,
+        beneficiary: [u8; 32],
+        weight_limit: WeightLimit,
+    ) -> DispatchResult {
+        let xcm_beneficiary: MultiLocation =
+            Junction::AccountId32 { network: None, id: beneficiary }.into();
+        let xcm_dest = VersionedMultiLocation::V3(Self::reserve_location(&fee_asset));
+        let assets = MultiAssets::from(
+            assets
+                .into_iter()
+                .map(|(location, amount)| MultiAsset {
+                    id: AssetId::Concrete(location),
+                    fun: Fungibility::Fungible(amount),
+                })
+                .collect::<Vec<_>>(),
+        );
+        // Assets are sorted, so the position of the fee asset has to be looked up
+        let fee_asset_item = assets
+            .inner()
+            .iter()
+            .position(|asset| asset.id == AssetId::Concrete(fee_asset))
+            .unwrap_or_default() as u32;
+
+        // Send xcm back to the reserve chain
+        pallet_xcm::Pallet::<T>::limited_reserve_transfer_assets(
+            frame_system::RawOrigin::Signed(Self::account_id()).into(),
+            Box::new(xcm_dest),
+            Box::new(xcm_beneficiary.into()),
+            Box::new(VersionedMultiAssets::V3(assets)),
+            fee_asset_item,
+            weight_limit,
+        )
+    }
+
+    /// The module that transfers to and from `state_machine` are exchanged with. This is the
+    /// token gateway contract on evm chains, or the configured asset gateway on substrate chains.
+    pub fn counterpart_module(state_machine: &StateMachine) -> Option<Vec<u8>> {
+        if is_substrate_chain(state_machine) {
+            SubstrateRoutes::<T>::get(state_machine).map(|route| route.module_id)
+        } else {
+            Some(Self::token_gateway_address().0.to_vec())
+        }
+    }
+
+    /// Ensure that `amount` is within the limits of the route to `state_machine`
+    pub fn ensure_transfer_limit(
+        state_machine: &StateMachine,
+        amount: u128,
+    ) -> Result<(), Error<T>> {
+        if !is_substrate_chain(state_machine) {
+            return Ok(())
+        }
+
+        let route = SubstrateRoutes::<T>::get(state_machine).ok_or(Error::<T>::UnknownRoute)?;
+        ensure!(amount <= route.max_transfer_amount, Error::<T>::TransferLimitExceeded);
+        Ok(())
+    }
+
+    /// Dispatch ismp request to the asset gateway on destination chain
     pub fn dispatch_request(
         multi_account: MultiAccount<T::AccountId>,
+        asset_id: H256,
         amount: <T::Assets as fungibles::Inspect<T::AccountId>>::Balance,
     ) -> Result<(), Error<T>> {
         let dispatcher = Dispatcher::<T>::default();
 
-        let mut to = [0u8; 32];
-        to[..20].copy_from_slice(&multi_account.evm_account.0);
+        let dest_module = Self::counterpart_module(&multi_account.dest_state_machine)
+            .ok_or(Error::<T>::UnknownRoute)?;
+        Self::ensure_transfer_limit(&multi_account.dest_state_machine, amount.into())?;
+
+        let to = multi_account.beneficiary.0;
         let from: [u8; 32] = multi_account.substrate_account.clone().into();
-        let asset_id = Self::dot_asset_id().0.into();
         let body = Body {
             amount: {
                 let amount: u128 = amount.into();
@@ -237,7 +758,7 @@ where
                 U256::from(amount).to_big_endian(&mut bytes);
                 alloy_primitives::U256::from_be_bytes(bytes)
             },
-            asset_id,
+            asset_id: asset_id.0.into(),
             redeem: false,
             from: from.into(),
             to: to.into(),
@@ -245,8 +766,12 @@ where
 
         let dispatch_post = DispatchPost {
             dest: multi_account.dest_state_machine,
-            from: Self::token_gateway_address().0.to_vec(),
-            to: Self::token_gateway_address().0.to_vec(),
+            from: if is_substrate_chain(&multi_account.dest_state_machine) {
+                Self::module_id().to_bytes()
+            } else {
+                Self::token_gateway_address().0.to_vec()
+            },
+            to: dest_module,
             timeout_timestamp: multi_account.timeout,
             data: {
                 // Prefix with the handleIncomingAsset enum variant
@@ -266,15 +791,27 @@ where
 
         Self::deposit_event(Event::<T>::AssetTeleported {
             from: multi_account.substrate_account,
-            to: multi_account.evm_account,
+            to: multi_account.beneficiary,
             dest: multi_account.dest_state_machine,
             amount,
+            asset_id,
         });
 
         Ok(())
     }
 }
 
+/// Returns true if the state machine is a substrate based chain
+pub(crate) fn is_substrate_chain(state_machine: &StateMachine) -> bool {
+    matches!(
+        state_machine,
+        StateMachine::Kusama(_) |
+            StateMachine::Polkadot(_) |
+            StateMachine::Grandpa(_) |
+            StateMachine::Beefy(_)
+    )
+}
+
 #[derive(RlpDecodable, RlpEncodable, Debug, Clone)]
 pub struct Body {
     // amount to be sent
@@ -307,9 +844,22 @@ where
 {
     fn on_accept(&self, post: ismp::router::Post) -> Result<(), ismp::error::Error> {
         let request = Request::Post(post.clone());
-        // Check that source module is equal to the known token gateway deployment address
+        // parachains/solochains may only send us requests through a configured route.
+        let source_module =
+            Pallet::<T>::counterpart_module(&request.source_chain()).ok_or_else(|| {
+                ismp::error::Error::ModuleDispatchError {
+                    msg: "Token Gateway: Illegal source chain".to_string(),
+                    meta: Meta {
+                        source: request.source_chain(),
+                        dest: request.dest_chain(),
+                        nonce: request.nonce(),
+                    },
+                }
+            })?;
+
+        // Check that source module is equal to the known asset gateway deployment
         ensure!(
-            request.source_module() == Pallet::<T>::token_gateway_address().0.to_vec(),
+            request.source_module() == source_module,
             ismp::error::Error::ModuleDispatchError {
                 msg: "Token Gateway: Unknown source contract address".to_string(),
                 meta: Meta {
@@ -320,28 +870,21 @@ where
             }
         );
 
-        // parachains/solochains shouldn't be sending us a request.
-        ensure!(
-            !matches!(
-                request.source_chain(),
-                StateMachine::Kusama(_) |
-                    StateMachine::Polkadot(_) |
-                    StateMachine::Grandpa(_) |
-                    StateMachine::Beefy(_)
-            ),
+        let body: Body = alloy_rlp::Decodable::decode(&mut &post.data[1..]).map_err(|_| {
             ismp::error::Error::ModuleDispatchError {
-                msg: "Token Gateway: Illegal source chain".to_string(),
+                msg: "Token Gateway: Failed to decode request body".to_string(),
                 meta: Meta {
                     source: request.source_chain(),
                     dest: request.dest_chain(),
                     nonce: request.nonce(),
                 },
             }
-        );
+        })?;
 
-        let body: Body = alloy_rlp::Decodable::decode(&mut &post.data[1..]).map_err(|_| {
+        // Check that the asset id is registered
+        let asset = Pallet::<T>::asset_registration(H256(body.asset_id.0)).ok_or_else(|| {
             ismp::error::Error::ModuleDispatchError {
-                msg: "Token Gateway: Failed to decode request body".to_string(),
+                msg: "Token Gateway: AssetId is unknown".to_string(),
                 meta: Meta {
                     source: request.source_chain(),
                     dest: request.dest_chain(),
@@ -350,26 +893,24 @@ where
             }
         })?;
 
-        // Check that the asset id is equal to the known asset id
-        ensure!(
-            body.asset_id.0 == Pallet::<T>::dot_asset_id().0,
+        let amount = { U256::from_big_endian(&body.amount.to_be_bytes::<32>()).low_u128() };
+
+        Pallet::<T>::ensure_transfer_limit(&request.source_chain(), amount).map_err(|_| {
             ismp::error::Error::ModuleDispatchError {
-                msg: "Token Gateway: AssetId is unknown".to_string(),
+                msg: "Token Gateway: Transfer limit exceeded".to_string(),
                 meta: Meta {
                     source: request.source_chain(),
                     dest: request.dest_chain(),
                     nonce: request.nonce(),
                 },
             }
-        );
-
-        let amount = { U256::from_big_endian(&body.amount.to_be_bytes::<32>()).low_u128() };
+        })?;
 
-        let asset_id = MultiLocation::parent();
+        let asset_id = asset.location;
 
         let protocol_account = Pallet::<T>::protocol_account_id();
         let pallet_account = Pallet::<T>::account_id();
-        let protocol_percentage = Pallet::<T>::protocol_fee_percentage();
+        let protocol_percentage = asset.protocol_fee_percentage;
 
         let protocol_fees = protocol_percentage * amount;
         let amount = amount - protocol_fees;
@@ -390,29 +931,35 @@ where
             },
         })?;
 
-        // We don't custody user funds, we send the dot back to the relaychain using xcm
-        let xcm_beneficiary: MultiLocation =
-            Junction::AccountId32 { network: None, id: body.to.0 }.into();
-        let xcm_dest = VersionedMultiLocation::V3(MultiLocation::parent());
-        let fee_asset_item = 0;
-        let weight_limit = WeightLimit::Unlimited;
-        let asset =
-            MultiAsset { id: AssetId::Concrete(asset_id), fun: Fungibility::Fungible(amount) };
-
-        let mut assets = MultiAssets::new();
-        assets.push(asset);
+        // Transfers above the rate limit of this route are held back for review
+        let token_gateway_asset_id = H256(body.asset_id.0);
+        if !Pallet::<T>::record_flow(
+            token_gateway_asset_id,
+            request.source_chain(),
+            TransferDirection::Inflow,
+            amount,
+        ) {
+            Pallet::<T>::queue_transfer(
+                token_gateway_asset_id,
+                amount,
+                QueuedTransferKind::Inbound {
+                    source: request.source_chain(),
+                    beneficiary: body.to.0,
+                },
+            );
+            return Ok(())
+        }
 
-        // Send xcm back to relaychain
-        pallet_xcm::Pallet::<T>::limited_reserve_transfer_assets(
-            frame_system::RawOrigin::Signed(Pallet::<T>::account_id()).into(),
-            Box::new(xcm_dest),
-            Box::new(xcm_beneficiary.into()),
-            Box::new(VersionedMultiAssets::V3(assets)),
-            fee_asset_item,
-            weight_limit,
+        // Unless custody mode is enabled, we send the asset back to its reserve chain using xcm
+        Pallet::<T>::deliver_inbound(
+            token_gateway_asset_id,
+            asset_id,
+            body.to.0,
+            amount,
+            request.source_chain(),
         )
         .map_err(|_| ismp::error::Error::ModuleDispatchError {
-            msg: "Token Gateway: Failed execute xcm to relay chain".to_string(),
+            msg: "Token Gateway: Failed execute xcm to reserve chain".to_string(),
             meta: Meta {
                 source: request.source_chain(),
                 dest: request.dest_chain(),
@@ -420,12 +967,6 @@ where
             },
         })?;
 
-        Pallet::<T>::deposit_event(Event::<T>::AssetReceived {
-            beneficiary: body.to.0.into(),
-            amount: amount.into(),
-            source: request.source_chain(),
-        });
-
         Ok(())
     }
 
@@ -441,7 +982,7 @@ where
     }
 
     fn on_timeout(&self, request: Timeout) -> Result<(), ismp::error::Error> {
-        // We don't custody user funds, we send the dot back to the relaychain using xcm
+        // We don't custody user funds, we send the asset back to its reserve chain using xcm
 
         match request {
             Timeout::Request(Request::Post(post)) => {
@@ -470,33 +1011,28 @@ where
                             },
                         }
                     })?;
-                // Send xcm back to relaychain
+                let asset_id = H256(body.asset_id.0);
+                let registration = Pallet::<T>::asset_registration(asset_id).ok_or_else(|| {
+                    ismp::error::Error::ModuleDispatchError {
+                        msg: "Token Gateway: AssetId is unknown".to_string(),
+                        meta: Meta {
+                            source: request.source_chain(),
+                            dest: request.dest_chain(),
+                            nonce: request.nonce(),
+                        },
+                    }
+                })?;
 
                 let amount = { U256::from_big_endian(&body.amount.to_be_bytes::<32>()).low_u128() };
                 // We do an xcm limited reserve transfer from the pallet custody account to the user
-                // on the relaychain;
-                let xcm_beneficiary: MultiLocation =
-                    Junction::AccountId32 { network: None, id: beneficiary.clone().into() }.into();
-                let xcm_dest = VersionedMultiLocation::V3(MultiLocation::parent());
-                let fee_asset_item = 0;
-                let weight_limit = WeightLimit::Unlimited;
-                let asset = MultiAsset {
-                    id: AssetId::Concrete(MultiLocation::parent()),
-                    fun: Fungibility::Fungible(amount),
-                };
-
-                let mut assets = MultiAssets::new();
-                assets.push(asset);
-                pallet_xcm::Pallet::<T>::limited_reserve_transfer_assets(
-                    frame_system::RawOrigin::Signed(Pallet::<T>::account_id()).into(),
-                    Box::new(xcm_dest),
-                    Box::new(xcm_beneficiary.into()),
-                    Box::new(VersionedMultiAssets::V3(assets)),
-                    fee_asset_item,
-                    weight_limit,
+                // on the reserve chain;
+                Pallet::<T>::send_to_reserve(
+                    registration.location,
+                    beneficiary.clone().into(),
+                    amount,
                 )
                 .map_err(|_| ismp::error::Error::ModuleDispatchError {
-                    msg: "Token Gateway: Failed execute xcm to relay chain".to_string(),
+                    msg: "Token Gateway: Failed execute xcm to reserve chain".to_string(),
                     meta: Meta {
                         source: request.source_chain(),
                         dest: request.dest_chain(),
@@ -507,6 +1043,7 @@ where
                 Pallet::<T>::deposit_event(Event::<T>::AssetRefunded {
                     beneficiary,
                     amount: amount.into(),
+                    asset_id,
                     source: request.dest_chain(),
                 });
 
@@ -0,0 +1,232 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate limiting of transfers through the gateway.
+//!
+//! Each asset can be given an inflow and an outflow limit per counterpart state machine. Usage of
+//! a limit drains linearly over the configured window of blocks, approximating a sliding window.
+//! Transfers that would exceed a limit are queued and can be released by anyone once the
+//! governance set [`TransferDelay`](crate::TransferDelay) has elapsed, or released early or
+//! rejected by the admin origin.
+
+use crate::{
+    xcm_utilities::MultiAccount, Config, Error, Event, FlowUsage, NextQueuedTransferId, Pallet,
+    QueuedTransfers, RateLimits, TransferDelay,
+};
+use codec::{Decode, Encode};
+use frame_support::traits::fungibles;
+use frame_system::pallet_prelude::BlockNumberFor;
+use ismp::host::StateMachine;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{Saturating, UniqueSaturatedInto, Zero},
+    DispatchResult, RuntimeDebug,
+};
+
+/// Inflow and outflow limits for an asset on a route
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RateLimit<BlockNumber> {
+    /// The maximum amount that can be received from the counterpart within the window
+    pub inflow: u128,
+    /// The maximum amount that can be sent to the counterpart within the window
+    pub outflow: u128,
+    /// Length of the window in blocks
+    pub window: BlockNumber,
+}
+
+/// Amounts that have recently flowed through a route
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct RouteUsage<BlockNumber> {
+    /// Amount received that still counts towards the inflow limit
+    pub inflow: u128,
+    /// Amount sent that still counts towards the outflow limit
+    pub outflow: u128,
+    /// The block at which usage was last updated
+    pub updated_at: BlockNumber,
+}
+
+/// Direction of a transfer through the gateway
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum TransferDirection {
+    /// Assets received from a counterpart gateway
+    Inflow,
+    /// Assets sent to a counterpart gateway
+    Outflow,
+}
+
+/// A transfer that has been held back for exceeding a rate limit
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct QueuedTransfer<AccountId, BlockNumber> {
+    /// Token gateway asset id
+    pub asset_id: H256,
+    /// Amount transferred, after protocol fees
+    pub amount: u128,
+    /// Details of the transfer
+    pub kind: QueuedTransferKind<AccountId>,
+    /// The block from which the transfer can be released
+    pub release_at: BlockNumber,
+}
+
+/// Details of a queued transfer
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum QueuedTransferKind<AccountId> {
+    /// Assets received from a counterpart, waiting to be sent to the beneficiary on the reserve
+    /// chain
+    Inbound {
+        /// The counterpart state machine
+        source: StateMachine,
+        /// Beneficiary account on the reserve chain
+        beneficiary: [u8; 32],
+    },
+    /// Assets received over xcm, waiting to be dispatched to a counterpart
+    Outbound {
+        /// Origin account on the reserve chain
+        from: AccountId,
+        /// Beneficiary account on the destination
+        to: H256,
+        /// The counterpart state machine
+        dest: StateMachine,
+        /// Request timeout in seconds
+        timeout: u64,
+    },
+}
+
+impl<T: Config> Pallet<T>
+where
+    T::AccountId: From<[u8; 32]> + Into<[u8; 32]>,
+    <T::Assets as fungibles::Inspect<T::AccountId>>::Balance: From<u128>,
+    u128: From<<T::Assets as fungibles::Inspect<T::AccountId>>::Balance>,
+{
+    /// Records `amount` against the rate limit of the route, returns false without recording
+    /// anything if the limit would be exceeded. Routes without a limit are unrestricted.
+    pub fn record_flow(
+        asset_id: H256,
+        state_machine: StateMachine,
+        direction: TransferDirection,
+        amount: u128,
+    ) -> bool {
+        let Some(limit) = RateLimits::<T>::get(asset_id, state_machine) else { return true };
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let mut usage = FlowUsage::<T>::get(asset_id, state_machine);
+        let elapsed: u128 = now.saturating_sub(usage.updated_at).unique_saturated_into();
+        let window: u128 = limit.window.unique_saturated_into();
+        usage.inflow = drained(usage.inflow, limit.inflow, elapsed, window);
+        usage.outflow = drained(usage.outflow, limit.outflow, elapsed, window);
+        usage.updated_at = now;
+
+        let (used, cap) = match direction {
+            TransferDirection::Inflow => (&mut usage.inflow, limit.inflow),
+            TransferDirection::Outflow => (&mut usage.outflow, limit.outflow),
+        };
+        let total = used.saturating_add(amount);
+        if total > cap {
+            return false
+        }
+        *used = total;
+
+        FlowUsage::<T>::insert(asset_id, state_machine, usage);
+        true
+    }
+
+    /// Hold back a transfer that exceeded the rate limit of its route
+    pub fn queue_transfer(
+        asset_id: H256,
+        amount: u128,
+        kind: QueuedTransferKind<T::AccountId>,
+    ) -> u64 {
+        let id = NextQueuedTransferId::<T>::mutate(|next| {
+            let id = *next;
+            *next = next.saturating_add(1);
+            id
+        });
+        let release_at =
+            frame_system::Pallet::<T>::block_number().saturating_add(TransferDelay::<T>::get());
+        let (state_machine, direction) = match &kind {
+            QueuedTransferKind::Inbound { source, .. } => (*source, TransferDirection::Inflow),
+            QueuedTransferKind::Outbound { dest, .. } => (*dest, TransferDirection::Outflow),
+        };
+
+        QueuedTransfers::<T>::insert(id, QueuedTransfer { asset_id, amount, kind, release_at });
+        Self::deposit_event(Event::<T>::RateLimitExceeded {
+            id,
+            asset_id,
+            state_machine,
+            direction,
+            amount,
+            release_at,
+        });
+
+        id
+    }
+
+    /// Complete a queued transfer
+    pub(crate) fn execute_queued_transfer(
+        transfer: QueuedTransfer<T::AccountId, BlockNumberFor<T>>,
+    ) -> DispatchResult {
+        match transfer.kind {
+            QueuedTransferKind::Inbound { source, beneficiary } => {
+                let registration =
+                    Self::asset_registration(transfer.asset_id).ok_or(Error::<T>::UnknownAsset)?;
+                Self::deliver_inbound(
+                    transfer.asset_id,
+                    registration.location,
+                    beneficiary,
+                    transfer.amount,
+                    source,
+                )?;
+            },
+            QueuedTransferKind::Outbound { from, to, dest, timeout } => {
+                let multi_account = MultiAccount {
+                    substrate_account: from,
+                    beneficiary: to,
+                    dest_state_machine: dest,
+                    timeout,
+                };
+                Self::dispatch_request(multi_account, transfer.asset_id, transfer.amount.into())?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Drop a queued transfer, refunding the sender of outgoing transfers
+    pub(crate) fn reject_queued_transfer(
+        transfer: QueuedTransfer<T::AccountId, BlockNumberFor<T>>,
+    ) -> DispatchResult {
+        if let QueuedTransferKind::Outbound { from, dest, .. } = transfer.kind {
+            let registration =
+                Self::asset_registration(transfer.asset_id).ok_or(Error::<T>::UnknownAsset)?;
+            Self::send_to_reserve(registration.location, from.clone().into(), transfer.amount)?;
+            Self::deposit_event(Event::<T>::AssetRefunded {
+                beneficiary: from,
+                amount: transfer.amount.into(),
+                asset_id: transfer.asset_id,
+                source: dest,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Usage remaining after `elapsed` blocks, the full limit drains over `window` blocks
+fn drained(used: u128, limit: u128, elapsed: u128, window: u128) -> u128 {
+    if window.is_zero() {
+        return 0
+    }
+
+    used.saturating_sub(limit.saturating_mul(elapsed) / window)
+}
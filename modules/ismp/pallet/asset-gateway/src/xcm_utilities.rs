@@ -1,13 +1,18 @@
-use crate::{Config, Pallet};
+use crate::{
+    is_substrate_chain,
+    rate_limit::{QueuedTransferKind, TransferDirection},
+    Config, Pallet,
+};
+use codec::Decode;
 use core::marker::PhantomData;
 use frame_support::traits::fungibles::{self, Mutate};
 use ismp::host::{Ethereum, StateMachine};
-use sp_core::{Get, H160};
+use sp_core::{Get, H256};
 use staging_xcm::{
     prelude::MultiLocation,
     v3::{
-        Error as XcmError, Junction, Junctions, MultiAsset, NetworkId, Result as XcmResult,
-        XcmContext,
+        AssetId, Error as XcmError, Junction, Junctions, MultiAsset, NetworkId,
+        Result as XcmResult, XcmContext,
     },
 };
 use staging_xcm_builder::{AssetChecking, FungiblesMutateAdapter};
@@ -27,6 +32,7 @@ const ARBITRUM_SEPOLIA_CHAIN_ID: u64 = 421614;
 const OPTIMISM_SEPOLIA_CHAIN_ID: u64 = 11155420;
 const BASE_SEPOLIA_CHAIN_ID: u64 = 84532;
 const SEPOLIA_CHAIN_ID: u64 = 11155111;
+const HOLESKY_CHAIN_ID: u64 = 17000;
 const BSC_TESTNET_CHAIN_ID: u64 = 97;
 
 pub struct WrappedNetworkId(pub NetworkId);
@@ -42,7 +48,7 @@ impl TryFrom<WrappedNetworkId> for StateMachine {
                 OPTIMISM_CHAIN_ID | OPTIMISM_SEPOLIA_CHAIN_ID =>
                     Ok(StateMachine::Ethereum(Ethereum::Optimism)),
                 BASE_CHAIN_ID | BASE_SEPOLIA_CHAIN_ID => Ok(StateMachine::Ethereum(Ethereum::Base)),
-                ETHEREUM_CHAIN_ID | SEPOLIA_CHAIN_ID =>
+                ETHEREUM_CHAIN_ID | SEPOLIA_CHAIN_ID | HOLESKY_CHAIN_ID =>
                     Ok(StateMachine::Ethereum(Ethereum::ExecutionLayer)),
                 BSC_CHAIN_ID | BSC_TESTNET_CHAIN_ID => Ok(StateMachine::Bsc),
                 _ => Err(()),
@@ -53,23 +59,26 @@ impl TryFrom<WrappedNetworkId> for StateMachine {
     }
 }
 
-/// Converts a MutiLocation to a substrate account and an evm account if the multilocation
+/// Converts a MutiLocation to a substrate account and a beneficiary account if the multilocation
 /// description matches a supported Ismp State machine
 pub struct MultilocationToMultiAccount<A>(PhantomData<A>);
 
 pub struct MultiAccount<A> {
     /// Origin substrate account
     pub substrate_account: A,
-    /// Destination evm account
-    pub evm_account: H160,
+    /// Destination account, evm addresses occupy the first 20 bytes
+    pub beneficiary: H256,
     /// Destination state machine
     pub dest_state_machine: StateMachine,
     /// Request time out in seconds
     pub timeout: u64,
 }
 
-// Supports a Multilocation interior of Junctions::X3
+// Supports a Multilocation interior of Junctions::X3 for evm destinations
 // Junctions::X3(AccountId32 { .. }, AccountKey20 { .. }, GeneralIndex(..))
+// and Junctions::X4 for substrate destinations
+// Junctions::X4(AccountId32 { .. }, GeneralKey { .. }, AccountId32 { .. }, GeneralIndex(..))
+// where the GeneralKey holds the SCALE encoded destination state machine.
 // The value specified in the GeneralIndex will be used as the timeout in seconds for the ismp
 // request that will be dispatched
 impl<A> ConvertLocation<MultiAccount<A>> for MultilocationToMultiAccount<A>
@@ -77,7 +86,7 @@ where
     A: From<[u8; 32]> + Into<[u8; 32]> + Clone,
 {
     fn convert_location(location: &MultiLocation) -> Option<MultiAccount<A>> {
-        // We only support locations X3 Junctions addressed to our parachain and an ethereum account
+        // We only support locations addressed to our parachain and an ethereum or substrate account
         match location {
             MultiLocation {
                 parents: 0,
@@ -92,9 +101,34 @@ where
                 // If it transforms correctly we return the ethereum account
                 let dest_state_machine =
                     StateMachine::try_from(WrappedNetworkId(network.clone())).ok()?;
+                let mut beneficiary = H256::zero();
+                beneficiary.0[..20].copy_from_slice(key);
+                Some(MultiAccount {
+                    substrate_account: A::from(*id),
+                    beneficiary,
+                    dest_state_machine,
+                    timeout: *timeout as u64,
+                })
+            },
+            MultiLocation {
+                parents: 0,
+                interior:
+                    Junctions::X4(
+                        Junction::AccountId32 { id, .. },
+                        Junction::GeneralKey { length, data },
+                        Junction::AccountId32 { id: beneficiary, .. },
+                        Junction::GeneralIndex(timeout),
+                    ),
+            } => {
+                // Ensure that the destination is a substrate based state machine
+                let dest_state_machine =
+                    StateMachine::decode(&mut data.get(..*length as usize)?).ok()?;
+                if !is_substrate_chain(&dest_state_machine) {
+                    return None
+                }
                 Some(MultiAccount {
                     substrate_account: A::from(*id),
-                    evm_account: H160::from(*key),
+                    beneficiary: H256(*beneficiary),
                     dest_state_machine,
                     timeout: *timeout as u64,
                 })
@@ -184,22 +218,54 @@ where
         // Ismp xcm transaction
         else if let Some(who) = MultilocationToMultiAccount::<T::AccountId>::convert_location(who)
         {
-            // We would remove the protocol fee at this point
+            // Only assets in the registry can be bridged
+            let AssetId::Concrete(location) = &what.id else {
+                return Err(MatchError::AssetNotHandled.into())
+            };
+            let token_gateway_asset_id =
+                Pallet::<T>::token_gateway_asset_id(location).ok_or(MatchError::AssetNotHandled)?;
+            let registration = Pallet::<T>::asset_registration(token_gateway_asset_id)
+                .ok_or(MatchError::AssetNotHandled)?;
 
+            // We would remove the protocol fee at this point
             let protocol_account = Pallet::<T>::protocol_account_id();
             let pallet_account = Pallet::<T>::account_id();
-            let protocol_percentage = Pallet::<T>::protocol_fee_percentage();
+            let protocol_percentage = registration.protocol_fee_percentage;
 
             let protocol_fees = protocol_percentage * u128::from(amount);
             let remainder = amount - protocol_fees.into();
+            // Ensure the destination is reachable before minting anything
+            Pallet::<T>::counterpart_module(&who.dest_state_machine)
+                .ok_or(MatchError::AccountIdConversionFailed)?;
+            Pallet::<T>::ensure_transfer_limit(&who.dest_state_machine, remainder.into())
+                .map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
             // Mint protocol fees
             T::Assets::mint_into(asset_id.clone(), &protocol_account, protocol_fees.into())
                 .map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
             // We custody the funds in the pallet account
             T::Assets::mint_into(asset_id, &pallet_account, remainder)
                 .map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
+            // Transfers above the rate limit of this route are held back for review
+            if !Pallet::<T>::record_flow(
+                token_gateway_asset_id,
+                who.dest_state_machine,
+                TransferDirection::Outflow,
+                remainder.into(),
+            ) {
+                Pallet::<T>::queue_transfer(
+                    token_gateway_asset_id,
+                    remainder.into(),
+                    QueuedTransferKind::Outbound {
+                        from: who.substrate_account,
+                        to: who.beneficiary,
+                        dest: who.dest_state_machine,
+                        timeout: who.timeout,
+                    },
+                );
+                return Ok(())
+            }
             // We dispatch an ismp request to the destination chain
-            Pallet::<T>::dispatch_request(who, remainder)
+            Pallet::<T>::dispatch_request(who, token_gateway_asset_id, remainder)
                 .map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
         } else {
             Err(MatchError::AccountIdConversionFailed)?
@@ -311,10 +311,11 @@ impl<T: Config> IsmpModule for IsmpModuleCallback<T> {
         let source_chain = request.source;
 
         match source_chain {
-            StateMachine::Ethereum(_) => Pallet::<T>::deposit_event(Event::Request {
-                source: source_chain,
-                data: unsafe { String::from_utf8_unchecked(request.data) },
-            }),
+            StateMachine::Ethereum(_) | StateMachine::Evm(_) =>
+                Pallet::<T>::deposit_event(Event::Request {
+                    source: source_chain,
+                    data: unsafe { String::from_utf8_unchecked(request.data) },
+                }),
             StateMachine::Polkadot(_) | StateMachine::Kusama(_) => {
                 let payload =
                     <Payload<T::AccountId, <T as Config>::Balance> as codec::Decode>::decode(
@@ -20,9 +20,12 @@
 
 extern crate alloc;
 
+pub mod payout;
 pub mod withdrawal;
 
-use crate::withdrawal::{Key, Signature, WithdrawalInputData, WithdrawalParams, WithdrawalProof};
+use crate::withdrawal::{
+    Key, Signature, SubstrateMessage, WithdrawalInputData, WithdrawalParams, WithdrawalProof,
+};
 use alloc::{collections::BTreeMap, vec::Vec};
 use alloy_primitives::Address;
 use codec::Encode;
@@ -43,7 +46,7 @@ use ismp::{
     router::{DispatchPost, DispatchRequest, IsmpDispatcher},
 };
 pub use pallet::*;
-use pallet_ismp::{dispatcher::Dispatcher, host::Host};
+use pallet_ismp::{dispatcher::Dispatcher, host::Host, pruning::PrunableEntry};
 use pallet_ismp_host_executive::HostParams;
 use sp_core::U256;
 use sp_runtime::DispatchError;
@@ -136,6 +139,15 @@ pub mod pallet {
             /// Amount withdrawn
             amount: U256,
         },
+        /// Fees withdrawn on hyperbridge have been paid out to the relayer on this chain
+        FeesPaidOut {
+            /// beneficiary account
+            beneficiary: T::AccountId,
+            /// source state machine
+            state_machine: StateMachine,
+            /// Amount paid out
+            amount: U256,
+        },
     }
 
     #[pallet::call]
@@ -299,9 +311,11 @@ where
         };
 
         let data = match withdrawal_data.dest_chain {
-            StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc =>
-                params.abi_encode(),
-            _ => params.encode(),
+            StateMachine::Ethereum(_) |
+            StateMachine::Polygon |
+            StateMachine::Bsc |
+            StateMachine::Evm(_) => params.abi_encode(),
+            _ => SubstrateMessage::Withdrawal(params).encode(),
         };
 
         let post = DispatchPost {
@@ -350,7 +364,10 @@ where
         // For evm chains each response receipt occupies two slots
         let mut slot_2_keys = alloc::vec![];
         match &withdrawal_proof.dest_proof.height.id.state_id {
-            StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc => {
+            StateMachine::Ethereum(_) |
+            StateMachine::Polygon |
+            StateMachine::Bsc |
+            StateMachine::Evm(_) => {
                 for (key, commitment) in dest_keys.iter().zip(withdrawal_proof.commitments.iter()) {
                     match commitment {
                         Key::Response { .. } => {
@@ -386,8 +403,51 @@ where
             });
         }
 
-        for commitment in commitments {
-            Claimed::<T>::insert(commitment, true)
+        let now = Host::<T>::default().timestamp();
+        for commitment in &commitments {
+            Claimed::<T>::insert(commitment, true);
+        }
+        // delivery and fee claim have been proven, proxied commitments can now be pruned
+        for key in &withdrawal_proof.commitments {
+            let entry = match key {
+                Key::Request(commitment) if commitments.contains(commitment) =>
+                    PrunableEntry::Commitment(*commitment),
+                Key::Response { request_commitment, response_commitment }
+                    if commitments.contains(response_commitment) =>
+                    PrunableEntry::Response {
+                        commitment: *response_commitment,
+                        request_commitment: *request_commitment,
+                    },
+                _ => continue,
+            };
+            pallet_ismp::Pallet::<T>::schedule_pruning(entry, now);
+        }
+
+        // Substrate chains hold the fees of the requests and responses they dispatch in escrow
+        // until they learn that delivery has been proven here, the released fees fund the
+        // withdrawals of those fees.
+        if matches!(
+            state_machine,
+            StateMachine::Beefy(_) |
+                StateMachine::Grandpa(_) |
+                StateMachine::Kusama(_) |
+                StateMachine::Polkadot(_)
+        ) && !commitments.is_empty()
+        {
+            let post = DispatchPost {
+                dest: state_machine,
+                from: MODULE_ID.to_vec(),
+                to: MODULE_ID.to_vec(),
+                timeout_timestamp: 0,
+                data: SubstrateMessage::ReleaseFees(commitments).encode(),
+            };
+            Dispatcher::<T>::default()
+                .dispatch_request(
+                    DispatchRequest::Post(post),
+                    H256::default().0.into(),
+                    0u32.into(),
+                )
+                .map_err(|_| Error::<T>::DispatchFailed)?;
         }
 
         for address in result.keys().collect::<hashbrown::HashSet<_>>().into_iter() {
@@ -422,7 +482,10 @@ where
         for key in &proof.commitments {
             match key {
                 Key::Request(commitment) => match proof.source_proof.height.id.state_id {
-                    StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc => {
+                    StateMachine::Ethereum(_) |
+                    StateMachine::Polygon |
+                    StateMachine::Bsc |
+                    StateMachine::Evm(_) => {
                         keys.push(
                             derive_unhashed_map_key::<Host<T>>(
                                 commitment.0.to_vec(),
@@ -441,7 +504,10 @@ where
                 },
                 Key::Response { response_commitment, .. } => {
                     match proof.source_proof.height.id.state_id {
-                        StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc => {
+                        StateMachine::Ethereum(_) |
+                        StateMachine::Polygon |
+                        StateMachine::Bsc |
+                        StateMachine::Evm(_) => {
                             keys.push(
                                 derive_unhashed_map_key::<Host<T>>(
                                     response_commitment.0.to_vec(),
@@ -472,7 +538,10 @@ where
         for key in &proof.commitments {
             match key {
                 Key::Request(commitment) => match proof.dest_proof.height.id.state_id {
-                    StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc => {
+                    StateMachine::Ethereum(_) |
+                    StateMachine::Polygon |
+                    StateMachine::Bsc |
+                    StateMachine::Evm(_) => {
                         keys.push(
                             derive_unhashed_map_key::<Host<T>>(
                                 commitment.0.to_vec(),
@@ -491,7 +560,10 @@ where
                 },
                 Key::Response { request_commitment, .. } => {
                     match proof.dest_proof.height.id.state_id {
-                        StateMachine::Ethereum(_) | StateMachine::Polygon | StateMachine::Bsc => {
+                        StateMachine::Ethereum(_) |
+                        StateMachine::Polygon |
+                        StateMachine::Bsc |
+                        StateMachine::Evm(_) => {
                             keys.push(
                                 derive_unhashed_map_key::<Host<T>>(
                                     request_commitment.0.to_vec(),
@@ -544,7 +616,8 @@ where
                         match proof.source_proof.height.id.state_id {
                             StateMachine::Ethereum(_) |
                             StateMachine::Polygon |
-                            StateMachine::Bsc => {
+                            StateMachine::Bsc |
+                            StateMachine::Evm(_) => {
                                 use alloy_rlp::Decodable;
                                 let fee = alloy_primitives::U256::decode(&mut &*encoded_metadata)
                                     .map_err(|_| Error::<T>::ProofValidationError)?;
@@ -575,7 +648,8 @@ where
                         match proof.dest_proof.height.id.state_id {
                             StateMachine::Ethereum(_) |
                             StateMachine::Polygon |
-                            StateMachine::Bsc => {
+                            StateMachine::Bsc |
+                            StateMachine::Evm(_) => {
                                 use alloy_rlp::Decodable;
                                 Address::decode(&mut &*encoded_receipt)
                                     .map_err(|_| Error::<T>::ProofValidationError)?
@@ -607,7 +681,8 @@ where
                         match proof.source_proof.height.id.state_id {
                             StateMachine::Ethereum(_) |
                             StateMachine::Polygon |
-                            StateMachine::Bsc => {
+                            StateMachine::Bsc |
+                            StateMachine::Evm(_) => {
                                 use alloy_rlp::Decodable;
                                 let fee = alloy_primitives::U256::decode(&mut &*encoded_metadata)
                                     .map_err(|_| Error::<T>::ProofValidationError)?;
@@ -638,7 +713,8 @@ where
                         match proof.dest_proof.height.id.state_id {
                             StateMachine::Ethereum(_) |
                             StateMachine::Polygon |
-                            StateMachine::Bsc => {
+                            StateMachine::Bsc |
+                            StateMachine::Evm(_) => {
                                 use alloy_rlp::Decodable;
                                 let response_commitment =
                                     alloy_primitives::B256::decode(&mut &*encoded_receipt)
@@ -0,0 +1,184 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payout of relayer fee withdrawals on substrate chains.
+//!
+//! Withdrawals initiated through [`Pallet::withdraw`] for substrate destinations are sent to
+//! [`MODULE_ID`] as a SCALE encoded [`SubstrateMessage::Withdrawal`]. Runtimes route requests for
+//! [`MODULE_ID`] to [`WithdrawalPayout`], which pays the beneficiary out of a fee pot.
+//!
+//! Once [`Pallet::accumulate`] has proven the delivery of requests and responses dispatched by a
+//! substrate chain, their commitments are sent to the chain in a
+//! [`SubstrateMessage::ReleaseFees`], and [`WithdrawalPayout`] releases their escrowed fees into
+//! the fee pot. The fee pot must not be the account holding fees in escrow, those remain
+//! refundable until delivery is proven.
+
+use crate::{
+    withdrawal::{SubstrateMessage, WithdrawalParams},
+    Config, Event, Pallet, MODULE_ID,
+};
+use alloc::{format, string::ToString};
+use codec::Decode;
+use core::marker::PhantomData;
+use frame_support::traits::{fungible::Mutate, tokens::Preservation, Get};
+use ismp::{
+    error::Error as IsmpError,
+    events::Meta,
+    host::StateMachine,
+    module::IsmpModule,
+    router::{Post, Response, Timeout},
+};
+use sp_core::U256;
+
+/// The account pallet-ismp releases escrowed fees into once delivery has been proven, this is the
+/// usual fee pot for [`WithdrawalPayout`]
+pub struct FeePotAccount<T>(PhantomData<T>);
+
+impl<T: pallet_ismp::Config> Get<T::AccountId> for FeePotAccount<T> {
+    fn get() -> T::AccountId {
+        pallet_ismp::Pallet::<T>::fee_pot_account()
+    }
+}
+
+/// An [`IsmpModule`] that pays out relayer fee withdrawals sent by the relayer module on
+/// Hyperbridge. `Hyperbridge` should resolve to the state machine of the coprocessor, which is
+/// usually `<Runtime as pallet_ismp::Config>::Coprocessor`, and `FeePot` to the account the fees
+/// are paid from.
+pub struct WithdrawalPayout<T, FeePot, Hyperbridge>(PhantomData<(T, FeePot, Hyperbridge)>);
+
+impl<T, FeePot, Hyperbridge> Default for WithdrawalPayout<T, FeePot, Hyperbridge> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, FeePot, Hyperbridge> WithdrawalPayout<T, FeePot, Hyperbridge>
+where
+    T: Config,
+    T::AccountId: From<[u8; 32]>,
+    FeePot: Get<T::AccountId>,
+{
+    /// Pays the beneficiary of a withdrawal out of the fee pot
+    fn pay_out(
+        source: StateMachine,
+        params: WithdrawalParams,
+        meta: Meta,
+    ) -> Result<(), IsmpError> {
+        let beneficiary: [u8; 32] =
+            params.beneficiary_address.as_slice().try_into().map_err(|_| {
+                IsmpError::ModuleDispatchError {
+                    msg: "Relayer Payout: Invalid beneficiary address".to_string(),
+                    meta: meta.clone(),
+                }
+            })?;
+
+        let amount = (params.amount <= U256::from(u128::MAX))
+            .then(|| params.amount.low_u128())
+            .and_then(|amount| T::Balance::try_from(amount).ok())
+            .ok_or_else(|| IsmpError::ModuleDispatchError {
+                msg: "Relayer Payout: Invalid amount".to_string(),
+                meta: meta.clone(),
+            })?;
+
+        let beneficiary = T::AccountId::from(beneficiary);
+        <T as pallet_ismp::Config>::Currency::transfer(
+            &FeePot::get(),
+            &beneficiary,
+            amount,
+            Preservation::Expendable,
+        )
+        .map_err(|err| IsmpError::ModuleDispatchError {
+            msg: format!("Relayer Payout: Failed to pay out fees: {err:?}"),
+            meta,
+        })?;
+
+        Pallet::<T>::deposit_event(Event::<T>::FeesPaidOut {
+            beneficiary,
+            state_machine: source,
+            amount: params.amount,
+        });
+
+        Ok(())
+    }
+}
+
+impl<T, FeePot, Hyperbridge> IsmpModule for WithdrawalPayout<T, FeePot, Hyperbridge>
+where
+    T: Config,
+    T::AccountId: From<[u8; 32]>,
+    FeePot: Get<T::AccountId>,
+    Hyperbridge: Get<Option<StateMachine>>,
+{
+    fn on_accept(&self, post: Post) -> Result<(), IsmpError> {
+        let meta = Meta { source: post.source, dest: post.dest, nonce: post.nonce };
+
+        // Only messages from the relayer module on hyperbridge are accepted
+        if Hyperbridge::get() != Some(post.source) || post.from != MODULE_ID.to_vec() {
+            Err(IsmpError::ModuleDispatchError {
+                msg: "Relayer Payout: Unknown source module".to_string(),
+                meta: meta.clone(),
+            })?
+        }
+
+        let message = SubstrateMessage::decode(&mut &post.data[..]).map_err(|_| {
+            IsmpError::ModuleDispatchError {
+                msg: "Relayer Payout: Failed to decode message".to_string(),
+                meta: meta.clone(),
+            }
+        })?;
+
+        match message {
+            SubstrateMessage::Withdrawal(params) => Self::pay_out(post.source, params, meta),
+            SubstrateMessage::ReleaseFees(commitments) => {
+                for commitment in commitments {
+                    pallet_ismp::Pallet::<T>::release_fee(commitment).map_err(|err| {
+                        IsmpError::ModuleDispatchError {
+                            msg: format!("Relayer Payout: Failed to release fees: {err:?}"),
+                            meta: meta.clone(),
+                        }
+                    })?;
+                }
+
+                Ok(())
+            },
+        }
+    }
+
+    fn on_response(&self, response: Response) -> Result<(), IsmpError> {
+        Err(IsmpError::ModuleDispatchError {
+            msg: "Relayer Payout does not accept responses".to_string(),
+            meta: Meta {
+                source: response.source_chain(),
+                dest: response.dest_chain(),
+                nonce: response.nonce(),
+            },
+        })
+    }
+
+    fn on_timeout(&self, timeout: Timeout) -> Result<(), IsmpError> {
+        // Withdrawals are dispatched by hyperbridge, so they never time out on this chain
+        let (source, dest, nonce) = match timeout {
+            Timeout::Request(request) =>
+                (request.source_chain(), request.dest_chain(), request.nonce()),
+            Timeout::Response(response) =>
+                (response.source_chain(), response.dest_chain(), response.nonce()),
+        };
+
+        Err(IsmpError::ModuleDispatchError {
+            msg: "Relayer Payout does not dispatch requests".to_string(),
+            meta: Meta { source, dest, nonce },
+        })
+    }
+}
@@ -61,6 +61,17 @@ pub struct WithdrawalParams {
     pub amount: U256,
 }
 
+/// Messages sent by the relayer module on hyperbridge to [`MODULE_ID`](crate::MODULE_ID) on
+/// substrate chains, they are handled by [`WithdrawalPayout`](crate::payout::WithdrawalPayout).
+#[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+pub enum SubstrateMessage {
+    /// Pay out fees withdrawn by a relayer from the fee pot
+    Withdrawal(WithdrawalParams),
+    /// The delivery of these requests and responses dispatched by the chain has been proven,
+    /// their escrowed fees can be released into the fee pot
+    ReleaseFees(Vec<H256>),
+}
+
 impl WithdrawalParams {
     pub fn abi_encode(&self) -> Vec<u8> {
         let mut data = vec![0];
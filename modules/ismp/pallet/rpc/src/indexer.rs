@@ -0,0 +1,535 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An index of the requests and responses dispatched by the host, maintained by the node in its
+//! offchain storage.
+//!
+//! The offchain storage can't be iterated, so the index is built by following finalized blocks,
+//! reading the commitments from the `Request` and `Response` events of each block and the full
+//! leaves from the offchain-indexed mmr. Entries are appended to a list for all requests (or
+//! responses) and to a list for their source and destination pair, both ordered by block number.
+//! The node must be run with offchain indexing enabled for the leaves to be available.
+//!
+//! Indexing starts at the finalized block when the node is first started with the index, earlier
+//! blocks are not indexed since their state may have been pruned.
+
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use ismp::{
+    host::StateMachine,
+    router::{Request, Response},
+};
+use pallet_ismp_runtime_api::IsmpRuntimeApi;
+use sc_client_api::BlockchainEvents;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+    offchain::{storage::OffchainDb, OffchainDbExt, OffchainStorage, STORAGE_PREFIX},
+    H256,
+};
+use sp_runtime::{
+    traits::{Block as BlockT, NumberFor},
+    SaturatedConversion,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Prefix of all the keys written by the index
+const INDEX_PREFIX: &[u8] = b"ismp-index";
+
+/// Page size used when a query doesn't provide a limit
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Maximum number of entries returned in a page
+const MAX_PAGE_SIZE: u32 = 1_000;
+
+/// Maximum number of entries scanned by a single query, queries with selective filters return
+/// a cursor to continue scanning from
+const MAX_SCANNED_ENTRIES: u64 = 10_000;
+
+/// The kind of leaf an entry was built from
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LeafKind {
+    /// An outgoing request
+    Request,
+    /// An outgoing response
+    Response,
+}
+
+/// An entry in the index
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Commitment of the request or response
+    pub commitment: H256,
+    /// The source state machine
+    pub source: StateMachine,
+    /// The destination state machine
+    pub dest: StateMachine,
+    /// The request nonce
+    pub nonce: u64,
+    /// The sending module
+    pub from: Vec<u8>,
+    /// The receiving module
+    pub to: Vec<u8>,
+    /// The block in which the request or response was dispatched
+    pub block_number: u64,
+}
+
+impl IndexEntry {
+    fn request(request: &Request, commitment: H256, block_number: u64) -> Self {
+        Self {
+            commitment,
+            source: request.source_chain(),
+            dest: request.dest_chain(),
+            nonce: request.nonce(),
+            from: request.source_module(),
+            to: request.destination_module(),
+            block_number,
+        }
+    }
+
+    fn response(response: &Response, commitment: H256, block_number: u64) -> Option<Self> {
+        let Response::Post(response) = response else { return None };
+        Some(Self {
+            commitment,
+            source: response.source_chain(),
+            dest: response.dest_chain(),
+            nonce: response.nonce(),
+            from: response.source_module(),
+            to: response.destination_module(),
+            block_number,
+        })
+    }
+}
+
+/// A list in the index, either of all entries of a kind or of those for a source and destination
+/// pair
+type ListId = (LeafKind, Option<(StateMachine, StateMachine)>);
+
+/// The lengths of all lists in the index and the last indexed block. Entries appended past the
+/// length of a list are only visible once this is written, which makes indexing a block atomic.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+struct IndexState {
+    /// The last block that has been indexed
+    last_indexed_block: Option<u64>,
+    /// Number of entries in each list
+    lengths: BTreeMap<ListId, u64>,
+}
+
+/// Filters and pagination for queries to the index. All filters are optional and are combined.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct IndexQuery {
+    /// The source state machine
+    #[serde(default)]
+    pub source: Option<StateMachine>,
+    /// The destination state machine
+    #[serde(default)]
+    pub dest: Option<StateMachine>,
+    /// The lowest nonce, inclusive
+    #[serde(default)]
+    pub min_nonce: Option<u64>,
+    /// The highest nonce, inclusive
+    #[serde(default)]
+    pub max_nonce: Option<u64>,
+    /// The sending module
+    #[serde(default)]
+    pub from: Option<Vec<u8>>,
+    /// The receiving module
+    #[serde(default)]
+    pub to: Option<Vec<u8>>,
+    /// The first block to search, inclusive
+    #[serde(default)]
+    pub from_block: Option<u64>,
+    /// The last block to search, inclusive
+    #[serde(default)]
+    pub to_block: Option<u64>,
+    /// The cursor returned with the previous page
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    /// Maximum number of items in the page, defaults to 100 and is capped at 1000
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl IndexQuery {
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        self.source.map_or(true, |source| source == entry.source) &&
+            self.dest.map_or(true, |dest| dest == entry.dest) &&
+            self.min_nonce.map_or(true, |nonce| entry.nonce >= nonce) &&
+            self.max_nonce.map_or(true, |nonce| entry.nonce <= nonce) &&
+            self.from.as_ref().map_or(true, |from| *from == entry.from) &&
+            self.to.as_ref().map_or(true, |to| *to == entry.to)
+    }
+}
+
+/// A request or response found in the index
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct IndexedItem<T> {
+    /// Commitment of the request or response
+    pub commitment: H256,
+    /// The block in which the request or response was dispatched
+    pub block_number: u64,
+    /// The request or response
+    pub item: T,
+}
+
+/// A page of query results
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct IndexedPage<T> {
+    /// The matching requests or responses
+    pub items: Vec<IndexedItem<T>>,
+    /// Cursor to query the next page with, if there are more entries to search
+    pub next_cursor: Option<u64>,
+}
+
+/// Reads and writes the index in the node's offchain storage
+#[derive(Clone)]
+pub struct OffchainIndex<S> {
+    storage: S,
+}
+
+impl<S: OffchainStorage> OffchainIndex<S> {
+    /// Create an index over the given offchain storage
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    fn get<V: Decode>(&self, key: &[u8]) -> Option<V> {
+        self.storage
+            .get(STORAGE_PREFIX, key)
+            .and_then(|value| V::decode(&mut &*value).ok())
+    }
+
+    fn entry_key(
+        kind: LeafKind,
+        pair: Option<(StateMachine, StateMachine)>,
+        index: u64,
+    ) -> Vec<u8> {
+        (INDEX_PREFIX, kind, pair, index).encode()
+    }
+
+    fn state_key() -> Vec<u8> {
+        (INDEX_PREFIX, b"state").encode()
+    }
+
+    fn state(&self) -> IndexState {
+        self.get(&Self::state_key()).unwrap_or_default()
+    }
+
+    fn len(&self, kind: LeafKind, pair: Option<(StateMachine, StateMachine)>) -> u64 {
+        self.state().lengths.get(&(kind, pair)).copied().unwrap_or_default()
+    }
+
+    fn entry(
+        &self,
+        kind: LeafKind,
+        pair: Option<(StateMachine, StateMachine)>,
+        index: u64,
+    ) -> Option<IndexEntry> {
+        self.get(&Self::entry_key(kind, pair, index))
+    }
+
+    /// Appends the entry to the list of all entries and to the list for its state machine pair.
+    /// The entry is only visible once the updated `state` is committed.
+    fn push(&mut self, state: &mut IndexState, kind: LeafKind, entry: &IndexEntry) {
+        for pair in [None, Some((entry.source, entry.dest))] {
+            let len = state.lengths.entry((kind, pair)).or_default();
+            self.storage
+                .set(STORAGE_PREFIX, &Self::entry_key(kind, pair, *len), &entry.encode());
+            *len += 1;
+        }
+    }
+
+    /// Writes the list lengths and last indexed block in a single value
+    fn commit(&mut self, state: &IndexState) {
+        self.storage.set(STORAGE_PREFIX, &Self::state_key(), &state.encode());
+    }
+
+    /// The last block that has been indexed
+    pub fn last_indexed_block(&self) -> Option<u64> {
+        self.state().last_indexed_block
+    }
+
+    /// Index of the first entry dispatched at or after the given block
+    fn lower_bound(
+        &self,
+        kind: LeafKind,
+        pair: Option<(StateMachine, StateMachine)>,
+        block_number: u64,
+    ) -> u64 {
+        let (mut low, mut high) = (0, self.len(kind, pair));
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.entry(kind, pair, mid) {
+                Some(entry) if entry.block_number < block_number => low = mid + 1,
+                _ => high = mid,
+            }
+        }
+        low
+    }
+
+    /// Returns the entries matching the query and the cursor for the next page
+    pub fn query(&self, kind: LeafKind, query: &IndexQuery) -> (Vec<IndexEntry>, Option<u64>) {
+        // the pair list only holds entries for a single source and destination, so it is
+        // much smaller to scan
+        let pair = query.source.zip(query.dest);
+        let len = self.len(kind, pair);
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as usize;
+
+        let mut index = match (query.cursor, query.from_block) {
+            (Some(cursor), _) => cursor,
+            (None, Some(block_number)) => self.lower_bound(kind, pair, block_number),
+            (None, None) => 0,
+        };
+        let end = len.min(index.saturating_add(MAX_SCANNED_ENTRIES));
+
+        let mut entries = vec![];
+        while index < end && entries.len() < limit {
+            let Some(entry) = self.entry(kind, pair, index) else { break };
+            // entries are ordered by block number, so there are no more matches
+            if query.to_block.is_some_and(|to_block| entry.block_number > to_block) {
+                return (entries, None);
+            }
+            index += 1;
+            if query.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        (entries, (index < len).then_some(index))
+    }
+
+    /// Adds the requests and responses dispatched in the block to the index
+    pub fn index_block<C, Block>(&mut self, client: &C, at: Block::Hash) -> Result<(), String>
+    where
+        Block: BlockT,
+        S: Clone + Send + Sync + 'static,
+        C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+        C::Api: IsmpRuntimeApi<Block, Block::Hash>,
+    {
+        let block_number = client
+            .number(at)
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("Unknown block {at:?}"))?
+            .saturated_into::<u64>();
+
+        let mut api = client.runtime_api();
+        api.register_extension(OffchainDbExt::new(OffchainDb::new(self.storage.clone())));
+
+        let events = api
+            .block_events(at)
+            .map_err(|err| format!("Failed to read block events: {err:?}"))?;
+        let mut state = self.state();
+        for event in events {
+            let (kind, commitment, entry) = match event {
+                pallet_ismp::events::Event::Request { commitment, .. } => {
+                    let entry = api
+                        .get_requests(at, vec![commitment])
+                        .map_err(|err| format!("Failed to read request: {err:?}"))?
+                        .first()
+                        .map(|request| IndexEntry::request(request, commitment, block_number));
+                    (LeafKind::Request, commitment, entry)
+                },
+                pallet_ismp::events::Event::Response { commitment, .. } => {
+                    let entry = api
+                        .get_responses(at, vec![commitment])
+                        .map_err(|err| format!("Failed to read response: {err:?}"))?
+                        .first()
+                        .and_then(|response| {
+                            IndexEntry::response(response, commitment, block_number)
+                        });
+                    (LeafKind::Response, commitment, entry)
+                },
+                _ => continue,
+            };
+
+            match entry {
+                Some(entry) => self.push(&mut state, kind, &entry),
+                None => log::warn!(
+                    target: "ismp-rpc",
+                    "Leaf {commitment:?} is missing from the offchain db, is offchain indexing enabled?"
+                ),
+            }
+        }
+
+        state.last_indexed_block = Some(block_number);
+        self.commit(&state);
+
+        Ok(())
+    }
+
+    /// Indexes every block after the last indexed block up to the given block. If nothing has
+    /// been indexed yet, indexing starts at the given block. Blocks that can't be read are
+    /// skipped, since their state has most likely been pruned.
+    fn catch_up<C, Block>(&mut self, client: &C, finalized: NumberFor<Block>)
+    where
+        Block: BlockT,
+        S: Clone + Send + Sync + 'static,
+        C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+        C::Api: IsmpRuntimeApi<Block, Block::Hash>,
+    {
+        let finalized = finalized.saturated_into::<u64>();
+        let start = self.last_indexed_block().map_or(finalized, |number| number + 1);
+        for number in start..=finalized {
+            let hash = match client.hash(number.saturated_into()) {
+                Ok(Some(hash)) => hash,
+                result => {
+                    log::error!(target: "ismp-rpc", "Skipping block {number}, failed to find its hash: {result:?}");
+                    continue;
+                },
+            };
+            if let Err(err) = self.index_block(client, hash) {
+                log::error!(target: "ismp-rpc", "Skipping block {number}, failed to index it: {err}");
+            }
+        }
+    }
+}
+
+/// Builds the index from the last indexed block and keeps it up to date as blocks are finalized.
+/// This should be spawned as a blocking task by the node.
+pub async fn run_indexer<C, Block, S>(client: Arc<C>, storage: S)
+where
+    Block: BlockT,
+    S: OffchainStorage + Clone + Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+    C::Api: IsmpRuntimeApi<Block, Block::Hash>,
+{
+    let mut index = OffchainIndex::new(storage);
+    let mut notifications = client.finality_notification_stream();
+    loop {
+        index.catch_up(&*client, client.info().finalized_number);
+        if notifications.next().await.is_none() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::offchain::storage::InMemOffchainStorage;
+
+    const SOURCE: StateMachine = StateMachine::Kusama(2000);
+
+    /// Two requests per block, alternating between two destinations
+    fn entries(blocks: u64) -> Vec<IndexEntry> {
+        (0..blocks * 2)
+            .map(|nonce| IndexEntry {
+                commitment: H256::from_low_u64_be(nonce),
+                source: SOURCE,
+                dest: StateMachine::Kusama(2001 + (nonce % 2) as u32),
+                nonce,
+                from: vec![1u8; 32],
+                to: vec![2u8; 32],
+                block_number: 1 + nonce / 2,
+            })
+            .collect()
+    }
+
+    fn index(entries: &[IndexEntry]) -> OffchainIndex<InMemOffchainStorage> {
+        let mut index = OffchainIndex::new(InMemOffchainStorage::default());
+        let mut state = index.state();
+        for entry in entries {
+            index.push(&mut state, LeafKind::Request, entry);
+        }
+        state.last_indexed_block = entries.last().map(|entry| entry.block_number);
+        index.commit(&state);
+        index
+    }
+
+    #[test]
+    fn should_paginate_query_results() {
+        let entries = entries(10);
+        let index = index(&entries);
+        assert_eq!(index.last_indexed_block(), Some(10));
+
+        let mut query = IndexQuery { limit: Some(8), ..Default::default() };
+        let mut results = vec![];
+        loop {
+            let (page, next_cursor) = index.query(LeafKind::Request, &query);
+            assert!(page.len() <= 8);
+            results.extend(page);
+            match next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(results, entries);
+        // responses are indexed separately
+        assert_eq!(index.query(LeafKind::Response, &IndexQuery::default()), (vec![], None));
+    }
+
+    #[test]
+    fn should_find_the_first_entry_of_a_block() {
+        let entries = entries(10);
+        let index = index(&entries);
+
+        assert_eq!(index.lower_bound(LeafKind::Request, None, 0), 0);
+        assert_eq!(index.lower_bound(LeafKind::Request, None, 4), 6);
+        assert_eq!(index.lower_bound(LeafKind::Request, None, 11), 20);
+        let pair = Some((SOURCE, StateMachine::Kusama(2002)));
+        assert_eq!(index.lower_bound(LeafKind::Request, pair, 4), 3);
+
+        let query = IndexQuery { from_block: Some(4), to_block: Some(5), ..Default::default() };
+        let (page, next_cursor) = index.query(LeafKind::Request, &query);
+        assert_eq!(page, entries[6..10].to_vec());
+        assert_eq!(next_cursor, None);
+    }
+
+    #[test]
+    fn should_filter_query_results() {
+        let entries = entries(10);
+        let index = index(&entries);
+
+        let query = IndexQuery {
+            source: Some(SOURCE),
+            dest: Some(StateMachine::Kusama(2002)),
+            min_nonce: Some(4),
+            max_nonce: Some(9),
+            ..Default::default()
+        };
+        let (page, next_cursor) = index.query(LeafKind::Request, &query);
+        assert_eq!(
+            page,
+            entries
+                .iter()
+                .filter(|entry| [5, 7, 9].contains(&entry.nonce))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(next_cursor, None);
+
+        let query = IndexQuery { to: Some(vec![3u8; 32]), ..Default::default() };
+        assert_eq!(index.query(LeafKind::Request, &query), (vec![], None));
+    }
+
+    #[test]
+    fn should_only_return_committed_entries() {
+        let entries = entries(2);
+        let mut index = index(&entries[..2]);
+
+        let mut state = index.state();
+        for entry in &entries[2..] {
+            index.push(&mut state, LeafKind::Request, entry);
+        }
+        assert_eq!(index.query(LeafKind::Request, &IndexQuery::default()).0, entries[..2].to_vec());
+        assert_eq!(index.last_indexed_block(), Some(1));
+
+        state.last_indexed_block = Some(2);
+        index.commit(&state);
+        assert_eq!(index.query(LeafKind::Request, &IndexQuery::default()).0, entries);
+        assert_eq!(index.last_indexed_block(), Some(2));
+    }
+}
@@ -17,31 +17,42 @@
 
 //! RPC Implementation for the Interoperable State Machine Protocol
 
+pub mod indexer;
+
 use jsonrpsee::{
     core::{Error as RpcError, RpcResult as Result},
     proc_macros::rpc,
-    types::{error::CallError, ErrorObject},
+    types::{error::CallError, ErrorObject, SubscriptionResult},
+    SubscriptionSink,
 };
 
 use codec::{Decode, Encode};
+use futures::{future, stream::BoxStream, FutureExt, StreamExt};
+use indexer::{IndexQuery, IndexedItem, IndexedPage, LeafKind, OffchainIndex};
 use ismp::{
     consensus::{ConsensusClientId, StateMachineId},
     events::{Event, StateMachineUpdated},
+    host::StateMachine,
+    messaging::Message,
     router::{Request, Response},
 };
 use pallet_ismp::{
     child_trie::CHILD_TRIE_PREFIX,
     mmr::primitives::{Leaf, NodeIndex},
-    primitives::{LeafIndexAndPos, LeafIndexQuery},
+    primitives::{DryRunResult, LeafIndexAndPos, LeafIndexQuery},
     ProofKeys,
 };
 use pallet_ismp_runtime_api::IsmpRuntimeApi;
-use sc_client_api::{Backend, BlockBackend, ChildInfo, ProofProvider, StateBackend};
+use sc_client_api::{
+    Backend, BlockBackend, BlockchainEvents, ChildInfo, ProofProvider, StateBackend,
+};
+use sc_rpc::SubscriptionTaskExecutor;
 use serde::{Deserialize, Serialize};
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::{
     offchain::{storage::OffchainDb, OffchainDbExt, OffchainStorage},
+    traits::SpawnNamed,
     H256,
 };
 use sp_runtime::traits::{Block as BlockT, Hash, Header};
@@ -117,6 +128,102 @@ pub struct EventWithMetadata {
     pub event: Event,
 }
 
+/// An event streamed by `ismp_subscribeEvents`
+#[derive(Serialize, Deserialize)]
+pub struct StreamedEvent {
+    /// The event metadata
+    pub meta: EventMetadata,
+    /// Index of the extrinsic that emitted the event
+    pub extrinsic_index: u32,
+    /// Whether the block that emitted the event has been finalized
+    pub finalized: bool,
+    /// The event in question
+    pub event: Event,
+}
+
+/// The kinds of [`Event`]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// [`Event::StateMachineUpdated`]
+    StateMachineUpdated,
+    /// [`Event::StateCommitmentVetoed`]
+    StateCommitmentVetoed,
+    /// [`Event::PostRequest`]
+    PostRequest,
+    /// [`Event::PostResponse`]
+    PostResponse,
+    /// [`Event::GetRequest`]
+    GetRequest,
+    /// [`Event::PostRequestHandled`]
+    PostRequestHandled,
+    /// [`Event::PostResponseHandled`]
+    PostResponseHandled,
+    /// [`Event::PostRequestTimeoutHandled`]
+    PostRequestTimeoutHandled,
+    /// [`Event::PostResponseTimeoutHandled`]
+    PostResponseTimeoutHandled,
+    /// [`Event::GetRequestHandled`]
+    GetRequestHandled,
+    /// [`Event::GetRequestTimeoutHandled`]
+    GetRequestTimeoutHandled,
+}
+
+impl From<&Event> for EventKind {
+    fn from(event: &Event) -> Self {
+        match event {
+            Event::StateMachineUpdated(_) => EventKind::StateMachineUpdated,
+            Event::StateCommitmentVetoed(_) => EventKind::StateCommitmentVetoed,
+            Event::PostRequest(_) => EventKind::PostRequest,
+            Event::PostResponse(_) => EventKind::PostResponse,
+            Event::GetRequest(_) => EventKind::GetRequest,
+            Event::PostRequestHandled(_) => EventKind::PostRequestHandled,
+            Event::PostResponseHandled(_) => EventKind::PostResponseHandled,
+            Event::PostRequestTimeoutHandled(_) => EventKind::PostRequestTimeoutHandled,
+            Event::PostResponseTimeoutHandled(_) => EventKind::PostResponseTimeoutHandled,
+            Event::GetRequestHandled(_) => EventKind::GetRequestHandled,
+            Event::GetRequestTimeoutHandled(_) => EventKind::GetRequestTimeoutHandled,
+        }
+    }
+}
+
+/// Filters for the events streamed by `ismp_subscribeEvents`
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Only stream events for requests and responses from this state machine, or state machine
+    /// updates and vetoes for it. Handled events don't carry their source and are skipped.
+    #[serde(default)]
+    pub source: Option<StateMachine>,
+    /// Only stream events for requests and responses to this state machine. Handled events
+    /// don't carry their destination and are skipped.
+    #[serde(default)]
+    pub dest: Option<StateMachine>,
+    /// Only stream these kinds of events, all events are streamed if empty
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+    /// Only stream events once their block has been finalized
+    #[serde(default)]
+    pub finalized_only: bool,
+}
+
+impl EventFilter {
+    /// Returns true if the event passes this filter
+    pub fn matches(&self, event: &Event) -> bool {
+        let (source, dest) = match event {
+            Event::PostRequest(post) => (Some(post.source), Some(post.dest)),
+            Event::GetRequest(get) => (Some(get.source), Some(get.dest)),
+            Event::PostResponse(response) =>
+                (Some(response.source_chain()), Some(response.dest_chain())),
+            Event::StateMachineUpdated(updated) => (Some(updated.state_machine_id.state_id), None),
+            Event::StateCommitmentVetoed(vetoed) => (Some(vetoed.height.id.state_id), None),
+            _ => (None, None),
+        };
+
+        (self.kinds.is_empty() || self.kinds.contains(&EventKind::from(event))) &&
+            (self.source.is_none() || self.source == source) &&
+            (self.dest.is_none() || self.dest == dest)
+    }
+}
+
 /// ISMP RPC methods.
 #[rpc(client, server)]
 pub trait IsmpApi<Hash>
@@ -180,6 +287,32 @@ where
         from: BlockNumberOrHash<Hash>,
         to: BlockNumberOrHash<Hash>,
     ) -> Result<HashMap<String, Vec<EventWithMetadata>>>;
+
+    /// Execute scale encoded `Vec<Message>` without persisting any state changes, returning the
+    /// outcome and estimated weight of each message. Defaults to the best block if no height is
+    /// provided.
+    #[method(name = "ismp_dryRunMessages")]
+    fn dry_run_messages(&self, height: Option<u32>, messages: Vec<u8>)
+        -> Result<Vec<DryRunResult>>;
+
+    /// Stream ISMP events as new best blocks are imported and as blocks are finalized, so events
+    /// are streamed once from the best block and again once their block is finalized.
+    #[subscription(
+        name = "ismp_subscribeEvents" => "ismp_events",
+        unsubscribe = "ismp_unsubscribeEvents",
+        item = StreamedEvent
+    )]
+    fn subscribe_events(&self, filter: Option<EventFilter>);
+
+    /// Query the requests dispatched by this chain from the node's offchain index, ordered by
+    /// the block they were dispatched in. Pass the returned cursor to fetch the next page.
+    #[method(name = "ismp_queryIndexedRequests")]
+    fn query_indexed_requests(&self, query: IndexQuery) -> Result<IndexedPage<Request>>;
+
+    /// Query the responses dispatched by this chain from the node's offchain index, ordered by
+    /// the block they were dispatched in. Pass the returned cursor to fetch the next page.
+    #[method(name = "ismp_queryIndexedResponses")]
+    fn query_indexed_responses(&self, query: IndexQuery) -> Result<IndexedPage<Response>>;
 }
 
 /// An implementation of ISMP specific RPC methods.
@@ -187,21 +320,175 @@ pub struct IsmpRpcHandler<C, B, S, T> {
     client: Arc<C>,
     backend: Arc<T>,
     offchain_db: OffchainDb<S>,
+    index: OffchainIndex<S>,
+    executor: SubscriptionTaskExecutor,
     _marker: std::marker::PhantomData<B>,
 }
 
-impl<C, B, S, T> IsmpRpcHandler<C, B, S, T> {
+impl<C, B, S: Clone, T> IsmpRpcHandler<C, B, S, T> {
     /// Create new `IsmpRpcHandler` with the given reference to the client.
-    pub fn new(client: Arc<C>, backend: Arc<T>, offchain_storage: S) -> Self {
+    pub fn new(
+        client: Arc<C>,
+        backend: Arc<T>,
+        offchain_storage: S,
+        executor: SubscriptionTaskExecutor,
+    ) -> Self {
         Self {
             client,
-            offchain_db: OffchainDb::new(offchain_storage),
+            offchain_db: OffchainDb::new(offchain_storage.clone()),
+            index: OffchainIndex::new(offchain_storage),
             backend,
+            executor,
             _marker: Default::default(),
         }
     }
 }
 
+/// Reads the ISMP events deposited in a block along with the index of the extrinsic that emitted
+/// each of them.
+fn block_events_with_metadata<C, Block, S>(
+    client: &C,
+    offchain_db: &OffchainDb<S>,
+    at: Block::Hash,
+) -> Result<Vec<(u32, EventWithMetadata)>>
+where
+    Block: BlockT,
+    S: OffchainStorage + Clone + Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockBackend<Block>,
+    C::Api: IsmpRuntimeApi<Block, Block::Hash>,
+    Block::Hash: Into<H256>,
+    u64: From<<Block::Header as Header>::Number>,
+{
+    let header = client
+        .header(at)
+        .map_err(|e| runtime_error_into_rpc_error(e.to_string()))?
+        .ok_or_else(|| runtime_error_into_rpc_error("Invalid block number or hash provided"))?;
+
+    let mut api = client.runtime_api();
+    api.register_extension(OffchainDbExt::new(offchain_db.clone()));
+
+    let block_events = api.block_events_with_metadata(at).map_err(|e| {
+        runtime_error_into_rpc_error(format!("failed to read block events {:?}", e))
+    })?;
+
+    let mut events = vec![];
+
+    for (event, index) in block_events {
+        let event = match event {
+            pallet_ismp::events::Event::Request { commitment, .. } => api
+                .get_requests(at, vec![commitment])
+                .map_err(|_| runtime_error_into_rpc_error("Error fetching requests"))?
+                .into_iter()
+                .map(|req| match req {
+                    Request::Post(post) => Event::PostRequest(post),
+                    Request::Get(get) => Event::GetRequest(get),
+                })
+                .next(),
+            pallet_ismp::events::Event::Response { commitment, .. } => api
+                .get_responses(at, vec![commitment])
+                .map_err(|_| runtime_error_into_rpc_error("Error fetching response"))?
+                .into_iter()
+                .filter_map(|res| match res {
+                    Response::Post(post) => Some(Event::PostResponse(post)),
+                    _ => None,
+                })
+                .next(),
+            pallet_ismp::events::Event::StateMachineUpdated { state_machine_id, latest_height } =>
+                Some(Event::StateMachineUpdated(StateMachineUpdated {
+                    state_machine_id,
+                    latest_height,
+                })),
+            pallet_ismp::events::Event::PostRequestHandled(handled) =>
+                Some(Event::PostRequestHandled(handled)),
+            pallet_ismp::events::Event::PostResponseHandled(handled) =>
+                Some(Event::PostResponseHandled(handled)),
+            pallet_ismp::events::Event::GetRequestHandled(handled) =>
+                Some(Event::GetRequestHandled(handled)),
+            pallet_ismp::events::Event::PostRequestTimeoutHandled(handled) =>
+                Some(Event::PostRequestTimeoutHandled(handled)),
+            pallet_ismp::events::Event::PostResponseTimeoutHandled(handled) =>
+                Some(Event::PostResponseTimeoutHandled(handled)),
+            pallet_ismp::events::Event::GetRequestTimeoutHandled(handled) =>
+                Some(Event::GetRequestTimeoutHandled(handled)),
+        };
+
+        if let Some(event) = event {
+            // get the block extrinsics
+            let extrinsic = client
+                .block_body(at)
+                .map_err(|err| {
+                    runtime_error_into_rpc_error(format!(
+                        "Error fetching extrinsic for block {at:?}: {err:?}"
+                    ))
+                })?
+                .ok_or_else(|| {
+                    runtime_error_into_rpc_error(format!("No extrinsics found for block {at:?}"))
+                })?
+                // using swap remove should be fine unless the node is in an inconsistent
+                // state
+                .swap_remove(index as usize);
+
+            let extrinsic =
+                Vec::<u8>::decode(&mut extrinsic.encode().as_slice()).map_err(|err| {
+                    runtime_error_into_rpc_error(format!(
+                        "Could not decode extrinsic with index {index:?}: {err:?}"
+                    ))
+                })?;
+            let extrinsic_hash = <Block::Header as Header>::Hashing::hash(extrinsic.as_slice());
+            events.push((
+                index,
+                EventWithMetadata {
+                    meta: EventMetadata {
+                        block_hash: at.into(),
+                        transaction_hash: extrinsic_hash.into(),
+                        block_number: u64::from(*header.number()),
+                    },
+                    event,
+                },
+            ));
+        }
+    }
+
+    Ok(events)
+}
+
+/// Turns the hashes of new best blocks and of newly finalized blocks into the events streamed by
+/// `ismp_subscribeEvents`, reading the events of each block with `read_events`.
+fn event_stream<H, F>(
+    best: BoxStream<'static, H>,
+    finalized: BoxStream<'static, Vec<H>>,
+    filter: EventFilter,
+    read_events: F,
+) -> BoxStream<'static, StreamedEvent>
+where
+    H: Send + 'static,
+    F: Fn(H) -> Vec<(u32, EventWithMetadata)> + Send + 'static,
+{
+    let best = if filter.finalized_only {
+        futures::stream::empty().boxed()
+    } else {
+        best.map(|hash| (vec![hash], false)).boxed()
+    };
+    let finalized = finalized.map(|hashes| (hashes, true));
+
+    futures::stream::select(best, finalized)
+        .flat_map(move |(hashes, finalized)| {
+            let events = hashes
+                .into_iter()
+                .flat_map(&read_events)
+                .filter(|(_, event)| filter.matches(&event.event))
+                .map(|(extrinsic_index, EventWithMetadata { meta, event })| StreamedEvent {
+                    meta,
+                    extrinsic_index,
+                    finalized,
+                    event,
+                })
+                .collect::<Vec<_>>();
+            futures::stream::iter(events)
+        })
+        .boxed()
+}
+
 impl<C, Block, S, T> IsmpApiServer<Block::Hash> for IsmpRpcHandler<C, Block, S, T>
 where
     Block: BlockT,
@@ -213,7 +500,8 @@ where
         + ProvideRuntimeApi<Block>
         + HeaderBackend<Block>
         + ProofProvider<Block>
-        + BlockBackend<Block>,
+        + BlockBackend<Block>
+        + BlockchainEvents<Block>,
     C::Api: IsmpRuntimeApi<Block, Block::Hash>,
     Block::Hash: Into<H256>,
     u64: From<<Block::Header as Header>::Number>,
@@ -501,94 +789,11 @@ where
             .ok_or_else(|| runtime_error_into_rpc_error("Invalid block number or hash provided"))?;
 
         while header.number() >= from_block.number() {
-            let mut api = self.client.runtime_api();
-            api.register_extension(OffchainDbExt::new(self.offchain_db.clone()));
             let at = header.hash();
-
-            let block_events = api.block_events_with_metadata(at).map_err(|e| {
-                runtime_error_into_rpc_error(format!("failed to read block events {:?}", e))
-            })?;
-
-            let mut temp = vec![];
-
-            for (event, index) in block_events {
-                let event = match event {
-                    pallet_ismp::events::Event::Request { commitment, .. } => api
-                        .get_requests(at, vec![commitment])
-                        .map_err(|_| runtime_error_into_rpc_error("Error fetching requests"))?
-                        .into_iter()
-                        .map(|req| match req {
-                            Request::Post(post) => Event::PostRequest(post),
-                            Request::Get(get) => Event::GetRequest(get),
-                        })
-                        .next(),
-                    pallet_ismp::events::Event::Response { commitment, .. } => api
-                        .get_responses(at, vec![commitment])
-                        .map_err(|_| runtime_error_into_rpc_error("Error fetching response"))?
-                        .into_iter()
-                        .filter_map(|res| match res {
-                            Response::Post(post) => Some(Event::PostResponse(post)),
-                            _ => None,
-                        })
-                        .next(),
-                    pallet_ismp::events::Event::StateMachineUpdated {
-                        state_machine_id,
-                        latest_height,
-                    } => Some(Event::StateMachineUpdated(StateMachineUpdated {
-                        state_machine_id,
-                        latest_height,
-                    })),
-                    pallet_ismp::events::Event::PostRequestHandled(handled) =>
-                        Some(Event::PostRequestHandled(handled)),
-                    pallet_ismp::events::Event::PostResponseHandled(handled) =>
-                        Some(Event::PostResponseHandled(handled)),
-                    pallet_ismp::events::Event::GetRequestHandled(handled) =>
-                        Some(Event::GetRequestHandled(handled)),
-                    pallet_ismp::events::Event::PostRequestTimeoutHandled(handled) =>
-                        Some(Event::PostRequestTimeoutHandled(handled)),
-                    pallet_ismp::events::Event::PostResponseTimeoutHandled(handled) =>
-                        Some(Event::PostResponseTimeoutHandled(handled)),
-                    pallet_ismp::events::Event::GetRequestTimeoutHandled(handled) =>
-                        Some(Event::GetRequestTimeoutHandled(handled)),
-                };
-
-                if let Some(event) = event {
-                    // get the block extrinsics
-                    let extrinsic = self
-                        .client
-                        .block_body(at)
-                        .map_err(|err| {
-                            runtime_error_into_rpc_error(format!(
-                                "Error fetching extrinsic for block {at:?}: {err:?}"
-                            ))
-                        })?
-                        .ok_or_else(|| {
-                            runtime_error_into_rpc_error(format!(
-                                "No extrinsics found for block {at:?}"
-                            ))
-                        })?
-                        // using swap remove should be fine unless the node is in an inconsistent
-                        // state
-                        .swap_remove(index as usize);
-
-                    let extrinsic =
-                        Vec::<u8>::decode(&mut extrinsic.encode().as_slice()).map_err(|err| {
-                            runtime_error_into_rpc_error(format!(
-                                "Could not decode extrinsic with index {index:?}: {err:?}"
-                            ))
-                        })?;
-                    let extrinsic_hash =
-                        <Block::Header as Header>::Hashing::hash(extrinsic.as_slice());
-                    temp.push(EventWithMetadata {
-                        meta: EventMetadata {
-                            block_hash: at.into(),
-                            transaction_hash: extrinsic_hash.into(),
-                            block_number: u64::from(*header.number()),
-                        },
-                        event,
-                    });
-                }
-            }
+            let temp = block_events_with_metadata(&*self.client, &self.offchain_db, at)?
+                .into_iter()
+                .map(|(_, event)| event)
+                .collect::<Vec<_>>();
 
             events.insert(header.hash().to_string(), temp);
             header = self
@@ -601,4 +806,282 @@ where
         }
         Ok(events)
     }
+
+    fn dry_run_messages(
+        &self,
+        height: Option<u32>,
+        messages: Vec<u8>,
+    ) -> Result<Vec<DryRunResult>> {
+        let messages = Vec::<Message>::decode(&mut &*messages).map_err(|err| {
+            runtime_error_into_rpc_error(format!("Could not decode messages: {err:?}"))
+        })?;
+        let at = match height {
+            Some(height) =>
+                self.client.block_hash(height.into()).ok().flatten().ok_or_else(|| {
+                    runtime_error_into_rpc_error(
+                        "Could not find valid blockhash for provided height",
+                    )
+                })?,
+            None => self.client.info().best_hash,
+        };
+        let api = self.client.runtime_api();
+        let supported = api
+            .has_api_with::<dyn IsmpRuntimeApi<Block, Block::Hash>, _>(at, |version| version >= 2)
+            .map_err(|e| runtime_error_into_rpc_error(e.to_string()))?;
+        if !supported {
+            Err(runtime_error_into_rpc_error("Runtime does not support dry running messages"))?
+        }
+        api.dry_run_messages(at, messages)
+            .map_err(|_| runtime_error_into_rpc_error("Error running messages"))
+    }
+
+    fn subscribe_events(
+        &self,
+        mut sink: SubscriptionSink,
+        filter: Option<EventFilter>,
+    ) -> SubscriptionResult {
+        let filter = filter.unwrap_or_default();
+
+        let best = self
+            .client
+            .import_notification_stream()
+            .filter_map(|notification| {
+                future::ready(notification.is_new_best.then_some(notification.hash))
+            })
+            .boxed();
+        // blocks finalized along with the new finalized head are only reported in its tree route
+        let finalized = self
+            .client
+            .finality_notification_stream()
+            .map(|notification| {
+                let mut hashes = notification.tree_route.to_vec();
+                hashes.push(notification.hash);
+                hashes
+            })
+            .boxed();
+
+        let client = self.client.clone();
+        let offchain_db = self.offchain_db.clone();
+        let stream = event_stream(best, finalized, filter, move |at| {
+            block_events_with_metadata(&*client, &offchain_db, at).unwrap_or_else(|err| {
+                log::error!(target: "ismp-rpc", "Failed to read events in {at:?}: {err:?}");
+                vec![]
+            })
+        });
+
+        let fut = async move {
+            sink.pipe_from_stream(stream).await;
+        };
+        self.executor.spawn("ismp-rpc-subscription", Some("rpc"), fut.boxed());
+
+        Ok(())
+    }
+
+    fn query_indexed_requests(&self, query: IndexQuery) -> Result<IndexedPage<Request>> {
+        let (entries, next_cursor) = self.index.query(LeafKind::Request, &query);
+        let mut api = self.client.runtime_api();
+        api.register_extension(OffchainDbExt::new(self.offchain_db.clone()));
+        let at = self.client.info().best_hash;
+        let items = entries
+            .into_iter()
+            .map(|entry| {
+                let request = api
+                    .get_requests(at, vec![entry.commitment])
+                    .map_err(|_| runtime_error_into_rpc_error("Error fetching requests"))?
+                    .pop()
+                    .ok_or_else(|| runtime_error_into_rpc_error("Indexed request not found"))?;
+                Ok(IndexedItem {
+                    commitment: entry.commitment,
+                    block_number: entry.block_number,
+                    item: request,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(IndexedPage { items, next_cursor })
+    }
+
+    fn query_indexed_responses(&self, query: IndexQuery) -> Result<IndexedPage<Response>> {
+        let (entries, next_cursor) = self.index.query(LeafKind::Response, &query);
+        let mut api = self.client.runtime_api();
+        api.register_extension(OffchainDbExt::new(self.offchain_db.clone()));
+        let at = self.client.info().best_hash;
+        let items = entries
+            .into_iter()
+            .map(|entry| {
+                let response = api
+                    .get_responses(at, vec![entry.commitment])
+                    .map_err(|_| runtime_error_into_rpc_error("Error fetching responses"))?
+                    .pop()
+                    .ok_or_else(|| runtime_error_into_rpc_error("Indexed response not found"))?;
+                Ok(IndexedItem {
+                    commitment: entry.commitment,
+                    block_number: entry.block_number,
+                    item: response,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(IndexedPage { items, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ismp::{events::RequestResponseHandled, router::PostResponse};
+
+    const SOURCE: StateMachine = StateMachine::Kusama(2000);
+    const DEST: StateMachine = StateMachine::Kusama(2001);
+
+    fn post(source: StateMachine, dest: StateMachine) -> ismp::router::Post {
+        ismp::router::Post {
+            source,
+            dest,
+            nonce: 0,
+            from: vec![1u8; 32],
+            to: vec![2u8; 32],
+            timeout_timestamp: 0,
+            data: vec![],
+        }
+    }
+
+    fn state_machine_updated(state_id: StateMachine) -> Event {
+        Event::StateMachineUpdated(StateMachineUpdated {
+            state_machine_id: StateMachineId { state_id, consensus_state_id: *b"PARA" },
+            latest_height: 1,
+        })
+    }
+
+    /// A post request and a state machine update in every block
+    fn block_events(block_number: u64) -> Vec<(u32, EventWithMetadata)> {
+        let meta = EventMetadata { block_number, ..Default::default() };
+        vec![
+            (
+                0,
+                EventWithMetadata {
+                    meta: meta.clone(),
+                    event: Event::PostRequest(post(SOURCE, DEST)),
+                },
+            ),
+            (1, EventWithMetadata { meta, event: state_machine_updated(SOURCE) }),
+        ]
+    }
+
+    /// Runs the event stream over the given best and finalized blocks, returning the block
+    /// number, finality and kind of every streamed event in a stable order.
+    fn streamed(
+        best: Vec<u64>,
+        finalized: Vec<Vec<u64>>,
+        filter: EventFilter,
+    ) -> Vec<(u64, bool, EventKind)> {
+        let stream = event_stream(
+            futures::stream::iter(best).boxed(),
+            futures::stream::iter(finalized).boxed(),
+            filter,
+            block_events,
+        );
+        let mut events = futures::executor::block_on(stream.collect::<Vec<_>>())
+            .into_iter()
+            .map(|streamed| {
+                (streamed.meta.block_number, streamed.finalized, EventKind::from(&streamed.event))
+            })
+            .collect::<Vec<_>>();
+        // best and finalized blocks are interleaved by the stream
+        events.sort_by_key(|(number, finalized, kind)| (*number, *finalized, *kind as u8));
+        events
+    }
+
+    #[test]
+    fn should_filter_events_by_kind_and_state_machine() {
+        let request = Event::PostRequest(post(SOURCE, DEST));
+        let response = Event::PostResponse(PostResponse {
+            post: post(DEST, SOURCE),
+            response: vec![],
+            timeout_timestamp: 0,
+        });
+        let updated = state_machine_updated(DEST);
+        let handled = Event::PostRequestHandled(RequestResponseHandled {
+            commitment: H256::zero(),
+            relayer: vec![],
+        });
+
+        let filter = EventFilter::default();
+        assert!([&request, &response, &updated, &handled].iter().all(|e| filter.matches(e)));
+
+        let filter = EventFilter { source: Some(SOURCE), ..Default::default() };
+        assert!(filter.matches(&request));
+        // the response is sent from the destination of the request
+        assert!(!filter.matches(&response));
+        assert!(!filter.matches(&updated));
+        assert!(!filter.matches(&handled));
+
+        let filter = EventFilter { source: Some(DEST), ..Default::default() };
+        assert!(filter.matches(&response));
+        assert!(filter.matches(&updated));
+
+        let filter = EventFilter { dest: Some(DEST), ..Default::default() };
+        assert!(filter.matches(&request));
+        assert!(!filter.matches(&response));
+        // state machine updates have no destination
+        assert!(!filter.matches(&updated));
+        assert!(!filter.matches(&handled));
+
+        let filter = EventFilter {
+            kinds: vec![EventKind::PostResponse, EventKind::PostRequestHandled],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&request));
+        assert!(filter.matches(&response));
+        assert!(!filter.matches(&updated));
+        assert!(filter.matches(&handled));
+
+        let filter = EventFilter {
+            source: Some(SOURCE),
+            dest: Some(DEST),
+            kinds: vec![EventKind::PostRequest],
+            ..Default::default()
+        };
+        assert!(filter.matches(&request));
+        assert!(!filter.matches(&response));
+    }
+
+    #[test]
+    fn should_stream_best_and_finalized_events() {
+        use EventKind::{PostRequest, StateMachineUpdated};
+
+        let events = streamed(vec![1, 2], vec![vec![1, 2]], EventFilter::default());
+        assert_eq!(
+            events,
+            vec![
+                (1, false, StateMachineUpdated),
+                (1, false, PostRequest),
+                (1, true, StateMachineUpdated),
+                (1, true, PostRequest),
+                (2, false, StateMachineUpdated),
+                (2, false, PostRequest),
+                (2, true, StateMachineUpdated),
+                (2, true, PostRequest),
+            ]
+        );
+
+        let filter = EventFilter { finalized_only: true, ..Default::default() };
+        let events = streamed(vec![1, 2, 3], vec![vec![1, 2]], filter);
+        assert_eq!(
+            events,
+            vec![
+                (1, true, StateMachineUpdated),
+                (1, true, PostRequest),
+                (2, true, StateMachineUpdated),
+                (2, true, PostRequest),
+            ]
+        );
+
+        let filter = EventFilter { kinds: vec![PostRequest], ..Default::default() };
+        let events = streamed(vec![3], vec![vec![1]], filter);
+        assert_eq!(events, vec![(1, true, PostRequest), (3, false, PostRequest)]);
+
+        let filter = EventFilter { dest: Some(SOURCE), ..Default::default() };
+        assert!(streamed(vec![1], vec![vec![1]], filter).is_empty());
+    }
 }
@@ -20,11 +20,12 @@
 
 use ismp::{
     consensus::{ConsensusClientId, StateMachineId},
+    messaging::Message,
     router::{Request, Response},
 };
 use pallet_ismp::{
     mmr::primitives::{Leaf, LeafIndex},
-    primitives::{Error, Proof},
+    primitives::{DryRunResult, Error, Proof},
     ProofKeys,
 };
 use sp_core::H256;
@@ -33,6 +34,7 @@ use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
     /// ISMP Runtime Apis
+    #[api_version(2)]
     pub trait IsmpRuntimeApi<Hash: codec::Codec> {
         /// Return the number of MMR leaves.
         fn mmr_leaf_count() -> Result<LeafIndex, Error>;
@@ -68,5 +70,10 @@ sp_api::decl_runtime_apis! {
 
         /// Get actual responses
         fn get_responses(leaf_positions: Vec<H256>) -> Vec<Response>;
+
+        /// Execute the messages without persisting any state changes, returning the outcome and
+        /// estimated weight of each message
+        #[api_version(2)]
+        fn dry_run_messages(messages: Vec<Message>) -> Vec<DryRunResult>;
     }
 }
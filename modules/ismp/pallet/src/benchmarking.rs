@@ -0,0 +1,393 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for the ISMP message handlers and extrinsics.
+//!
+//! To run the benchmarks, add the [`BenchmarkClient`] to the consensus clients configured for
+//! pallet-ismp and route [`MODULE_ID`] to the [`BenchmarkIsmpModule`] in the runtime's router.
+//! Module callbacks and consensus proof verification are not included in these weights, they
+//! are provided separately through [`Config::WeightProvider`].
+
+use crate::{
+    child_trie::{RequestCommitments, RequestReceipts, ResponseReceipts},
+    circuit_breaker::PauseScope,
+    dispatcher::FeeMetadata,
+    host::Host,
+    primitives::ModuleId,
+    retry::{RetryEntry, RetryItem},
+    *,
+};
+use alloc::{boxed::Box, collections::BTreeMap, vec, vec::Vec};
+use frame_benchmarking::v2::*;
+use frame_support::{
+    traits::{
+        fungible::{Inspect, Mutate},
+        Get, UnixTime,
+    },
+    PalletId,
+};
+use frame_system::RawOrigin;
+use ismp::{
+    consensus::{
+        ConsensusClient, ConsensusClientId, ConsensusStateId, StateCommitment, StateMachineClient,
+        StateMachineHeight, StateMachineId, VerifiedCommitments,
+    },
+    error::Error as IsmpError,
+    host::{IsmpHost, StateMachine},
+    messaging::{
+        CreateConsensusState, Message, Proof, RequestMessage, ResponseMessage,
+        StateCommitmentHeight, TimeoutMessage,
+    },
+    module::IsmpModule,
+    router::{Post, PostResponse, Request, RequestResponse, Response, Timeout},
+    util::hash_request,
+};
+use sp_runtime::traits::Saturating;
+
+/// Consensus client id of the [`BenchmarkClient`]
+pub const BENCHMARK_CONSENSUS_CLIENT_ID: ConsensusClientId = *b"BNCH";
+
+/// Consensus state id used by the benchmarks
+pub const BENCHMARK_CONSENSUS_STATE_ID: ConsensusStateId = *b"bnch";
+
+/// Module id of the [`BenchmarkIsmpModule`]
+pub const MODULE_ID: ModuleId = ModuleId::Pallet(PalletId(*b"ismpbnch"));
+
+/// The counterparty state machine used by the benchmarks
+const BENCHMARK_STATE_MACHINE: StateMachine = StateMachine::Evm(1);
+
+/// A consensus client which accepts every proof, so that only the cost of handling the messages
+/// is measured.
+#[derive(Default)]
+pub struct BenchmarkClient;
+
+impl ConsensusClient for BenchmarkClient {
+    fn verify_consensus(
+        &self,
+        _host: &dyn IsmpHost,
+        _consensus_state_id: ConsensusStateId,
+        _trusted_consensus_state: Vec<u8>,
+        _proof: Vec<u8>,
+    ) -> Result<(Vec<u8>, VerifiedCommitments), IsmpError> {
+        Ok(Default::default())
+    }
+
+    fn verify_fraud_proof(
+        &self,
+        _host: &dyn IsmpHost,
+        _trusted_consensus_state: Vec<u8>,
+        _proof_1: Vec<u8>,
+        _proof_2: Vec<u8>,
+    ) -> Result<(), IsmpError> {
+        Ok(())
+    }
+
+    fn consensus_client_id(&self) -> ConsensusClientId {
+        BENCHMARK_CONSENSUS_CLIENT_ID
+    }
+
+    fn state_machine(&self, _id: StateMachine) -> Result<Box<dyn StateMachineClient>, IsmpError> {
+        Ok(Box::new(BenchmarkStateMachine))
+    }
+}
+
+/// A state machine client which accepts every membership and non-membership proof
+pub struct BenchmarkStateMachine;
+
+impl StateMachineClient for BenchmarkStateMachine {
+    fn verify_membership(
+        &self,
+        _host: &dyn IsmpHost,
+        _item: RequestResponse,
+        _root: StateCommitment,
+        _proof: &Proof,
+    ) -> Result<(), IsmpError> {
+        Ok(())
+    }
+
+    fn state_trie_key(&self, _request: RequestResponse) -> Vec<Vec<u8>> {
+        Default::default()
+    }
+
+    fn verify_state_proof(
+        &self,
+        _host: &dyn IsmpHost,
+        _keys: Vec<Vec<u8>>,
+        _root: StateCommitment,
+        _proof: &Proof,
+    ) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, IsmpError> {
+        Ok(Default::default())
+    }
+}
+
+/// A module which accepts every request, response and timeout
+#[derive(Default)]
+pub struct BenchmarkIsmpModule;
+
+impl IsmpModule for BenchmarkIsmpModule {
+    fn on_accept(&self, _request: Post) -> Result<(), IsmpError> {
+        Ok(())
+    }
+
+    fn on_response(&self, _response: Response) -> Result<(), IsmpError> {
+        Ok(())
+    }
+
+    fn on_timeout(&self, _request: Timeout) -> Result<(), IsmpError> {
+        Ok(())
+    }
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    fn now<T: Config>() -> u64 {
+        <T::TimeProvider as UnixTime>::now().as_secs()
+    }
+
+    fn create_consensus_state<T: Config>() -> CreateConsensusState {
+        CreateConsensusState {
+            consensus_state: vec![],
+            consensus_client_id: BENCHMARK_CONSENSUS_CLIENT_ID,
+            consensus_state_id: BENCHMARK_CONSENSUS_STATE_ID,
+            unbonding_period: u64::MAX,
+            challenge_period: 0,
+            state_machine_commitments: vec![(
+                StateMachineId {
+                    state_id: BENCHMARK_STATE_MACHINE,
+                    consensus_state_id: BENCHMARK_CONSENSUS_STATE_ID,
+                },
+                StateCommitmentHeight {
+                    commitment: StateCommitment {
+                        // far enough in the future for all outgoing requests to have timed out
+                        timestamp: now::<T>() + 60 * 60,
+                        overlay_root: Some(Default::default()),
+                        state_root: Default::default(),
+                    },
+                    height: 1,
+                },
+            )],
+        }
+    }
+
+    /// Creates the benchmark consensus client and returns a proof at its state machine height
+    fn setup_client<T: Config>() -> Proof {
+        ismp::handlers::create_client(&Host::<T>::default(), create_consensus_state::<T>())
+            .expect("BenchmarkClient should be configured");
+
+        Proof {
+            height: StateMachineHeight {
+                id: StateMachineId {
+                    state_id: BENCHMARK_STATE_MACHINE,
+                    consensus_state_id: BENCHMARK_CONSENSUS_STATE_ID,
+                },
+                height: 1,
+            },
+            proof: vec![],
+        }
+    }
+
+    /// Dispatches `n` requests to the benchmark state machine
+    fn dispatch_requests<T: Config>(n: u32, timeout_timestamp: u64) -> Vec<Post> {
+        let caller: T::AccountId = whitelisted_caller();
+        (0..n)
+            .map(|nonce| {
+                let post = Post {
+                    source: T::HostStateMachine::get(),
+                    dest: BENCHMARK_STATE_MACHINE,
+                    nonce: nonce.into(),
+                    from: MODULE_ID.to_bytes(),
+                    to: MODULE_ID.to_bytes(),
+                    timeout_timestamp,
+                    data: vec![1u8; 64],
+                };
+                Pallet::<T>::dispatch_request(
+                    Request::Post(post.clone()),
+                    FeeMetadata { origin: caller.clone(), fee: Default::default() },
+                )
+                .expect("Request should be dispatched");
+                post
+            })
+            .collect()
+    }
+
+    #[benchmark]
+    fn create_consensus_client() {
+        let message = create_consensus_state::<T>();
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, message);
+
+        assert_eq!(
+            ConsensusStateClient::<T>::get(BENCHMARK_CONSENSUS_STATE_ID),
+            Some(BENCHMARK_CONSENSUS_CLIENT_ID)
+        );
+    }
+
+    #[benchmark]
+    fn update_consensus_state() {
+        setup_client::<T>();
+        let message = UpdateConsensusState {
+            consensus_state_id: BENCHMARK_CONSENSUS_STATE_ID,
+            unbonding_period: Some(1_000_000),
+            challenge_period: Some(1_000_000),
+        };
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, message);
+
+        assert_eq!(UnbondingPeriod::<T>::get(BENCHMARK_CONSENSUS_STATE_ID), Some(1_000_000));
+    }
+
+    #[benchmark]
+    fn handle_request_message(n: Linear<1, 100>) {
+        let proof = setup_client::<T>();
+        let requests = (0..n)
+            .map(|nonce| Post {
+                source: BENCHMARK_STATE_MACHINE,
+                dest: T::HostStateMachine::get(),
+                nonce: nonce.into(),
+                from: MODULE_ID.to_bytes(),
+                to: MODULE_ID.to_bytes(),
+                timeout_timestamp: now::<T>() + 60 * 60,
+                data: vec![1u8; 64],
+            })
+            .collect::<Vec<_>>();
+        let commitment = hash_request::<Host<T>>(&Request::Post(requests[0].clone()));
+        let messages =
+            vec![Message::Request(RequestMessage { requests, proof, signer: vec![1u8; 32] })];
+
+        #[extrinsic_call]
+        handle(RawOrigin::None, messages);
+
+        assert!(RequestReceipts::<T>::contains_key(commitment));
+    }
+
+    #[benchmark]
+    fn handle_response_message(n: Linear<1, 100>) {
+        let proof = setup_client::<T>();
+        let responses = dispatch_requests::<T>(n, now::<T>() + 60 * 60)
+            .into_iter()
+            .map(|post| {
+                Response::Post(PostResponse {
+                    post,
+                    response: vec![1u8; 64],
+                    timeout_timestamp: now::<T>() + 60 * 60,
+                })
+            })
+            .collect::<Vec<_>>();
+        let commitment = hash_request::<Host<T>>(&responses[0].request());
+        let messages = vec![Message::Response(ResponseMessage {
+            datagram: RequestResponse::Response(responses),
+            proof,
+            signer: vec![1u8; 32],
+        })];
+
+        #[extrinsic_call]
+        handle(RawOrigin::None, messages);
+
+        assert!(ResponseReceipts::<T>::contains_key(commitment));
+    }
+
+    #[benchmark]
+    fn fund_request() {
+        let caller: T::AccountId = whitelisted_caller();
+        let amount = T::Currency::minimum_balance().saturating_mul(10u32.into());
+        T::Currency::set_balance(&caller, amount.saturating_mul(10u32.into()));
+        T::Currency::set_balance(&Pallet::<T>::fee_escrow_account(), amount);
+        let request = Request::Post(dispatch_requests::<T>(1, now::<T>() + 60 * 60).remove(0));
+        let commitment = hash_request::<Host<T>>(&request);
+        EscrowedFees::<T>::insert(commitment, T::Balance::default());
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), request, amount);
+
+        assert_eq!(EscrowedFees::<T>::get(commitment), Some(amount));
+    }
+
+    #[benchmark]
+    fn pause() {
+        let scope = PauseScope::Module {
+            state_machine: BENCHMARK_STATE_MACHINE,
+            module: MODULE_ID.to_bytes(),
+        };
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, scope.clone());
+
+        assert!(Paused::<T>::contains_key(scope));
+    }
+
+    #[benchmark]
+    fn unpause() {
+        let scope = PauseScope::Module {
+            state_machine: BENCHMARK_STATE_MACHINE,
+            module: MODULE_ID.to_bytes(),
+        };
+        Paused::<T>::insert(&scope, ());
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, scope.clone());
+
+        assert!(!Paused::<T>::contains_key(scope));
+    }
+
+    #[benchmark]
+    fn retry_callback() {
+        let caller: T::AccountId = whitelisted_caller();
+        let post = Post {
+            source: BENCHMARK_STATE_MACHINE,
+            dest: T::HostStateMachine::get(),
+            nonce: 0,
+            from: MODULE_ID.to_bytes(),
+            to: MODULE_ID.to_bytes(),
+            timeout_timestamp: now::<T>() + 60 * 60,
+            data: vec![1u8; 64],
+        };
+        let commitment = hash_request::<Host<T>>(&Request::Post(post.clone()));
+        RetryQueue::<T>::insert(
+            commitment,
+            RetryEntry {
+                item: RetryItem::Request(post),
+                relayer: vec![1u8; 32],
+                attempts: 0,
+                retry_at: Default::default(),
+            },
+        );
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), commitment);
+
+        assert!(RequestReceipts::<T>::contains_key(commitment));
+        assert!(!RetryQueue::<T>::contains_key(commitment));
+    }
+
+    #[benchmark]
+    fn handle_timeout_message(n: Linear<1, 100>) {
+        let timeout_proof = setup_client::<T>();
+        let requests = dispatch_requests::<T>(n, now::<T>() + 1)
+            .into_iter()
+            .map(Request::Post)
+            .collect::<Vec<_>>();
+        let commitment = hash_request::<Host<T>>(&requests[0]);
+        let messages = vec![Message::Timeout(TimeoutMessage::Post { requests, timeout_proof })];
+
+        #[extrinsic_call]
+        handle(RawOrigin::None, messages);
+
+        assert!(!RequestCommitments::<T>::contains_key(commitment));
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Emergency controls for pausing ISMP traffic.
+//!
+//! Unlike frozen consensus clients and state machines, which are set by fraud proofs and block
+//! everything from a chain, pauses are set by the [`Config::AdminOrigin`] and can be scoped to
+//! incoming traffic, outgoing traffic or a single module's traffic with a state machine.
+
+use crate::{Config, Pallet, Paused};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use ismp::host::{StateMachine, Traffic};
+use sp_runtime::RuntimeDebug;
+
+/// The scope of a pause set by the circuit breaker
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum PauseScope {
+    /// Reject all incoming requests and responses
+    Incoming,
+    /// Reject all outgoing requests and responses
+    Outgoing,
+    /// Reject all requests, responses and timeouts between the module on the host and the state
+    /// machine
+    Module {
+        /// The counterparty state machine
+        state_machine: StateMachine,
+        /// The raw module id on the host
+        module: Vec<u8>,
+    },
+}
+
+impl<T: Config> Pallet<T> {
+    /// Returns true if the given kind of traffic between the module and the state machine has
+    /// been paused
+    pub fn is_paused(traffic: Traffic, state_machine: StateMachine, module: &[u8]) -> bool {
+        let global = match traffic {
+            Traffic::Incoming => Paused::<T>::contains_key(PauseScope::Incoming),
+            Traffic::Outgoing => Paused::<T>::contains_key(PauseScope::Outgoing),
+            Traffic::Timeout => false,
+        };
+
+        global ||
+            Paused::<T>::contains_key(PauseScope::Module {
+                state_machine,
+                module: module.to_vec(),
+            })
+    }
+}
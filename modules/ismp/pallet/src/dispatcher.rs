@@ -14,17 +14,32 @@
 // limitations under the License.
 
 //! Implementation for the ISMP Router
-use crate::{child_trie::RequestReceipts, host::Host, primitives::LeafIndexAndPos, Pallet};
+use crate::{
+    child_trie::RequestReceipts, host::Host, primitives::LeafIndexAndPos, EscrowedFees, Pallet,
+};
 use alloc::string::ToString;
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
-use frame_support::traits::UnixTime;
+use frame_support::{
+    storage::{with_transaction, TransactionOutcome},
+    traits::UnixTime,
+    PalletId,
+};
 use ismp::{
     error::Error as IsmpError,
     host::IsmpHost,
     router::{DispatchRequest, Get, IsmpDispatcher, Post, PostResponse, Request, Response},
-    util::hash_request,
+    util::{hash_request, hash_response},
 };
+use sp_runtime::DispatchError;
+
+/// Pallet id used to derive the account that holds fees charged by the [`FeeDispatcher`] in
+/// escrow. The account should be endowed with the existential deposit.
+pub const FEE_ESCROW_ID: PalletId = PalletId(*b"ismp/fee");
+
+/// Pallet id used to derive the account that fees are released into once the delivery of a
+/// request or response has been proven. Relayer fee withdrawals are paid out of this account.
+pub const FEE_POT_ID: PalletId = PalletId(*b"ismp/pot");
 
 /// A receipt or an outgoing or incoming request or response
 #[derive(Encode, Decode, scale_info::TypeInfo)]
@@ -65,21 +80,15 @@ impl<T> Default for Dispatcher<T> {
     }
 }
 
-impl<T> IsmpDispatcher for Dispatcher<T>
+impl<T> Dispatcher<T>
 where
     T: crate::Config,
 {
-    type Account = T::AccountId;
-    type Balance = T::Balance;
-
-    fn dispatch_request(
-        &self,
-        request: DispatchRequest,
-        origin: Self::Account,
-        fee: Self::Balance,
-    ) -> Result<(), IsmpError> {
+    /// Creates the request to be committed from the user provided [`DispatchRequest`], assigning
+    /// it the next nonce
+    fn create_request(request: DispatchRequest) -> Request {
         let host = Host::<T>::default();
-        let request = match request {
+        match request {
             DispatchRequest::Get(dispatch_get) => {
                 let get = Get {
                     source: host.host_state_machine(),
@@ -114,7 +123,24 @@ where
                 };
                 Request::Post(post)
             },
-        };
+        }
+    }
+}
+
+impl<T> IsmpDispatcher for Dispatcher<T>
+where
+    T: crate::Config,
+{
+    type Account = T::AccountId;
+    type Balance = T::Balance;
+
+    fn dispatch_request(
+        &self,
+        request: DispatchRequest,
+        origin: Self::Account,
+        fee: Self::Balance,
+    ) -> Result<(), IsmpError> {
+        let request = Self::create_request(request);
 
         Pallet::<T>::dispatch_request(request, FeeMetadata { origin, fee })?;
 
@@ -138,3 +164,79 @@ where
         Ok(())
     }
 }
+
+/// The fee dispatcher charges the origin a fee for every outgoing request and response.
+/// The fee must be at least [`Config::PerByteFee`](crate::Config::PerByteFee) for every byte of
+/// the payload, it is held in escrow by the [`FEE_ESCROW_ID`] account and refunded to the origin
+/// if the request or response times out, or released into the [`FEE_POT_ID`] account once its
+/// delivery has been proven.
+pub struct FeeDispatcher<T>(PhantomData<T>);
+
+impl<T> Default for FeeDispatcher<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> IsmpDispatcher for FeeDispatcher<T>
+where
+    T: crate::Config,
+{
+    type Account = T::AccountId;
+    type Balance = T::Balance;
+
+    fn dispatch_request(
+        &self,
+        request: DispatchRequest,
+        origin: Self::Account,
+        fee: Self::Balance,
+    ) -> Result<(), IsmpError> {
+        let size = match &request {
+            DispatchRequest::Post(post) => post.data.len(),
+            DispatchRequest::Get(get) => get.keys.iter().map(|key| key.len()).sum(),
+        };
+
+        transactional(|| {
+            Pallet::<T>::escrow_fee(&origin, fee, size)?;
+
+            let request = Dispatcher::<T>::create_request(request);
+            let commitment = hash_request::<Host<T>>(&request);
+            Pallet::<T>::dispatch_request(request, FeeMetadata { origin, fee })?;
+            EscrowedFees::<T>::insert(commitment, fee);
+
+            Ok(())
+        })
+    }
+
+    fn dispatch_response(
+        &self,
+        response: PostResponse,
+        origin: Self::Account,
+        fee: Self::Balance,
+    ) -> Result<(), IsmpError> {
+        let size = response.response.len();
+        let commitment = hash_response::<Host<T>>(&Response::Post(response.clone()));
+
+        transactional(|| {
+            Pallet::<T>::escrow_fee(&origin, fee, size)?;
+            Dispatcher::<T>::default().dispatch_response(response, origin, fee)?;
+            EscrowedFees::<T>::insert(commitment, fee);
+
+            Ok(())
+        })
+    }
+}
+
+/// Charges the fee and dispatches in a storage transaction, so that callers which are not
+/// transactional themselves are never charged for a request or response that failed to dispatch.
+fn transactional(dispatch: impl FnOnce() -> Result<(), IsmpError>) -> Result<(), IsmpError> {
+    with_transaction(|| {
+        let result = dispatch();
+        let outcome =
+            if result.is_ok() { TransactionOutcome::Commit } else { TransactionOutcome::Rollback };
+        outcome(Ok::<_, DispatchError>(result))
+    })
+    .unwrap_or_else(|_| {
+        Err(IsmpError::ImplementationSpecific("Transactional limit reached".into()))
+    })
+}
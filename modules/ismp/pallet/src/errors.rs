@@ -23,6 +23,7 @@ use ismp::{
 use sp_std::prelude::*;
 
 #[derive(Clone, Debug, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum HandlingError {
     ChallengePeriodNotElapsed {
@@ -165,6 +166,11 @@ pub enum HandlingError {
         /// Unknown response metadata
         meta: Meta,
     },
+    /// Traffic for the request or response has been paused
+    Paused {
+        /// The request or response metadata
+        meta: Meta,
+    },
 }
 
 impl From<ismp::error::Error> for HandlingError {
@@ -246,6 +252,7 @@ impl From<ismp::error::Error> for HandlingError {
             IsmpError::InvalidResponseType { meta } => HandlingError::InvalidResponseType { meta },
             IsmpError::UnknownRequest { meta } => HandlingError::UnknownRequest { meta },
             IsmpError::UnknownResponse { meta } => HandlingError::UnknownResponse { meta },
+            IsmpError::Paused { meta } => HandlingError::Paused { meta },
         }
     }
 }
@@ -95,7 +95,12 @@ pub fn to_core_protocol_event<T: Config>(event: PalletEvent<T>) -> Option<Event>
         PalletEvent::ConsensusClientFrozen { .. } |
         PalletEvent::Errors { .. } |
         PalletEvent::__Ignore(_, _) |
-        PalletEvent::StateCommitmentVetoed { .. } => None,
+        PalletEvent::StateCommitmentVetoed { .. } |
+        PalletEvent::RequestFunded { .. } |
+        PalletEvent::TrafficPaused { .. } |
+        PalletEvent::TrafficUnpaused { .. } |
+        PalletEvent::CallbackQueued { .. } |
+        PalletEvent::CallbackDropped { .. } => None,
     }
 }
 
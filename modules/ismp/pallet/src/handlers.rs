@@ -2,21 +2,32 @@
 
 use crate::{
     child_trie::{RequestCommitments, ResponseCommitments},
-    dispatcher::{FeeMetadata, LeafMetadata},
+    dispatcher::{FeeMetadata, LeafMetadata, FEE_ESCROW_ID, FEE_POT_ID},
     host::Host,
     mmr::primitives::Leaf,
-    Config, Event, Pallet, Responded,
+    Config, Error, EscrowedFees, Event, Pallet, Responded,
 };
-use alloc::string::ToString;
+use alloc::{format, string::ToString};
+use frame_support::traits::{fungible::Mutate, tokens::Preservation, Get};
 use ismp::{
     error::Error as IsmpError,
+    host::{IsmpHost, Traffic},
     router::{Request, Response},
     util::{hash_request, hash_response},
 };
+use sp_core::H256;
+use sp_runtime::{
+    traits::{AccountIdConversion, Saturating, Zero},
+    DispatchResult,
+};
 
 impl<T: Config> Pallet<T> {
     /// Dispatch an outgoing request
     pub fn dispatch_request(request: Request, meta: FeeMetadata<T>) -> Result<(), IsmpError> {
+        if Self::is_paused(Traffic::Outgoing, request.dest_chain(), &request.source_module()) {
+            Err(IsmpError::Paused { meta: (&request).into() })?
+        }
+
         let commitment = hash_request::<Host<T>>(&request);
 
         if RequestCommitments::<T>::contains_key(commitment) {
@@ -44,7 +55,13 @@ impl<T: Config> Pallet<T> {
 
     /// Dispatch an outgoing response
     pub fn dispatch_response(response: Response, meta: FeeMetadata<T>) -> Result<(), IsmpError> {
-        let req_commitment = hash_request::<Host<T>>(&response.request());
+        let request = response.request();
+        if Self::is_paused(Traffic::Outgoing, response.dest_chain(), &request.destination_module())
+        {
+            Err(IsmpError::Paused { meta: (&response).into() })?
+        }
+
+        let req_commitment = hash_request::<Host<T>>(&request);
 
         if Responded::<T>::contains_key(req_commitment) {
             Err(IsmpError::ImplementationSpecific("Request has been responded to".to_string()))?
@@ -73,4 +90,134 @@ impl<T: Config> Pallet<T> {
         Responded::<T>::insert(req_commitment, true);
         Ok(())
     }
+
+    /// The account that holds fees charged by the
+    /// [`FeeDispatcher`](crate::dispatcher::FeeDispatcher) in escrow
+    pub fn fee_escrow_account() -> T::AccountId {
+        FEE_ESCROW_ID.into_account_truncating()
+    }
+
+    /// The account fees are released into once delivery has been proven, relayer fee withdrawals
+    /// are paid out of this account
+    pub fn fee_pot_account() -> T::AccountId {
+        FEE_POT_ID.into_account_truncating()
+    }
+
+    /// Ensures the fee covers the per byte fee for a payload of the given size and transfers it
+    /// from the origin into escrow
+    pub(crate) fn escrow_fee(
+        origin: &T::AccountId,
+        fee: T::Balance,
+        size: usize,
+    ) -> Result<(), IsmpError> {
+        let min_fee = T::PerByteFee::get().saturating_mul((size as u32).into());
+        if fee < min_fee {
+            Err(IsmpError::ImplementationSpecific(format!(
+                "Insufficient fee, expected at least {min_fee:?}"
+            )))?
+        }
+
+        if fee.is_zero() {
+            return Ok(());
+        }
+
+        T::Currency::transfer(origin, &Self::fee_escrow_account(), fee, Preservation::Preserve)
+            .map_err(|err| {
+                IsmpError::ImplementationSpecific(format!("Failed to charge fee: {err:?}"))
+            })?;
+
+        Ok(())
+    }
+
+    /// Adds `amount` to the fee of an outgoing request that is yet to be timed out, the amount is
+    /// held in escrow alongside any fee that was charged when the request was dispatched. Only the
+    /// account that dispatched the request can fund it, and only while its fee is still held in
+    /// escrow, i.e. before it has been claimed by a relayer or refunded.
+    ///
+    /// The updated fee is written to the request's commitment metadata, so it is reflected in fee
+    /// proofs for the request generated after this call.
+    pub fn add_request_fee(
+        origin: T::AccountId,
+        request: Request,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        if amount.is_zero() {
+            Err(Error::<T>::ZeroFee)?
+        }
+
+        let commitment = hash_request::<Host<T>>(&request);
+        let mut metadata =
+            RequestCommitments::<T>::get(commitment).ok_or(Error::<T>::UnknownRequest)?;
+        if metadata.meta.origin != origin {
+            Err(Error::<T>::NotRequestOrigin)?
+        }
+
+        if request.timed_out(Host::<T>::default().timestamp()) {
+            Err(Error::<T>::RequestTimedOut)?
+        }
+
+        // Escrowed fees are taken once they are released to relayers or refunded
+        if !EscrowedFees::<T>::contains_key(commitment) {
+            Err(Error::<T>::FeeAlreadyClaimed)?
+        }
+
+        T::Currency::transfer(&origin, &Self::fee_escrow_account(), amount, Preservation::Preserve)
+            .map_err(|_| Error::<T>::FeeTransferFailed)?;
+
+        metadata.meta.fee = metadata.meta.fee.saturating_add(amount);
+        let fee = metadata.meta.fee;
+        RequestCommitments::<T>::insert(commitment, metadata);
+        EscrowedFees::<T>::mutate(commitment, |escrowed| {
+            *escrowed = Some(escrowed.unwrap_or_default().saturating_add(amount))
+        });
+
+        Pallet::<T>::deposit_event(Event::RequestFunded { commitment, fee });
+
+        Ok(())
+    }
+
+    /// Refunds the fee held in escrow for a request or response commitment to the account that
+    /// paid it
+    pub(crate) fn refund_fee(commitment: H256, meta: &FeeMetadata<T>) -> Result<(), IsmpError> {
+        let Some(fee) = EscrowedFees::<T>::take(commitment) else { return Ok(()) };
+
+        if fee.is_zero() {
+            return Ok(());
+        }
+
+        T::Currency::transfer(
+            &Self::fee_escrow_account(),
+            &meta.origin,
+            fee,
+            Preservation::Expendable,
+        )
+        .map_err(|err| {
+            IsmpError::ImplementationSpecific(format!("Failed to refund fee: {err:?}"))
+        })?;
+
+        Ok(())
+    }
+
+    /// Releases the fee held in escrow for a request or response commitment into the fee pot,
+    /// this should be called once its delivery has been proven. On substrate chains this happens
+    /// when the relayer module on hyperbridge reports the commitments it has paid relayers for.
+    pub fn release_fee(commitment: H256) -> Result<(), IsmpError> {
+        let Some(fee) = EscrowedFees::<T>::take(commitment) else { return Ok(()) };
+
+        if fee.is_zero() {
+            return Ok(());
+        }
+
+        T::Currency::transfer(
+            &Self::fee_escrow_account(),
+            &Self::fee_pot_account(),
+            fee,
+            Preservation::Expendable,
+        )
+        .map_err(|err| {
+            IsmpError::ImplementationSpecific(format!("Failed to release fee: {err:?}"))
+        })?;
+
+        Ok(())
+    }
 }
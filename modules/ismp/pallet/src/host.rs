@@ -18,7 +18,7 @@ use crate::{
     child_trie::{RequestCommitments, RequestReceipts, ResponseCommitments, ResponseReceipts},
     primitives::ConsensusClientProvider,
     ChallengePeriod, Config, ConsensusClientUpdateTime, ConsensusStateClient, ConsensusStates,
-    FrozenConsensusClients, FrozenStateMachine, LatestStateMachineHeight, Nonce, Responded,
+    FrozenConsensusClients, FrozenStateMachine, LatestStateMachineHeight, Nonce, Pallet, Responded,
     ResponseReceipt, StateCommitments, StateMachineUpdateTime, UnbondingPeriod,
 };
 use alloc::{format, string::ToString};
@@ -30,7 +30,7 @@ use ismp::{
         StateMachineId,
     },
     error::Error,
-    host::{IsmpHost, StateMachine},
+    host::{IsmpHost, StateMachine, Traffic},
     router::{IsmpRouter, PostResponse, Request, Response},
     util::{hash_post_response, hash_request, hash_response},
 };
@@ -230,6 +230,10 @@ impl<T: Config> IsmpHost for Host<T> {
 
     fn delete_request_commitment(&self, req: &Request) -> Result<(), Error> {
         let hash = hash_request::<Self>(req);
+        // Commitments are only deleted on timeouts, so refund any fees held in escrow
+        if let Some(leaf_meta) = RequestCommitments::<T>::get(hash) {
+            Pallet::<T>::refund_fee(hash, &leaf_meta.meta)?;
+        }
         // We can't delete actual leaves in the mmr so this serves as a replacement for that
         RequestCommitments::<T>::remove(hash);
         Ok(())
@@ -239,6 +243,10 @@ impl<T: Config> IsmpHost for Host<T> {
         let req_commitment = hash_request::<Self>(&res.request());
         let hash = hash_post_response::<Self>(res);
 
+        // Commitments are only deleted on timeouts, so refund any fees held in escrow
+        if let Some(leaf_meta) = ResponseCommitments::<T>::get(hash) {
+            Pallet::<T>::refund_fee(hash, &leaf_meta.meta)?;
+        }
         // We can't delete actual leaves in the mmr so this serves as a replacement for that
         ResponseCommitments::<T>::remove(hash);
         Responded::<T>::remove(req_commitment);
@@ -303,6 +311,10 @@ impl<T: Config> IsmpHost for Host<T> {
         FrozenStateMachine::<T>::insert(state_machine, true);
         Ok(())
     }
+
+    fn is_paused(&self, traffic: Traffic, state_machine: StateMachine, module: &[u8]) -> bool {
+        Pallet::<T>::is_paused(traffic, state_machine, module)
+    }
 }
 
 impl<T: Config> ismp::util::Keccak256 for Host<T> {
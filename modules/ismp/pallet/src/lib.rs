@@ -22,17 +22,24 @@
 extern crate alloc;
 extern crate core;
 
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
 pub mod dispatcher;
-mod errors;
+pub mod errors;
 pub mod events;
 pub mod handlers;
 pub mod host;
+pub mod migrations;
 pub mod mmr;
 use events::deposit_ismp_events;
 pub use mmr::ProofKeys;
 pub mod child_trie;
+pub mod circuit_breaker;
 pub mod primitives;
+pub mod pruning;
+pub mod retry;
 pub mod weight_info;
+pub mod weights;
 
 pub use mmr::utils::NodesUtils;
 
@@ -40,6 +47,7 @@ use crate::host::Host;
 use codec::{Decode, Encode};
 use frame_support::{
     dispatch::{DispatchResult, DispatchResultWithPostInfo, Pays, PostDispatchInfo},
+    storage::{with_transaction, TransactionOutcome},
     traits::Get,
 };
 use ismp::{
@@ -58,7 +66,7 @@ use crate::{
         primitives::{DataOrHash, Leaf, LeafIndex, NodeIndex},
         Mmr,
     },
-    primitives::LeafIndexAndPos,
+    primitives::{DryRunResult, LeafIndexAndPos},
     weight_info::get_weight,
 };
 use frame_system::pallet_prelude::BlockNumberFor;
@@ -71,7 +79,7 @@ use sp_runtime::{
         InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidity,
         TransactionValidityError, ValidTransaction,
     },
-    RuntimeDebug,
+    DispatchError, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
@@ -86,12 +94,19 @@ pub mod pallet {
     use super::*;
     use crate::{
         child_trie::CHILD_TRIE_PREFIX,
+        circuit_breaker::PauseScope,
         errors::HandlingError,
         mmr::primitives::{LeafIndex, NodeIndex},
         primitives::{ConsensusClientProvider, WeightUsed, ISMP_ID},
+        pruning::PrunableEntry,
+        retry::RetryEntry,
         weight_info::WeightProvider,
+        weights::WeightInfo,
+    };
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{fungible, UnixTime},
     };
-    use frame_support::{pallet_prelude::*, traits::UnixTime};
     use frame_system::pallet_prelude::*;
     use ismp::{
         consensus::{
@@ -134,11 +149,34 @@ pub mod pallet {
 
         /// Weight provider for consensus clients and module callbacks
         type WeightProvider: WeightProvider;
+
+        /// Weight information for the extrinsics and message handlers in this pallet
+        type WeightInfo: WeightInfo;
+
+        /// The currency used to charge fees for outgoing requests and responses dispatched
+        /// through the [`FeeDispatcher`](crate::dispatcher::FeeDispatcher)
+        type Currency: fungible::Mutate<Self::AccountId, Balance = Self::Balance>;
+
+        /// The minimum fee charged per byte of an outgoing request or response payload
+        #[pallet::constant]
+        type PerByteFee: Get<Self::Balance>;
+
+        /// Duration in seconds that commitments are kept for after their fees have been claimed.
+        ///
+        /// Once it elapses the commitments are pruned, along with the [`Responded`] entries of the
+        /// requests that pruned responses were sent for. Receipts are kept indefinitely, since
+        /// source chains prove timeouts by proving the absence of a receipt.
+        #[pallet::constant]
+        type PruningHorizon: Get<u64>;
     }
 
+    /// The in-code storage version.
+    pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
     // Simple declaration of the `Pallet` type. It is placeholder we use to implement traits and
     // method.
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     #[pallet::without_storage_info]
     pub struct Pallet<T>(_);
 
@@ -222,11 +260,19 @@ pub mod pallet {
         StorageMap<_, Twox64Concat, StateMachineHeight, u64, OptionQuery>;
 
     /// Tracks requests that have been responded to
-    /// The key is the request commitment
+    /// The key is the request commitment, entries are removed when the response commitment is
+    /// pruned
     #[pallet::storage]
     #[pallet::getter(fn responded)]
     pub type Responded<T: Config> = StorageMap<_, Identity, H256, bool, ValueQuery>;
 
+    /// Fees held in escrow for outgoing requests and responses dispatched through the
+    /// [`FeeDispatcher`](crate::dispatcher::FeeDispatcher), keyed by their commitment. Entries are
+    /// removed when the fee is refunded on timeout or released into the fee pot on delivery.
+    #[pallet::storage]
+    #[pallet::getter(fn escrowed_fees)]
+    pub type EscrowedFees<T: Config> = StorageMap<_, Identity, H256, T::Balance, OptionQuery>;
+
     /// Latest nonce for messages sent from this chain
     #[pallet::storage]
     #[pallet::getter(fn nonce)]
@@ -255,6 +301,34 @@ pub mod pallet {
     #[pallet::getter(fn intermediate_number_of_leaves)]
     pub type IntermediateNumberOfLeaves<T> = StorageValue<_, LeafIndex, ValueQuery>;
 
+    /// Child trie entries scheduled for pruning, keyed by the hourly bucket after which they can
+    /// be removed
+    #[pallet::storage]
+    pub type PruningSchedule<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, u64, Blake2_128Concat, PrunableEntry, (), OptionQuery>;
+
+    /// The earliest bucket in the pruning schedule that hasn't been drained
+    #[pallet::storage]
+    pub type PruningCursor<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+    /// Number of entries in the pruning schedule
+    #[pallet::storage]
+    pub type PendingPrunes<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Position of the next mmr node to be checked by the migration that removes all nodes except
+    /// the peaks from runtime storage
+    #[pallet::storage]
+    pub type NodesMigrationCursor<T: Config> = StorageValue<_, NodeIndex, OptionQuery>;
+
+    /// Traffic that has been paused by the circuit breaker
+    #[pallet::storage]
+    pub type Paused<T: Config> = StorageMap<_, Blake2_128Concat, PauseScope, (), OptionQuery>;
+
+    /// Verified requests and responses whose module callbacks failed, keyed by their commitment
+    #[pallet::storage]
+    pub type RetryQueue<T: Config> =
+        CountedStorageMap<_, Identity, H256, RetryEntry<BlockNumberFor<T>>, OptionQuery>;
+
     // Pallet implements [`Hooks`] trait to define some logic to execute in some context.
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
@@ -331,6 +405,14 @@ pub mod pallet {
             <frame_system::Pallet<T>>::deposit_log(digest);
         }
 
+        fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let consumed = Self::migrate_mmr_nodes(remaining_weight);
+            let consumed = consumed
+                .saturating_add(Self::prune_child_trie(remaining_weight.saturating_sub(consumed)));
+            consumed
+                .saturating_add(Self::drain_retry_queue(remaining_weight.saturating_sub(consumed)))
+        }
+
         fn offchain_worker(_n: BlockNumberFor<T>) {}
     }
 
@@ -358,7 +440,7 @@ pub mod pallet {
         }
 
         /// Create a consensus client, using a subjectively chosen consensus state.
-        #[pallet::weight(<T as frame_system::Config>::DbWeight::get().reads_writes(1, 1))]
+        #[pallet::weight(T::WeightInfo::create_consensus_client())]
         #[pallet::call_index(1)]
         pub fn create_consensus_client(
             origin: OriginFor<T>,
@@ -378,7 +460,7 @@ pub mod pallet {
         }
 
         /// Set the unbonding period for a consensus state.
-        #[pallet::weight(<T as frame_system::Config>::DbWeight::get().writes(2))]
+        #[pallet::weight(T::WeightInfo::update_consensus_state())]
         #[pallet::call_index(2)]
         pub fn update_consensus_state(
             origin: OriginFor<T>,
@@ -416,6 +498,59 @@ pub mod pallet {
 
             Ok(())
         }
+
+        /// Increase the fee of an outgoing request that is yet to be delivered or timed out. Can
+        /// only be called by the account that dispatched the request.
+        #[pallet::weight(T::WeightInfo::fund_request())]
+        #[pallet::call_index(4)]
+        pub fn fund_request(
+            origin: OriginFor<T>,
+            request: Request,
+            amount: T::Balance,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::add_request_fee(who, request, amount)
+        }
+
+        /// Pause ISMP traffic in the given scope until it is unpaused.
+        #[pallet::weight(T::WeightInfo::pause())]
+        #[pallet::call_index(5)]
+        pub fn pause(origin: OriginFor<T>, scope: PauseScope) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            Paused::<T>::insert(&scope, ());
+            Self::deposit_event(Event::<T>::TrafficPaused { scope });
+
+            Ok(())
+        }
+
+        /// Resume ISMP traffic in the given scope.
+        #[pallet::weight(T::WeightInfo::unpause())]
+        #[pallet::call_index(6)]
+        pub fn unpause(origin: OriginFor<T>, scope: PauseScope) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            Paused::<T>::remove(&scope);
+            Self::deposit_event(Event::<T>::TrafficUnpaused { scope });
+
+            Ok(())
+        }
+
+        /// Retry the delivery of a request or response in the retry queue, without waiting for
+        /// its scheduled attempt. Can be called by anyone.
+        #[pallet::weight(Pallet::<T>::retry_weight(*commitment))]
+        #[pallet::call_index(7)]
+        pub fn retry_callback(origin: OriginFor<T>, commitment: H256) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let entry = RetryQueue::<T>::get(commitment).ok_or(Error::<T>::UnknownRetry)?;
+            if let Err(err) = Self::retry_delivery(commitment, entry) {
+                Self::deposit_event(Event::<T>::Errors { errors: vec![err.into()] });
+            }
+
+            Ok(())
+        }
     }
 
     #[pallet::event]
@@ -484,6 +619,34 @@ pub mod pallet {
         PostResponseTimeoutHandled(TimeoutHandled),
         /// Get request timeout handled
         GetRequestTimeoutHandled(TimeoutHandled),
+        /// The fee of an outgoing request has been increased
+        RequestFunded {
+            /// Commitment of the request
+            commitment: H256,
+            /// The new total fee of the request
+            fee: T::Balance,
+        },
+        /// ISMP traffic has been paused by the circuit breaker
+        TrafficPaused {
+            /// The paused scope
+            scope: PauseScope,
+        },
+        /// ISMP traffic has been resumed by the circuit breaker
+        TrafficUnpaused {
+            /// The resumed scope
+            scope: PauseScope,
+        },
+        /// A verified request or response whose module callback failed has been added to the
+        /// retry queue
+        CallbackQueued {
+            /// Commitment of the request or response
+            commitment: H256,
+        },
+        /// A request or response has been removed from the retry queue without being delivered
+        CallbackDropped {
+            /// Commitment of the request or response
+            commitment: H256,
+        },
     }
 
     /// Pallet errors
@@ -497,6 +660,20 @@ pub mod pallet {
         UnbondingPeriodUpdateFailed,
         /// Couldn't update challenge period
         ChallengePeriodUpdateFailed,
+        /// The request commitment does not exist
+        UnknownRequest,
+        /// Only the account that dispatched the request can fund it
+        NotRequestOrigin,
+        /// Couldn't transfer the fee into escrow
+        FeeTransferFailed,
+        /// Requests can't be funded with a zero amount
+        ZeroFee,
+        /// The request has timed out and can no longer be funded
+        RequestTimedOut,
+        /// The fee held in escrow for the request has already been claimed or refunded
+        FeeAlreadyClaimed,
+        /// The commitment is not in the retry queue
+        UnknownRetry,
     }
 
     /// Users should not pay to submit valid ISMP datagrams.
@@ -516,33 +693,35 @@ pub mod pallet {
             };
 
             let host = Host::<T>::default();
-            let _ = messages
+            let results = messages
                 .iter()
                 .map(|msg| handle_incoming_message(&host, msg.clone()))
                 .collect::<Result<Vec<_>, _>>()
-                .map_err(|_err| {
-                    log::info!(target: "ismp", "Validation Errors: {:#?}", _err);
-                    TransactionValidityError::Invalid(InvalidTransaction::BadProof)
-                })?
-                .into_iter()
-                // check that requests will be successfully dispatched
-                // so we can not be spammed with failing txs
-                .map(|result| match result {
-                    MessageResult::Request(results) |
-                    MessageResult::Response(results) |
-                    MessageResult::Timeout(results) =>
-                        results.into_iter().map(|result| result.map(|_| ())).collect::<Vec<_>>(),
-                    MessageResult::ConsensusMessage(_) | MessageResult::FrozenClient(_) => {
-                        vec![Ok(())]
-                    },
-                })
-                .flatten()
-                .collect::<Result<Vec<_>, _>>()
                 .map_err(|_err| {
                     log::info!(target: "ismp", "Validation Errors: {:#?}", _err);
                     TransactionValidityError::Invalid(InvalidTransaction::BadProof)
                 })?;
 
+            // check that requests will be successfully dispatched so we can not be spammed with
+            // failing txs. Failed callbacks are only deferred to the retry queue alongside
+            // deliveries that succeeded, since the transaction is free.
+            let delivered = results.iter().any(|result| match result {
+                MessageResult::Request(results) |
+                MessageResult::Response(results) |
+                MessageResult::Timeout(results) => results.iter().any(|result| result.is_ok()),
+                MessageResult::ConsensusMessage(_) | MessageResult::FrozenClient(_) => false,
+            });
+            let queueable = messages
+                .iter()
+                .zip(results.iter())
+                .flat_map(|(message, result)| Self::failed_callbacks(message, result))
+                .collect::<Option<Vec<_>>>()
+                .is_some_and(|items| items.is_empty() || (delivered && Self::can_queue(&items)));
+            if !queueable {
+                log::info!(target: "ismp", "Validation Errors: undeliverable requests or responses");
+                Err(TransactionValidityError::Invalid(InvalidTransaction::BadProof))?
+            }
+
             let mut requests = messages
                 .into_iter()
                 .map(|message| match message {
@@ -623,7 +802,11 @@ impl<T: Config> Pallet<T> {
         let mut errors: Vec<HandlingError> = vec![];
         let total_weight = get_weight::<T>(&messages);
         for message in messages {
-            match handle_incoming_message(&host, message.clone()) {
+            let result = handle_incoming_message(&host, message.clone());
+            if let Ok(ref result) = result {
+                Self::queue_failed_callbacks(&message, result);
+            }
+            match result {
                 Ok(MessageResult::ConsensusMessage(res)) => deposit_ismp_events::<T>(
                     res.into_iter().map(|ev| Ok(ev)).collect(),
                     &mut errors,
@@ -655,6 +838,34 @@ impl<T: Config> Pallet<T> {
         })
     }
 
+    /// Executes the messages against the current state without persisting any changes and
+    /// returns the outcome and estimated weight of each message. Messages are executed in order,
+    /// so later messages observe the effects of earlier ones in the batch.
+    pub fn dry_run_messages(messages: Vec<Message>) -> Vec<DryRunResult> {
+        let host = Host::<T>::default();
+        let results = with_transaction(|| {
+            let results = messages
+                .into_iter()
+                .map(|message| {
+                    WeightConsumed::<T>::kill();
+                    let weight = get_weight::<T>(core::slice::from_ref(&message));
+                    let outcome = handle_incoming_message(&host, message).into();
+                    let acc_weight = WeightConsumed::<T>::get();
+                    let weight = weight
+                        .saturating_sub(acc_weight.weight_limit)
+                        .saturating_add(acc_weight.weight_used);
+                    DryRunResult { outcome, weight }
+                })
+                .collect::<Vec<_>>();
+
+            TransactionOutcome::Rollback(Ok::<_, DispatchError>(results))
+        });
+
+        // a storage layer can only fail to open if the transactional limit has been reached, which
+        // cannot happen when called from a runtime api.
+        results.unwrap_or_default()
+    }
+
     /// Return the on-chain MMR root hash.
     pub fn mmr_root() -> H256 {
         Self::mmr_root_hash()
@@ -674,6 +885,11 @@ impl<T: Config> Pallet<T> {
         Nodes::<T>::insert(pos, node)
     }
 
+    /// Remove a node from storage
+    fn remove_node(pos: NodeIndex) {
+        Nodes::<T>::remove(pos)
+    }
+
     /// Set the number of leaves in the mmr
     fn set_num_leaves(num_leaves: LeafIndex) {
         NumberOfLeaves::<T>::put(num_leaves)
@@ -684,6 +900,13 @@ impl<T: Config> Pallet<T> {
         (T::INDEXING_PREFIX, commitment).encode()
     }
 
+    /// Returns the offchain key under which the children of an inner node of the mmr are stored.
+    /// Nodes are keyed by their hash rather than their position, so that sibling blocks appending
+    /// different nodes at the same position can never overwrite each other.
+    pub fn node_offchain_key(hash: H256) -> Vec<u8> {
+        (T::INDEXING_PREFIX, b"nodes", hash).encode()
+    }
+
     /// Gets the request from the offchain storage
     pub fn get_request(commitment: H256) -> Option<Request> {
         let key = Pallet::<T>::full_leaf_offchain_key(commitment);
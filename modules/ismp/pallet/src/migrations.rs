@@ -0,0 +1,122 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for pallet-ismp
+
+use crate::{
+    host::Host,
+    mmr::{
+        primitives::{DataOrHash, MmrHasher, NodeIndex},
+        utils::NodesUtils,
+    },
+    Config, Nodes, NodesMigrationCursor, NumberOfLeaves, Pallet,
+};
+use codec::Encode;
+use frame_support::{traits::Get, weights::Weight};
+use merkle_mountain_range::{helper, Merge};
+
+/// Maximum number of mmr nodes checked by the migration in a single block
+pub const MIGRATION_BATCH_SIZE: u64 = 1_000;
+
+/// Migrates the mmr to only keep its peaks in runtime storage.
+pub mod v1 {
+    use super::*;
+    use crate::STORAGE_VERSION;
+    use core::marker::PhantomData;
+    use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+    /// Schedules the removal of all the mmr nodes that are not peaks from runtime storage. The
+    /// nodes are removed in bounded batches in `on_idle`, the children of every inner node are
+    /// copied into the offchain DB before they are removed so that proofs can still be generated
+    /// for older leaves.
+    pub struct MigrateToPeaksOnly<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToPeaksOnly<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 1 {
+                log::info!(target: "ismp", "MigrateToPeaksOnly should be removed");
+                return T::DbWeight::get().reads(1);
+            }
+
+            NodesMigrationCursor::<T>::put(0);
+            STORAGE_VERSION.put::<Pallet<T>>();
+            T::DbWeight::get().reads_writes(1, 2)
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Copies the children of an inner node that were only kept in runtime storage into the
+    /// offchain db, keyed by the hash of the node, and removes them from runtime storage. Leaves
+    /// are already in the offchain db, keyed by their commitment. The node itself may have been
+    /// removed already, so its hash is recomputed from its children.
+    pub(crate) fn index_legacy_node(pos: NodeIndex) {
+        let height = helper::pos_height_in_tree(pos);
+        if height == 0 {
+            return;
+        }
+
+        let (left, right) = (pos - (1 << height), pos - 1);
+        let (Some(left_hash), Some(right_hash)) = (Nodes::<T>::get(left), Nodes::<T>::get(right))
+        else {
+            return;
+        };
+        let Ok(node) = MmrHasher::<Host<T>>::merge(
+            &DataOrHash::Hash(left_hash),
+            &DataOrHash::Hash(right_hash),
+        ) else {
+            return;
+        };
+        let hash = node.hash::<Host<T>>();
+        sp_io::offchain_index::set(
+            &Pallet::<T>::node_offchain_key(hash),
+            &(left_hash, right_hash).encode(),
+        );
+        Nodes::<T>::remove(left);
+        Nodes::<T>::remove(right);
+    }
+
+    /// Removes the next batch of mmr nodes that are not peaks from runtime storage, without
+    /// exceeding the provided weight. Returns the weight consumed.
+    pub(crate) fn migrate_mmr_nodes(remaining_weight: Weight) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let per_node = db_weight.reads_writes(2, 3);
+        let mut consumed = db_weight.reads_writes(2, 1);
+        if remaining_weight.any_lt(consumed) {
+            return Weight::zero();
+        }
+
+        let Some(mut pos) = NodesMigrationCursor::<T>::get() else { return db_weight.reads(1) };
+        let size = NodesUtils::new(NumberOfLeaves::<T>::get()).size();
+        let end = size.min(pos.saturating_add(MIGRATION_BATCH_SIZE));
+
+        while pos < end && !remaining_weight.any_lt(consumed.saturating_add(per_node)) {
+            // A node is only removed once its parent has been visited and its hash indexed as one
+            // of the parent's children. Peaks have no parent and stay in runtime storage.
+            Self::index_legacy_node(pos);
+            pos += 1;
+            consumed.saturating_accrue(per_node);
+        }
+
+        if pos >= size {
+            log::info!(target: "ismp", "Finished pruning mmr nodes from runtime storage");
+            NodesMigrationCursor::<T>::kill();
+        } else {
+            NodesMigrationCursor::<T>::put(pos);
+        }
+
+        consumed
+    }
+}
@@ -15,30 +15,34 @@
 
 //! An MMR storage implementation.
 use crate::mmr::primitives::{DataOrHash, NodeIndex};
-use codec::Encode;
+use codec::{Decode, Encode};
 use log::{debug, trace};
 use merkle_mountain_range::helper;
-use sp_core::offchain::StorageKind;
+use sp_core::{offchain::StorageKind, H256};
 use sp_std::iter::Peekable;
 #[cfg(not(feature = "std"))]
 use sp_std::prelude::*;
 
-use crate::{host::Host, mmr::utils::NodesUtils, Config, Pallet};
+use crate::{host::Host, mmr::utils::NodesUtils, Config, Nodes, NodesMigrationCursor, Pallet};
 
 /// A marker type for runtime-specific storage implementation.
 ///
 /// Allows appending new items to the MMR and proof verification.
 /// MMR nodes are appended to two different storages:
-/// 1. We add nodes (leaves) hashes to the on-chain storage (see [crate::Nodes]).
-/// 2. We add full leaves (and all inner nodes as well) into the `IndexingAPI` during block
-///    processing, so the values end up in the Offchain DB if indexing is enabled.
+/// 1. We add the hashes of the current peaks to the on-chain storage (see [crate::Nodes]), older
+///    peaks are pruned as they are merged.
+/// 2. We add full leaves, keyed by their commitment, and the children of every inner node, keyed by
+///    the hash of the node, into the `IndexingAPI` during block processing, so the values end up in
+///    the Offchain DB if indexing is enabled. Since nothing is keyed by its position, nodes indexed
+///    by sibling blocks never overwrite each other.
 pub struct RuntimeStorage;
 
 /// A marker type for offchain-specific storage implementation.
 ///
 /// Allows proof generation and verification, but does not support appending new items.
-/// MMR nodes are assumed to be stored in the Off-Chain DB. Note this storage type
-/// DOES NOT support adding new items to the MMR.
+/// MMR nodes are assumed to be stored in the Off-Chain DB, inner nodes are found by descending
+/// from the on-chain peak they belong to. Note this storage type DOES NOT support adding new
+/// items to the MMR.
 pub struct OffchainStorage;
 
 /// A storage layer for MMR.
@@ -58,22 +62,39 @@ where
     T: Config,
 {
     fn get_elem(&self, pos: NodeIndex) -> merkle_mountain_range::Result<Option<DataOrHash>> {
-        let commitment = Pallet::<T>::mmr_positions(pos);
-        if let Some(commitment) = commitment {
+        if let Some(commitment) = Pallet::<T>::mmr_positions(pos) {
             let key = Pallet::<T>::full_leaf_offchain_key(commitment);
             debug!(
                 target: "ismp::mmr", "offchain db get {}: key {:?}",
                 pos, key
             );
-            // Try to retrieve the element from Off-chain DB.
+            // Try to retrieve the full leaf from Off-chain DB.
             if let Some(elem) = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key) {
-                return Ok(codec::Decode::decode(&mut &*elem).ok());
+                return Ok(Decode::decode(&mut &*elem).ok());
             }
-        } else {
-            return Ok(Pallet::<T>::get_node(pos));
         }
 
-        Ok(None)
+        // Peaks are kept in runtime storage, as well as nodes that are yet to be migrated
+        if let Some(node) = Pallet::<T>::get_node(pos) {
+            return Ok(Some(node));
+        }
+
+        // Every other node is found by descending from the peak of the mountain it belongs to.
+        let size = NodesUtils::new(Pallet::<T>::number_of_leaves()).size();
+        let Some(peak) = helper::get_peaks(size).into_iter().find(|peak| *peak >= pos) else {
+            return Ok(None);
+        };
+        let Some(mut hash) = Nodes::<T>::get(peak) else { return Ok(None) };
+        let mut node = peak;
+        let mut height = helper::pos_height_in_tree(peak);
+        while node != pos {
+            let Some((left, right)) = Self::children(node, height, hash) else { return Ok(None) };
+            let left_pos = node - (1 << height);
+            (node, hash) = if pos <= left_pos { (left_pos, left) } else { (node - 1, right) };
+            height -= 1;
+        }
+
+        Ok(Some(DataOrHash::Hash(hash)))
     }
 
     fn append(&mut self, _: NodeIndex, _: Vec<DataOrHash>) -> merkle_mountain_range::Result<()> {
@@ -110,20 +131,51 @@ where
             return Err(merkle_mountain_range::Error::InconsistentStore);
         }
 
+        let (peaks_to_prune, mut peaks_to_store) =
+            peaks_to_prune_and_store(size, size + elems.len() as NodeIndex);
+
         // Now we are going to iterate over elements to insert
         // and keep track of the current `node_index` and `leaf_index`.
         let mut leaf_index = leaves;
         let mut node_index = size;
+        let mut previous = H256::default();
 
         for elem in elems {
-            // Store element
-            Self::store_to_elem(node_index, &elem);
+            let hash = elem.hash::<Host<T>>();
+            match elem {
+                DataOrHash::Data(_) => Self::store_leaf(node_index, &elem),
+                DataOrHash::Hash(_) => {
+                    // The right child of an inner node is the element appended right before it,
+                    // while its left child is an older peak that is yet to be pruned.
+                    let height = helper::pos_height_in_tree(node_index);
+                    let left = Nodes::<T>::get(node_index - (1 << height))
+                        .ok_or(merkle_mountain_range::Error::InconsistentStore)?;
+                    Self::store_children(node_index, hash, (left, previous));
+                },
+            }
+
+            // Only the new peaks are stored on-chain
+            if peaks_to_store.next_if_eq(&node_index).is_some() {
+                Pallet::<T>::insert_node(node_index, hash);
+            }
 
             // Increase the indices.
             if let DataOrHash::Data(..) = elem {
                 leaf_index += 1;
             }
             node_index += 1;
+            previous = hash;
+        }
+
+        // Old peaks that have been merged into the new peaks are no longer needed on-chain. The
+        // children of peaks created before the migration to peaks only storage may still be in
+        // runtime storage, so they are copied into the offchain db before the peak is removed.
+        let migrating = NodesMigrationCursor::<T>::exists();
+        for pos in peaks_to_prune {
+            if migrating {
+                Pallet::<T>::index_legacy_node(pos);
+            }
+            Pallet::<T>::remove_node(pos);
         }
 
         // Update current number of leaves.
@@ -133,35 +185,51 @@ where
     }
 }
 
+impl<T> Storage<OffchainStorage, T>
+where
+    T: Config,
+{
+    /// Returns the hashes of the left and right children of the inner node at `pos` with the
+    /// given height.
+    fn children(pos: NodeIndex, height: u32, hash: H256) -> Option<(H256, H256)> {
+        let key = Pallet::<T>::node_offchain_key(hash);
+        if let Some(children) = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key) {
+            return Decode::decode(&mut &*children).ok();
+        }
+
+        // The children of nodes that are yet to be migrated are still in runtime storage
+        Some((Nodes::<T>::get(pos - (1 << height))?, Nodes::<T>::get(pos - 1)?))
+    }
+}
+
 impl<T> Storage<RuntimeStorage, T>
 where
     T: Config,
 {
-    /// Store a node in the offchain db or runtime storage
-    fn store_to_elem(pos: NodeIndex, node: &DataOrHash) {
-        let encoded_node = node.encode();
-        let commitment = node.hash::<Host<T>>();
-        match node {
-            DataOrHash::Data(_) => {
-                let key = Pallet::<T>::full_leaf_offchain_key(commitment);
-                debug!(
-                    target: "ismp::mmr", "offchain db set: pos {} key {:?}",
-                    pos, key
-                );
-                // Indexing API is used to store the full node content.
-                sp_io::offchain_index::set(&key, &encoded_node);
-                // Store leaf hash on chain
-                Pallet::<T>::insert_node(pos, commitment);
-            },
-            DataOrHash::Hash(hash) => {
-                Pallet::<T>::insert_node(pos, *hash);
-            },
-        };
+    /// Store a leaf in the offchain db
+    fn store_leaf(pos: NodeIndex, leaf: &DataOrHash) {
+        let key = Pallet::<T>::full_leaf_offchain_key(leaf.hash::<Host<T>>());
+        debug!(
+            target: "ismp::mmr", "offchain db set: pos {} key {:?}",
+            pos, key
+        );
+        // Indexing API is used to store the full leaf content.
+        sp_io::offchain_index::set(&key, &leaf.encode());
+    }
+
+    /// Store the children of an inner node in the offchain db
+    fn store_children(pos: NodeIndex, hash: H256, children: (H256, H256)) {
+        let key = Pallet::<T>::node_offchain_key(hash);
+        debug!(
+            target: "ismp::mmr", "offchain db set: pos {} key {:?}",
+            pos, key
+        );
+        sp_io::offchain_index::set(&key, &children.encode());
     }
 }
 
 /// Calculate peaks to prune and store
-fn _peaks_to_prune_and_store(
+fn peaks_to_prune_and_store(
     old_size: NodeIndex,
     new_size: NodeIndex,
 ) -> (impl Iterator<Item = NodeIndex>, Peekable<impl Iterator<Item = NodeIndex>>) {
@@ -14,12 +14,18 @@
 // limitations under the License.
 
 //! Pallet primitives
-use crate::mmr::primitives::NodeIndex;
+use crate::{errors::HandlingError, mmr::primitives::NodeIndex};
 use alloc::format;
 use codec::{Decode, Encode};
 use core::time::Duration;
 use frame_support::{weights::Weight, PalletId};
-use ismp::consensus::ConsensusClient;
+use ismp::{
+    consensus::{ConsensusClient, ConsensusStateId},
+    error::Error as IsmpError,
+    events::Event,
+    handlers::MessageResult,
+    module::DispatchResult,
+};
 use scale_info::TypeInfo;
 use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
 use sp_core::{
@@ -124,6 +130,50 @@ pub struct WeightUsed {
     pub weight_limit: Weight,
 }
 
+/// The outcome of handling a single message during a dry run
+#[derive(Clone, Debug, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum DryRunOutcome {
+    /// The consensus message was handled and produced these events
+    ConsensusMessage(Vec<Event>),
+    /// The fraud proof was accepted and the consensus client would be frozen
+    FrozenClient(ConsensusStateId),
+    /// The result of handling each request in the message
+    Request(Vec<Result<Event, HandlingError>>),
+    /// The result of handling each response in the message
+    Response(Vec<Result<Event, HandlingError>>),
+    /// The result of handling each timeout in the message
+    Timeout(Vec<Result<Event, HandlingError>>),
+    /// The message was rejected entirely
+    Error(HandlingError),
+}
+
+impl From<Result<MessageResult, IsmpError>> for DryRunOutcome {
+    fn from(result: Result<MessageResult, IsmpError>) -> Self {
+        let convert = |results: Vec<DispatchResult>| -> Vec<Result<Event, HandlingError>> {
+            results.into_iter().map(|res| res.map_err(Into::into)).collect()
+        };
+        match result {
+            Ok(MessageResult::ConsensusMessage(events)) => DryRunOutcome::ConsensusMessage(events),
+            Ok(MessageResult::FrozenClient(id)) => DryRunOutcome::FrozenClient(id),
+            Ok(MessageResult::Request(results)) => DryRunOutcome::Request(convert(results)),
+            Ok(MessageResult::Response(results)) => DryRunOutcome::Response(convert(results)),
+            Ok(MessageResult::Timeout(results)) => DryRunOutcome::Timeout(convert(results)),
+            Err(err) => DryRunOutcome::Error(err.into()),
+        }
+    }
+}
+
+/// The outcome and estimated weight of a message executed in a dry run
+#[derive(Clone, Debug, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct DryRunResult {
+    /// The outcome of handling the message
+    pub outcome: DryRunOutcome,
+    /// The estimated weight of handling the message, including module callbacks
+    pub weight: Weight,
+}
+
 /// The `ConsensusEngineId` of ISMP digest in the parachain header.
 pub const ISMP_ID: sp_runtime::ConsensusEngineId = *b"ISMP";
 
@@ -0,0 +1,127 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Garbage collection of commitments in the ISMP child trie.
+//!
+//! Entries are scheduled into hourly buckets once it is known when they can be removed, and the
+//! buckets that have elapsed are drained in `on_idle`.
+//!
+//! Pruning a response commitment also removes the [`Responded`] entry of the request it responds
+//! to, since the response can no longer be timed out once its delivery has been proven.
+//!
+//! Receipts are never pruned. Source chains prove timeouts by proving the absence of a receipt, so
+//! removing the receipt of a delivered request would allow it to be timed out and refunded on its
+//! source chain.
+
+use crate::{
+    child_trie::{RequestCommitments, ResponseCommitments},
+    Config, Pallet, PendingPrunes, PruningCursor, PruningSchedule, Responded,
+};
+use codec::{Decode, Encode};
+use core::time::Duration;
+use frame_support::{
+    traits::{Get, UnixTime},
+    weights::Weight,
+};
+use sp_core::H256;
+use sp_runtime::RuntimeDebug;
+
+/// Size in seconds of the buckets entries are scheduled into
+pub const PRUNING_INTERVAL: u64 = 60 * 60;
+
+/// An entry in the ISMP child trie that can be pruned
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum PrunableEntry {
+    /// A request commitment whose delivery and fee claim have been proven
+    Commitment(H256),
+    /// A response commitment whose delivery and fee claim have been proven
+    Response {
+        /// Commitment of the response
+        commitment: H256,
+        /// Commitment of the request it responds to
+        request_commitment: H256,
+    },
+}
+
+impl<T: Config> Pallet<T> {
+    /// Schedules an entry to be pruned once [`Config::PruningHorizon`] has elapsed after the
+    /// given timestamp.
+    pub fn schedule_pruning(entry: PrunableEntry, timestamp: Duration) {
+        let now = <T::TimeProvider as UnixTime>::now().as_secs() / PRUNING_INTERVAL;
+        let prune_at = timestamp.as_secs().saturating_add(T::PruningHorizon::get());
+        // round up so that the entry is only pruned after the horizon has fully elapsed
+        let bucket = (prune_at.saturating_add(PRUNING_INTERVAL - 1) / PRUNING_INTERVAL).max(now);
+
+        PruningCursor::<T>::mutate(|cursor| {
+            *cursor = Some(cursor.map_or(bucket, |cursor| cursor.min(bucket)))
+        });
+        if !PruningSchedule::<T>::contains_key(bucket, &entry) {
+            PruningSchedule::<T>::insert(bucket, entry, ());
+            PendingPrunes::<T>::mutate(|count| *count = count.saturating_add(1));
+        }
+    }
+
+    /// Removes the entries in all the elapsed buckets, without exceeding the provided weight.
+    /// Returns the weight consumed.
+    pub(crate) fn prune_child_trie(remaining_weight: Weight) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let per_entry = db_weight.reads_writes(2, 6);
+        let mut consumed = db_weight.reads_writes(2, 2);
+        if remaining_weight.any_lt(consumed) {
+            return Weight::zero();
+        }
+
+        let Some(mut cursor) = PruningCursor::<T>::get() else { return db_weight.reads(1) };
+        let now = <T::TimeProvider as UnixTime>::now().as_secs() / PRUNING_INTERVAL;
+        let mut pending = PendingPrunes::<T>::get();
+
+        while cursor <= now && !remaining_weight.any_lt(consumed.saturating_add(per_entry)) {
+            let Some(entry) = PruningSchedule::<T>::iter_key_prefix(cursor).next() else {
+                if pending == 0 {
+                    break;
+                }
+                // this bucket has been drained, move on to the next one
+                cursor += 1;
+                consumed.saturating_accrue(db_weight.reads(1));
+                continue;
+            };
+
+            PruningSchedule::<T>::remove(cursor, &entry);
+            match entry {
+                // response commitments scheduled before `Response` existed were also scheduled
+                // as `Commitment`
+                PrunableEntry::Commitment(commitment) => {
+                    RequestCommitments::<T>::remove(commitment);
+                    ResponseCommitments::<T>::remove(commitment);
+                },
+                PrunableEntry::Response { commitment, request_commitment } => {
+                    ResponseCommitments::<T>::remove(commitment);
+                    Responded::<T>::remove(request_commitment);
+                },
+            }
+            pending = pending.saturating_sub(1);
+            consumed.saturating_accrue(per_entry);
+        }
+
+        PendingPrunes::<T>::put(pending);
+        if pending == 0 {
+            PruningCursor::<T>::kill();
+        } else {
+            PruningCursor::<T>::put(cursor);
+        }
+
+        consumed
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deferred delivery of requests and responses whose module callbacks failed.
+//!
+//! Requests and responses that were verified against a state proof but rejected by the
+//! destination module are kept in a bounded queue, so that they can be delivered again without a
+//! new proof. The queue is drained in `on_idle` with an exponential backoff between attempts, and
+//! delivery can also be triggered by anyone through [`Call::retry_callback`](crate::Call).
+//!
+//! Since message handling is free, unsigned transactions in which no request or response is
+//! delivered are still rejected, so failed callbacks are only queued alongside successful ones.
+//! Traffic rejected because it is paused is never queued.
+
+use crate::{
+    host::Host, primitives::ModuleId, weight_info::WeightProvider, weights::WeightInfo, Config,
+    Event, Pallet, RetryQueue,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+use codec::{Decode, Encode};
+use frame_support::{
+    storage::{with_transaction, TransactionOutcome},
+    traits::Get,
+    weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use ismp::{
+    error::Error as IsmpError,
+    events::RequestResponseHandled,
+    handlers::MessageResult,
+    host::{IsmpHost, Traffic},
+    messaging::Message,
+    module::DispatchResult,
+    router::{Post, PostResponse, Request, RequestResponse, Response},
+    util::{hash_request, hash_response},
+};
+use sp_core::H256;
+use sp_runtime::{traits::Saturating, DispatchError, RuntimeDebug};
+
+/// Maximum number of entries held in the retry queue
+pub const MAX_RETRY_QUEUE_LEN: u32 = 1_000;
+
+/// Number of failed deliveries after which an entry is dropped from the retry queue
+pub const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// A verified request or response whose module callback failed
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum RetryItem {
+    /// An incoming post request
+    Request(Post),
+    /// An incoming post response
+    Response(PostResponse),
+}
+
+/// An entry in the retry queue
+#[derive(Encode, Decode, scale_info::TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RetryEntry<BlockNumber> {
+    /// The request or response to be delivered
+    pub item: RetryItem,
+    /// The relayer that delivered the proof, receipts are stored on its behalf
+    pub relayer: Vec<u8>,
+    /// Number of failed deliveries
+    pub attempts: u32,
+    /// The block from which delivery will be retried in `on_idle`
+    pub retry_at: BlockNumber,
+}
+
+impl<T: Config> Pallet<T> {
+    /// Returns an entry for each request or response in the message that was verified but failed
+    /// to be delivered, or `None` for failures that can't be retried from the queue, such as
+    /// paused traffic.
+    pub(crate) fn failed_callbacks(
+        message: &Message,
+        result: &MessageResult,
+    ) -> Vec<Option<RetryItem>> {
+        let (items, results): (Vec<Option<RetryItem>>, _) = match (message, result) {
+            (Message::Request(msg), MessageResult::Request(results)) => (
+                msg.requests
+                    .iter()
+                    .cloned()
+                    .map(|post| Some(RetryItem::Request(post)))
+                    .collect(),
+                results,
+            ),
+            (Message::Response(msg), MessageResult::Response(results)) => match &msg.datagram {
+                RequestResponse::Response(responses) => (
+                    responses
+                        .iter()
+                        .map(|response| match response {
+                            Response::Post(response) => Some(RetryItem::Response(response.clone())),
+                            // get responses are delivered with the values read from the proof
+                            Response::Get(_) => None,
+                        })
+                        .collect(),
+                    results,
+                ),
+                RequestResponse::Request(_) => (vec![], results),
+            },
+            (_, MessageResult::Timeout(results)) => (vec![], results),
+            _ => return vec![],
+        };
+
+        results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.as_ref().err().map(|err| (index, err)))
+            .map(|(index, err)| {
+                // paused traffic is retried by the relayer once it is resumed
+                if matches!(err, IsmpError::Paused { .. }) || Self::is_final(err) {
+                    return None
+                }
+                items.get(index).cloned().flatten()
+            })
+            .collect()
+    }
+
+    /// Returns true if all the items can be added to the retry queue
+    pub(crate) fn can_queue(items: &[RetryItem]) -> bool {
+        RetryQueue::<T>::count().saturating_add(items.len() as u32) <= MAX_RETRY_QUEUE_LEN &&
+            items.iter().all(|item| !RetryQueue::<T>::contains_key(item.commitment::<T>()))
+    }
+
+    /// Adds the requests and responses in the message that were verified but failed to be
+    /// delivered to the retry queue
+    pub(crate) fn queue_failed_callbacks(message: &Message, result: &MessageResult) {
+        let relayer = match message {
+            Message::Request(msg) => &msg.signer,
+            Message::Response(msg) => &msg.signer,
+            _ => return,
+        };
+
+        for item in Self::failed_callbacks(message, result).into_iter().flatten() {
+            let commitment = item.commitment::<T>();
+            if !Self::can_queue(core::slice::from_ref(&item)) {
+                continue;
+            }
+
+            RetryQueue::<T>::insert(
+                commitment,
+                RetryEntry {
+                    item,
+                    relayer: relayer.clone(),
+                    attempts: 0,
+                    retry_at: frame_system::Pallet::<T>::block_number().saturating_add(1u32.into()),
+                },
+            );
+            Self::deposit_event(Event::<T>::CallbackQueued { commitment });
+        }
+    }
+
+    /// Attempts to deliver the entry, removing it from the queue once it has been delivered, it
+    /// can no longer be delivered or it has exhausted its attempts. Otherwise the next attempt is
+    /// scheduled with an exponential backoff.
+    pub(crate) fn retry_delivery(
+        commitment: H256,
+        mut entry: RetryEntry<BlockNumberFor<T>>,
+    ) -> DispatchResult {
+        // module state changes from a failed callback must not be persisted
+        let result = with_transaction(|| {
+            let result = Self::deliver(&entry);
+            let outcome = if result.is_ok() {
+                TransactionOutcome::Commit
+            } else {
+                TransactionOutcome::Rollback
+            };
+            outcome(Ok::<_, DispatchError>(result))
+        })
+        .unwrap_or_else(|_| {
+            Err(IsmpError::ImplementationSpecific("Transactional limit reached".into()))
+        });
+
+        match result {
+            Ok(_) => RetryQueue::<T>::remove(commitment),
+            Err(ref err) if Self::is_final(err) => {
+                RetryQueue::<T>::remove(commitment);
+                Self::deposit_event(Event::<T>::CallbackDropped { commitment });
+            },
+            Err(_) => {
+                entry.attempts += 1;
+                if entry.attempts >= MAX_RETRY_ATTEMPTS {
+                    RetryQueue::<T>::remove(commitment);
+                    Self::deposit_event(Event::<T>::CallbackDropped { commitment });
+                } else {
+                    let backoff = 2u32.saturating_pow(entry.attempts);
+                    entry.retry_at =
+                        frame_system::Pallet::<T>::block_number().saturating_add(backoff.into());
+                    RetryQueue::<T>::insert(commitment, entry);
+                }
+            },
+        }
+
+        result
+    }
+
+    /// Delivers the request or response to its module and stores its receipt
+    fn deliver(entry: &RetryEntry<BlockNumberFor<T>>) -> DispatchResult {
+        let host = Host::<T>::default();
+        let router = host.ismp_router();
+        match &entry.item {
+            RetryItem::Request(post) => {
+                let request = Request::Post(post.clone());
+                if host.request_receipt(&request).is_some() {
+                    Err(IsmpError::DuplicateRequest { meta: (&request).into() })?
+                }
+                if request.timed_out(host.timestamp()) {
+                    Err(IsmpError::RequestTimeout { meta: (&request).into() })?
+                }
+                if host.is_paused(Traffic::Incoming, post.source, &post.to) {
+                    Err(IsmpError::Paused { meta: (&request).into() })?
+                }
+
+                router.module_for_id(post.to.clone())?.on_accept(post.clone())?;
+                host.store_request_receipt(&request, &entry.relayer)?;
+                let event = RequestResponseHandled {
+                    commitment: hash_request::<Host<T>>(&request),
+                    relayer: entry.relayer.clone(),
+                };
+                Self::deposit_event(Event::<T>::PostRequestHandled(event.clone()));
+                Ok(ismp::events::Event::PostRequestHandled(event))
+            },
+            RetryItem::Response(post_response) => {
+                let response = Response::Post(post_response.clone());
+                if host.request_commitment(hash_request::<Host<T>>(&response.request())).is_err() {
+                    Err(IsmpError::UnsolicitedResponse { meta: (&response).into() })?
+                }
+                if host.response_receipt(&response).is_some() {
+                    Err(IsmpError::DuplicateResponse { meta: (&response).into() })?
+                }
+                if response.timed_out(host.timestamp()) {
+                    Err(IsmpError::ResponseTimeout { response: (&response).into() })?
+                }
+                if host.is_paused(
+                    Traffic::Incoming,
+                    response.source_chain(),
+                    &response.destination_module(),
+                ) {
+                    Err(IsmpError::Paused { meta: (&response).into() })?
+                }
+
+                router
+                    .module_for_id(response.destination_module())?
+                    .on_response(response.clone())?;
+                host.store_response_receipt(&response, &entry.relayer)?;
+                let event = RequestResponseHandled {
+                    commitment: hash_response::<Host<T>>(&response),
+                    relayer: entry.relayer.clone(),
+                };
+                Self::deposit_event(Event::<T>::PostResponseHandled(event.clone()));
+                Ok(ismp::events::Event::PostResponseHandled(event))
+            },
+        }
+    }
+
+    /// Errors after which an entry can never be delivered
+    fn is_final(err: &IsmpError) -> bool {
+        matches!(
+            err,
+            IsmpError::DuplicateRequest { .. } |
+                IsmpError::DuplicateResponse { .. } |
+                IsmpError::RequestTimeout { .. } |
+                IsmpError::ResponseTimeout { .. } |
+                IsmpError::UnsolicitedResponse { .. }
+        )
+    }
+
+    /// Returns the weight of retrying the delivery of the entry with the given commitment
+    pub fn retry_weight(commitment: H256) -> Weight {
+        let callback_weight = RetryQueue::<T>::get(commitment)
+            .map(|entry| entry.item.callback_weight::<T>())
+            .unwrap_or_default();
+        T::WeightInfo::retry_callback().saturating_add(callback_weight)
+    }
+
+    /// Retries the deliveries in the queue that are due, without exceeding the provided weight.
+    /// Returns the weight consumed.
+    pub(crate) fn drain_retry_queue(remaining_weight: Weight) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let mut consumed = db_weight.reads(1);
+        if remaining_weight.any_lt(consumed) || RetryQueue::<T>::count() == 0 {
+            return consumed.min(remaining_weight);
+        }
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let mut due = vec![];
+        for (commitment, entry) in RetryQueue::<T>::iter() {
+            consumed.saturating_accrue(db_weight.reads(1));
+            if remaining_weight.any_lt(consumed) {
+                break;
+            }
+            if entry.retry_at <= now {
+                due.push((commitment, entry));
+            }
+        }
+
+        for (commitment, entry) in due {
+            let weight =
+                T::WeightInfo::retry_callback().saturating_add(entry.item.callback_weight::<T>());
+            if remaining_weight.any_lt(consumed.saturating_add(weight)) {
+                break;
+            }
+            consumed.saturating_accrue(weight);
+            if let Err(err) = Self::retry_delivery(commitment, entry) {
+                log::trace!(target: "ismp", "Failed to deliver {commitment:?} from the retry queue: {err:?}");
+            }
+        }
+
+        consumed
+    }
+}
+
+impl RetryItem {
+    /// The commitment the entry is keyed by, this is the request commitment for requests and the
+    /// response commitment for responses
+    pub fn commitment<T: Config>(&self) -> H256 {
+        match self {
+            RetryItem::Request(post) => hash_request::<Host<T>>(&Request::Post(post.clone())),
+            RetryItem::Response(response) =>
+                hash_response::<Host<T>>(&Response::Post(response.clone())),
+        }
+    }
+
+    /// Weight of the module callback that delivers this item
+    fn callback_weight<T: Config>(&self) -> Weight {
+        let module = match self {
+            RetryItem::Request(post) => post.to.clone(),
+            RetryItem::Response(response) => response.destination_module(),
+        };
+        let handle = ModuleId::from_bytes(&module)
+            .ok()
+            .and_then(|id| <T as Config>::WeightProvider::module_callback(id))
+            .unwrap_or(Box::new(()));
+        match self {
+            RetryItem::Request(post) => handle.on_accept(post),
+            RetryItem::Response(response) => handle.on_response(&Response::Post(response.clone())),
+        }
+    }
+}
@@ -15,10 +15,12 @@
 
 //! Users of ismp should benchmark consensus clients and module callbacks
 //! This module provides a guide on how to provide static weights for consensus clients and module
-//! callbacks
+//! callbacks, the weight of handling the messages themselves is provided by
+//! [`Config::WeightInfo`]
 
-use crate::{primitives::ModuleId, Config};
+use crate::{primitives::ModuleId, weights::WeightInfo, Config, ConsensusStateClient};
 use alloc::boxed::Box;
+use core::marker::PhantomData;
 use frame_support::weights::Weight;
 use ismp::{
     consensus::ConsensusClientId,
@@ -44,6 +46,34 @@ impl ConsensusClientWeight for () {
     }
 }
 
+/// Benchmarked weight functions for a consensus client, parameterised by the size in bytes of the
+/// proofs it verifies
+pub trait ConsensusClientWeightInfo {
+    /// Returns the weight of verifying a consensus proof of `n` bytes
+    fn verify_consensus(n: u32) -> Weight;
+    /// Returns the weight of verifying a fraud proof whose two proofs are `n` bytes in total
+    fn verify_fraud_proof(n: u32) -> Weight;
+}
+
+/// A [`ConsensusClientWeight`] backed by the benchmarked weight functions of a consensus client
+pub struct BenchmarkedConsensusClient<W>(PhantomData<W>);
+
+impl<W> Default for BenchmarkedConsensusClient<W> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<W: ConsensusClientWeightInfo> ConsensusClientWeight for BenchmarkedConsensusClient<W> {
+    fn verify_consensus(&self, msg: &ConsensusMessage) -> Weight {
+        W::verify_consensus(msg.consensus_proof.len() as u32)
+    }
+
+    fn verify_fraud_proof(&self, msg: &FraudProofMessage) -> Weight {
+        W::verify_fraud_proof((msg.proof_1.len() + msg.proof_2.len()) as u32)
+    }
+}
+
 /// A trait that provides weight information about how module callbacks execute
 pub trait IsmpModuleWeight {
     /// Returns the weight used in processing this request
@@ -91,10 +121,10 @@ impl WeightProvider for () {
 pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
     messages.into_iter().fold(Weight::zero(), |acc, msg| match msg {
         Message::Consensus(msg) => {
-            let consensus_handler =
-                <T as Config>::WeightProvider::consensus_client(msg.consensus_state_id)
-                    .unwrap_or(Box::new(()));
-            consensus_handler.verify_consensus(&msg)
+            let consensus_handler = ConsensusStateClient::<T>::get(msg.consensus_state_id)
+                .and_then(|id| <T as Config>::WeightProvider::consensus_client(id))
+                .unwrap_or(Box::new(()));
+            acc + consensus_handler.verify_consensus(&msg)
         },
         Message::Request(msg) => {
             let cb_weight = msg.requests.iter().fold(Weight::zero(), |acc, req| {
@@ -105,7 +135,7 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                     .unwrap_or(Box::new(()));
                 acc + handle.on_accept(&req)
             });
-            acc + cb_weight
+            acc + T::WeightInfo::handle_request_message(msg.requests.len() as u32) + cb_weight
         },
         Message::Response(msg) => match &msg.datagram {
             RequestResponse::Response(responses) => {
@@ -123,7 +153,7 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                     acc + handle.on_response(&res)
                 });
 
-                acc + cb_weight
+                acc + T::WeightInfo::handle_response_message(responses.len() as u32) + cb_weight
             },
             RequestResponse::Request(requests) => {
                 let cb_weight = requests.iter().fold(Weight::zero(), |acc, req| {
@@ -141,7 +171,7 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                     }))
                 });
 
-                acc + cb_weight
+                acc + T::WeightInfo::handle_response_message(requests.len() as u32) + cb_weight
             },
         },
         Message::Timeout(msg) => match msg {
@@ -158,7 +188,7 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                     acc + handle.on_timeout(&Timeout::Request(req.clone()))
                 });
 
-                acc + cb_weight
+                acc + T::WeightInfo::handle_timeout_message(requests.len() as u32) + cb_weight
             },
             TimeoutMessage::PostResponse { responses, .. } => {
                 let cb_weight = responses.iter().fold(Weight::zero(), |acc, res| {
@@ -170,7 +200,7 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                     acc + handle.on_timeout(&Timeout::Response(res.clone()))
                 });
 
-                acc + cb_weight
+                acc + T::WeightInfo::handle_timeout_message(responses.len() as u32) + cb_weight
             },
             TimeoutMessage::Get { requests } => {
                 let cb_weight = requests.iter().fold(Weight::zero(), |acc, req| {
@@ -184,15 +214,15 @@ pub fn get_weight<T: Config>(messages: &[Message]) -> Weight {
                         .unwrap_or(Box::new(()));
                     acc + handle.on_timeout(&Timeout::Request(req.clone()))
                 });
-                acc + cb_weight
+                acc + T::WeightInfo::handle_timeout_message(requests.len() as u32) + cb_weight
             },
         },
 
         Message::FraudProof(msg) => {
-            let consensus_handler =
-                <T as Config>::WeightProvider::consensus_client(msg.consensus_state_id)
-                    .unwrap_or(Box::new(()));
-            consensus_handler.verify_fraud_proof(&msg)
+            let consensus_handler = ConsensusStateClient::<T>::get(msg.consensus_state_id)
+                .and_then(|id| <T as Config>::WeightProvider::consensus_client(id))
+                .unwrap_or(Box::new(()));
+            acc + consensus_handler.verify_fraud_proof(&msg)
         },
     })
 }
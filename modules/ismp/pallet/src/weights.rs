@@ -0,0 +1,228 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights for pallet_ismp
+//!
+//! These are hand-written placeholder estimates, they have not been produced by the benchmarking
+//! CLI. They must be replaced with generated weights on the reference hardware before being relied
+//! on in production, and regenerated whenever the benchmarks or the message handlers change, using:
+//!
+//! ```sh
+//! ./target/release/hyperbridge benchmark pallet \
+//!     --chain=gargantua-2000 \
+//!     --pallet=pallet_ismp \
+//!     --extrinsic='*' \
+//!     --steps=50 \
+//!     --repeat=20 \
+//!     --output=modules/ismp/pallet/src/weights.rs
+//! ```
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed for pallet_ismp.
+pub trait WeightInfo {
+    /// Weight of the `create_consensus_client` extrinsic
+    fn create_consensus_client() -> Weight;
+    /// Weight of the `update_consensus_state` extrinsic
+    fn update_consensus_state() -> Weight;
+    /// Weight of handling a request message with `n` requests, excluding module callbacks
+    fn handle_request_message(n: u32) -> Weight;
+    /// Weight of handling a response message with `n` responses, excluding module callbacks
+    fn handle_response_message(n: u32) -> Weight;
+    /// Weight of handling a timeout message with `n` requests, excluding module callbacks
+    fn handle_timeout_message(n: u32) -> Weight;
+    /// Weight of the `fund_request` extrinsic
+    fn fund_request() -> Weight;
+    /// Weight of the `pause` extrinsic
+    fn pause() -> Weight;
+    /// Weight of the `unpause` extrinsic
+    fn unpause() -> Weight;
+    /// Weight of the `retry_callback` extrinsic, excluding the module callback
+    fn retry_callback() -> Weight;
+}
+
+/// Placeholder weights for pallet_ismp, until they are generated on the reference hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Storage: `Ismp::ConsensusStateClient` (r:1 w:1)
+    /// Storage: `Ismp::ConsensusStates` (r:0 w:1)
+    /// Storage: `Ismp::ConsensusClientUpdateTime` (r:0 w:1)
+    /// Storage: `Ismp::UnbondingPeriod` (r:0 w:1)
+    /// Storage: `Ismp::ChallengePeriod` (r:0 w:1)
+    /// Storage: `Ismp::StateCommitments` (r:0 w:1)
+    /// Storage: `Ismp::StateMachineUpdateTime` (r:0 w:1)
+    /// Storage: `Ismp::LatestStateMachineHeight` (r:0 w:1)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    fn create_consensus_client() -> Weight {
+        Weight::from_parts(42_000_000, 3_555)
+            .saturating_add(T::DbWeight::get().reads(2_u64))
+            .saturating_add(T::DbWeight::get().writes(8_u64))
+    }
+    /// Storage: `Ismp::UnbondingPeriod` (r:0 w:1)
+    /// Storage: `Ismp::ChallengePeriod` (r:0 w:1)
+    fn update_consensus_state() -> Weight {
+        Weight::from_parts(15_000_000, 0).saturating_add(T::DbWeight::get().writes(2_u64))
+    }
+    /// Storage: `Ismp::ConsensusStateClient` (r:1 w:0)
+    /// Storage: `Ismp::FrozenConsensusClients` (r:1 w:0)
+    /// Storage: `Ismp::FrozenStateMachine` (r:1 w:0)
+    /// Storage: `Ismp::ChallengePeriod` (r:1 w:0)
+    /// Storage: `Ismp::StateMachineUpdateTime` (r:1 w:0)
+    /// Storage: `Ismp::StateCommitments` (r:1 w:0)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    /// Storage: `Ismp::PruningCursor` (r:1 w:1)
+    /// Storage: `Ismp::PendingPrunes` (r:1 w:1)
+    /// Storage: `Ismp::PruningSchedule` (r:n w:n)
+    /// Storage: `:child_storage:default:ISMP` (r:n w:n)
+    /// The range of component `n` is `[1, 100]`.
+    fn handle_request_message(n: u32) -> Weight {
+        Weight::from_parts(48_000_000, 4_687)
+            .saturating_add(Weight::from_parts(21_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(9_u64))
+            .saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes(2_u64))
+            .saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    /// Storage: `Ismp::ConsensusStateClient` (r:1 w:0)
+    /// Storage: `Ismp::FrozenConsensusClients` (r:1 w:0)
+    /// Storage: `Ismp::FrozenStateMachine` (r:1 w:0)
+    /// Storage: `Ismp::ChallengePeriod` (r:1 w:0)
+    /// Storage: `Ismp::StateMachineUpdateTime` (r:1 w:0)
+    /// Storage: `Ismp::StateCommitments` (r:1 w:0)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    /// Storage: `Ismp::PruningCursor` (r:1 w:1)
+    /// Storage: `Ismp::PendingPrunes` (r:1 w:1)
+    /// Storage: `Ismp::PruningSchedule` (r:n w:n)
+    /// Storage: `:child_storage:default:ISMP` (r:2n w:2n)
+    /// The range of component `n` is `[1, 100]`.
+    fn handle_response_message(n: u32) -> Weight {
+        Weight::from_parts(48_000_000, 4_687)
+            .saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(9_u64))
+            .saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes(2_u64))
+            .saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    /// Storage: `Ismp::ConsensusStateClient` (r:1 w:0)
+    /// Storage: `Ismp::FrozenConsensusClients` (r:1 w:0)
+    /// Storage: `Ismp::FrozenStateMachine` (r:1 w:0)
+    /// Storage: `Ismp::ChallengePeriod` (r:1 w:0)
+    /// Storage: `Ismp::StateMachineUpdateTime` (r:1 w:0)
+    /// Storage: `Ismp::StateCommitments` (r:1 w:0)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    /// Storage: `Ismp::EscrowedFees` (r:n w:n)
+    /// Storage: `:child_storage:default:ISMP` (r:n w:n)
+    /// The range of component `n` is `[1, 100]`.
+    fn handle_timeout_message(n: u32) -> Weight {
+        Weight::from_parts(45_000_000, 4_687)
+            .saturating_add(Weight::from_parts(24_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(7_u64))
+            .saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    /// Storage: `:child_storage:default:ISMP` (r:1 w:1)
+    /// Storage: `System::Account` (r:2 w:2)
+    /// Storage: `Ismp::EscrowedFees` (r:1 w:1)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    fn fund_request() -> Weight {
+        Weight::from_parts(62_000_000, 6_196)
+            .saturating_add(T::DbWeight::get().reads(5_u64))
+            .saturating_add(T::DbWeight::get().writes(4_u64))
+    }
+    /// Storage: `Ismp::Paused` (r:0 w:1)
+    fn pause() -> Weight {
+        Weight::from_parts(12_000_000, 0).saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Ismp::Paused` (r:0 w:1)
+    fn unpause() -> Weight {
+        Weight::from_parts(12_000_000, 0).saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Ismp::RetryQueue` (r:1 w:1)
+    /// Storage: `Ismp::CounterForRetryQueue` (r:1 w:1)
+    /// Storage: `Ismp::Paused` (r:2 w:0)
+    /// Storage: `Timestamp::Now` (r:1 w:0)
+    /// Storage: `:child_storage:default:ISMP` (r:1 w:1)
+    /// Storage: `Ismp::PruningSchedule` (r:0 w:1)
+    /// Storage: `Ismp::PendingPrunes` (r:1 w:1)
+    fn retry_callback() -> Weight {
+        Weight::from_parts(48_000_000, 4_102)
+            .saturating_add(T::DbWeight::get().reads(7_u64))
+            .saturating_add(T::DbWeight::get().writes(5_u64))
+    }
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+    fn create_consensus_client() -> Weight {
+        Weight::from_parts(42_000_000, 3_555)
+            .saturating_add(RocksDbWeight::get().reads(2_u64))
+            .saturating_add(RocksDbWeight::get().writes(8_u64))
+    }
+    fn update_consensus_state() -> Weight {
+        Weight::from_parts(15_000_000, 0).saturating_add(RocksDbWeight::get().writes(2_u64))
+    }
+    fn handle_request_message(n: u32) -> Weight {
+        Weight::from_parts(48_000_000, 4_687)
+            .saturating_add(Weight::from_parts(21_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(9_u64))
+            .saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes(2_u64))
+            .saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    fn handle_response_message(n: u32) -> Weight {
+        Weight::from_parts(48_000_000, 4_687)
+            .saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(9_u64))
+            .saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes(2_u64))
+            .saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    fn handle_timeout_message(n: u32) -> Weight {
+        Weight::from_parts(45_000_000, 4_687)
+            .saturating_add(Weight::from_parts(24_000_000, 0).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(7_u64))
+            .saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(n.into())))
+            .saturating_add(Weight::from_parts(0, 2_580).saturating_mul(n.into()))
+    }
+    fn fund_request() -> Weight {
+        Weight::from_parts(62_000_000, 6_196)
+            .saturating_add(RocksDbWeight::get().reads(5_u64))
+            .saturating_add(RocksDbWeight::get().writes(4_u64))
+    }
+    fn pause() -> Weight {
+        Weight::from_parts(12_000_000, 0).saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn unpause() -> Weight {
+        Weight::from_parts(12_000_000, 0).saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn retry_callback() -> Weight {
+        Weight::from_parts(48_000_000, 4_102)
+            .saturating_add(RocksDbWeight::get().reads(7_u64))
+            .saturating_add(RocksDbWeight::get().writes(5_u64))
+    }
+}
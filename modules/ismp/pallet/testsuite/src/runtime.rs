@@ -38,6 +38,7 @@ use ismp::{
 };
 use ismp_sync_committee::constants::sepolia::Sepolia;
 use pallet_ismp::{host::Host, primitives::ModuleId};
+use pallet_ismp_relayer::payout::{FeePotAccount, WithdrawalPayout};
 use sp_core::{
     crypto::AccountId32,
     offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt},
@@ -69,6 +70,8 @@ frame_support::construct_runtime!(
         PalletXcm: pallet_xcm,
         Assets: pallet_assets,
         Gateway: pallet_asset_gateway,
+        PolygonPos: ismp_polygon_pos::pallet,
+        IsmpParachain: ismp_parachain,
     }
 );
 
@@ -166,6 +169,9 @@ impl pallet_timestamp::Config for Test {
 
 parameter_types! {
     pub const Coprocessor: Option<StateMachine> = None;
+    pub const Hyperbridge: Option<StateMachine> = Some(StateMachine::Kusama(4009));
+    pub const PerByteFee: Balance = 10;
+    pub const PruningHorizon: u64 = 60 * 60;
 }
 
 impl pallet_ismp::Config for Test {
@@ -182,6 +188,10 @@ impl pallet_ismp::Config for Test {
         ismp_bsc::BscClient<Host<Test>>,
     );
     type WeightProvider = ();
+    type WeightInfo = ();
+    type Currency = Balances;
+    type PerByteFee = PerByteFee;
+    type PruningHorizon = PruningHorizon;
 }
 
 impl pallet_ismp_relayer::Config for Test {
@@ -190,6 +200,12 @@ impl pallet_ismp_relayer::Config for Test {
 
 impl pallet_ismp_host_executive::Config for Test {}
 
+impl ismp_polygon_pos::pallet::Config for Test {}
+
+impl ismp_parachain::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+}
+
 impl pallet_call_decompressor::Config for Test {
     type MaxCallSize = ConstU32<2>;
 }
@@ -198,7 +214,13 @@ impl pallet_call_decompressor::Config for Test {
 pub struct ModuleRouter;
 
 impl IsmpRouter for ModuleRouter {
-    fn module_for_id(&self, _bytes: Vec<u8>) -> Result<Box<dyn IsmpModule>, ismp::error::Error> {
+    fn module_for_id(&self, bytes: Vec<u8>) -> Result<Box<dyn IsmpModule>, ismp::error::Error> {
+        if bytes == pallet_ismp_relayer::MODULE_ID.to_vec() {
+            return Ok(Box::new(
+                WithdrawalPayout::<Test, FeePotAccount<Test>, Hyperbridge>::default(),
+            ))
+        }
+
         Ok(Box::new(MockModule))
     }
 }
@@ -218,17 +240,31 @@ where
     pallet_timestamp::Pallet::<T>::set_timestamp(value.into());
 }
 
+parameter_types! {
+    /// Module whose callbacks are rejected by the [`MockModule`]
+    pub storage UnavailableModule: Option<Vec<u8>> = None;
+}
+
 /// Mock module
 #[derive(Default)]
 pub struct MockModule;
 
-impl IsmpModule for MockModule {
-    fn on_accept(&self, _request: Post) -> Result<(), ismp::error::Error> {
+impl MockModule {
+    fn ensure_available(module: &[u8]) -> Result<(), ismp::error::Error> {
+        if UnavailableModule::get().as_deref() == Some(module) {
+            Err(ismp::error::Error::ImplementationSpecific("Module is unavailable".to_string()))?
+        }
         Ok(())
     }
+}
 
-    fn on_response(&self, _response: Response) -> Result<(), ismp::error::Error> {
-        Ok(())
+impl IsmpModule for MockModule {
+    fn on_accept(&self, request: Post) -> Result<(), ismp::error::Error> {
+        Self::ensure_available(&request.to)
+    }
+
+    fn on_response(&self, response: Response) -> Result<(), ismp::error::Error> {
+        Self::ensure_available(&response.destination_module())
     }
 
     fn on_timeout(&self, _request: Timeout) -> Result<(), ismp::error::Error> {
@@ -0,0 +1,45 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use crate::runtime::*;
+use codec::Encode;
+use ismp::{consensus::ConsensusClient, error::Error};
+use ismp_parachain::{
+    consensus::{ParachainConsensusClient, ParachainConsensusProof},
+    Parachains,
+};
+use pallet_ismp::host::Host;
+
+#[test]
+fn should_reject_parachain_fraud_proofs() {
+    new_test_ext().execute_with(|| {
+        Parachains::<Test>::insert(2000, ());
+        let host = Host::<Test>::default();
+        let client = ParachainConsensusClient::<Test, IsmpParachain>::default();
+        let proof = |relay_height| {
+            ParachainConsensusProof { para_ids: vec![2000], relay_height, storage_proof: vec![] }
+                .encode()
+        };
+
+        // the relay chain oracle only tracks a single relay chain fork, so conflicting parachain
+        // heads can't be proven and the client never accepts fraud proofs
+        let result = client.verify_fraud_proof(&host, vec![], proof(10), proof(10));
+        assert!(matches!(result, Err(Error::ImplementationSpecific(_))));
+        let result = client.verify_fraud_proof(&host, vec![], proof(10), proof(11));
+        assert!(matches!(result, Err(Error::ImplementationSpecific(_))));
+    })
+}
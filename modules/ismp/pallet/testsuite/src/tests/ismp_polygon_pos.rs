@@ -0,0 +1,426 @@
+// Copyright (C) 2023 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use crate::runtime::{new_test_ext, Test};
+use alloy_primitives::B256;
+use codec::{Decode, Encode};
+use ethereum_trie::{keccak::KeccakHasher, EIP1186Layout, MemoryDB};
+use evm_common::{derive_map_key_with_offset, types::Account};
+use frame_support::crypto::ecdsa::ECDSAExt;
+use geth_primitives::{CodecHeader, Header};
+use ismp::{
+    consensus::{ConsensusClient, StateCommitment, StateMachineHeight, StateMachineId},
+    host::{Ethereum, IsmpHost, StateMachine},
+    util::Keccak256,
+};
+use ismp_polygon_pos::{
+    checkpoint::{
+        CheckpointConfig, CheckpointProof, HEADER_BLOCKS_SLOT, HEADER_BLOCK_END_OFFSET,
+        HEADER_BLOCK_ROOT_OFFSET, HEADER_BLOCK_START_OFFSET,
+    },
+    pallet::Headers,
+    ConsensusState, PolygonClient, PolygonClientUpdate, POLYGON_CONSENSUS_ID,
+};
+use pallet_ismp::host::Host;
+use polygon_pos_verifier::primitives::{check_membership, checkpoint_leaf};
+use sp_core::{ecdsa, Pair, H160, H256};
+use std::time::Duration;
+use trie_db::{Recorder, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut};
+
+type Layout = EIP1186Layout<KeccakHasher>;
+
+const ROOT_CHAIN: H160 = H160([1u8; 20]);
+
+fn validators() -> Vec<(H160, ecdsa::Pair)> {
+    (0..5u64)
+        .map(|_| {
+            let pair = ecdsa::Pair::from_seed_slice(H256::random().as_bytes()).unwrap();
+            (pair.public().to_eth_address().unwrap().into(), pair)
+        })
+        .collect()
+}
+
+fn header(parent_hash: H256, number: u64) -> CodecHeader {
+    CodecHeader {
+        parent_hash,
+        uncle_hash: H256::random(),
+        coinbase: Default::default(),
+        state_root: H256::random(),
+        transactions_root: H256::random(),
+        receipts_root: H256::random(),
+        logs_bloom: Default::default(),
+        difficulty: Default::default(),
+        number: number.into(),
+        gas_limit: 30_000_000,
+        gas_used: 20_000_000,
+        timestamp: 1000,
+        extra_data: vec![0; 32],
+        mix_hash: Default::default(),
+        nonce: Default::default(),
+        base_fee_per_gas: None,
+        withdrawals_hash: None,
+        blob_gas_used: None,
+        excess_blob_gas_used: None,
+        parent_beacon_root: None,
+    }
+}
+
+fn sign(mut header: CodecHeader, signer: &ecdsa::Pair) -> CodecHeader {
+    let msg = Header::from(&header).hash::<Host<Test>>();
+    header.extra_data.extend_from_slice(&signer.sign_prehashed(&msg.0).0);
+    header
+}
+
+/// Builds a chain of headers on top of `parent_hash` signed by the given validators in turn
+fn chain(parent_hash: H256, validators: &[(H160, ecdsa::Pair)]) -> (Vec<CodecHeader>, Vec<H256>) {
+    let mut parent_hash = parent_hash;
+    let mut headers = vec![];
+    let mut hashes = vec![];
+    for (number, (_, signer)) in (201..).zip(validators.iter()) {
+        let header = sign(header(parent_hash, number), signer);
+        parent_hash = Header::from(&header).hash::<Host<Test>>();
+        headers.push(header);
+        hashes.push(parent_hash);
+    }
+    (headers, hashes)
+}
+
+/// Builds a trie with the given entries and returns its root along with a proof of all the keys
+fn build_trie(entries: &[(Vec<u8>, Vec<u8>)]) -> (H256, Vec<Vec<u8>>) {
+    let mut db = MemoryDB::<KeccakHasher>::default();
+    let mut root = Default::default();
+    {
+        let mut trie = TrieDBMutBuilder::<Layout>::new(&mut db, &mut root).build();
+        for (key, value) in entries {
+            trie.insert(key, value).unwrap();
+        }
+    }
+
+    let mut recorder = Recorder::<Layout>::default();
+    {
+        let trie = TrieDBBuilder::<Layout>::new(&db, &root).with_recorder(&mut recorder).build();
+        for (key, _) in entries {
+            trie.get(key).unwrap();
+        }
+    }
+    let proof = recorder.drain().into_iter().map(|record| record.data).collect();
+
+    (H256(root.0), proof)
+}
+
+/// Produces an L1 state root in which the `RootChain` contract holds a checkpoint with the given
+/// id, root and block range, along with the account and storage proofs of the checkpoint.
+fn root_chain_fixture(
+    header_block_id: u64,
+    root: H256,
+    start: u64,
+    end: u64,
+) -> (H256, Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let key = |offset| {
+        derive_map_key_with_offset::<Host<Test>>(
+            H256::from_low_u64_be(header_block_id).0.to_vec(),
+            HEADER_BLOCKS_SLOT,
+            offset,
+        )
+        .0
+        .to_vec()
+    };
+    let storage = vec![
+        (
+            key(HEADER_BLOCK_ROOT_OFFSET),
+            alloy_rlp::encode(alloy_primitives::U256::from_be_bytes(root.0)),
+        ),
+        (key(HEADER_BLOCK_START_OFFSET), alloy_rlp::encode(alloy_primitives::U256::from(start))),
+        (key(HEADER_BLOCK_END_OFFSET), alloy_rlp::encode(alloy_primitives::U256::from(end))),
+    ];
+    let (storage_root, storage_proof) = build_trie(&storage);
+
+    let account = Account {
+        nonce: 1,
+        balance: alloy_primitives::U256::ZERO,
+        storage_root: B256::from(storage_root.0),
+        code_hash: B256::ZERO,
+    };
+    let account_key = Host::<Test>::keccak256(&ROOT_CHAIN.0).0.to_vec();
+    let (state_root, contract_proof) = build_trie(&[(account_key, alloy_rlp::encode(&account))]);
+
+    (state_root, contract_proof, storage_proof)
+}
+
+#[test]
+fn verify_fraud_proof() {
+    new_test_ext().execute_with(|| {
+        let validators = validators();
+        let consensus_state = ConsensusState {
+            finalized_validators: validators.iter().map(|(signer, _)| *signer).collect(),
+            ..Default::default()
+        };
+        let header = header(H256::random(), 200);
+
+        // Fraud Proof Scenario 1: Different blocks same signer
+        let mut header_1 = header.clone();
+        header_1.parent_hash = H256::random();
+        let mut header_2 = header.clone();
+        header_2.parent_hash = H256::random();
+        let header_1 = sign(header_1, &validators[0].1);
+        let header_2 = sign(header_2, &validators[0].1);
+
+        let client = PolygonClient::<Test, Host<Test>>::default();
+        let host = Host::<Test>::default();
+
+        assert!(client
+            .verify_fraud_proof(
+                &host,
+                consensus_state.encode(),
+                header_1.encode(),
+                header_2.encode()
+            )
+            .is_ok());
+
+        // Fraud proof scenario 2: in turn difficulty in two competing headers
+        let mut header_1 = header.clone();
+        header_1.gas_used = 10_000_000;
+        header_1.difficulty = (consensus_state.finalized_validators.len() as u64).into();
+        let mut header_2 = header.clone();
+        header_2.gas_used = 15_000_000;
+        header_2.difficulty = (consensus_state.finalized_validators.len() as u64).into();
+        let header_1 = sign(header_1, &validators[0].1);
+        let header_2 = sign(header_2, &validators[1].1);
+
+        assert!(client
+            .verify_fraud_proof(
+                &host,
+                consensus_state.encode(),
+                header_1.encode(),
+                header_2.encode()
+            )
+            .is_ok());
+    })
+}
+
+#[test]
+fn should_track_forks_and_reject_checkpoints_when_disabled() {
+    new_test_ext().execute_with(|| {
+        let validators = validators();
+        let finalized_hash = H256::random();
+        let consensus_state = ConsensusState {
+            finalized_hash,
+            finalized_validators: validators.iter().map(|(signer, _)| *signer).collect(),
+            ..Default::default()
+        };
+
+        let mut parent_hash = finalized_hash;
+        let mut headers = vec![];
+        for (number, (_, signer)) in (201..204).zip(validators.iter()) {
+            let header = sign(header(parent_hash, number), signer);
+            parent_hash = Header::from(&header).hash::<Host<Test>>();
+            headers.push(header);
+        }
+
+        let client = PolygonClient::<Test, Host<Test>>::default();
+        let host = Host::<Test>::default();
+        let update = PolygonClientUpdate {
+            consensus_update: headers.try_into().unwrap(),
+            chain_head: finalized_hash,
+            checkpoint_proof: None,
+        };
+        let (encoded_state, commitments) = client
+            .verify_consensus(
+                &host,
+                POLYGON_CONSENSUS_ID,
+                consensus_state.encode(),
+                update.encode(),
+            )
+            .unwrap();
+        let consensus_state = ConsensusState::decode(&mut &*encoded_state).unwrap();
+
+        // Not enough headers to finalize anything
+        assert!(commitments.is_empty());
+        assert_eq!(consensus_state.forks.len(), 1);
+        assert_eq!(consensus_state.forks[0].hashes.len(), 3);
+        assert!(consensus_state.forks[0]
+            .hashes
+            .iter()
+            .all(|(_, hash)| Headers::<Test>::get(hash).is_some()));
+
+        let update = PolygonClientUpdate {
+            consensus_update: vec![].try_into().unwrap(),
+            chain_head: parent_hash,
+            checkpoint_proof: Some(CheckpointProof {
+                l1_height: 0,
+                header_block_id: 10_000,
+                contract_proof: vec![],
+                storage_proof: vec![],
+                header_hash: parent_hash,
+                merkle_proof: vec![],
+            }),
+        };
+        assert!(client
+            .verify_consensus(
+                &host,
+                POLYGON_CONSENSUS_ID,
+                consensus_state.encode(),
+                update.encode()
+            )
+            .is_err());
+    })
+}
+
+#[test]
+fn should_verify_checkpoint_merkle_proofs() {
+    let hash = |left: H256, right: H256| Host::<Test>::keccak256(&[left.0, right.0].concat());
+    // three leaves padded to the next power of two
+    let leaves = vec![H256::random(), H256::random(), H256::random(), H256::zero()];
+    let root = hash(hash(leaves[0], leaves[1]), hash(leaves[2], leaves[3]));
+
+    let proof = vec![leaves[3], hash(leaves[0], leaves[1])];
+    assert!(check_membership::<Host<Test>>(leaves[2], 2, root, &proof));
+    assert!(!check_membership::<Host<Test>>(leaves[2], 3, root, &proof));
+    // index doesn't fit in the tree
+    assert!(!check_membership::<Host<Test>>(leaves[2], 6, root, &proof));
+
+    let proof = vec![leaves[0], hash(leaves[2], leaves[3])];
+    assert!(check_membership::<Host<Test>>(leaves[1], 1, root, &proof));
+}
+
+#[test]
+fn should_finalize_checkpointed_headers_and_prune_forks() {
+    new_test_ext().execute_with(|| {
+        let validators = validators();
+        let finalized_hash = H256::random();
+        let l1_state_machine = StateMachineId {
+            state_id: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            consensus_state_id: *b"ETH0",
+        };
+        let mut consensus_state = ConsensusState {
+            finalized_hash,
+            finalized_validators: validators.iter().map(|(signer, _)| *signer).collect(),
+            checkpoint_config: Some(CheckpointConfig { root_chain: ROOT_CHAIN, l1_state_machine }),
+            ..Default::default()
+        };
+
+        let client = PolygonClient::<Test, Host<Test>>::default();
+        let host = Host::<Test>::default();
+
+        // Two competing forks on top of the finalized header
+        let (fork_a, hashes_a) = chain(finalized_hash, &validators[..3]);
+        let (fork_b, hashes_b) = chain(finalized_hash, &validators[3..]);
+        for headers in [fork_a.clone(), fork_b] {
+            let update = PolygonClientUpdate {
+                consensus_update: headers.try_into().unwrap(),
+                chain_head: finalized_hash,
+                checkpoint_proof: None,
+            };
+            let (encoded_state, commitments) = client
+                .verify_consensus(
+                    &host,
+                    POLYGON_CONSENSUS_ID,
+                    consensus_state.encode(),
+                    update.encode(),
+                )
+                .unwrap();
+            // Nothing is finalized without a checkpoint
+            assert!(commitments.is_empty());
+            consensus_state = ConsensusState::decode(&mut &*encoded_state).unwrap();
+        }
+        assert_eq!(consensus_state.forks.len(), 2);
+
+        // Checkpoint the first fork on the L1, the leaves are padded to the next power of two
+        let hash = |left: H256, right: H256| Host::<Test>::keccak256(&[left.0, right.0].concat());
+        let leaves = fork_a
+            .iter()
+            .map(checkpoint_leaf::<Host<Test>>)
+            .chain([H256::zero()])
+            .collect::<Vec<_>>();
+        let root = hash(hash(leaves[0], leaves[1]), hash(leaves[2], leaves[3]));
+        let header_block_id = 10_000;
+        let (state_root, contract_proof, storage_proof) =
+            root_chain_fixture(header_block_id, root, 201, 203);
+
+        let height = StateMachineHeight { id: l1_state_machine, height: 100 };
+        host.store_state_machine_commitment(
+            height,
+            StateCommitment { timestamp: 0, overlay_root: None, state_root },
+        )
+        .unwrap();
+        host.store_state_machine_update_time(height, Duration::from_secs(0)).unwrap();
+        host.store_challenge_period(l1_state_machine.consensus_state_id, 0).unwrap();
+
+        let checkpoint_proof = CheckpointProof {
+            l1_height: height.height,
+            header_block_id,
+            contract_proof,
+            storage_proof,
+            header_hash: hashes_a[1],
+            merkle_proof: vec![leaves[0], hash(leaves[2], leaves[3])],
+        };
+
+        // A proof for the wrong position in the checkpoint is rejected
+        let mut invalid_proof = checkpoint_proof.clone();
+        invalid_proof.merkle_proof = vec![leaves[3], hash(leaves[0], leaves[1])];
+        let update = PolygonClientUpdate {
+            consensus_update: vec![].try_into().unwrap(),
+            chain_head: hashes_a[2],
+            checkpoint_proof: Some(invalid_proof),
+        };
+        assert!(client
+            .verify_consensus(
+                &host,
+                POLYGON_CONSENSUS_ID,
+                consensus_state.encode(),
+                update.encode()
+            )
+            .is_err());
+
+        let update = PolygonClientUpdate {
+            consensus_update: vec![].try_into().unwrap(),
+            chain_head: hashes_a[2],
+            checkpoint_proof: Some(checkpoint_proof),
+        };
+        let (encoded_state, commitments) = client
+            .verify_consensus(
+                &host,
+                POLYGON_CONSENSUS_ID,
+                consensus_state.encode(),
+                update.encode(),
+            )
+            .unwrap();
+        let consensus_state = ConsensusState::decode(&mut &*encoded_state).unwrap();
+
+        let commitment = &commitments.get(&StateMachine::Polygon).unwrap()[0];
+        assert_eq!(commitment.height, 202);
+        assert_eq!(commitment.commitment.state_root, fork_a[1].state_root);
+
+        // Only the headers after the finalized one are tracked, the competing fork is dropped
+        assert_eq!(consensus_state.finalized_hash, hashes_a[1]);
+        assert_eq!(consensus_state.forks.len(), 1);
+        assert_eq!(
+            consensus_state.forks[0]
+                .hashes
+                .iter()
+                .map(|(_, hash)| *hash)
+                .collect::<Vec<_>>(),
+            vec![hashes_a[2]]
+        );
+
+        // Headers below the finalized header and those of the dropped fork are pruned
+        assert!(Headers::<Test>::get(hashes_a[0]).is_none());
+        assert!(Headers::<Test>::get(hashes_a[1]).is_some());
+        assert!(Headers::<Test>::get(hashes_a[2]).is_some());
+        assert!(hashes_b.iter().all(|hash| Headers::<Test>::get(hash).is_none()));
+    })
+}
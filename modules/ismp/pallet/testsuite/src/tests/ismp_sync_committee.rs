@@ -0,0 +1,47 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use codec::{Decode, Encode};
+use ismp_sync_committee::types::BeaconClientUpdate;
+use std::collections::BTreeMap;
+
+#[test]
+fn should_decode_beacon_client_updates_with_and_without_bold_payloads() {
+    let update = BeaconClientUpdate {
+        consensus_update: Default::default(),
+        l2_oracle_payload: Default::default(),
+        dispute_game_payload: Default::default(),
+        arbitrum_payload: Default::default(),
+        arbitrum_bold_payload: Some(BTreeMap::new()),
+    };
+
+    // updates encoded before the BoLD payload was added
+    let legacy = (
+        &update.consensus_update,
+        &update.l2_oracle_payload,
+        &update.dispute_game_payload,
+        &update.arbitrum_payload,
+    )
+        .encode();
+    let decoded = BeaconClientUpdate::decode(&mut &legacy[..]).unwrap();
+    assert!(decoded.arbitrum_bold_payload.is_none());
+
+    let encoded = update.encode();
+    let decoded = BeaconClientUpdate::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(decoded.arbitrum_bold_payload.map(|payload| payload.is_empty()), Some(true));
+    assert_eq!(decoded.consensus_update, update.consensus_update);
+}
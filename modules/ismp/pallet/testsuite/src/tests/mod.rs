@@ -1,4 +1,7 @@
 mod child_trie_proof_check;
+mod ismp_parachain;
+mod ismp_polygon_pos;
+mod ismp_sync_committee;
 mod pallet_asset_gateway;
 mod pallet_call_decompressor;
 mod pallet_fishermen;
@@ -2,18 +2,23 @@
 
 use crate::{
     relay_chain::{self, RuntimeOrigin},
-    runtime::Test,
+    runtime::{RuntimeEvent, Test},
     xcm::{MockNet, ParaA, Relay},
 };
 use alloy_primitives::private::alloy_rlp;
-use frame_support::{assert_ok, traits::fungibles::Inspect};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::fungibles::Inspect, weights::Weight};
+use frame_system::RawOrigin;
 use ismp::{
     host::{Ethereum, StateMachine},
     module::IsmpModule,
     router::{Post, Request, Timeout},
 };
-use pallet_asset_gateway::{Body, Module};
+use pallet_asset_gateway::{
+    rate_limit::RateLimit, AssetRegistration, Body, Module, QueuedTransfers, SubstrateRoute,
+};
 use sp_core::{ByteArray, H160, H256, U256};
+use sp_runtime::{AccountId32, Percent};
 use staging_xcm::v3::{Junction, Junctions, MultiLocation, NetworkId, WeightLimit};
 use xcm_simulator::TestExt;
 use xcm_simulator_example::ALICE;
@@ -292,3 +297,518 @@ fn should_process_on_timeout_module_callback_correctly() {
         assert_eq!(current_balance, alice_balance + transferred);
     })
 }
+
+#[test]
+fn should_resolve_transfers_through_the_asset_registry() {
+    MockNet::reset();
+
+    let beneficiary: MultiLocation = Junctions::X3(
+        Junction::AccountId32 { network: None, id: ALICE.into() },
+        Junction::AccountKey20 {
+            network: Some(NetworkId::Ethereum { chain_id: 1 }),
+            key: [1u8; 20],
+        },
+        Junction::GeneralIndex(60 * 60),
+    )
+    .into_location();
+    let weight_limit = WeightLimit::Unlimited;
+
+    let dest: MultiLocation = Junction::Parachain(PARA_ID).into();
+    let asset_id = MultiLocation::parent();
+    let token_gateway_asset_id = H256::repeat_byte(1);
+    let protocol_fee_percentage = Percent::from_percent(5);
+
+    ParaA::execute_with(|| {
+        // Sibling parachain assets are accepted, local assets are not
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::register_asset(
+                RawOrigin::Root.into(),
+                H256::repeat_byte(2),
+                AssetRegistration { location: MultiLocation::here(), protocol_fee_percentage },
+            ),
+            pallet_asset_gateway::Error::<Test>::UnsupportedAssetLocation
+        );
+
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::register_asset(
+            RawOrigin::Root.into(),
+            token_gateway_asset_id,
+            AssetRegistration { location: asset_id, protocol_fee_percentage },
+        ));
+
+        // A location can only be registered under a single asset id
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::register_asset(
+                RawOrigin::Root.into(),
+                H256::repeat_byte(2),
+                AssetRegistration { location: asset_id, protocol_fee_percentage },
+            ),
+            pallet_asset_gateway::Error::<Test>::LocationAlreadyRegistered
+        );
+    });
+
+    Relay::execute_with(|| {
+        let result = RelayChainPalletXcm::limited_reserve_transfer_assets(
+            RuntimeOrigin::signed(ALICE),
+            Box::new(dest.clone().into()),
+            Box::new(beneficiary.clone().into()),
+            Box::new((Junctions::Here, SEND_AMOUNT).into()),
+            0,
+            weight_limit,
+        );
+        assert_ok!(result);
+    });
+
+    ParaA::execute_with(|| {
+        assert_eq!(pallet_ismp::Nonce::<Test>::get(), 1);
+
+        // The registered protocol fee was applied
+        let custodied_amount = SEND_AMOUNT - (protocol_fee_percentage * SEND_AMOUNT);
+        let pallet_account_balance = <pallet_assets::Pallet<Test> as Inspect<
+            <Test as frame_system::Config>::AccountId,
+        >>::balance(
+            asset_id,
+            &pallet_asset_gateway::Pallet::<Test>::account_id(),
+        );
+        assert_eq!(custodied_amount, pallet_account_balance);
+
+        // The request was dispatched with the registered asset id
+        let teleported = frame_system::Pallet::<Test>::events().into_iter().any(|record| {
+            matches!(
+                record.event,
+                RuntimeEvent::Gateway(pallet_asset_gateway::Event::AssetTeleported {
+                    asset_id: id,
+                    ..
+                }) if id == token_gateway_asset_id
+            )
+        });
+        assert!(teleported);
+
+        let post = |asset_id: H256| {
+            let body = Body {
+                amount: {
+                    let mut bytes = [0u8; 32];
+                    U256::from(100u128).to_big_endian(&mut bytes);
+                    alloy_primitives::U256::from_be_bytes(bytes)
+                },
+                asset_id: asset_id.0.into(),
+                redeem: false,
+                from: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+                to: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+            };
+            Post {
+                source: StateMachine::Bsc,
+                dest: StateMachine::Kusama(100),
+                nonce: 0,
+                from: H160::zero().0.to_vec(),
+                to: H160::zero().0.to_vec(),
+                timeout_timestamp: 0,
+                data: {
+                    let mut encoded = alloy_rlp::encode(body);
+                    // Prefix with zero
+                    encoded.insert(0, 0);
+                    encoded
+                },
+            }
+        };
+
+        let ismp_module = Module::<Test>::default();
+        assert_ok!(ismp_module.on_accept(post(token_gateway_asset_id)));
+        // Unregistered asset ids are rejected
+        assert!(ismp_module.on_accept(post(H256::repeat_byte(3))).is_err());
+
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::deregister_asset(
+            RawOrigin::Root.into(),
+            token_gateway_asset_id,
+        ));
+        assert!(ismp_module.on_accept(post(token_gateway_asset_id)).is_err());
+        assert_eq!(
+            pallet_asset_gateway::Pallet::<Test>::token_gateway_asset_id(&asset_id),
+            Some(pallet_asset_gateway::Pallet::<Test>::dot_asset_id())
+        );
+    });
+}
+
+#[test]
+fn should_route_transfers_to_and_from_substrate_chains() {
+    MockNet::reset();
+
+    let counterpart = StateMachine::Polkadot(2000);
+    let module_id = b"gateway0".to_vec();
+    let route = SubstrateRoute { module_id: module_id.clone(), max_transfer_amount: 500 };
+
+    let beneficiary: MultiLocation = Junctions::X4(
+        Junction::AccountId32 { network: None, id: ALICE.into() },
+        Junction::GeneralKey {
+            length: counterpart.encode().len() as u8,
+            data: {
+                let mut data = [0u8; 32];
+                data[..counterpart.encode().len()].copy_from_slice(&counterpart.encode());
+                data
+            },
+        },
+        Junction::AccountId32 { network: None, id: [1u8; 32] },
+        Junction::GeneralIndex(60 * 60),
+    )
+    .into_location();
+    let dest: MultiLocation = Junction::Parachain(PARA_ID).into();
+    let send = |amount: u128| {
+        Relay::execute_with(|| {
+            assert_ok!(RelayChainPalletXcm::limited_reserve_transfer_assets(
+                RuntimeOrigin::signed(ALICE),
+                Box::new(dest.clone().into()),
+                Box::new(beneficiary.clone().into()),
+                Box::new((Junctions::Here, amount).into()),
+                0,
+                WeightLimit::Unlimited,
+            ));
+        })
+    };
+
+    // Transfers to substrate chains without a route are not dispatched
+    send(SEND_AMOUNT);
+    ParaA::execute_with(|| {
+        assert_eq!(pallet_ismp::Nonce::<Test>::get(), 0);
+
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::set_substrate_route(
+                RawOrigin::Root.into(),
+                StateMachine::Bsc,
+                Some(route.clone()),
+            ),
+            pallet_asset_gateway::Error::<Test>::UnsupportedStateMachine
+        );
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::set_substrate_route(
+                RawOrigin::Root.into(),
+                counterpart,
+                Some(SubstrateRoute { module_id: vec![0u8; 3], max_transfer_amount: 500 }),
+            ),
+            pallet_asset_gateway::Error::<Test>::InvalidModuleId
+        );
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_substrate_route(
+            RawOrigin::Root.into(),
+            counterpart,
+            Some(route.clone()),
+        ));
+    });
+
+    // Transfers above the route limit are not dispatched
+    send(SEND_AMOUNT);
+    ParaA::execute_with(|| assert_eq!(pallet_ismp::Nonce::<Test>::get(), 0));
+
+    send(400);
+    ParaA::execute_with(|| {
+        assert_eq!(pallet_ismp::Nonce::<Test>::get(), 1);
+
+        let teleported = frame_system::Pallet::<Test>::events().into_iter().any(|record| {
+            matches!(
+                record.event,
+                RuntimeEvent::Gateway(pallet_asset_gateway::Event::AssetTeleported {
+                    to,
+                    dest,
+                    ..
+                }) if to == H256([1u8; 32]) && dest == counterpart
+            )
+        });
+        assert!(teleported);
+
+        let post = |from: Vec<u8>, amount: u128| {
+            let body = Body {
+                amount: {
+                    let mut bytes = [0u8; 32];
+                    U256::from(amount).to_big_endian(&mut bytes);
+                    alloy_primitives::U256::from_be_bytes(bytes)
+                },
+                asset_id: H256::zero().0.into(),
+                redeem: false,
+                from: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+                to: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+            };
+            Post {
+                source: counterpart,
+                dest: StateMachine::Kusama(100),
+                nonce: 0,
+                from,
+                to: H160::zero().0.to_vec(),
+                timeout_timestamp: 0,
+                data: {
+                    let mut encoded = alloy_rlp::encode(body);
+                    // Prefix with zero
+                    encoded.insert(0, 0);
+                    encoded
+                },
+            }
+        };
+
+        let ismp_module = Module::<Test>::default();
+        // Only the configured counterpart module is accepted
+        assert!(ismp_module.on_accept(post(H160::zero().0.to_vec(), 100)).is_err());
+        // Transfers above the route limit are rejected
+        assert!(ismp_module.on_accept(post(module_id.clone(), 501)).is_err());
+        assert_ok!(ismp_module.on_accept(post(module_id.clone(), 100)));
+
+        // Removing the route rejects further transfers from the counterpart chain
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_substrate_route(
+            RawOrigin::Root.into(),
+            counterpart,
+            None,
+        ));
+        assert!(ismp_module.on_accept(post(module_id.clone(), 100)).is_err());
+    });
+}
+
+#[test]
+fn should_queue_transfers_that_exceed_rate_limits() {
+    MockNet::reset();
+
+    let beneficiary: MultiLocation = Junctions::X3(
+        Junction::AccountId32 { network: None, id: ALICE.into() },
+        Junction::AccountKey20 {
+            network: Some(NetworkId::Ethereum { chain_id: 1 }),
+            key: [1u8; 20],
+        },
+        Junction::GeneralIndex(60 * 60),
+    )
+    .into_location();
+    let dest: MultiLocation = Junction::Parachain(PARA_ID).into();
+    let ethereum = StateMachine::Ethereum(Ethereum::ExecutionLayer);
+    let dot_asset_id = H256::zero();
+
+    ParaA::execute_with(|| {
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_transfer_delay(
+            RawOrigin::Root.into(),
+            5,
+        ));
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_rate_limit(
+            RawOrigin::Root.into(),
+            dot_asset_id,
+            ethereum,
+            Some(RateLimit { inflow: 0, outflow: 100, window: 10 }),
+        ));
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_rate_limit(
+            RawOrigin::Root.into(),
+            dot_asset_id,
+            StateMachine::Bsc,
+            Some(RateLimit { inflow: 50, outflow: 0, window: 10 }),
+        ));
+    });
+
+    let alice_balance = Relay::execute_with(|| {
+        assert_ok!(RelayChainPalletXcm::limited_reserve_transfer_assets(
+            RuntimeOrigin::signed(ALICE),
+            Box::new(dest.clone().into()),
+            Box::new(beneficiary.clone().into()),
+            Box::new((Junctions::Here, SEND_AMOUNT).into()),
+            0,
+            WeightLimit::Unlimited,
+        ));
+        pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE)
+    });
+
+    // The outgoing transfer exceeded the outflow limit and was queued instead of dispatched
+    let custodied_amount = ParaA::execute_with(|| {
+        assert_eq!(pallet_ismp::Nonce::<Test>::get(), 0);
+        let transfer = QueuedTransfers::<Test>::get(0).unwrap();
+        assert_eq!(transfer.release_at, 6);
+
+        // Rejected outgoing transfers are refunded on the relay chain
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::resolve_queued_transfer(
+            RawOrigin::Root.into(),
+            0,
+            false,
+        ));
+        assert!(QueuedTransfers::<Test>::get(0).is_none());
+        transfer.amount
+    });
+
+    Relay::execute_with(|| {
+        let current_balance = pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE);
+        assert_eq!(current_balance, alice_balance + custodied_amount);
+    });
+
+    // Mint some more funds into custody, within the outflow limit
+    Relay::execute_with(|| {
+        assert_ok!(RelayChainPalletXcm::limited_reserve_transfer_assets(
+            RuntimeOrigin::signed(ALICE),
+            Box::new(dest.clone().into()),
+            Box::new(beneficiary.clone().into()),
+            Box::new((Junctions::Here, 100).into()),
+            0,
+            WeightLimit::Unlimited,
+        ));
+    });
+
+    let alice_balance = Relay::execute_with(|| {
+        pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE)
+    });
+
+    let released = ParaA::execute_with(|| {
+        assert_eq!(pallet_ismp::Nonce::<Test>::get(), 1);
+
+        let body = Body {
+            amount: {
+                let mut bytes = [0u8; 32];
+                U256::from(60u128).to_big_endian(&mut bytes);
+                alloy_primitives::U256::from_be_bytes(bytes)
+            },
+            asset_id: dot_asset_id.0.into(),
+            redeem: false,
+            from: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+            to: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+        };
+        let post = Post {
+            source: StateMachine::Bsc,
+            dest: StateMachine::Kusama(100),
+            nonce: 0,
+            from: H160::zero().0.to_vec(),
+            to: H160::zero().0.to_vec(),
+            timeout_timestamp: 0,
+            data: {
+                let mut encoded = alloy_rlp::encode(body);
+                // Prefix with zero
+                encoded.insert(0, 0);
+                encoded
+            },
+        };
+
+        // The incoming transfer exceeded the inflow limit and was queued
+        assert_ok!(Module::<Test>::default().on_accept(post));
+        let transfer = QueuedTransfers::<Test>::get(1).unwrap();
+
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::release_queued_transfer(
+                RawOrigin::Signed(ALICE).into(),
+                1,
+            ),
+            pallet_asset_gateway::Error::<Test>::TransferNotReleasable
+        );
+
+        frame_system::Pallet::<Test>::set_block_number(transfer.release_at);
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::release_queued_transfer(
+            RawOrigin::Signed(ALICE).into(),
+            1,
+        ));
+        assert!(QueuedTransfers::<Test>::get(1).is_none());
+        transfer.amount
+    });
+
+    Relay::execute_with(|| {
+        let current_balance = pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE);
+        assert_eq!(current_balance, alice_balance + released);
+    });
+}
+
+#[test]
+fn should_hold_incoming_transfers_in_custody_until_claimed() {
+    MockNet::reset();
+
+    let beneficiary: MultiLocation = Junctions::X3(
+        Junction::AccountId32 { network: None, id: ALICE.into() },
+        Junction::AccountKey20 {
+            network: Some(NetworkId::Ethereum { chain_id: 1 }),
+            key: [1u8; 20],
+        },
+        Junction::GeneralIndex(60 * 60),
+    )
+    .into_location();
+    let dest: MultiLocation = Junction::Parachain(PARA_ID).into();
+    let dot_asset_id = H256::zero();
+
+    let alice_balance = Relay::execute_with(|| {
+        assert_ok!(RelayChainPalletXcm::limited_reserve_transfer_assets(
+            RuntimeOrigin::signed(ALICE),
+            Box::new(dest.clone().into()),
+            Box::new(beneficiary.clone().into()),
+            Box::new((Junctions::Here, SEND_AMOUNT).into()),
+            0,
+            WeightLimit::Unlimited,
+        ));
+        pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE)
+    });
+
+    let claimed = ParaA::execute_with(|| {
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::set_custody_mode(
+            RawOrigin::Root.into(),
+            true,
+        ));
+
+        let body = Body {
+            amount: {
+                let mut bytes = [0u8; 32];
+                U256::from(500u128).to_big_endian(&mut bytes);
+                alloy_primitives::U256::from_be_bytes(bytes)
+            },
+            asset_id: dot_asset_id.0.into(),
+            redeem: false,
+            from: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+            to: alloy_primitives::B256::from_slice(ALICE.as_slice()),
+        };
+        let post = Post {
+            source: StateMachine::Bsc,
+            dest: StateMachine::Kusama(100),
+            nonce: 0,
+            from: H160::zero().0.to_vec(),
+            to: H160::zero().0.to_vec(),
+            timeout_timestamp: 0,
+            data: {
+                let mut encoded = alloy_rlp::encode(body);
+                // Prefix with zero
+                encoded.insert(0, 0);
+                encoded
+            },
+        };
+        assert_ok!(Module::<Test>::default().on_accept(post));
+
+        let claims = pallet_asset_gateway::Pallet::<Test>::pending_claims(ALICE);
+        let amount = 500 - (pallet_asset_gateway::Pallet::<Test>::protocol_fee_percentage() * 500);
+        assert_eq!(claims, vec![(dot_asset_id, amount)]);
+
+        // Only the beneficiary can choose the fee asset and weight limit
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::claim_assets(
+                RawOrigin::Signed(AccountId32::new([2u8; 32])).into(),
+                ALICE,
+                dot_asset_id,
+                None,
+                WeightLimit::Limited(Weight::from_parts(1, 0)),
+            ),
+            pallet_asset_gateway::Error::<Test>::NotBeneficiary
+        );
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::claim_assets(
+                RawOrigin::Signed(AccountId32::new([2u8; 32])).into(),
+                ALICE,
+                dot_asset_id,
+                Some(dot_asset_id),
+                WeightLimit::Unlimited,
+            ),
+            pallet_asset_gateway::Error::<Test>::NotBeneficiary
+        );
+
+        // Anyone can deliver the claim to the beneficiary
+        assert_ok!(pallet_asset_gateway::Pallet::<Test>::claim_assets(
+            RawOrigin::Signed(AccountId32::new([2u8; 32])).into(),
+            ALICE,
+            dot_asset_id,
+            None,
+            WeightLimit::Unlimited,
+        ));
+        assert!(pallet_asset_gateway::Pallet::<Test>::pending_claims(ALICE).is_empty());
+        assert_noop!(
+            pallet_asset_gateway::Pallet::<Test>::claim_assets(
+                RawOrigin::Signed(AccountId32::new([2u8; 32])).into(),
+                ALICE,
+                dot_asset_id,
+                None,
+                WeightLimit::Unlimited,
+            ),
+            pallet_asset_gateway::Error::<Test>::NoPendingClaim
+        );
+        amount
+    });
+
+    Relay::execute_with(|| {
+        let current_balance = pallet_balances::Pallet::<relay_chain::Runtime>::free_balance(&ALICE);
+        assert_eq!(current_balance, alice_balance + claimed);
+    });
+}
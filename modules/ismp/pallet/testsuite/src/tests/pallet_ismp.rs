@@ -16,13 +16,26 @@
 #![cfg(test)]
 
 use crate::runtime::*;
-use frame_support::pallet_prelude::Hooks;
+use frame_support::{
+    pallet_prelude::{Hooks, Weight},
+    storage::{with_transaction, TransactionOutcome},
+    traits::{
+        fungible::{Inspect, Mutate},
+        Get, OnRuntimeUpgrade, StorageVersion,
+    },
+};
 use pallet_ismp::{
-    child_trie::{RequestCommitments, RequestReceipts},
-    dispatcher::{Dispatcher, FeeMetadata},
+    child_trie::{RequestCommitments, RequestReceipts, ResponseCommitments, CHILD_TRIE_PREFIX},
+    circuit_breaker::PauseScope,
+    dispatcher::{Dispatcher, FeeDispatcher, FeeMetadata, LeafMetadata},
+    errors::HandlingError,
     host::Host,
+    migrations::v1::MigrateToPeaksOnly,
     mmr::primitives::{DataOrHash, MmrHasher},
-    IntermediateLeaves, NodesUtils, ProofKeys,
+    primitives::{DryRunOutcome, HashAlgorithm, LeafIndexAndPos, SubstrateStateProof},
+    pruning::PrunableEntry,
+    EscrowedFees, IntermediateLeaves, MmrPositions, Nodes, NodesMigrationCursor, NodesUtils,
+    PendingPrunes, ProofKeys, PruningCursor, Responded, RetryQueue,
 };
 
 use std::{
@@ -31,22 +44,27 @@ use std::{
 };
 
 use ismp::{
-    consensus::{StateMachineHeight, StateMachineId},
+    consensus::{StateCommitment, StateMachineClient, StateMachineHeight, StateMachineId},
     host::{Ethereum, IsmpHost, StateMachine},
-    messaging::{Proof, ResponseMessage, TimeoutMessage},
+    messaging::{Proof, RequestMessage, ResponseMessage, TimeoutMessage},
     router::{
-        DispatchGet, DispatchRequest, GetResponse, IsmpDispatcher, Post, Request, RequestResponse,
+        DispatchGet, DispatchPost, DispatchRequest, GetResponse, IsmpDispatcher, Post, Request,
+        RequestResponse,
     },
     util::hash_request,
 };
 
+use codec::Encode;
 use ismp::{messaging::Message, router::Response};
 use ismp_testsuite::{
     check_challenge_period, check_client_expiry, missing_state_commitment_check,
     post_request_timeout_check, post_response_timeout_check, write_outgoing_commitments,
 };
-use merkle_mountain_range::MerkleProof;
-use sp_core::{crypto::AccountId32, H256};
+use merkle_mountain_range::{helper, Merge, MerkleProof};
+use sp_core::{crypto::AccountId32, storage::ChildInfo, H256};
+use sp_runtime::{DispatchError, StateVersion};
+use sp_state_machine::{prove_child_read, Backend};
+use substrate_state_machine::SubstrateStateMachine;
 
 fn on_initialize() {
     let number = frame_system::Pallet::<Test>::block_number() + 1;
@@ -180,6 +198,10 @@ fn should_generate_and_verify_batch_proof_for_leaves_inserted_across_multiple_bl
         let (commitments_second, positions_second) = push_leaves(100..200);
         Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
         let root = pallet_ismp::Pallet::<Test>::mmr_root();
+        // only the peaks are kept in runtime storage
+        let peaks = helper::get_peaks(NodesUtils::new(200).size());
+        assert_eq!(Nodes::<Test>::iter_keys().count(), peaks.len());
+        assert!(peaks.into_iter().all(|pos| Nodes::<Test>::contains_key(pos)));
         positions.extend_from_slice(&positions_second);
         commitments.extend_from_slice(&commitments_second);
         (root, (commitments, positions))
@@ -215,6 +237,155 @@ fn should_generate_and_verify_batch_proof_for_leaves_inserted_across_multiple_bl
     })
 }
 
+#[test]
+fn should_migrate_mmr_nodes_from_the_legacy_storage_layout() {
+    let _ = env_logger::try_init();
+    let mut ext = new_test_ext();
+    let (root, (commitments, positions)) = ext.execute_with(|| {
+        on_initialize();
+        let (mut commitments, mut positions) = push_leaves(0..100);
+        Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
+
+        // rewrite the mmr into the layout used before the migration, where every node is kept in
+        // runtime storage and inner nodes are not in the offchain db
+        let mut nodes = vec![];
+        for pos in 0..NodesUtils::new(100).size() {
+            let height = helper::pos_height_in_tree(pos);
+            let node = if height == 0 {
+                MmrPositions::<Test>::get(pos).expect("Leaf positions are stored")
+            } else {
+                let left = DataOrHash::Hash(nodes[(pos - (1 << height)) as usize]);
+                let right = DataOrHash::Hash(nodes[(pos - 1) as usize]);
+                let node =
+                    MmrHasher::<Host<Test>>::merge(&left, &right).unwrap().hash::<Host<Test>>();
+                sp_io::offchain_index::clear(&Ismp::node_offchain_key(node));
+                node
+            };
+            Nodes::<Test>::insert(pos, node);
+            nodes.push(node);
+        }
+        StorageVersion::new(0).put::<Ismp>();
+        MigrateToPeaksOnly::<Test>::on_runtime_upgrade();
+        assert_eq!(NodesMigrationCursor::<Test>::get(), Some(0));
+
+        // leaves appended before the migration completes merge the legacy peaks
+        on_initialize();
+        let (commitments_second, positions_second) = push_leaves(100..200);
+        Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
+        let root = pallet_ismp::Pallet::<Test>::mmr_root();
+
+        let block = frame_system::Pallet::<Test>::block_number();
+        while NodesMigrationCursor::<Test>::get().is_some() {
+            Ismp::on_idle(block, Weight::MAX);
+        }
+        let peaks = helper::get_peaks(NodesUtils::new(200).size());
+        assert_eq!(Nodes::<Test>::iter_keys().count(), peaks.len());
+        positions.extend_from_slice(&positions_second);
+        commitments.extend_from_slice(&commitments_second);
+        (root, (commitments, positions))
+    });
+    ext.persist_offchain_overlay();
+
+    ext.execute_with(move || {
+        // proofs for leaves inserted before the migration need the legacy inner nodes
+        let indices = vec![positions[0], positions[37], positions[99], positions[150]];
+        let proof_key = ProofKeys::Requests(vec![
+            commitments[0],
+            commitments[37],
+            commitments[99],
+            commitments[150],
+        ]);
+        let (leaves, proof) = pallet_ismp::Pallet::<Test>::generate_proof(proof_key).unwrap();
+
+        let mmr_size = NodesUtils::new(proof.leaf_count).size();
+        let nodes = proof.items.into_iter().map(|h| DataOrHash::Hash(h.into())).collect();
+        let proof = MerkleProof::<DataOrHash, MmrHasher<Host<Test>>>::new(mmr_size, nodes);
+        let calculated_root = proof
+            .calculate_root(
+                indices
+                    .into_iter()
+                    .zip(leaves.into_iter().map(|leaf| DataOrHash::Data(leaf)))
+                    .collect(),
+            )
+            .unwrap();
+
+        assert_eq!(root, calculated_root.hash::<Host<Test>>());
+    })
+}
+
+#[test]
+fn should_not_overwrite_offchain_mmr_nodes_indexed_by_sibling_blocks() {
+    let _ = env_logger::try_init();
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        on_initialize();
+        push_leaves(0..3);
+        Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
+    });
+    ext.persist_offchain_overlay();
+    ext.commit_all().unwrap();
+    let parent = ext.backend.clone();
+
+    // import the canonical block
+    let (root, (commitments, positions)) = ext.execute_with(|| {
+        on_initialize();
+        let leaves = push_leaves(3..10);
+        Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
+        (pallet_ismp::Pallet::<Test>::mmr_root(), leaves)
+    });
+    ext.persist_offchain_overlay();
+    ext.commit_all().unwrap();
+    let canonical = ext.backend.clone();
+
+    // import a sibling block that appends different nodes at the same positions
+    ext.backend = parent;
+    ext.execute_with(|| {
+        on_initialize();
+        for nonce in 3..10 {
+            let post = Post {
+                source: StateMachine::Kusama(2000),
+                dest: StateMachine::Kusama(2002),
+                nonce,
+                from: vec![0u8; 32],
+                to: vec![18; 32],
+                timeout_timestamp: 100 * nonce,
+                data: vec![3u8; 64],
+            };
+            pallet_ismp::Pallet::<Test>::dispatch_request(
+                Request::Post(post),
+                FeeMetadata { origin: AccountId32::new([0u8; 32]), fee: 10u128 },
+            )
+            .unwrap();
+        }
+        Ismp::on_finalize(frame_system::Pallet::<Test>::block_number() + 1);
+        assert_ne!(pallet_ismp::Pallet::<Test>::mmr_root(), root);
+    });
+    ext.persist_offchain_overlay();
+    ext.commit_all().unwrap();
+
+    // proofs on the canonical chain are unaffected by the sibling block
+    ext.backend = canonical;
+    ext.execute_with(move || {
+        let proof_key = ProofKeys::Requests(vec![commitments[0], commitments[6]]);
+        let indices = vec![positions[0], positions[6]];
+        let (leaves, proof) = pallet_ismp::Pallet::<Test>::generate_proof(proof_key).unwrap();
+
+        let mmr_size = NodesUtils::new(proof.leaf_count).size();
+        let nodes = proof.items.into_iter().map(|h| DataOrHash::Hash(h.into())).collect();
+        let proof = MerkleProof::<DataOrHash, MmrHasher<Host<Test>>>::new(mmr_size, nodes);
+        let calculated_root = proof
+            .calculate_root(
+                indices
+                    .into_iter()
+                    .zip(leaves.into_iter().map(|leaf| DataOrHash::Data(leaf)))
+                    .collect(),
+            )
+            .unwrap();
+
+        assert_eq!(root, calculated_root.hash::<Host<Test>>());
+    })
+}
+
 fn set_timestamp(now: Option<u64>) {
     Timestamp::set_timestamp(
         now.unwrap_or(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64),
@@ -355,6 +526,553 @@ fn should_handle_get_request_timeouts_correctly() {
     })
 }
 
+#[test]
+fn should_dry_run_messages_without_persisting_changes() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+
+        let msg = DispatchGet {
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: 1000,
+        };
+        Dispatcher::<Test>::default()
+            .dispatch_request(DispatchRequest::Get(msg), [0u8; 32].into(), 0u32.into())
+            .unwrap();
+        let request = Request::Get(ismp::router::Get {
+            source: host.host_state_machine(),
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            nonce: 0,
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: Duration::from_millis(Timestamp::now()).as_secs() + 1000,
+        });
+        let commitment = hash_request::<Host<Test>>(&request);
+
+        set_timestamp(Some(Duration::from_secs(100_000_000).as_millis() as u64));
+        let timeout_msg = Message::Timeout(TimeoutMessage::Get { requests: vec![request] });
+        let results =
+            pallet_ismp::Pallet::<Test>::dry_run_messages(vec![timeout_msg.clone(), timeout_msg]);
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            &results[0].outcome,
+            DryRunOutcome::Timeout(res) if res.len() == 1 && res[0].is_ok()
+        ));
+        // the second message observes the commitment deleted by the first one
+        assert!(matches!(
+            results[1].outcome,
+            DryRunOutcome::Error(HandlingError::UnknownRequest { .. })
+        ));
+        assert!(results.iter().all(|result| result.weight.ref_time() > 0));
+        // none of the changes were persisted
+        assert!(host.request_commitment(commitment).is_ok());
+    })
+}
+
+#[test]
+fn should_escrow_and_refund_fees_for_timed_out_requests() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+
+        let origin = AccountId32::new([1u8; 32]);
+        let escrow = pallet_ismp::Pallet::<Test>::fee_escrow_account();
+        Balances::mint_into(&origin, UNIT).unwrap();
+        Balances::mint_into(&escrow, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let dispatcher = FeeDispatcher::<Test>::default();
+        let msg = DispatchGet {
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: 1000,
+        };
+        // 64 bytes of keys at a per byte fee of 10
+        assert!(dispatcher
+            .dispatch_request(DispatchRequest::Get(msg.clone()), origin.clone(), 639)
+            .is_err());
+        // the fee is not charged for requests that fail to dispatch
+        Ismp::pause(RuntimeOrigin::root(), PauseScope::Outgoing).unwrap();
+        assert!(dispatcher
+            .dispatch_request(DispatchRequest::Get(msg.clone()), origin.clone(), 640)
+            .is_err());
+        assert_eq!(Balances::balance(&origin), UNIT);
+        Ismp::unpause(RuntimeOrigin::root(), PauseScope::Outgoing).unwrap();
+        dispatcher
+            .dispatch_request(DispatchRequest::Get(msg), origin.clone(), 640)
+            .unwrap();
+
+        let request = Request::Get(ismp::router::Get {
+            source: host.host_state_machine(),
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            nonce: 0,
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: Duration::from_millis(Timestamp::now()).as_secs() + 1000,
+        });
+        let commitment = hash_request::<Host<Test>>(&request);
+        assert_eq!(EscrowedFees::<Test>::get(commitment), Some(640));
+        assert_eq!(Balances::balance(&origin), UNIT - 640);
+        assert_eq!(Balances::balance(&escrow), EXISTENTIAL_DEPOSIT + 640);
+
+        set_timestamp(Some(Duration::from_secs(100_000_000).as_millis() as u64));
+        let timeout_msg = TimeoutMessage::Get { requests: vec![request] };
+        pallet_ismp::Pallet::<Test>::handle_messages(vec![Message::Timeout(timeout_msg)]).unwrap();
+
+        assert!(host.request_commitment(commitment).is_err());
+        assert_eq!(EscrowedFees::<Test>::get(commitment), None);
+        assert_eq!(Balances::balance(&origin), UNIT);
+        assert_eq!(Balances::balance(&escrow), EXISTENTIAL_DEPOSIT);
+    })
+}
+
+#[test]
+fn should_release_escrowed_fees_into_the_fee_pot_once_delivered() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+
+        let origin = AccountId32::new([1u8; 32]);
+        let escrow = pallet_ismp::Pallet::<Test>::fee_escrow_account();
+        let pot = pallet_ismp::Pallet::<Test>::fee_pot_account();
+        Balances::mint_into(&origin, UNIT).unwrap();
+        Balances::mint_into(&escrow, EXISTENTIAL_DEPOSIT).unwrap();
+        Balances::mint_into(&pot, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let msg = DispatchGet {
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: 1000,
+        };
+        FeeDispatcher::<Test>::default()
+            .dispatch_request(DispatchRequest::Get(msg), origin.clone(), 640)
+            .unwrap();
+        let request = Request::Get(ismp::router::Get {
+            source: host.host_state_machine(),
+            dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+            nonce: 0,
+            from: vec![0u8; 32],
+            keys: vec![vec![1u8; 32], vec![1u8; 32]],
+            height: 2,
+            timeout_timestamp: Duration::from_millis(Timestamp::now()).as_secs() + 1000,
+        });
+        let commitment = hash_request::<Host<Test>>(&request);
+
+        // delivery of the request has been proven
+        pallet_ismp::Pallet::<Test>::release_fee(commitment).unwrap();
+        assert_eq!(EscrowedFees::<Test>::get(commitment), None);
+        assert_eq!(Balances::balance(&escrow), EXISTENTIAL_DEPOSIT);
+        assert_eq!(Balances::balance(&pot), EXISTENTIAL_DEPOSIT + 640);
+
+        // released fees can no longer be refunded
+        set_timestamp(Some(Duration::from_secs(100_000_000).as_millis() as u64));
+        let timeout_msg = TimeoutMessage::Get { requests: vec![request] };
+        pallet_ismp::Pallet::<Test>::handle_messages(vec![Message::Timeout(timeout_msg)]).unwrap();
+        assert_eq!(Balances::balance(&origin), UNIT - 640);
+        assert_eq!(Balances::balance(&pot), EXISTENTIAL_DEPOSIT + 640);
+    })
+}
+
+#[test]
+fn should_add_to_the_fee_of_pending_requests() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        on_initialize();
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+
+        let origin = AccountId32::new([1u8; 32]);
+        let escrow = pallet_ismp::Pallet::<Test>::fee_escrow_account();
+        Balances::mint_into(&origin, UNIT).unwrap();
+        Balances::mint_into(&escrow, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let pot = pallet_ismp::Pallet::<Test>::fee_pot_account();
+        Balances::mint_into(&pot, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let dispatch = || {
+            let msg = DispatchGet {
+                dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+                from: vec![0u8; 32],
+                keys: vec![vec![1u8; 32], vec![1u8; 32]],
+                height: 2,
+                timeout_timestamp: 1000,
+            };
+            FeeDispatcher::<Test>::default()
+                .dispatch_request(DispatchRequest::Get(msg), origin.clone(), 640)
+                .unwrap();
+        };
+        let request = |nonce: u64| {
+            Request::Get(ismp::router::Get {
+                source: host.host_state_machine(),
+                dest: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+                nonce,
+                from: vec![0u8; 32],
+                keys: vec![vec![1u8; 32], vec![1u8; 32]],
+                height: 2,
+                timeout_timestamp: Duration::from_millis(Timestamp::now()).as_secs() + 1000,
+            })
+        };
+
+        assert_eq!(
+            Ismp::fund_request(RuntimeOrigin::signed(origin.clone()), request(0), 100),
+            Err(pallet_ismp::Error::<Test>::UnknownRequest.into())
+        );
+        dispatch();
+        let commitment = hash_request::<Host<Test>>(&request(0));
+
+        // only the account that dispatched the request can fund it
+        assert_eq!(
+            Ismp::fund_request(RuntimeOrigin::signed(AccountId32::new([2u8; 32])), request(0), 100),
+            Err(pallet_ismp::Error::<Test>::NotRequestOrigin.into())
+        );
+        assert_eq!(
+            Ismp::fund_request(RuntimeOrigin::signed(origin.clone()), request(0), 0),
+            Err(pallet_ismp::Error::<Test>::ZeroFee.into())
+        );
+
+        Ismp::fund_request(RuntimeOrigin::signed(origin.clone()), request(0), 360).unwrap();
+        let RuntimeEvent::Ismp(pallet_ismp::Event::<Test>::RequestFunded { fee, .. }) =
+            last_event::<Test>()
+        else {
+            panic!("RequestFunded event not found")
+        };
+        assert_eq!(fee, 1000);
+        // the new fee is committed to the child trie where it can be proven by relayers
+        assert_eq!(RequestCommitments::<Test>::get(commitment).unwrap().meta.fee, 1000);
+        assert_eq!(EscrowedFees::<Test>::get(commitment), Some(1000));
+        assert_eq!(Balances::balance(&origin), UNIT - 1000);
+
+        // requests can't be funded once their fee has been claimed
+        dispatch();
+        pallet_ismp::Pallet::<Test>::release_fee(hash_request::<Host<Test>>(&request(1))).unwrap();
+        assert_eq!(
+            Ismp::fund_request(RuntimeOrigin::signed(origin.clone()), request(1), 100),
+            Err(pallet_ismp::Error::<Test>::FeeAlreadyClaimed.into())
+        );
+        assert_eq!(Balances::balance(&pot), EXISTENTIAL_DEPOSIT + 640);
+
+        // or after they have timed out, the full fee is refunded instead
+        let request = request(0);
+        set_timestamp(Some(Duration::from_secs(100_000_000).as_millis() as u64));
+        assert_eq!(
+            Ismp::fund_request(RuntimeOrigin::signed(origin.clone()), request.clone(), 100),
+            Err(pallet_ismp::Error::<Test>::RequestTimedOut.into())
+        );
+        let timeout_msg = TimeoutMessage::Get { requests: vec![request] };
+        pallet_ismp::Pallet::<Test>::handle_messages(vec![Message::Timeout(timeout_msg)]).unwrap();
+        assert_eq!(Balances::balance(&origin), UNIT - 640);
+        assert_eq!(Balances::balance(&escrow), EXISTENTIAL_DEPOSIT);
+    })
+}
+
+#[test]
+fn should_reject_paused_traffic() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+        let counterparty = StateMachine::Ethereum(Ethereum::ExecutionLayer);
+
+        // outgoing requests are rejected until traffic is resumed
+        let dispatch = || {
+            Dispatcher::<Test>::default().dispatch_request(
+                DispatchRequest::Post(DispatchPost {
+                    dest: counterparty,
+                    from: vec![0u8; 32],
+                    to: vec![0u8; 32],
+                    timeout_timestamp: 0,
+                    data: vec![0u8; 64],
+                }),
+                [0u8; 32].into(),
+                0u32.into(),
+            )
+        };
+        Ismp::pause(RuntimeOrigin::root(), PauseScope::Outgoing).unwrap();
+        assert!(matches!(dispatch(), Err(ismp::error::Error::Paused { .. })));
+        Ismp::unpause(RuntimeOrigin::root(), PauseScope::Outgoing).unwrap();
+        dispatch().unwrap();
+
+        // incoming requests for a paused module are rejected without storing a receipt, so they
+        // can be delivered again once the module is unpaused
+        let post = Post {
+            source: counterparty,
+            dest: host.host_state_machine(),
+            nonce: 0,
+            from: vec![0u8; 32],
+            to: vec![1u8; 32],
+            timeout_timestamp: 0,
+            data: vec![0u8; 64],
+        };
+        let message = Message::Request(RequestMessage {
+            requests: vec![post.clone()],
+            proof: Proof {
+                height: StateMachineHeight {
+                    id: StateMachineId {
+                        state_id: counterparty,
+                        consensus_state_id: MOCK_CONSENSUS_STATE_ID,
+                    },
+                    height: 3,
+                },
+                proof: vec![],
+            },
+            signer: vec![],
+        });
+        let scope = PauseScope::Module { state_machine: counterparty, module: vec![1u8; 32] };
+        assert_eq!(
+            Ismp::pause(RuntimeOrigin::signed(AccountId32::new([1u8; 32])), scope.clone()),
+            Err(DispatchError::BadOrigin)
+        );
+        Ismp::pause(RuntimeOrigin::root(), scope.clone()).unwrap();
+
+        let results = Ismp::dry_run_messages(vec![message.clone()]);
+        let DryRunOutcome::Request(ref res) = results[0].outcome else {
+            panic!("Expected request outcome")
+        };
+        assert!(matches!(res.as_slice(), [Err(HandlingError::Paused { .. })]));
+        Ismp::handle_messages(vec![message.clone()]).unwrap();
+        assert!(host.request_receipt(&Request::Post(post.clone())).is_none());
+
+        Ismp::unpause(RuntimeOrigin::root(), scope).unwrap();
+        Ismp::handle_messages(vec![message]).unwrap();
+        assert!(host.request_receipt(&Request::Post(post)).is_some());
+    })
+}
+
+#[test]
+fn should_retry_failed_callbacks_from_the_queue() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let host = Host::<Test>::default();
+        setup_mock_client::<_, Test>(&host);
+        host.store_challenge_period(MOCK_CONSENSUS_STATE_ID, 0).unwrap();
+        let counterparty = StateMachine::Ethereum(Ethereum::ExecutionLayer);
+        let post = |nonce: u64, to: Vec<u8>| Post {
+            source: counterparty,
+            dest: host.host_state_machine(),
+            nonce,
+            from: vec![0u8; 32],
+            to,
+            timeout_timestamp: 0,
+            data: vec![0u8; 64],
+        };
+        let message = |requests: Vec<Post>| {
+            Message::Request(RequestMessage {
+                requests,
+                proof: Proof {
+                    height: StateMachineHeight {
+                        id: StateMachineId {
+                            state_id: counterparty,
+                            consensus_state_id: MOCK_CONSENSUS_STATE_ID,
+                        },
+                        height: 3,
+                    },
+                    proof: vec![],
+                },
+                signer: vec![2u8; 32],
+            })
+        };
+        // transaction pool validation doesn't persist any state changes
+        let validate = |messages: Vec<Message>| {
+            with_transaction(|| {
+                TransactionOutcome::Rollback(Ok::<_, DispatchError>(Ismp::validate_messages(
+                    RuntimeOrigin::none(),
+                    messages,
+                )))
+            })
+            .unwrap()
+        };
+        let failing = post(0, vec![1u8; 32]);
+        let commitment = hash_request::<Host<Test>>(&Request::Post(failing.clone()));
+
+        // paused traffic is not queued, it is delivered again once it is resumed
+        let scope = PauseScope::Module { state_machine: counterparty, module: vec![1u8; 32] };
+        Ismp::pause(RuntimeOrigin::root(), scope.clone()).unwrap();
+        Ismp::handle_messages(vec![message(vec![failing.clone()])]).unwrap();
+        assert!(!RetryQueue::<Test>::contains_key(commitment));
+        assert!(validate(vec![message(vec![failing.clone()])]).is_err());
+        Ismp::unpause(RuntimeOrigin::root(), scope).unwrap();
+
+        // unsigned transactions in which no callback succeeds are rejected
+        UnavailableModule::set(&Some(vec![1u8; 32]));
+        assert!(validate(vec![message(vec![failing.clone()])]).is_err());
+
+        // failed callbacks are queued alongside successful deliveries
+        let delivered = post(1, vec![3u8; 32]);
+        let batch = vec![message(vec![failing.clone(), delivered.clone()])];
+        validate(batch.clone()).unwrap();
+        Ismp::handle_messages(batch).unwrap();
+        assert!(RetryQueue::<Test>::contains_key(commitment));
+        assert!(host.request_receipt(&Request::Post(failing.clone())).is_none());
+        assert!(host.request_receipt(&Request::Post(delivered)).is_some());
+
+        // queued requests can't be resubmitted with a new proof
+        assert!(validate(vec![message(vec![failing.clone(), post(2, vec![3u8; 32])])]).is_err());
+
+        // a failed retry schedules the next attempt
+        let relayer = RuntimeOrigin::signed(AccountId32::new([1u8; 32]));
+        Ismp::retry_callback(relayer.clone(), commitment).unwrap();
+        let entry = RetryQueue::<Test>::get(commitment).unwrap();
+        assert_eq!(entry.attempts, 1);
+        assert_eq!(
+            Ismp::retry_callback(relayer, H256::zero()),
+            Err(pallet_ismp::Error::<Test>::UnknownRetry.into())
+        );
+
+        // entries are only drained once they are due
+        UnavailableModule::set(&None);
+        let block = frame_system::Pallet::<Test>::block_number();
+        Ismp::on_idle(block, Weight::MAX);
+        assert!(RetryQueue::<Test>::contains_key(commitment));
+
+        frame_system::Pallet::<Test>::set_block_number(entry.retry_at);
+        Ismp::on_idle(entry.retry_at, Weight::MAX);
+        assert!(!RetryQueue::<Test>::contains_key(commitment));
+        assert_eq!(RetryQueue::<Test>::count(), 0);
+        assert!(host.request_receipt(&Request::Post(failing)).is_some());
+        assert_eq!(RequestReceipts::<Test>::get(commitment), Some(vec![2u8; 32]));
+    })
+}
+
+#[test]
+fn should_prune_claimed_commitments_after_the_pruning_horizon() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        set_timestamp(Some(0));
+        let commitment = H256::repeat_byte(1);
+        RequestCommitments::<Test>::insert(
+            commitment,
+            LeafMetadata {
+                mmr: LeafIndexAndPos { leaf_index: 0, pos: 0 },
+                meta: FeeMetadata { origin: [0u8; 32].into(), fee: 0 },
+            },
+        );
+        Ismp::schedule_pruning(PrunableEntry::Commitment(commitment), Duration::from_secs(1000));
+        assert_eq!(PendingPrunes::<Test>::get(), 1);
+
+        let block = frame_system::Pallet::<Test>::block_number();
+        // the horizon has not elapsed
+        set_timestamp(Some((1000 + PruningHorizon::get() - 1) * 1000));
+        Ismp::on_idle(block, Weight::MAX);
+        assert!(RequestCommitments::<Test>::contains_key(commitment));
+
+        // entries are pruned once the bucket they were scheduled in has elapsed
+        set_timestamp(Some(2 * 60 * 60 * 1000));
+        Ismp::on_idle(block, Weight::MAX);
+        assert!(!RequestCommitments::<Test>::contains_key(commitment));
+        assert_eq!(PendingPrunes::<Test>::get(), 0);
+        assert_eq!(PruningCursor::<Test>::get(), None);
+    })
+}
+
+#[test]
+fn should_prune_responded_entries_with_their_response_commitments() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        set_timestamp(Some(0));
+        let (commitment, request_commitment) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        ResponseCommitments::<Test>::insert(
+            commitment,
+            LeafMetadata {
+                mmr: LeafIndexAndPos { leaf_index: 0, pos: 0 },
+                meta: FeeMetadata { origin: [0u8; 32].into(), fee: 0 },
+            },
+        );
+        Responded::<Test>::insert(request_commitment, true);
+        Ismp::schedule_pruning(
+            PrunableEntry::Response { commitment, request_commitment },
+            Duration::from_secs(1000),
+        );
+
+        let block = frame_system::Pallet::<Test>::block_number();
+        set_timestamp(Some((1000 + PruningHorizon::get() + 60 * 60) * 1000));
+        Ismp::on_idle(block, Weight::MAX);
+        assert!(!ResponseCommitments::<Test>::contains_key(commitment));
+        assert!(!Responded::<Test>::contains_key(request_commitment));
+        assert_eq!(PendingPrunes::<Test>::get(), 0);
+    })
+}
+
+#[test]
+fn should_reject_timeout_proofs_for_delivered_requests_after_the_pruning_horizon() {
+    let mut ext = new_test_ext();
+    let post = Post {
+        source: StateMachine::Ethereum(Ethereum::ExecutionLayer),
+        dest: StateMachine::Kusama(2000),
+        nonce: 0,
+        from: vec![0u8; 32],
+        to: vec![0u8; 32],
+        timeout_timestamp: 1000,
+        data: vec![0u8; 64],
+    };
+    let request = Request::Post(post);
+    ext.execute_with(|| {
+        set_timestamp(Some(0));
+        let host = Host::<Test>::default();
+        host.store_request_receipt(&request, &vec![0u8; 32]).unwrap();
+        Responded::<Test>::insert(hash_request::<Host<Test>>(&request), true);
+
+        // well past the timeout and the pruning horizon
+        set_timestamp(Some((1000 + 2 * PruningHorizon::get()) * 1000));
+        let block = frame_system::Pallet::<Test>::block_number();
+        Ismp::on_idle(block, Weight::MAX);
+        assert!(Responded::<Test>::contains_key(hash_request::<Host<Test>>(&request)));
+    });
+
+    // produce the non-membership proof a relayer would submit to the source chain
+    ext.commit_all().unwrap();
+    let child_info = ChildInfo::new_default(CHILD_TRIE_PREFIX);
+    let state_machine = SubstrateStateMachine::<Test>::default();
+    let keys = state_machine.state_trie_key(RequestResponse::Request(vec![request.clone()]));
+    let backend = ext.as_backend();
+    let overlay_root =
+        backend.child_storage_root(&child_info, std::iter::empty(), StateVersion::V0).0;
+    let storage_proof = prove_child_read(backend, &child_info, &keys).unwrap();
+    let proof = Proof {
+        height: StateMachineHeight {
+            id: StateMachineId {
+                state_id: StateMachine::Kusama(2000),
+                consensus_state_id: *b"mock",
+            },
+            height: 1,
+        },
+        proof: SubstrateStateProof::OverlayProof {
+            hasher: HashAlgorithm::Blake2,
+            storage_proof: storage_proof.into_iter_nodes().collect(),
+        }
+        .encode(),
+    };
+    let root = StateCommitment {
+        timestamp: 0,
+        overlay_root: Some(overlay_root),
+        state_root: H256::zero(),
+    };
+
+    ext.execute_with(|| {
+        let values = state_machine
+            .verify_state_proof(&Host::<Test>::default(), keys, root, &proof)
+            .unwrap();
+        // the receipt is still present, so the timeout handler rejects the proof
+        assert!(values.into_values().all(|value| value.is_some()));
+    });
+}
+
 #[test]
 fn should_handle_get_request_responses_correctly() {
     let mut ext = new_test_ext();
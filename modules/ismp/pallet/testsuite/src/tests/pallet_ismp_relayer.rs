@@ -19,34 +19,42 @@ use alloy_primitives::hex;
 use codec::{Decode, Encode};
 use ethereum_trie::{keccak::KeccakHasher, MemoryDB, StorageProof};
 use evm_common::types::EvmStateProof;
-use frame_support::crypto::ecdsa::ECDSAExt;
+use frame_support::{
+    crypto::ecdsa::ECDSAExt,
+    traits::{
+        fungible::{Inspect, Mutate},
+        Get,
+    },
+};
 use ismp::{
     consensus::{StateCommitment, StateMachineHeight, StateMachineId},
     host::{IsmpHost, StateMachine},
     messaging::Proof,
-    router::{Post, Request},
+    router::{DispatchPost, DispatchRequest, IsmpDispatcher, IsmpRouter, Post, Request},
     util::{hash_post_response, hash_request},
 };
 use pallet_ismp::{
     child_trie::{RequestCommitments, RequestReceipts, ResponseCommitments, ResponseReceipts},
-    dispatcher::FeeMetadata,
+    dispatcher::{FeeDispatcher, FeeMetadata},
     host::Host,
     primitives::{HashAlgorithm, SubstrateStateProof},
-    ResponseReceipt,
+    EscrowedFees, ResponseReceipt,
 };
 use pallet_ismp_relayer::{
     self as pallet_ismp_relayer, message,
-    withdrawal::{Key, Signature, WithdrawalInputData, WithdrawalProof},
+    withdrawal::{
+        Key, Signature, SubstrateMessage, WithdrawalInputData, WithdrawalParams, WithdrawalProof,
+    },
     Claimed,
 };
-use sp_core::{Pair, H160, H256, U256};
+use sp_core::{crypto::AccountId32, Pair, H160, H256, U256};
 use sp_trie::LayoutV0;
 use std::{fs::File, io::Read, time::Duration};
 use trie_db::{Recorder, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut};
 
 use crate::runtime::{
-    new_test_ext, set_timestamp, RuntimeCall, RuntimeOrigin, Test, MOCK_CONSENSUS_CLIENT_ID,
-    MOCK_CONSENSUS_STATE_ID,
+    new_test_ext, set_timestamp, Balances, Hyperbridge, ModuleRouter, RuntimeCall, RuntimeOrigin,
+    Test, EXISTENTIAL_DEPOSIT, MOCK_CONSENSUS_CLIENT_ID, MOCK_CONSENSUS_STATE_ID, UNIT,
 };
 use ismp::host::Ethereum;
 use ismp_bsc::BSC_CONSENSUS_ID;
@@ -286,6 +294,27 @@ fn test_withdrawal_proof() {
             pallet_ismp_relayer::Fees::<Test>::get(StateMachine::Kusama(2000), vec![2; 32]),
             U256::from(5000u128)
         );
+
+        // the source chain is told to release the fees it holds in escrow for the claimed
+        // requests and responses
+        let claimed = requests
+            .iter()
+            .step_by(2)
+            .cloned()
+            .chain(responses.iter().step_by(2).map(|(_, response)| *response))
+            .collect::<Vec<_>>();
+        let release = Post {
+            source: host.host_state_machine(),
+            dest: StateMachine::Kusama(2000),
+            nonce: 0,
+            from: pallet_ismp_relayer::MODULE_ID.to_vec(),
+            to: pallet_ismp_relayer::MODULE_ID.to_vec(),
+            timeout_timestamp: 0,
+            data: SubstrateMessage::ReleaseFees(claimed).encode(),
+        };
+        assert!(host
+            .request_commitment(hash_request::<Host<Test>>(&Request::Post(release)))
+            .is_ok());
     })
 }
 
@@ -624,3 +653,148 @@ pub fn encode_accumulate_fees_call() -> Vec<u8> {
 
     call.encode()
 }
+
+#[test]
+fn should_pay_out_withdrawals_from_hyperbridge() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let origin = AccountId32::new([1u8; 32]);
+        let escrow = pallet_ismp::Pallet::<Test>::fee_escrow_account();
+        let pot = pallet_ismp::Pallet::<Test>::fee_pot_account();
+        Balances::mint_into(&origin, 2 * UNIT).unwrap();
+        Balances::mint_into(&escrow, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let module = ModuleRouter::default()
+            .module_for_id(pallet_ismp_relayer::MODULE_ID.to_vec())
+            .unwrap();
+
+        // The fee pot is funded by the fees released once hyperbridge has proven delivery
+        let post = DispatchPost {
+            dest: StateMachine::Kusama(2001),
+            from: vec![0u8; 32],
+            to: vec![0u8; 32],
+            timeout_timestamp: 0,
+            data: vec![],
+        };
+        FeeDispatcher::<Test>::default()
+            .dispatch_request(DispatchRequest::Post(post), origin.clone(), UNIT)
+            .unwrap();
+        let commitment = hash_request::<Host<Test>>(&Request::Post(Post {
+            source: Host::<Test>::default().host_state_machine(),
+            dest: StateMachine::Kusama(2001),
+            nonce: 0,
+            from: vec![0u8; 32],
+            to: vec![0u8; 32],
+            timeout_timestamp: 0,
+            data: vec![],
+        }));
+        module
+            .on_accept(Post {
+                source: Hyperbridge::get().unwrap(),
+                dest: StateMachine::Kusama(2000),
+                nonce: 0,
+                from: pallet_ismp_relayer::MODULE_ID.to_vec(),
+                to: pallet_ismp_relayer::MODULE_ID.to_vec(),
+                timeout_timestamp: 0,
+                data: SubstrateMessage::ReleaseFees(vec![commitment]).encode(),
+            })
+            .unwrap();
+        assert_eq!(Balances::balance(&pot), UNIT);
+
+        let beneficiary = AccountId32::new([2u8; 32]);
+        let beneficiary_address = [2u8; 32].to_vec();
+        let withdrawal = |source: StateMachine, from: Vec<u8>| Post {
+            source,
+            dest: StateMachine::Kusama(2000),
+            nonce: 0,
+            from,
+            to: pallet_ismp_relayer::MODULE_ID.to_vec(),
+            timeout_timestamp: 0,
+            data: SubstrateMessage::Withdrawal(WithdrawalParams {
+                beneficiary_address: beneficiary_address.clone(),
+                amount: U256::from(UNIT / 2),
+            })
+            .encode(),
+        };
+
+        // Only the relayer module on hyperbridge can withdraw fees
+        assert!(module
+            .on_accept(withdrawal(
+                StateMachine::Kusama(2001),
+                pallet_ismp_relayer::MODULE_ID.to_vec()
+            ))
+            .is_err());
+        assert!(module
+            .on_accept(withdrawal(Hyperbridge::get().unwrap(), H160::zero().0.to_vec()))
+            .is_err());
+
+        module
+            .on_accept(withdrawal(
+                Hyperbridge::get().unwrap(),
+                pallet_ismp_relayer::MODULE_ID.to_vec(),
+            ))
+            .unwrap();
+        assert_eq!(Balances::balance(&beneficiary), UNIT / 2);
+        assert_eq!(Balances::balance(&pot), UNIT / 2);
+    })
+}
+
+#[test]
+fn should_release_escrowed_fees_once_hyperbridge_proves_delivery() {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let origin = AccountId32::new([1u8; 32]);
+        let escrow = pallet_ismp::Pallet::<Test>::fee_escrow_account();
+        let pot = pallet_ismp::Pallet::<Test>::fee_pot_account();
+        Balances::mint_into(&origin, UNIT).unwrap();
+        Balances::mint_into(&escrow, EXISTENTIAL_DEPOSIT).unwrap();
+        Balances::mint_into(&pot, EXISTENTIAL_DEPOSIT).unwrap();
+
+        let post = DispatchPost {
+            dest: StateMachine::Kusama(2001),
+            from: vec![0u8; 32],
+            to: vec![0u8; 32],
+            timeout_timestamp: 0,
+            data: vec![1u8; 64],
+        };
+        FeeDispatcher::<Test>::default()
+            .dispatch_request(DispatchRequest::Post(post), origin.clone(), 640)
+            .unwrap();
+        let commitment = hash_request::<Host<Test>>(&Request::Post(Post {
+            source: Host::<Test>::default().host_state_machine(),
+            dest: StateMachine::Kusama(2001),
+            nonce: 0,
+            from: vec![0u8; 32],
+            to: vec![0u8; 32],
+            timeout_timestamp: 0,
+            data: vec![1u8; 64],
+        }));
+        assert_eq!(EscrowedFees::<Test>::get(commitment), Some(640));
+
+        let release = |source: StateMachine| Post {
+            source,
+            dest: StateMachine::Kusama(2000),
+            nonce: 0,
+            from: pallet_ismp_relayer::MODULE_ID.to_vec(),
+            to: pallet_ismp_relayer::MODULE_ID.to_vec(),
+            timeout_timestamp: 0,
+            data: SubstrateMessage::ReleaseFees(vec![commitment]).encode(),
+        };
+        let module = ModuleRouter::default()
+            .module_for_id(pallet_ismp_relayer::MODULE_ID.to_vec())
+            .unwrap();
+
+        // Only hyperbridge can release escrowed fees
+        assert!(module.on_accept(release(StateMachine::Kusama(2001))).is_err());
+        assert_eq!(EscrowedFees::<Test>::get(commitment), Some(640));
+
+        module.on_accept(release(Hyperbridge::get().unwrap())).unwrap();
+        assert_eq!(EscrowedFees::<Test>::get(commitment), None);
+        assert_eq!(Balances::balance(&escrow), EXISTENTIAL_DEPOSIT);
+        assert_eq!(Balances::balance(&pot), EXISTENTIAL_DEPOSIT + 640);
+
+        // fees are only released once
+        module.on_accept(release(Hyperbridge::get().unwrap())).unwrap();
+        assert_eq!(Balances::balance(&pot), EXISTENTIAL_DEPOSIT + 640);
+    })
+}
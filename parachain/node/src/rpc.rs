@@ -25,8 +25,8 @@ use std::sync::Arc;
 use gargantua_runtime::{opaque::Block, AccountId, Balance, Index as Nonce};
 
 use crate::runtime_api::opaque;
-use sc_client_api::{AuxStore, BlockBackend, ProofProvider};
-pub use sc_rpc::DenyUnsafe;
+use sc_client_api::{AuxStore, BlockBackend, BlockchainEvents, ProofProvider};
+pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -46,6 +46,8 @@ pub struct FullDeps<C, P, B> {
     pub deny_unsafe: DenyUnsafe,
     /// Backend used by the node.
     pub backend: Arc<B>,
+    /// Executor for RPC subscriptions
+    pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all RPC extensions.
@@ -58,6 +60,7 @@ where
         + AuxStore
         + BlockBackend<Block>
         + ProofProvider<Block>
+        + BlockchainEvents<Block>
         + HeaderMetadata<Block, Error = BlockChainError>
         + Send
         + Sync
@@ -75,7 +78,7 @@ where
     use substrate_frame_rpc_system::{System, SystemApiServer};
 
     let mut module = RpcExtension::new(());
-    let FullDeps { client, pool, deny_unsafe, backend } = deps;
+    let FullDeps { client, pool, deny_unsafe, backend, subscription_executor } = deps;
 
     module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
     module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
@@ -86,6 +89,7 @@ where
             backend
                 .offchain_storage()
                 .ok_or("Backend doesn't provide the required offchain storage")?,
+            subscription_executor,
         )
         .into_rpc(),
     )?;
@@ -280,17 +280,26 @@ where
         );
     }
 
+    if let Some(offchain_storage) = backend.offchain_storage() {
+        task_manager.spawn_handle().spawn_blocking(
+            "ismp-indexer",
+            None,
+            pallet_ismp_rpc::indexer::run_indexer(client.clone(), offchain_storage),
+        );
+    }
+
     let rpc_builder = {
         let client = client.clone();
         let backend = backend.clone();
         let transaction_pool = transaction_pool.clone();
 
-        Box::new(move |deny_unsafe, _| {
+        Box::new(move |deny_unsafe, subscription_executor| {
             let deps = crate::rpc::FullDeps {
                 client: client.clone(),
                 pool: transaction_pool.clone(),
                 deny_unsafe,
                 backend: backend.clone(),
+                subscription_executor,
             };
 
             crate::rpc::create_full(deps).map_err(Into::into)
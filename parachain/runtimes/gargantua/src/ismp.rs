@@ -26,6 +26,7 @@ use frame_support::{
 };
 use frame_system::EnsureRoot;
 use ismp::{
+    consensus::ConsensusClientId,
     error::Error,
     host::StateMachine,
     module::IsmpModule,
@@ -34,12 +35,18 @@ use ismp::{
 use pallet_asset_gateway::TokenGatewayParams;
 #[cfg(feature = "runtime-benchmarks")]
 use pallet_assets::BenchmarkHelper;
+use pallet_ismp_relayer::payout::{FeePotAccount, WithdrawalPayout};
 use sp_core::{crypto::AccountId32, H160, H256};
 use sp_runtime::Percent;
 
 use ismp::router::Timeout;
 use ismp_sync_committee::constants::sepolia::Sepolia;
-use pallet_ismp::{dispatcher::FeeMetadata, host::Host, primitives::ModuleId};
+use pallet_ismp::{
+    dispatcher::FeeMetadata,
+    host::Host,
+    primitives::ModuleId,
+    weight_info::{ConsensusClientWeight, IsmpModuleWeight, WeightProvider},
+};
 use sp_std::prelude::*;
 use staging_xcm::latest::MultiLocation;
 
@@ -65,6 +72,11 @@ impl Get<Option<StateMachine>> for Coprocessor {
         Some(HostStateMachine::get())
     }
 }
+parameter_types! {
+    pub const PerByteFee: Balance = 0;
+    pub const PruningHorizon: u64 = 7 * 24 * 60 * 60;
+}
+
 impl pallet_ismp::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     const INDEXING_PREFIX: &'static [u8] = b"ISMP";
@@ -73,24 +85,63 @@ impl pallet_ismp::Config for Runtime {
     type Coprocessor = Coprocessor;
     type TimeProvider = Timestamp;
     type Router = Router;
+    #[cfg(not(feature = "runtime-benchmarks"))]
+    type ConsensusClients = (
+        ismp_bsc::BscClient<Host<Runtime>>,
+        ismp_sync_committee::SyncCommitteeConsensusClient<Host<Runtime>, Sepolia>,
+    );
+    #[cfg(feature = "runtime-benchmarks")]
     type ConsensusClients = (
         ismp_bsc::BscClient<Host<Runtime>>,
         ismp_sync_committee::SyncCommitteeConsensusClient<Host<Runtime>, Sepolia>,
+        pallet_ismp::benchmarking::BenchmarkClient,
     );
-    type WeightProvider = ();
+    type WeightProvider = IsmpWeightProvider;
+    type WeightInfo = pallet_ismp::weights::SubstrateWeight<Runtime>;
+    type Currency = Balances;
+    type PerByteFee = PerByteFee;
+    type PruningHorizon = PruningHorizon;
+}
+
+/// Provides the benchmarked weights of the consensus clients.
+pub struct IsmpWeightProvider;
+
+impl WeightProvider for IsmpWeightProvider {
+    fn consensus_client(id: ConsensusClientId) -> Option<Box<dyn ConsensusClientWeight>> {
+        match id {
+            ismp_bsc::BSC_CONSENSUS_ID =>
+                Some(Box::new(ismp_bsc::weights::BscClientWeight::<Runtime>::default())),
+            ismp_sync_committee::BEACON_CONSENSUS_ID => Some(Box::new(
+                ismp_sync_committee::weights::SyncCommitteeClientWeight::<Runtime>::default(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn module_callback(_dest_module: ModuleId) -> Option<Box<dyn IsmpModuleWeight>> {
+        None
+    }
 }
 
 impl pallet_ismp_demo::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type Balance = Balance;
     type NativeCurrency = Balances;
-    type IsmpDispatcher = pallet_ismp::dispatcher::Dispatcher<Runtime>;
+    type IsmpDispatcher = pallet_ismp::dispatcher::FeeDispatcher<Runtime>;
 }
 
 impl pallet_ismp_relayer::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
 }
 
+/// Releases the fees escrowed by the [`pallet_ismp::dispatcher::FeeDispatcher`] once the
+/// coprocessor has proven their delivery, and pays out relayer fee withdrawals from them.
+pub type RelayerPayout = WithdrawalPayout<
+    Runtime,
+    FeePotAccount<Runtime>,
+    <Runtime as pallet_ismp::Config>::Coprocessor,
+>;
+
 impl pallet_ismp_host_executive::Config for Runtime {}
 
 impl pallet_call_decompressor::Config for Runtime {
@@ -168,8 +219,10 @@ impl IsmpModule for ProxyModule {
         match pallet_id {
             pallet_ismp_demo::PALLET_ID =>
                 pallet_ismp_demo::IsmpModuleCallback::<Runtime>::default().on_accept(request),
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_accept(request),
+            id if id.to_bytes() == pallet_ismp_relayer::MODULE_ID.to_vec() =>
+                RelayerPayout::default().on_accept(request),
             _ => Err(Error::ImplementationSpecific("Destination module not found".to_string())),
         }
     }
@@ -193,7 +246,7 @@ impl IsmpModule for ProxyModule {
         match pallet_id {
             pallet_ismp_demo::PALLET_ID =>
                 pallet_ismp_demo::IsmpModuleCallback::<Runtime>::default().on_response(response),
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_response(response),
             _ => Err(Error::ImplementationSpecific("Destination module not found".to_string())),
         }
@@ -212,7 +265,7 @@ impl IsmpModule for ProxyModule {
         match pallet_id {
             pallet_ismp_demo::PALLET_ID =>
                 pallet_ismp_demo::IsmpModuleCallback::<Runtime>::default().on_timeout(timeout),
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_timeout(timeout),
             // instead of returning an error, do nothing. The timeout is for a connected chain.
             _ => Ok(()),
@@ -225,6 +278,11 @@ pub struct Router;
 
 impl IsmpRouter for Router {
     fn module_for_id(&self, _bytes: Vec<u8>) -> Result<Box<dyn IsmpModule>, Error> {
+        #[cfg(feature = "runtime-benchmarks")]
+        if _bytes == pallet_ismp::benchmarking::MODULE_ID.to_bytes() {
+            return Ok(Box::new(pallet_ismp::benchmarking::BenchmarkIsmpModule));
+        }
+
         Ok(Box::new(ProxyModule::default()))
     }
 }
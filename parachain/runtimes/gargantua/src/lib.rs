@@ -150,8 +150,12 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllPalletsWithSystem,
+    Migrations,
 >;
 
+/// Pending storage migrations
+pub type Migrations = (pallet_ismp::migrations::v1::MigrateToPeaksOnly<Runtime>,);
+
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
 /// node's balance type.
 ///
@@ -586,6 +590,9 @@ mod benches {
     define_benchmarks!(
         [frame_system, SystemBench::<Runtime>]
         [pallet_balances, Balances]
+        [pallet_ismp, Ismp]
+        [ismp_sync_committee, IsmpSyncCommittee]
+        [ismp_bsc, BscBench::<Runtime>]
         [pallet_session, SessionBench::<Runtime>]
         [pallet_timestamp, Timestamp]
         [pallet_collator_selection, CollatorSelection]
@@ -824,6 +831,12 @@ impl_runtime_apis! {
         fn get_responses(commitments: Vec<H256>) -> Vec<Response> {
             Ismp::get_responses(commitments)
         }
+
+        fn dry_run_messages(
+            messages: Vec<::ismp::messaging::Message>,
+        ) -> Vec<pallet_ismp::primitives::DryRunResult> {
+            Ismp::dry_run_messages(messages)
+        }
     }
 
     // impl ismp_parachain_runtime_api::IsmpParachainApi<Block> for Runtime {
@@ -832,6 +845,12 @@ impl_runtime_apis! {
     //     }
     // }
 
+    impl pallet_asset_gateway_runtime_api::AssetGatewayApi<Block, AccountId> for Runtime {
+        fn pending_claims(account: AccountId) -> Vec<(H256, u128)> {
+            Gateway::pending_claims(account)
+        }
+    }
+
     impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
         fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
             ParachainSystem::collect_collation_info(header)
@@ -867,6 +886,7 @@ impl_runtime_apis! {
             use frame_support::traits::StorageInfoTrait;
             use frame_system_benchmarking::Pallet as SystemBench;
             use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
+            use ismp_bsc::benchmarking::Pallet as BscBench;
 
             let mut list = Vec::<BenchmarkList>::new();
             list_benchmarks!(list, extra);
@@ -887,6 +907,9 @@ impl_runtime_apis! {
             use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
             impl cumulus_pallet_session_benchmarking::Config for Runtime {}
 
+            use ismp_bsc::benchmarking::Pallet as BscBench;
+            impl ismp_bsc::benchmarking::Config for Runtime {}
+
             let whitelist: Vec<TrackedStorageKey> = vec![
                 // Block Number
                 hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
@@ -66,6 +66,11 @@ impl Get<Option<StateMachine>> for Coprocessor {
     }
 }
 
+parameter_types! {
+    pub const PerByteFee: Balance = 0;
+    pub const PruningHorizon: u64 = 7 * 24 * 60 * 60;
+}
+
 impl pallet_ismp::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     const INDEXING_PREFIX: &'static [u8] = b"ISMP";
@@ -74,11 +79,22 @@ impl pallet_ismp::Config for Runtime {
     type TimeProvider = Timestamp;
     type Router = Router;
     type Coprocessor = Coprocessor;
+    #[cfg(not(feature = "runtime-benchmarks"))]
     type ConsensusClients = (
         ismp_bsc::BscClient<Host<Runtime>>,
         ismp_sync_committee::SyncCommitteeConsensusClient<Host<Runtime>, Mainnet>,
     );
+    #[cfg(feature = "runtime-benchmarks")]
+    type ConsensusClients = (
+        ismp_bsc::BscClient<Host<Runtime>>,
+        ismp_sync_committee::SyncCommitteeConsensusClient<Host<Runtime>, Mainnet>,
+        pallet_ismp::benchmarking::BenchmarkClient,
+    );
     type WeightProvider = ();
+    type WeightInfo = pallet_ismp::weights::SubstrateWeight<Runtime>;
+    type Currency = Balances;
+    type PerByteFee = PerByteFee;
+    type PruningHorizon = PruningHorizon;
 }
 
 impl pallet_ismp_relayer::Config for Runtime {
@@ -160,7 +176,7 @@ impl IsmpModule for ProxyModule {
         let token_gateway = ModuleId::Evm(Gateway::token_gateway_address());
 
         match pallet_id {
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_accept(request),
             _ => Err(Error::ImplementationSpecific("Destination module not found".to_string())),
         }
@@ -183,7 +199,7 @@ impl IsmpModule for ProxyModule {
 
         let token_gateway = ModuleId::Evm(Gateway::token_gateway_address());
         match pallet_id {
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_response(response),
             _ => Err(Error::ImplementationSpecific("Destination module not found".to_string())),
         }
@@ -200,7 +216,7 @@ impl IsmpModule for ProxyModule {
             .map_err(|err| Error::ImplementationSpecific(err.to_string()))?;
         let token_gateway = ModuleId::Evm(Gateway::token_gateway_address());
         match pallet_id {
-            id if id == token_gateway =>
+            id if id == token_gateway || id == Gateway::module_id() =>
                 pallet_asset_gateway::Module::<Runtime>::default().on_timeout(timeout),
             // instead of returning an error, do nothing. The timeout is for a connected chain.
             _ => Ok(()),
@@ -213,6 +229,11 @@ pub struct Router;
 
 impl IsmpRouter for Router {
     fn module_for_id(&self, _bytes: Vec<u8>) -> Result<Box<dyn IsmpModule>, Error> {
+        #[cfg(feature = "runtime-benchmarks")]
+        if _bytes == pallet_ismp::benchmarking::MODULE_ID.to_bytes() {
+            return Ok(Box::new(pallet_ismp::benchmarking::BenchmarkIsmpModule));
+        }
+
         Ok(Box::new(ProxyModule::default()))
     }
 }
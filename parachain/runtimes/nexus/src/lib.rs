@@ -152,8 +152,12 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllPalletsWithSystem,
+    Migrations,
 >;
 
+/// Pending storage migrations
+pub type Migrations = (pallet_ismp::migrations::v1::MigrateToPeaksOnly<Runtime>,);
+
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
 /// node's balance type.
 ///
@@ -600,6 +604,7 @@ mod benches {
     define_benchmarks!(
         [frame_system, SystemBench::<Runtime>]
         [pallet_balances, Balances]
+        [pallet_ismp, Ismp]
         [pallet_session, SessionBench::<Runtime>]
         [pallet_timestamp, Timestamp]
         [pallet_collator_selection, CollatorSelection]
@@ -847,6 +852,12 @@ impl_runtime_apis! {
         fn get_responses(commitments: Vec<H256>) -> Vec<Response> {
             Ismp::get_responses(commitments)
         }
+
+        fn dry_run_messages(
+            messages: Vec<::ismp::messaging::Message>,
+        ) -> Vec<pallet_ismp::primitives::DryRunResult> {
+            Ismp::dry_run_messages(messages)
+        }
     }
 
     // impl ismp_parachain_runtime_api::IsmpParachainApi<Block> for Runtime {
@@ -855,6 +866,12 @@ impl_runtime_apis! {
     //     }
     // }
 
+    impl pallet_asset_gateway_runtime_api::AssetGatewayApi<Block, AccountId> for Runtime {
+        fn pending_claims(account: AccountId) -> Vec<(H256, u128)> {
+            Gateway::pending_claims(account)
+        }
+    }
+
     impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
         fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
             ParachainSystem::collect_collation_info(header)